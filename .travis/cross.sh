@@ -138,6 +138,9 @@ case "$PLATFORM" in
     "wasm32-unknown-unknown")
         rustup target add wasm32-unknown-unknown
         cargo check --target wasm32-unknown-unknown -p tract-onnx -p tract-tensorflow
+        cargo check --target wasm32-unknown-unknown -p tract-core --features wasm
+        # the portable path, with generic kernels only
+        cargo test -p tract-core --features wasm
     ;;
     *)
         echo "Don't know what to do for platform: $PLATFORM"
@@ -35,17 +35,21 @@ export CACHEDIR
 
 if [ `arch` = "x86_64" ]
 then
-	ALL_FEATURES=--all-features
+	# tensorflow conformance, against libtensorflow
+	CONFORM_FEATURES="--features conform"
 fi
 
-cargo check --workspace --all-targets $ALL_FEATURES
+cargo check --workspace --all-targets
+cargo check -p tract-tensorflow --all-targets $CONFORM_FEATURES
 
 # useful as debug_asserts will come into play
-cargo test -p tract-core $ALL_FEATURES
+cargo test -p tract-core
+cargo test -p tract-core --features safe-alloc
 cargo test -p onnx-test-suite -- --skip real_
 cargo clean
 
-cargo build --release $ALL_FEATURES
+cargo build --release
+cargo build --release -p tract $CONFORM_FEATURES
 
 if [ `arch` = "x86_64" ]
 then
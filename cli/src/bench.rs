@@ -12,7 +12,7 @@ pub fn criterion(params: &Parameters) -> CliResult<()> {
 
     let mut crit = criterion::Criterion::default();
     let mut group = crit.benchmark_group("net");
-    let inputs = crate::tensor::make_inputs_for_model(model)?;
+    let inputs = crate::tensor::make_inputs_for_model(model, &params.symbols)?;
     group.bench_function("run", move |b| b.iter(|| state.run(inputs.clone())));
     Ok(())
 }
@@ -34,7 +34,7 @@ pub fn handle(params: &Parameters, limits: &BenchLimits, probe: Option<&Probe>)
     if let Some(p) = &progress {
     p.store(iters as _, std::sync::atomic::Ordering::Relaxed);
     }
-    state.run(crate::tensor::make_inputs_for_model(model)?)?;
+    state.run(crate::tensor::make_inputs_for_model(model, &params.symbols)?)?;
     iters += 1;
     }
     let dur = start.elapsed();
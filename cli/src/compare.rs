@@ -13,6 +13,7 @@ use crate::*;
 #[cfg(feature = "conform")]
 pub fn handle_tensorflow(
     cumulative: bool,
+    bisect: bool,
     resilient: bool,
     params: &mut Parameters,
     output_params: DisplayParams,
@@ -25,7 +26,7 @@ pub fn handle_tensorflow(
         .iter()
         .map(|&i| tract.outlet_typedfact(i))
         .collect::<TractResult<Vec<_>>>()?;
-    let generated = crate::tensor::make_inputs(&*input_facts)?;
+    let generated = crate::tensor::make_inputs(&*input_facts, &params.symbols)?;
 
     // Execute the model on tensorflow first.
     info!("Running the model on tensorflow.");
@@ -73,6 +74,7 @@ pub fn handle_tensorflow(
     }
     dispatch_model_no_pulse!(params.tract_model, |m| compare(
         cumulative,
+        bisect,
         m,
         &all_values,
         &params,
@@ -82,6 +84,7 @@ pub fn handle_tensorflow(
 
 pub fn handle_npz(
     cumulative: bool,
+    bisect: bool,
     npz: &str,
     params: &Parameters,
     output_params: DisplayParams,
@@ -96,6 +99,7 @@ pub fn handle_npz(
     }
     dispatch_model_no_pulse!(params.tract_model, |m| compare(
         cumulative,
+        bisect,
         m,
         &values,
         &params,
@@ -106,6 +110,7 @@ pub fn handle_npz(
 #[cfg(feature = "onnx")]
 pub fn handle_pbdir(
     cumulative: bool,
+    bisect: bool,
     pbdir: &str,
     params: &Parameters,
     output_params: DisplayParams,
@@ -120,6 +125,7 @@ pub fn handle_pbdir(
     }
     dispatch_model_no_pulse!(params.tract_model, |m| compare(
         cumulative,
+        bisect,
         m,
         &values,
         &params,
@@ -129,6 +135,7 @@ pub fn handle_pbdir(
 
 pub fn compare<F, O>(
     cumulative: bool,
+    bisect: bool,
     tract: &ModelImpl<F, O>,
     all_values: &HashMap<String, CliResult<Tensor>>,
     params: &Parameters,
@@ -139,6 +146,10 @@ where
     O: AsRef<dyn Op> + AsMut<dyn Op> + Display + Debug + Clone + Hash,
     ModelImpl<F, O>: Model,
 {
+    if bisect {
+        return report_first_divergence(tract, all_values);
+    }
+
     let eval_order = ::tract_core::model::eval_order(&tract)?;
 
     // Execute the model step-by-step on tract.
@@ -254,3 +265,118 @@ where
     };
     Ok(())
 }
+
+fn report_first_divergence<F, O>(
+    tract: &ModelImpl<F, O>,
+    all_values: &HashMap<String, CliResult<Tensor>>,
+) -> CliResult<()>
+where
+    F: Fact + Clone + for<'a> From<&'a Tensor> + Hash,
+    O: AsRef<dyn Op> + AsMut<dyn Op> + Display + Debug + Clone + Hash,
+    ModelImpl<F, O>: Model,
+{
+    if let Some((n, reason)) = first_divergence(tract, all_values)? {
+        let node = &tract.nodes()[n];
+        println!("{} {}", Red.bold().paint("First divergence at"), node);
+        println!("{}", reason);
+        for (ix, input) in node.inputs.iter().enumerate() {
+            println!("input fact  #{}: {:?}", ix, tract.outlet_typedfact(*input)?);
+        }
+        for ix in 0..node.outputs.len() {
+            println!("output fact #{}: {:?}", ix, tract.outlet_typedfact(OutletId::new(n, ix))?);
+        }
+        bail!("Diverging from reference at {}", node)
+    }
+    println!("{}", Green.paint("No divergence from reference found."));
+    Ok(())
+}
+
+/// Runs the model node by node, in eval order, letting tract values flow
+/// (cumulative mode), and returns the first node failing or producing an
+/// output that does not match the reference, with a description of the issue.
+pub fn first_divergence<F, O>(
+    tract: &ModelImpl<F, O>,
+    all_values: &HashMap<String, CliResult<Tensor>>,
+) -> CliResult<Option<(usize, String)>>
+where
+    F: Fact + Clone + for<'a> From<&'a Tensor> + Hash,
+    O: AsRef<dyn Op> + AsMut<dyn Op> + Display + Debug + Clone + Hash,
+    ModelImpl<F, O>: Model,
+{
+    let plan = SimplePlan::new(tract)?;
+    let mut state = SimpleState::new(plan)?;
+    for (ix, input) in tract.input_outlets()?.iter().enumerate() {
+        let name = &tract.node(input.node).name;
+        let value = all_values
+            .get(name)
+            .and_then(|v| v.as_ref().ok())
+            .ok_or_else(|| format!("No reference value for input {}", name))?;
+        state.set_input(ix, value.clone())?;
+    }
+    for n in ::tract_core::model::eval_order(&tract)? {
+        let node = &tract.nodes()[n];
+        if tract.input_outlets()?.iter().any(|o| o.node == n)
+            || node.op().validation() == Validation::Random
+        {
+            continue;
+        }
+        if let Err(e) = state.compute_recursively(n) {
+            return Ok(Some((n, format!("Error: {}", e))));
+        }
+        for ix in 0..node.outputs.len() {
+            let reference =
+                tract.outlet_label(OutletId::new(n, ix)).and_then(|lbl| all_values.get(lbl));
+            if let Some(Ok(reference)) = reference {
+                let found = &state.values[n].as_ref().unwrap()[ix];
+                let approx = node.op().validation() == Validation::Rounding;
+                if let Err(e) = found.close_enough(reference, approx) {
+                    return Ok(Some((n, format!("Wrong value for output {}, {}", ix, e))));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn model(factor: f32) -> TypedModel {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [3].as_ref()).unwrap();
+        let mut wire = model.add_source("x", fact).unwrap();
+        let ops: Vec<(&str, Box<dyn TypedOp>)> = vec![
+            ("a", Box::new(tract_core::ops::math::add::unary(rctensor1(&[1f32])))),
+            ("b", Box::new(tract_core::ops::math::mul::unary(rctensor1(&[factor])))),
+            ("c", Box::new(tract_core::ops::math::add::unary(rctensor1(&[3f32])))),
+        ];
+        for (name, op) in ops {
+            wire = model.wire_node(name, op, &[wire]).unwrap()[0];
+        }
+        model.set_output_outlets(&[wire]).unwrap();
+        for id in 0..model.nodes().len() {
+            let name = model.node(id).name.clone();
+            model.set_outlet_label(OutletId::new(id, 0), name).unwrap();
+        }
+        model
+    }
+
+    #[test]
+    fn bisect_finds_injected_bug() {
+        let input = tensor1(&[1f32, 2., 3.]);
+        let reference = model(2.);
+        let mut state = SimpleState::new(SimplePlan::new(&reference).unwrap()).unwrap();
+        state.set_input(0, input).unwrap();
+        let mut values = HashMap::new();
+        for node in reference.nodes() {
+            state.compute_recursively(node.id).unwrap();
+            let value = state.values[node.id].as_ref().unwrap()[0].clone();
+            values.insert(node.name.clone(), Ok(value.into_tensor()));
+        }
+        assert_eq!(first_divergence(&reference, &values).unwrap(), None);
+        let buggy = model(3.);
+        let (node, _) = first_divergence(&buggy, &values).unwrap().unwrap();
+        assert_eq!(buggy.node(node).name, "b");
+    }
+}
@@ -1,18 +1,126 @@
+use crate::CliResult;
+use std::collections::HashMap;
 use tract_hir::internal::*;
 
-pub fn parse_costs(spec: &str) -> TVec<(Cost, usize)> {
+/// Accepted values for a cost in `--assert-cost`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CostBound {
+    /// `FMA(F32)=2060448`
+    Exactly(usize),
+    /// `FMA(F32)<=2100000`
+    AtMost(usize),
+    /// `FMA(F32)~=2060448±1%`: within a percentage of the value.
+    Around(usize, f64),
+}
+
+impl CostBound {
+    pub fn accepts(&self, value: usize) -> bool {
+        match *self {
+            CostBound::Exactly(n) => value == n,
+            CostBound::AtMost(n) => value <= n,
+            CostBound::Around(n, percent) => {
+                (value as f64 - n as f64).abs() <= n as f64 * percent / 100.0
+            }
+        }
+    }
+}
+
+pub fn parse_costs(spec: &str) -> CliResult<TVec<(Cost, CostBound)>> {
     spec.split(",")
         .map(|spec| {
-            let mut toks = spec.split("=");
-            let name = toks.next().unwrap();
-            let n = toks.next().unwrap().parse::<usize>().unwrap();
+            let (name, bound) = if let Some(ix) = spec.find("<=") {
+                (&spec[..ix], CostBound::AtMost(spec[ix + 2..].parse()?))
+            } else if let Some(ix) = spec.find("~=") {
+                let mut toks = spec[ix + 2..].split('±');
+                let n = toks.next().unwrap().parse()?;
+                let percent = toks
+                    .next()
+                    .filter(|t| t.ends_with('%'))
+                    .and_then(|t| t.trim_end_matches('%').parse::<f64>().ok())
+                    .ok_or_else(|| format!("Expected a tolerance like ±1% in {}", spec))?;
+                (&spec[..ix], CostBound::Around(n, percent))
+            } else if let Some(ix) = spec.find('=') {
+                (&spec[..ix], CostBound::Exactly(spec[ix + 1..].parse()?))
+            } else {
+                bail!("Invalid cost assertion {}", spec)
+            };
             let c = match name {
                 "FMA(F32)" => Cost::FMA(f32::datum_type()),
                 "Div(F32)" => Cost::Div(f32::datum_type()),
                 "Buffer(F32)" => Cost::Buffer(f32::datum_type()),
-                _ => panic!("Unknown cost specifier {}", name),
+                _ => bail!("Unknown cost specifier {}", name),
             };
-            (c, n)
+            Ok((c, bound))
         })
         .collect()
 }
+
+/// Checks total costs against the asserted bounds. Every kind of cost of the
+/// model must be asserted.
+pub fn check_costs(assert: &[(Cost, CostBound)], total: &[(Cost, TDim)]) -> CliResult<()> {
+    let assert: HashMap<Cost, CostBound> = assert.iter().cloned().collect();
+    let total: HashMap<Cost, TDim> = total.iter().cloned().collect();
+    let met = assert.len() == total.len()
+        && assert.iter().all(|(cost, bound)| {
+            total
+                .get(cost)
+                .and_then(|n| n.to_integer().ok())
+                .map(|n| bound.accepts(n as usize))
+                .unwrap_or(false)
+        });
+    if !met {
+        bail!("Cost assertion not met: expected {:?} got {:?}", assert, total);
+    }
+    Ok(())
+}
+
+/// Cost of a FMA in a given datum type, relative to a f32 FMA, as a
+/// (numerator, denominator) ratio.
+///
+/// f16 is assumed to go twice as fast as f32, and f64 twice as slow.
+pub fn fma_weight(dt: DatumType) -> (u32, u32) {
+    match dt {
+        DatumType::F16 => (1, 2),
+        DatumType::F64 => (2, 1),
+        _ => (1, 1),
+    }
+}
+
+/// Sums up the FMA costs, weighted by precision, in equivalent f32 FMAs.
+pub fn equivalent_f32_fmas(costs: &[(Cost, TDim)]) -> TDim {
+    costs.iter().fold(0.to_dim(), |acc, (c, n)| match c {
+        Cost::FMA(dt) => {
+            let (num, den) = fma_weight(*dt);
+            acc + n.clone() * num / den
+        }
+        _ => acc,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::matmul::MatMulUnary;
+
+    #[test]
+    fn cost_within_bounds() {
+        let total = [(Cost::FMA(f32::datum_type()), 2060000.to_dim())];
+        for spec in &["FMA(F32)=2060000", "FMA(F32)<=2100000", "FMA(F32)~=2060448±1%"] {
+            check_costs(&parse_costs(spec).unwrap(), &total).unwrap();
+        }
+        for spec in &["FMA(F32)=2060448", "FMA(F32)<=2000000", "FMA(F32)~=2100000±1%"] {
+            assert!(check_costs(&parse_costs(spec).unwrap(), &total).is_err());
+        }
+        assert!(parse_costs("FMA(F32)~=2060448").is_err());
+    }
+
+    #[test]
+    fn f16_fmas_weigh_half() {
+        let a = Tensor::zero::<f16>(&[8, 8]).unwrap().into_arc_tensor();
+        let op = MatMulUnary::new(a, false, false, false, None);
+        let fact = TypedFact::dt_shape(f16::datum_type(), [8, 8].as_ref()).unwrap();
+        let cost = op.cost(&[&fact]).unwrap();
+        assert_eq!(&*cost, &[(Cost::FMA(f16::datum_type()), 512.to_dim())]);
+        assert_eq!(equivalent_f32_fmas(&cost), 256.to_dim());
+    }
+}
@@ -22,7 +22,17 @@ pub fn handle(
             .tract_model
             .downcast_ref::<TypedModel>()
             .ok_or("Can only profile typed models")?;
-        crate::profile::profile(model, bench_limits, &mut annotations)?;
+        crate::profile::profile(model, bench_limits, &mut annotations, &params.symbols)?;
+        if let Some(path) = matches.value_of("metrics") {
+            std::fs::write(path, crate::profile::prometheus_metrics(model, &annotations)?)?;
+        }
+        if matches.is_present("explain-fused") {
+            for node in model.nodes() {
+                if let Some(lines) = crate::profile::explain_fused(model, node)? {
+                    annotations.node_mut(NodeQId(tvec!(), node.id)).sections.push(lines);
+                }
+            }
+        }
     }
 
     if let Some(asserts) = &params.assertions {
@@ -38,19 +48,32 @@ pub fn handle(
 
     if options.cost {
         let total = annotations.tags.values().sum::<NodeTags>();
-        let assert = matches.value_of("assert-cost").map(|a| crate::cost::parse_costs(a));
-        if let Some(assert) = assert {
-            let assert: HashMap<Cost, TDim> =
-                assert.iter().map(|(c, n)| (*c, n.to_dim())).collect();
-            let total = total.cost.iter().cloned().collect::<HashMap<_, _>>();
-            if assert != total {
-                bail!("Cost assertion not met: expected {:?} got {:?}", assert, total);
-            }
+        if let Some(assert) = matches.value_of("assert-cost") {
+            crate::cost::check_costs(&crate::cost::parse_costs(assert)?, &total.cost)?;
+        }
+    }
+
+    if matches.is_present("receptive-field") {
+        let model = params
+            .tract_model
+            .downcast_ref::<TypedModel>()
+            .ok_or("Can only compute receptive fields of typed models")?;
+        let fields = tract_core::ops::cnn::receptive_fields(model)?;
+        for (name, fields) in model.output_names().iter().zip(fields.iter()) {
+            let fields: Vec<String> =
+                fields.iter().map(|f| format!("({}, {})", f.size, f.stride)).collect();
+            println!("Receptive field (size, stride) of {}: {}", name, fields.join(" "));
         }
     }
 
     if !options.quiet {
-        if options.json {
+        if matches.is_present("text") {
+            let model = params
+                .tract_model
+                .downcast_ref::<TypedModel>()
+                .ok_or("Can only dump typed models as text")?;
+            print!("{}", model.to_text()?);
+        } else if options.json {
             let export = crate::export::GraphPerfInfo::from(model, &annotations);
             serde_json::to_writer(std::io::stdout(), &export)?;
         } else {
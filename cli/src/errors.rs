@@ -18,6 +18,7 @@ error_chain! {
         NumParseInt(::std::num::ParseIntError);
         NdarrayShape(ndarray::ShapeError);
         NdarrayNpyReadNpz(ndarray_npy::ReadNpzError);
+        NdarrayNpyWriteNpz(ndarray_npy::WriteNpzError);
         SerdeJson(serde_json::error::Error);
     }
 }
@@ -27,6 +27,7 @@ use tract_itertools::Itertools;
 
 use tract_core::internal::*;
 use tract_core::model::{NormalizedModel, TypedModel};
+use tract_core::tract_linalg::KernelSelector;
 use tract_hir::internal::*;
 #[cfg(feature = "tf")]
 use tract_tensorflow::tfpb::tensorflow::GraphDef;
@@ -45,6 +46,8 @@ mod draw;
 mod dump;
 mod errors;
 mod export;
+#[cfg(feature = "onnx")]
+mod onnx_opset;
 mod optimize_check;
 mod profile;
 mod run;
@@ -96,11 +99,27 @@ fn main() {
     (@arg const_input: --("const-input") +takes_value +multiple number_of_values(1)
      "Treat input as a Const (by name), retaining its value.")
 
+    (@arg freeze_input: --("freeze-input") +takes_value +multiple number_of_values(1)
+     "Replace an input by a Const loaded from a file (name=@file.npz:thing.npy).")
+
+    (@arg check_finite: --("check-finite")
+     "Fail on the first NaN or infinite value computed by a floating point node")
+
+    (@arg list_dynamic: --("list-dynamic")
+     "List the outlets with a data-dependent shape, preventing full shape specialization")
+
+    (@arg set_outlet_label: --("set-outlet-label") +takes_value +multiple number_of_values(1)
+     "Label an outlet for display (node:slot=label, or node=label for slot 0).")
+
     (@arg input_bundle: --("input-bundle") +takes_value +multiple number_of_values(1)
      "Path to an input container (.npz)")
 
-    (@arg stream_axis: -s --("stream-axis") +takes_value
-     "Set Axis number to stream upon (first is 0)")
+    (@arg stream_axis: -s --("stream-axis") +takes_value +multiple number_of_values(1)
+     "Set Axis number to stream upon (first is 0), optionally naming its symbol (axis:N). \
+      Repeat for one axis per input, the last one applying to the remaining inputs.")
+
+    (@arg set: --set +takes_value +multiple number_of_values(1)
+     "Set a symbol value (N=4), for the dimensions it appears in. Repeat for each symbol.")
 
     (@arg kaldi_adjust_final_offset: --("kaldi-adjust-final-offset") +takes_value
      "Adjust value of final offset in network (for reproducibility)")
@@ -130,27 +149,46 @@ fn main() {
      "Override a fact.")
 
     (@arg analyse_fail_fast: --("analyse-fail-fast") "Stop analyse at first error.")
+    (@arg unknown_ops: --("unknown-ops") +takes_value possible_values(&["stub", "error", "skip"])
+     "What to do with unknown operators: load stubs (default), fail, or drop them, their \
+      outputs becoming extra model inputs (onnx only).")
+    (@arg allow_unimplemented: --("allow-unimplemented")
+     "Load unknown operators as stubs, same as --unknown-ops stub (onnx only).")
     (@arg recursive: --recursive "Apply to sub graphes")
 
     (@arg proto: --proto "Keep proto model around after parse")
     (@arg determinize: --determinize "Enforce a seed in random operator")
 
     (@arg partial: --partial "Before analyse, eliminate dead branches")
+    (@arg declutter_steps: --("declutter-steps") +takes_value
+     "Apply at most this number of declutter rules instead of a full declutter")
 
     (@arg pass: --pass +takes_value
      possible_values(&["load", "analyse", "incorporate", "type", "declutter",
                      "pulse-normalized", "pulse", "pulse-to-type", "pulse-declutter",
-                     "optimize"])
+                     "optimize", "optimize-size"])
      "Pass to stop preprocessing after.")
 
     (@arg optimize: -O --optimize "Optimize before running")
+    (@arg mmm_f32_kernel: --("mmm-f32-kernel") +takes_value
+     "Force f32 matrix multiplication kernel (generic, fma, vfpv2, neon or arm64simd)")
+    (@arg deterministic: --deterministic
+     "Only use the generic kernels when optimizing, for bit-identical results across machines")
+    (@arg assert_typed: --("assert-typed")
+     "Fail if some shapes are not fully resolved after typing")
+    (@arg max_im2col_bytes: --("max-im2col-bytes") +takes_value
+     "Compute convolutions directly when their im2col buffer would be bigger than this")
     (@arg pulse: --pulse +takes_value "Translate to pulse network")
+    (@arg print_delays: --("print-delays")
+     "Print the delay of each output of the pulsed network, in frames")
 
     (@arg verbosity: -v ... "Sets the level of verbosity.")
 
     (@arg machine_friendly: --("machine-friendly") "Machine friendly output")
 
     (@arg list_ops: --("list-ops") "List all known operators")
+    (@arg against_opset: --("against-opset") +takes_value
+     "With --list-ops, report supported and missing operators of an ONNX opset")
     );
 
     let compare = clap::SubCommand::with_name("compare")
@@ -161,6 +199,12 @@ fn main() {
                 .takes_value(false)
                 .help("Do not reset with reference values at each node"),
         )
+        .arg(
+            Arg::with_name("bisect")
+                .long("bisect")
+                .takes_value(false)
+                .help("Only report the first node diverging from reference, in cumulative mode"),
+        )
         .arg(
             Arg::with_name("resilient")
                 .long("resilient")
@@ -177,6 +221,12 @@ fn main() {
                 .takes_value(false)
                 .help("Do not reset with reference values at each node"),
         )
+        .arg(
+            Arg::with_name("bisect")
+                .long("bisect")
+                .takes_value(false)
+                .help("Only report the first node diverging from reference, in cumulative mode"),
+        )
         .arg(Arg::with_name("npz").takes_value(true).required(true).help("Npz filename"));
     app = app.subcommand(output_options(compare_npz));
 
@@ -190,6 +240,12 @@ fn main() {
                 .takes_value(false)
                 .help("Do not reset with reference values at each node"),
         )
+        .arg(
+            Arg::with_name("bisect")
+                .long("bisect")
+                .takes_value(false)
+                .help("Only report the first node diverging from reference, in cumulative mode"),
+        )
         .arg(Arg::with_name("pbdir").takes_value(true).required(true).help("protobuf dir"));
     app = app.subcommand(output_options(compare_pbdir));
 
@@ -207,11 +263,34 @@ fn main() {
         .long_about("Dumps the Tensorflow graph in human readable form.")
         .arg(Arg::with_name("cost").long("cost").help("Include const information"))
         .arg(Arg::with_name("profile").long("profile").help("Include results for profile run"))
+        .arg(
+            Arg::with_name("metrics")
+            .takes_value(true)
+            .long("metrics")
+            .requires("profile")
+            .help("Write profiled time per op type to this file, as Prometheus metrics")
+            )
+        .arg(
+            Arg::with_name("explain-fused")
+            .long("explain-fused")
+            .requires("profile")
+            .help("Show the fused operations of each node, and an estimated breakdown of their cost")
+            )
+        .arg(
+            Arg::with_name("text")
+            .long("text")
+            .help("Print the typed model in a stable textual form, suitable for diffing")
+            )
+        .arg(
+            Arg::with_name("receptive-field")
+            .long("receptive-field")
+            .help("Print the receptive field (size, stride) of each output per spatial axis")
+            )
         .arg(
             Arg::with_name("assert-cost")
             .takes_value(true)
             .long("assert-cost")
-            .help("Checks computed against the provided value (form: \"FMA(F32)=2060448 DIV(F32)=24576\")")
+            .help("Checks computed against the provided values (form: \"FMA(F32)=2060448,Div(F32)=24576\", or with bounds: \"FMA(F32)<=2100000\", \"FMA(F32)~=2060448±1%\")")
             )
         .arg(
             Arg::with_name("assert-output")
@@ -240,11 +319,30 @@ fn main() {
     let run = clap::SubCommand::with_name("run")
         .long_about("Run the graph")
         .arg(Arg::with_name("dump").long("dump").help("Show output"))
+        .arg(
+            Arg::with_name("checksum")
+                .long("checksum")
+                .help("Print a stable 64-bit checksum of each output"),
+        )
+        .arg(
+            Arg::with_name("stream-npz")
+                .takes_value(true)
+                .long("stream-npz")
+                .help("Stream the input from this npz through the pulsed model, pulse by pulse"),
+        )
+        .arg(
+            Arg::with_name("dump-on-fail")
+                .takes_value(true)
+                .long("dump-on-fail")
+                .help("Write outputs to this npz file if an assertion fails"),
+        )
         .arg(
             Arg::with_name("assert-output-bundle")
                 .takes_value(true)
+                .number_of_values(1)
+                .multiple(true)
                 .long("assert-output-bundle")
-                .help("Checks values against these tensor (.npz)"),
+                .help("Checks values against these tensor (.npz), passes if any bundle matches"),
         )
         .arg(
             Arg::with_name("assert-output")
@@ -268,7 +366,13 @@ fn main() {
     app = app.subcommand(output_options(optimize_check));
 
     let stream_check = clap::SubCommand::with_name("stream-check")
-        .long_about("Compare output of streamed and regular exec");
+        .long_about("Compare output of streamed and regular exec")
+        .arg(
+            Arg::with_name("pulses")
+                .long("pulses")
+                .takes_value(true)
+                .help("Comma-separated pulse sizes to check, skipping the invalid ones"),
+        );
     app = app.subcommand(output_options(stream_check));
 
     let matches = app.get_matches();
@@ -297,6 +401,7 @@ fn main() {
     let env = env_logger::Env::default().filter_or(env_logger::DEFAULT_FILTER_ENV, "warn");
 
     env_logger::Builder::from_env(env).format_timestamp_nanos().init();
+
     info_usage("init", probe.as_ref());
 
     if let Err(e) = handle(matches, probe.as_ref()) {
@@ -405,6 +510,9 @@ pub struct Parameters {
 
     input_values: Vec<Option<Arc<Tensor>>>,
 
+    /// Symbol values given by --set.
+    symbols: HashMap<char, i64>,
+
     assertions: Option<Assertions>,
 
     machine_friendly: bool,
@@ -415,10 +523,69 @@ type TfExt = tract_tensorflow::model::TfModelExtensions;
 #[cfg(not(feature = "tf"))]
 type TfExt = ();
 
+/// Streaming axis and symbol requested by --stream-axis for the input #ix.
+///
+/// Values are `axis` or `axis:SYMBOL`, the symbol defaulting to `S`.
+fn stream_axis(matches: &clap::ArgMatches, ix: usize) -> CliResult<Option<(usize, TDim)>> {
+    let values = match matches.values_of("stream_axis") {
+        Some(values) => values.collect::<Vec<_>>(),
+        None => return Ok(None),
+    };
+    let value = values.get(ix).or(values.last()).unwrap();
+    let mut split = value.splitn(2, ':');
+    let axis = split.next().unwrap().parse()?;
+    let sym = match split.next() {
+        Some(sym) if sym.len() == 1 && sym.chars().all(|c| c.is_ascii_alphabetic()) => {
+            TDim::sym(sym.chars().next().unwrap())
+        }
+        Some(sym) => bail!("Invalid stream symbol {:?}, expected a single letter", sym),
+        None => TDim::s(),
+    };
+    Ok(Some((axis, sym)))
+}
+
+/// Symbol values requested by --set, as `SYMBOL=value`.
+fn symbol_values(matches: &clap::ArgMatches) -> CliResult<HashMap<char, i64>> {
+    let mut symbols = HashMap::new();
+    for set in matches.values_of("set").into_iter().flatten() {
+        let mut split = set.splitn(2, '=');
+        let sym = split.next().unwrap();
+        let value =
+            split.next().ok_or_else(|| format!("Invalid --set {:?}, expected N=value", set))?;
+        if sym.len() != 1 || !sym.chars().all(|c| c.is_ascii_alphabetic()) {
+            bail!("Invalid symbol {:?}, expected a single letter", sym)
+        }
+        symbols.insert(sym.chars().next().unwrap(), value.parse()?);
+    }
+    Ok(symbols)
+}
+
+/// Codegen options requested by --max-im2col-bytes, --mmm-f32-kernel and
+/// --deterministic.
+fn codegen_config(matches: &clap::ArgMatches) -> CliResult<CodegenConfig> {
+    let max_im2col_bytes = match matches.value_of("max_im2col_bytes") {
+        Some(max) => Some(max.parse::<usize>().map_err(|_| {
+            format!("Invalid --max-im2col-bytes {:?}, expected a number of bytes", max)
+        })?),
+        None => None,
+    };
+    let kernel_selector =
+        if matches.is_present("mmm_f32_kernel") || matches.is_present("deterministic") {
+            Some(KernelSelector {
+                deterministic: matches.is_present("deterministic"),
+                mmm_f32_kernel: matches.value_of("mmm_f32_kernel").map(|k| k.to_string()),
+            })
+        } else {
+            None
+        };
+    Ok(CodegenConfig { max_im2col_bytes, kernel_selector })
+}
+
 impl Parameters {
     #[allow(unused_variables)]
     /// Parses the command-line arguments.
     pub fn from_clap(matches: &clap::ArgMatches, probe: Option<&Probe>) -> CliResult<Parameters> {
+        let codegen_config = codegen_config(matches)?;
         let filename = matches.value_of("model").ok_or("Model argument required")?;
         let filename = std::path::PathBuf::from(filename);
         let (filename, onnx_tc) =
@@ -457,7 +624,13 @@ impl Parameters {
             }
             #[cfg(feature = "onnx")]
             "onnx" => {
-                let onnx = tract_onnx::onnx();
+                let unknown_ops = match matches.value_of("unknown_ops") {
+                    _ if matches.is_present("allow_unimplemented") => tract_onnx::UnknownOps::Stub,
+                    Some("error") => tract_onnx::UnknownOps::Error,
+                    Some("skip") => tract_onnx::UnknownOps::Skip,
+                    _ => tract_onnx::UnknownOps::Stub,
+                };
+                let onnx = tract_onnx::onnx().with_unknown_ops(unknown_ops);
                 info_usage("loaded framework (onnx)", probe);
                 let graph = onnx.proto_model_for_path(&filename)?;
                 info_usage("proto model loaded", probe);
@@ -545,18 +718,16 @@ impl Parameters {
         };
 
         if let Some(override_facts) = matches.values_of("override_fact") {
-            for fact in override_facts {
-                let (name, fact) = tensor::for_string(fact)?;
-                let node = raw_model.node_by_name(name.unwrap())?.id;
+            for spec in override_facts {
+                let (name, fact) = tensor::for_string(spec)?;
+                let name =
+                    name.ok_or_else(|| format!("--override-fact needs a node name ({})", spec))?;
+                let node = raw_model.node_by_name(name)?.id;
                 raw_model.set_outlet_fact(OutletId::new(node, 0), fact)?;
             }
         };
 
-        let output_names = raw_model
-            .output_outlets()?
-            .iter()
-            .map(|o| raw_model.node(o.node).name.to_string())
-            .collect();
+        let output_names = raw_model.output_names().iter().map(|s| s.to_string()).collect();
 
         if let Some(sub) = matches.value_of("kaldi_downsample") {
             let period = sub.parse::<isize>()?;
@@ -619,8 +790,8 @@ impl Parameters {
                 raw_model.node_mut(outlet.node).inputs.clear();
                 raw_model.node_mut(outlet.node).op =
                     Box::new(tract_hir::ops::source::Source::new());
-                if let Some(s) = matches.value_of("stream_axis") {
-                    t.shape.set_dim(s.parse()?, TDim::s());
+                if let Some((axis, sym)) = stream_axis(matches, ix)? {
+                    t.shape.set_dim(axis, sym);
                 }
                 info!("Input #{}: {:?}", ix, t);
                 raw_model.set_outlet_fact(outlet, t.without_value())?;
@@ -642,8 +813,8 @@ impl Parameters {
                     if let Ok(t) = tensor::for_npz(&mut npz, &name) {
                         let shape = t.shape().to_vec();
                         let mut fact = InferenceFact::dt_shape(t.datum_type(), shape);
-                        if let Some(s) = matches.value_of("stream_axis") {
-                            fact.shape.set_dim(s.parse()?, TDim::s());
+                        if let Some((axis, sym)) = stream_axis(matches, ix)? {
+                            fact.shape.set_dim(axis, sym);
                         }
                         raw_model.set_input_fact(ix, fact.without_value())?;
                         input_values[ix] = Some(t.into_arc_tensor());
@@ -699,19 +870,37 @@ impl Parameters {
 
         let const_inputs = matches.values_of("const_input").map(|c| c.collect()).unwrap_or(vec![]);
         for i in (0..raw_model.inputs.len()).rev() {
-            let input = raw_model.inputs[i];
-            if const_inputs.contains(&raw_model.node_name(input.node)) {
-                if let Some(v) = input_values[i].take() {
-                    raw_model.node_mut(input.node).op = Box::new(
-                        tract_core::ops::konst::Const::new(v),
-                    );
+            let name = raw_model.node_name(raw_model.inputs[i].node).to_string();
+            if const_inputs.contains(&&*name) {
+                if let Some(v) = input_values.remove(i) {
+                    utils::freeze_input(&mut raw_model, &name, v)?;
                 } else {
-                    bail!(
-                        "Don't have value for input {}, can't make it const",
-                        raw_model.node_name(input.node)
-                    );
+                    bail!("Don't have value for input {}, can't make it const", name);
                 }
-                raw_model.inputs.remove(i);
+            }
+        }
+
+        if let Some(freezes) = matches.values_of("freeze_input") {
+            for spec in freezes {
+                let mut split = spec.splitn(2, "=");
+                let name = split.next().unwrap();
+                let file = split
+                    .next()
+                    .filter(|f| f.starts_with("@"))
+                    .ok_or_else(|| format!("--freeze-input expects name=@file, got {}", spec))?;
+                let value = tensor::for_data(&file[1..])?
+                    .1
+                    .value
+                    .concretize()
+                    .ok_or_else(|| format!("No value found in {}", file))?;
+                let ix = utils::freeze_input(&mut raw_model, name, value)?;
+                input_values.remove(ix);
+            }
+        }
+
+        if let Some(labels) = matches.values_of("set_outlet_label") {
+            for spec in labels {
+                utils::set_outlet_label(&mut raw_model, spec)?;
             }
         }
 
@@ -779,12 +968,43 @@ impl Parameters {
                         return Ok(Box::new(model) as _);
                     }
                 };
+                if matches.is_present("assert_typed") {
+                    model.assert_fully_typed()?;
+                }
                 if stop_at == "type" {
                     return Ok(Box::new(model) as _);
                 }
                 info_usage("after type", probe);
                 info!("Running 'declutter'");
-                let mut model = model.declutter()?;
+                let mut model = if let Some(steps) = matches.value_of("declutter_steps") {
+                    let mut model = model;
+                    for _ in 0..steps.parse::<usize>()? {
+                        let (decluttered, rule) = model.declutter_once()?;
+                        model = decluttered;
+                        if let Some(rule) = rule {
+                            info!("Applied declutter rule for {}", rule);
+                        } else {
+                            break;
+                        }
+                    }
+                    model
+                } else {
+                    model.declutter()?
+                };
+                if matches.is_present("check_finite") {
+                    model = tract_core::ops::check_finite::check_finite_outputs(&model)?;
+                }
+                if matches.is_present("list_dynamic") {
+                    for outlet in model.dynamic_outputs() {
+                        let node = model.node(outlet.node);
+                        println!(
+                            "Dynamic shape: {}:{} {:?}",
+                            node.name,
+                            outlet.slot,
+                            model.outlet_fact(outlet)?
+                        );
+                    }
+                }
                 typed_model = Some(model.clone());
                 if stop_at == "declutter" {
                     return Ok(Box::new(model) as _);
@@ -799,6 +1019,15 @@ impl Parameters {
                     info_usage("after pulse-normalize", probe);
                     info!("Running 'pulse' ({})", pulse);
                     let pulsed = ::tract_core::pulse::PulsedModel::new(&normalized_model, pulse)?;
+                    for (ix, delay) in pulsed.output_delays()?.into_iter().enumerate() {
+                        if !matches.is_present("print_delays") {
+                            info!("Pulsed output #{} delay: {} frames", ix, delay);
+                        } else if machine_friendly {
+                            println!("output_delay_{}: {}", ix, delay);
+                        } else {
+                            println!("Pulsed output #{} delay: {} frames", ix, delay);
+                        }
+                    }
                     if stop_at == "pulse" {
                         return Ok(Box::new(pulsed) as _);
                     }
@@ -816,8 +1045,14 @@ impl Parameters {
                     }
                     info_usage("after pulse-declutter", probe);
                 }
+                if stop_at == "optimize-size" {
+                    info!("Running 'optimize-size'");
+                    model = model.codegen_for_size()?;
+                    info_usage("after optimize-size", probe);
+                    return Ok(Box::new(model) as _);
+                }
                 info!("Running 'optimize'");
-                model = model.codegen()?;
+                model = model.codegen_with_config(&codegen_config)?;
                 info_usage("after optimize", probe);
                 Ok(Box::new(model) as _)
             })()?
@@ -834,6 +1069,7 @@ impl Parameters {
             tract_model,
             tf_model,
             input_values,
+            symbols: symbol_values(matches)?,
             output_names,
             assertions: None,
             machine_friendly,
@@ -881,7 +1117,8 @@ pub fn display_params_from_clap(
         //        successors: matches.value_of("successors").map(|id| id.parse().unwrap()),
         expect_canonic: root_matches.value_of("pass").unwrap_or("declutter") == "declutter"
             && !root_matches.is_present("optimize"),
-        outlet_labels: matches.is_present("outlet-labels"),
+        outlet_labels: matches.is_present("outlet-labels")
+            || root_matches.is_present("set_outlet_label"),
         io: if matches.is_present("io-long") {
             display_params::Io::Long
         } else if matches.is_present("io-none") {
@@ -896,40 +1133,46 @@ pub fn display_params_from_clap(
 
 pub struct Assertions {
     assert_outputs: Option<Vec<Option<Arc<Tensor>>>>,
+    assert_output_bundles: Option<Vec<Vec<Option<Arc<Tensor>>>>>,
     assert_output_facts: Option<Vec<InferenceFact>>,
 }
 
 impl Assertions {
     fn from_clap(sub_matches: &clap::ArgMatches, output_names: &[String]) -> CliResult<Assertions> {
-        let mut assert_outputs: Option<Vec<Option<Arc<Tensor>>>> = sub_matches
+        let assert_outputs: Option<Vec<Option<Arc<Tensor>>>> = sub_matches
             .values_of("assert-output")
             .map(|vs| vs.map(|v| tensor::for_string(v).unwrap().1.value.concretize()).collect());
 
-        if assert_outputs.is_none() {
-            if sub_matches.values_of("assert-output-bundle").is_some() {
-                let values = output_names
-                    .iter()
-                    .map(move |name| {
-                        let npy_name = format!("{}.npy", name);
-                        for output_bundle in sub_matches.values_of("assert-output-bundle").unwrap()
-                        {
-                            let mut npz =
-                                ndarray_npy::NpzReader::new(std::fs::File::open(output_bundle)?)?;
-                            if let Ok(t) = tensor::for_npz(&mut npz, &npy_name) {
-                                return Ok(Some(t.into_arc_tensor()));
-                            }
-                        }
-                        return Ok(None);
-                    })
-                    .collect::<CliResult<_>>()?;
-                assert_outputs = Some(values)
-            }
-        }
+        let assert_output_bundles = if assert_outputs.is_none() {
+            sub_matches
+                .values_of("assert-output-bundle")
+                .map(|bundles| {
+                    bundles
+                        .map(|bundle| Self::read_bundle(bundle, output_names))
+                        .collect::<CliResult<Vec<_>>>()
+                })
+                .transpose()?
+        } else {
+            None
+        };
 
         let assert_output_facts: Option<Vec<InferenceFact>> = sub_matches
             .values_of("assert-output-fact")
             .map(|vs| vs.map(|v| tensor::for_string(v).unwrap().1).collect());
-        Ok(Assertions { assert_outputs, assert_output_facts })
+        Ok(Assertions { assert_outputs, assert_output_bundles, assert_output_facts })
+    }
+
+    /// Reads the expected value of each output from a npz bundle, keyed by
+    /// output name.
+    fn read_bundle(path: &str, output_names: &[String]) -> CliResult<Vec<Option<Arc<Tensor>>>> {
+        let mut npz = ndarray_npy::NpzReader::new(std::fs::File::open(path)?)?;
+        Ok(output_names
+            .iter()
+            .map(|name| {
+                let npy_name = format!("{}.npy", name);
+                tensor::for_npz(&mut npz, &npy_name).ok().map(|t| t.into_arc_tensor())
+            })
+            .collect())
     }
 }
 
@@ -939,6 +1182,23 @@ fn handle(matches: clap::ArgMatches, probe: Option<&Probe>) -> CliResult<()> {
         #[cfg(feature = "onnx")]
         {
             let onnx = tract_onnx::onnx();
+            if let Some(opset) = matches.value_of("against_opset") {
+                let opset = opset.parse()?;
+                let (supported, missing) =
+                    onnx_opset::coverage(opset, |op| onnx.op_register.0.contains_key(op))?;
+                println!(
+                    "Onnx opset {}: {} operators supported out of {}\n",
+                    opset,
+                    supported.len(),
+                    supported.len() + missing.len()
+                );
+                println!("Supported:\n");
+                println!("{}", supported.join(", "));
+                println!("\nMissing:\n");
+                println!("{}", missing.join(", "));
+                println!("\n");
+                return Ok(());
+            }
             let names = onnx.op_register.0.keys().sorted().into_iter().join(", ");
             println!("Onnx:\n");
             println!("{}", names);
@@ -973,6 +1233,7 @@ fn handle(matches: clap::ArgMatches, probe: Option<&Probe>) -> CliResult<()> {
         #[cfg(feature = "conform")]
         ("compare", Some(m)) => compare::handle_tensorflow(
             m.is_present("cumulative"),
+            m.is_present("bisect"),
             m.is_present("resilient"),
             &mut params,
             display_params_from_clap(&matches, m)?,
@@ -982,6 +1243,7 @@ fn handle(matches: clap::ArgMatches, probe: Option<&Probe>) -> CliResult<()> {
 
         ("compare-npz", Some(m)) => compare::handle_npz(
             m.is_present("cumulative"),
+            m.is_present("bisect"),
             m.value_of("npz").unwrap(),
             &params,
             display_params_from_clap(&matches, m)?,
@@ -990,6 +1252,7 @@ fn handle(matches: clap::ArgMatches, probe: Option<&Probe>) -> CliResult<()> {
         #[cfg(feature = "onnx")]
         ("compare-pbdir", Some(m)) => compare::handle_pbdir(
             m.is_present("cumulative"),
+            m.is_present("bisect"),
             m.value_of("pbdir").unwrap(),
             &params,
             display_params_from_clap(&matches, m)?,
@@ -997,7 +1260,13 @@ fn handle(matches: clap::ArgMatches, probe: Option<&Probe>) -> CliResult<()> {
 
         ("run", Some(m)) => {
             params.assertions = Some(Assertions::from_clap(m, &*params.output_names)?);
-            run::handle(&params, m.is_present("dump"))
+            run::handle(
+                &params,
+                m.is_present("dump"),
+                m.value_of("dump-on-fail"),
+                m.is_present("checksum"),
+                m.value_of("stream-npz"),
+            )
         }
 
         ("optimize-check", Some(m)) => {
@@ -1005,7 +1274,11 @@ fn handle(matches: clap::ArgMatches, probe: Option<&Probe>) -> CliResult<()> {
         }
 
         ("stream-check", Some(m)) => {
-            stream_check::handle(&params, &display_params_from_clap(&matches, m)?)
+            let pulses = m
+                .value_of("pulses")
+                .map(|s| s.split(',').map(|p| p.parse()).collect::<Result<Vec<usize>, _>>())
+                .transpose()?;
+            stream_check::handle(&params, &display_params_from_clap(&matches, m)?, pulses)
         }
 
         ("", None) => dump::handle(
@@ -0,0 +1,206 @@
+use crate::errors::*;
+
+/// Last ONNX opset covered by `OPERATORS`.
+pub const LAST_OPSET: usize = 13;
+
+/// ONNX operators of the default domain: name, opset introducing the
+/// operator, and opset deprecating it if any.
+const OPERATORS: &[(&str, usize, Option<usize>)] = &[
+    ("Abs", 1, None),
+    ("Acos", 7, None),
+    ("Acosh", 9, None),
+    ("Add", 1, None),
+    ("And", 1, None),
+    ("ArgMax", 1, None),
+    ("ArgMin", 1, None),
+    ("Asin", 7, None),
+    ("Asinh", 9, None),
+    ("Atan", 7, None),
+    ("Atanh", 9, None),
+    ("AveragePool", 1, None),
+    ("BatchNormalization", 1, None),
+    ("BitShift", 11, None),
+    ("Cast", 1, None),
+    ("Ceil", 1, None),
+    ("Celu", 12, None),
+    ("Clip", 1, None),
+    ("Compress", 9, None),
+    ("Concat", 1, None),
+    ("ConcatFromSequence", 11, None),
+    ("Constant", 1, None),
+    ("ConstantOfShape", 9, None),
+    ("Conv", 1, None),
+    ("ConvInteger", 10, None),
+    ("ConvTranspose", 1, None),
+    ("Cos", 7, None),
+    ("Cosh", 9, None),
+    ("CumSum", 11, None),
+    ("DepthToSpace", 1, None),
+    ("DequantizeLinear", 10, None),
+    ("Det", 11, None),
+    ("Div", 1, None),
+    ("Dropout", 1, None),
+    ("DynamicQuantizeLinear", 11, None),
+    ("Einsum", 12, None),
+    ("Elu", 1, None),
+    ("Equal", 1, None),
+    ("Erf", 9, None),
+    ("Exp", 1, None),
+    ("Expand", 8, None),
+    ("EyeLike", 9, None),
+    ("Flatten", 1, None),
+    ("Floor", 1, None),
+    ("GRU", 1, None),
+    ("Gather", 1, None),
+    ("GatherElements", 11, None),
+    ("GatherND", 11, None),
+    ("Gemm", 1, None),
+    ("GlobalAveragePool", 1, None),
+    ("GlobalLpPool", 1, None),
+    ("GlobalMaxPool", 1, None),
+    ("Greater", 1, None),
+    ("GreaterOrEqual", 12, None),
+    ("HardSigmoid", 1, None),
+    ("Hardmax", 1, None),
+    ("Identity", 1, None),
+    ("If", 1, None),
+    ("InstanceNormalization", 1, None),
+    ("IsInf", 10, None),
+    ("IsNaN", 9, None),
+    ("LRN", 1, None),
+    ("LSTM", 1, None),
+    ("LeakyRelu", 1, None),
+    ("Less", 1, None),
+    ("LessOrEqual", 12, None),
+    ("Log", 1, None),
+    ("LogSoftmax", 1, None),
+    ("Loop", 1, None),
+    ("LpNormalization", 1, None),
+    ("LpPool", 1, None),
+    ("MatMul", 1, None),
+    ("MatMulInteger", 10, None),
+    ("Max", 1, None),
+    ("MaxPool", 1, None),
+    ("MaxRoiPool", 1, None),
+    ("MaxUnpool", 9, None),
+    ("Mean", 1, None),
+    ("MeanVarianceNormalization", 9, None),
+    ("Min", 1, None),
+    ("Mod", 10, None),
+    ("Mul", 1, None),
+    ("Multinomial", 7, None),
+    ("Neg", 1, None),
+    ("NegativeLogLikelihoodLoss", 12, None),
+    ("NonMaxSuppression", 10, None),
+    ("NonZero", 9, None),
+    ("Not", 1, None),
+    ("OneHot", 9, None),
+    ("Or", 1, None),
+    ("PRelu", 1, None),
+    ("Pad", 1, None),
+    ("Pow", 1, None),
+    ("QLinearConv", 10, None),
+    ("QLinearMatMul", 10, None),
+    ("QuantizeLinear", 10, None),
+    ("RNN", 1, None),
+    ("RandomNormal", 1, None),
+    ("RandomNormalLike", 1, None),
+    ("RandomUniform", 1, None),
+    ("RandomUniformLike", 1, None),
+    ("Range", 11, None),
+    ("Reciprocal", 1, None),
+    ("ReduceL1", 1, None),
+    ("ReduceL2", 1, None),
+    ("ReduceLogSum", 1, None),
+    ("ReduceLogSumExp", 1, None),
+    ("ReduceMax", 1, None),
+    ("ReduceMean", 1, None),
+    ("ReduceMin", 1, None),
+    ("ReduceProd", 1, None),
+    ("ReduceSum", 1, None),
+    ("ReduceSumSquare", 1, None),
+    ("Relu", 1, None),
+    ("Reshape", 1, None),
+    ("Resize", 10, None),
+    ("ReverseSequence", 10, None),
+    ("RoiAlign", 10, None),
+    ("Round", 11, None),
+    ("Scan", 8, None),
+    ("Scatter", 9, Some(11)),
+    ("ScatterElements", 11, None),
+    ("ScatterND", 11, None),
+    ("Selu", 1, None),
+    ("SequenceAt", 11, None),
+    ("SequenceConstruct", 11, None),
+    ("SequenceEmpty", 11, None),
+    ("SequenceErase", 11, None),
+    ("SequenceInsert", 11, None),
+    ("SequenceLength", 11, None),
+    ("Shape", 1, None),
+    ("Shrink", 9, None),
+    ("Sigmoid", 1, None),
+    ("Sign", 9, None),
+    ("Sin", 7, None),
+    ("Sinh", 9, None),
+    ("Size", 1, None),
+    ("Slice", 1, None),
+    ("Softmax", 1, None),
+    ("SoftmaxCrossEntropyLoss", 12, None),
+    ("Softplus", 1, None),
+    ("Softsign", 1, None),
+    ("SpaceToDepth", 1, None),
+    ("Split", 1, None),
+    ("SplitToSequence", 11, None),
+    ("Sqrt", 1, None),
+    ("Squeeze", 1, None),
+    ("StringNormalizer", 10, None),
+    ("Sub", 1, None),
+    ("Sum", 1, None),
+    ("Tan", 7, None),
+    ("Tanh", 1, None),
+    ("TfIdfVectorizer", 9, None),
+    ("ThresholdedRelu", 10, None),
+    ("Tile", 1, None),
+    ("TopK", 1, None),
+    ("Transpose", 1, None),
+    ("Unique", 11, None),
+    ("Unsqueeze", 1, None),
+    ("Upsample", 7, Some(10)),
+    ("Where", 9, None),
+    ("Xor", 1, None),
+];
+
+/// Split the operators of an ONNX opset between the ones for which `known`
+/// holds (supported) and the others (missing).
+pub fn coverage(
+    opset: usize,
+    known: impl Fn(&str) -> bool,
+) -> CliResult<(Vec<&'static str>, Vec<&'static str>)> {
+    if opset == 0 || opset > LAST_OPSET {
+        bail!("Unknown ONNX opset {}, expected 1 to {}", opset, LAST_OPSET);
+    }
+    Ok(OPERATORS
+        .iter()
+        .filter(|(_, since, removed)| *since <= opset && removed.map(|r| opset < r).unwrap_or(true))
+        .map(|(name, _, _)| *name)
+        .partition(|name| known(name)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn opset_13_coverage() {
+        let onnx = tract_onnx::onnx();
+        let (supported, missing) = coverage(13, |op| onnx.op_register.0.contains_key(op)).unwrap();
+        assert!(supported.contains(&"Conv"));
+        assert!(missing.contains(&"Unique"));
+        assert!(!supported.contains(&"Upsample") && !missing.contains(&"Upsample"));
+    }
+
+    #[test]
+    fn unknown_opset() {
+        assert!(coverage(LAST_OPSET + 1, |_| true).is_err());
+    }
+}
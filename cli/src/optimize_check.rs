@@ -1,15 +1,92 @@
+use std::collections::BTreeMap;
+use std::fmt;
 use tract_hir::internal::*;
 
 use crate::display_params;
 use crate::{CliResult, Parameters};
 
+/// Op histograms of a model before and after optimization.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FusionReport {
+    pub before: BTreeMap<String, usize>,
+    pub after: BTreeMap<String, usize>,
+}
+
+impl FusionReport {
+    pub fn new(plain: &TypedModel, optimized: &TypedModel) -> FusionReport {
+        FusionReport { before: op_histogram(plain), after: op_histogram(optimized) }
+    }
+
+    pub fn nodes_before(&self) -> usize {
+        self.before.values().sum()
+    }
+
+    pub fn nodes_after(&self) -> usize {
+        self.after.values().sum()
+    }
+
+    /// Op types with less nodes after optimization, and how many went away:
+    /// they have been fused in, or replaced by, other ops.
+    pub fn disappeared(&self) -> Vec<(String, usize)> {
+        histogram_diff(&self.before, &self.after)
+    }
+
+    /// Op types with more nodes after optimization, and how many came in.
+    pub fn appeared(&self) -> Vec<(String, usize)> {
+        histogram_diff(&self.after, &self.before)
+    }
+}
+
+impl fmt::Display for FusionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Nodes: {} before, {} after optimization",
+            self.nodes_before(),
+            self.nodes_after()
+        )?;
+        for (op, count) in self.disappeared() {
+            writeln!(f, " - {} x {}", count, op)?;
+        }
+        for (op, count) in self.appeared() {
+            writeln!(f, " + {} x {}", count, op)?;
+        }
+        Ok(())
+    }
+}
+
+fn op_histogram(model: &TypedModel) -> BTreeMap<String, usize> {
+    let mut histogram = BTreeMap::new();
+    for node in model.nodes() {
+        *histogram.entry(node.op.name().into_owned()).or_insert(0) += 1;
+    }
+    histogram
+}
+
+fn histogram_diff(
+    a: &BTreeMap<String, usize>,
+    b: &BTreeMap<String, usize>,
+) -> Vec<(String, usize)> {
+    a.iter()
+        .filter_map(|(op, &count)| {
+            let other = b.get(op).cloned().unwrap_or(0);
+            if count > other {
+                Some((op.clone(), count - other))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
 pub fn handle(params: &Parameters, _options: display_params::DisplayParams) -> CliResult<()> {
     let plain = params.typed_model.as_ref().unwrap();
     let optimized = params
         .tract_model
         .downcast_ref::<TypedModel>()
         .expect("Can only optmize-check typed models");
-    let generated = crate::tensor::make_inputs(&[plain.input_fact(0)?])?;
+    print!("{}", FusionReport::new(plain, optimized));
+    let generated = crate::tensor::make_inputs(&[plain.input_fact(0)?], &params.symbols)?;
 
     let original_plan = SimplePlan::new(plain)?;
     let mut original_state = SimpleState::new(original_plan)?;
@@ -66,3 +143,36 @@ pub fn handle(params: &Parameters, _options: display_params::DisplayParams) -> C
     info!("Looks good!");
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ops::cnn::{ConvUnary, KernelFormat, PaddingSpec, PoolSpec};
+    use tract_core::ops::math::{add, scalar_max};
+    use tract_core::ops::nn::DataFormat;
+
+    #[test]
+    fn conv_bias_relu_fusion_is_reported() {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [1usize, 5, 5, 2].as_ref()).unwrap();
+        let source = model.add_source("input", fact).unwrap();
+        let pool_spec =
+            PoolSpec::new(DataFormat::NHWC, tvec!(3, 3), PaddingSpec::Valid, None, None, Some(3));
+        let kernel = Tensor::zero::<f32>(&[3, 3, 2, 3]).unwrap().into_arc_tensor();
+        let conv = ConvUnary::new(pool_spec, KernelFormat::HWIO, kernel, 1, None, None);
+        let wire = model.wire_node("conv", conv, &[source]).unwrap();
+        let bias = add::unary(rctensor4(&[[[[1f32, 2., 3.]]]]));
+        let wire = model.wire_node("bias", bias, &wire).unwrap();
+        let wire = model.wire_node("relu", scalar_max(tensor0(0f32)), &wire).unwrap();
+        model.set_output_outlets(&wire).unwrap();
+
+        let optimized = model.clone().into_optimized().unwrap();
+        let report = FusionReport::new(&model, &optimized);
+        assert_eq!(report.nodes_before(), 4);
+        assert!(report.nodes_after() < report.nodes_before());
+        let disappeared = report.disappeared();
+        assert!(disappeared.contains(&("Add".to_string(), 1)));
+        assert!(disappeared.contains(&("ScalarMax".to_string(), 1)));
+        assert!(report.to_string().contains("- 1 x ScalarMax"));
+    }
+}
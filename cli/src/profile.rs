@@ -3,6 +3,8 @@ use tract_core::internal::*;
 use crate::annotations::*;
 use crate::errors::*;
 use crate::BenchLimits;
+use std::collections::BTreeMap;
+use std::fmt::Write;
 use std::time::{Duration, Instant};
 
 trait Scalable {
@@ -27,6 +29,7 @@ pub fn profile(
     model: &TypedModel,
     bench_limits: &BenchLimits,
     dg: &mut Annotations,
+    symbols: &HashMap<char, i64>,
 ) -> CliResult<()> {
     info!("Running entire network");
     let plan = SimplePlan::new(model)?;
@@ -35,7 +38,7 @@ pub fn profile(
     let start = Instant::now();
     while iters < bench_limits.max_iters && start.elapsed() < bench_limits.max_time {
         let _ = state.run_plan_with_eval(
-            crate::tensor::make_inputs_for_model(model)?,
+            crate::tensor::make_inputs_for_model(model, symbols)?,
             |session_state, state, node, input| {
                 let start = Instant::now();
                 let r = tract_core::plan::eval(session_state, state, node, input);
@@ -76,7 +79,7 @@ pub fn profile(
                         let inner_plan = SimplePlan::new(inner_model)?;
                         let mut state = SimpleState::new(inner_plan)?;
                         let _ = state.run_plan_with_eval(
-                            crate::tensor::make_inputs_for_model(inner_model)?,
+                            crate::tensor::make_inputs_for_model(inner_model, symbols)?,
                             |session_state, state, node, input| {
                                 let start = Instant::now();
                                 let r = tract_core::plan::eval(session_state, state, node, input);
@@ -108,3 +111,123 @@ pub fn profile(
     dg.profile_summary = Some(ProfileSummary { max, sum, entire, iters });
     Ok(())
 }
+
+/// Render the profiled time spent in each op type as Prometheus gauges.
+pub fn prometheus_metrics(model: &dyn Model, annotations: &Annotations) -> CliResult<String> {
+    let mut by_op: BTreeMap<String, Duration> = BTreeMap::new();
+    for (k, v) in &annotations.tags {
+        let op = k.model(model).ok_or("Invalid node id")?.node_op(k.1).name().to_string();
+        *by_op.entry(op).or_default() += v.profile.unwrap_or(Duration::default());
+    }
+    let mut metrics = String::new();
+    writeln!(metrics, "# HELP tract_op_seconds Time spent per iteration in each op type.")?;
+    writeln!(metrics, "# TYPE tract_op_seconds gauge")?;
+    for (op, dur) in by_op {
+        writeln!(
+            metrics,
+            "tract_op_seconds{{op=\"{}\"}} {:.9}",
+            op.replace('\\', "\\\\").replace('"', "\\\""),
+            dur.as_secs_f64()
+        )?;
+    }
+    Ok(metrics)
+}
+
+/// Explain a node with fused operations (typically a matrix multiplication
+/// that absorbed a bias or an activation): the chain of fused operations, and
+/// an estimated breakdown between the gemm and the fused element-wise work.
+pub fn explain_fused(model: &TypedModel, node: &TypedNode) -> CliResult<Option<Vec<String>>> {
+    let ops: Vec<String> = node.op.fused_ops().iter().map(|op| format!("{:?}", op)).collect();
+    if ops.is_empty() {
+        return Ok(None);
+    }
+    let inputs = model.node_input_facts(node.id)?;
+    let fma = node
+        .op
+        .cost(&*inputs)?
+        .iter()
+        .filter_map(|(cost, n)| if let Cost::FMA(_) = cost { Some(n.to_integer()) } else { None })
+        .sum::<TractResult<i64>>()? as usize;
+    let elements = model
+        .outlet_fact(OutletId::new(node.id, 0))?
+        .shape
+        .as_finite()
+        .map(|shape| shape.iter().product::<usize>())
+        .ok_or("Fused node output must have a finite shape")?;
+    let non_linear = ops.len() * elements;
+    let total = (fma + non_linear).max(1) as f32;
+    Ok(Some(vec![
+        format!("Fused ops: {}", ops.join(", ")),
+        format!(
+            "Estimated breakdown: gemm {} FMA ({:.1}%), fused {} ops x {} elements ({:.1}%)",
+            fma,
+            100.0 * fma as f32 / total,
+            ops.len(),
+            elements,
+            100.0 * non_linear as f32 / total
+        ),
+    ]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn explain_fused_conv_lists_activation() {
+        use tract_core::ops::cnn::{ConvUnary, KernelFormat, PaddingSpec, PoolSpec};
+        use tract_core::ops::nn::DataFormat;
+        let conv = ConvUnary::new(
+            PoolSpec::new(DataFormat::NCHW, tvec!(3), PaddingSpec::Valid, None, None, Some(1)),
+            KernelFormat::OIHW,
+            rctensor3(&[[[1f32, 1., 1.]]]),
+            1,
+            None,
+            None,
+        );
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [1, 1, 32].as_ref()).unwrap();
+        let source = model.add_source("source", fact).unwrap();
+        let wire = model.wire_node("conv", conv, &[source]).unwrap();
+        let relu = tract_core::ops::math::scalar_max(tensor0(0f32));
+        let wire = model.wire_node("relu", relu, &wire).unwrap();
+        model.set_output_outlets(&wire).unwrap();
+        let model = model.into_optimized().unwrap();
+        let explained = model
+            .nodes()
+            .iter()
+            .filter_map(|node| explain_fused(&model, node).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(explained.len(), 1);
+        assert_eq!(explained[0][0], "Fused ops: Max(0.0)");
+        // 30 outputs, 3 FMA each, and one max per output
+        assert!(explained[0][1].starts_with("Estimated breakdown: gemm 90 FMA"));
+        assert!(explained[0][1].contains("fused 1 ops x 30 elements"));
+    }
+
+    #[test]
+    fn metrics_per_op_type() {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [2].as_ref()).unwrap();
+        let source = model.add_source("source", fact).unwrap();
+        let a = model.wire_node("a", tract_core::ops::math::exp(), &[source]).unwrap();
+        let b = model.wire_node("b", tract_core::ops::math::exp(), &a).unwrap();
+        model.wire_node("c", tract_core::ops::math::abs(), &b).unwrap();
+        model.auto_outputs().unwrap();
+        let mut annotations = Annotations::default();
+        for id in 0..4 {
+            annotations.node_mut(NodeQId(tvec!(), id)).profile =
+                Some(Duration::from_millis(10 * id as u64));
+        }
+        let metrics = prometheus_metrics(&model, &annotations).unwrap();
+        let lines: Vec<&str> = metrics.lines().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(lines.len(), 3);
+        for op in &["Abs", "Exp", "Source"] {
+            let line = lines
+                .iter()
+                .find(|l| l.starts_with(&*format!("tract_op_seconds{{op=\"{}\"}} ", op)))
+                .unwrap();
+            assert!(line.split(' ').nth(1).unwrap().parse::<f64>().is_ok());
+        }
+    }
+}
@@ -1,10 +1,16 @@
 use crate::errors::*;
-use crate::{Model, Parameters};
+use crate::{Assertions, Model, Parameters};
 use tract_hir::internal::*;
 
-pub fn handle(params: &Parameters, dump: bool) -> CliResult<()> {
+pub fn handle(
+    params: &Parameters,
+    dump: bool,
+    dump_on_fail: Option<&str>,
+    checksum: bool,
+    stream_npz: Option<&str>,
+) -> CliResult<()> {
     let outputs = if let Some(pulse) = params.tract_model.downcast_ref::<PulsedModel>() {
-        run_pulse_t(pulse, &params)?
+        run_pulse_t(pulse, &params, stream_npz)?
     } else {
         dispatch_model!(params.tract_model, |m| run_regular(m, &params))?
     };
@@ -15,84 +21,263 @@ pub fn handle(params: &Parameters, dump: bool) -> CliResult<()> {
         }
     }
 
-    if let Some(asserts) = &params.assertions {
-        if let Some(asserts) = &asserts.assert_outputs {
-            crate::utils::check_outputs(&*outputs, &asserts)?;
+    if checksum {
+        for (ix, output) in outputs.iter().enumerate() {
+            let name = params.output_names.get(ix).map(|s| &**s).unwrap_or("");
+            println!("output #{} {}: {:016x}", ix, name, crate::tensor::checksum(output)?);
         }
-        if let Some(facts) = &asserts.assert_output_facts {
-            let outputs: Vec<InferenceFact> = outputs
-                .iter()
-                .map(|t| InferenceFact::dt_shape(t.datum_type(), t.shape()))
-                .collect();
-            crate::utils::check_inferred(&*outputs, &*facts)?;
+    }
+
+    if let Some(asserts) = &params.assertions {
+        if let Err(e) = check_assertions(&outputs, asserts) {
+            if let Some(path) = dump_on_fail {
+                dump_outputs(path, &outputs, &params.output_names)?;
+            }
+            return Err(e);
         }
     }
 
     Ok(())
 }
 
+fn check_assertions(outputs: &[Arc<Tensor>], asserts: &Assertions) -> CliResult<()> {
+    if let Some(asserts) = &asserts.assert_outputs {
+        crate::utils::check_outputs(&*outputs, &asserts)?;
+    }
+    if let Some(bundles) = &asserts.assert_output_bundles {
+        if let Some(ix) = bundles.iter().position(|b| b.iter().all(|t| t.is_none())) {
+            bail!("Reference bundle #{} holds none of the model outputs", ix);
+        }
+        let mut errors = vec![];
+        let matched = bundles.iter().enumerate().any(|(ix, bundle)| {
+            match crate::utils::check_outputs(&*outputs, &bundle) {
+                Ok(()) => {
+                    info!("Outputs match reference bundle #{}.", ix);
+                    true
+                }
+                Err(e) => {
+                    errors.push(format!("bundle #{}: {}", ix, e));
+                    false
+                }
+            }
+        });
+        if !matched {
+            bail!("Outputs match none of the reference bundles:\n{}", errors.join("\n"));
+        }
+    }
+    if let Some(facts) = &asserts.assert_output_facts {
+        let outputs: Vec<InferenceFact> =
+            outputs.iter().map(|t| InferenceFact::dt_shape(t.datum_type(), t.shape())).collect();
+        crate::utils::check_inferred(&*outputs, &*facts)?;
+    }
+    Ok(())
+}
+
+/// Write the outputs to a npz file, keyed by output node name.
+fn dump_outputs(path: &str, outputs: &[Arc<Tensor>], names: &[String]) -> CliResult<()> {
+    let names: Vec<String> = (0..outputs.len())
+        .map(|ix| names.get(ix).cloned().unwrap_or_else(|| format!("output_{}", ix)))
+        .collect();
+    crate::tensor::write_npz(path, names.iter().map(|s| &**s).zip(outputs.iter().map(|t| &**t)))
+}
+
 fn run_regular(tract: &dyn Model, params: &Parameters) -> CliResult<TVec<Arc<Tensor>>> {
+    let mut symbols = params.symbols.clone();
+    for (ix, input) in tract.input_outlets().iter().enumerate() {
+        if let Some(value) = params.input_values.get(ix).and_then(|x| x.as_ref()) {
+            crate::tensor::bind_symbols(&tract.outlet_typedfact(*input)?, value, &mut symbols);
+        }
+    }
     let mut inputs: TVec<Tensor> = tvec!();
     for (ix, input) in tract.input_outlets().iter().enumerate() {
         if let Some(input) = params.input_values.get(ix).and_then(|x| x.as_ref()) {
             inputs.push(input.clone().into_tensor())
         } else {
             let fact = tract.outlet_typedfact(*input)?;
-            inputs.push(crate::tensor::tensor_for_fact(&fact, None)?);
+            inputs.push(crate::tensor::tensor_for_fact(&fact, None, &symbols)?);
         }
     }
     Ok(dispatch_model!(tract, |m| SimplePlan::new(m)?.run(inputs))?)
 }
 
-fn run_pulse_t(model: &PulsedModel, params: &Parameters) -> CliResult<TVec<Arc<Tensor>>> {
+fn run_pulse_t(
+    model: &PulsedModel,
+    params: &Parameters,
+    stream_npz: Option<&str>,
+) -> CliResult<TVec<Arc<Tensor>>> {
+    let input = if let Some(path) = stream_npz {
+        read_stream_input(model, path)?
+    } else {
+        params.input_values[0].as_ref().ok_or("No input value to stream")?.clone()
+    };
+    Ok(tvec!(stream(model, &input, &params.symbols)?.into_arc_tensor()))
+}
+
+/// Reads the streamed input from a npz, keyed by the input name, or as its
+/// single array.
+fn read_stream_input(model: &PulsedModel, path: &str) -> CliResult<Arc<Tensor>> {
+    let mut npz = ndarray_npy::NpzReader::new(std::fs::File::open(path)?)?;
+    let name = format!("{}.npy", model.node(model.input_outlets()?[0].node).name);
+    let names = npz.names()?;
+    let name = if names.contains(&name) {
+        name
+    } else if names.len() == 1 {
+        names[0].clone()
+    } else {
+        bail!("Can not find {} in {}", name, path)
+    };
+    Ok(crate::tensor::for_npz(&mut npz, &name)?.into_arc_tensor())
+}
+
+/// Feeds `input` through the pulsed model, one pulse-sized chunk of the
+/// stream axis at a time, and returns the concatenated output, realigned
+/// with the input.
+///
+/// Zero chunks are fed after the end of the input until the delayed output
+/// is complete.
+fn stream(model: &PulsedModel, input: &Tensor, symbols: &HashMap<char, i64>) -> CliResult<Tensor> {
     let input_fact = model.input_fact(0)?;
     let output_fact = model.output_fact(0)?;
-
-    let output_pulse = output_fact.pulse();
-    //    println!("output_fact: {:?}", output_fact);
-    let axis = input_fact.axis;
-    let input: &Tensor = &params.input_values[0].as_ref().unwrap();
-    //    println!("input_shape: {:?}", input.shape());
+    let (axis, pulse) = (input_fact.axis, input_fact.pulse());
+    let input = input.to_array_view::<f32>()?;
     let input_dim = input.shape()[axis];
-    //    println!("output_fact: {:?}", output_fact);
-    let output_dim = output_fact.dim.eval(input_dim as i32).unwrap() as i32;
-    let mut output_shape = output_fact.shape.to_vec();
-    output_shape[output_fact.axis] =
-        output_dim as usize + output_fact.delay + 4 * output_fact.pulse();
+    let output_dim =
+        output_fact.dim.eval_with(&model.stream_resolver(input_dim, symbols)?)? as usize;
+    let needed = output_fact.delay + output_dim;
+    let chunks = input_dim.div_ceil(pulse).max(needed.div_ceil(output_fact.pulse()));
     let plan = SimplePlan::new(model)?;
     let mut state = ::tract_core::plan::SimpleState::new(&plan)?;
-    //    println!("output_shape: {:?}", output_shape);
-    let pulse = input_fact.pulse();
-    let mut result = tract_ndarray::ArrayD::<f32>::default(output_shape);
-    let input = input.to_array_view::<f32>()?;
-    for ix in 0..input_dim.div_ceil(pulse) {
-        let chunk =
-            input.slice_axis(tract_ndarray::Axis(axis), (ix * pulse..(ix + 1) * pulse).into());
-        let input = if chunk.shape()[input_fact.axis] < pulse {
-            let mut chunk_shape = chunk.shape().to_vec();
-            chunk_shape[input_fact.axis] = pulse;
-            let mut padded_chunk = tract_ndarray::ArrayD::<f32>::default(chunk_shape);
-            padded_chunk
-                .slice_axis_mut(
-                    tract_ndarray::Axis(input_fact.axis),
-                    (..chunk.shape()[input_fact.axis]).into(),
-                )
-                .assign(&chunk);
-            padded_chunk
-        } else {
-            chunk.to_owned()
+    state.session_state.resolved_symbols = symbols.clone();
+    let mut outputs = vec![];
+    for ix in 0..chunks {
+        let (start, end) = ((ix * pulse).min(input_dim), ((ix + 1) * pulse).min(input_dim));
+        let mut chunk_shape = input.shape().to_vec();
+        chunk_shape[axis] = pulse;
+        let mut chunk = tract_ndarray::ArrayD::<f32>::zeros(chunk_shape);
+        chunk
+            .slice_axis_mut(tract_ndarray::Axis(axis), (..end - start).into())
+            .assign(&input.slice_axis(tract_ndarray::Axis(axis), (start..end).into()));
+        if (ix + 1) * pulse >= input_dim {
+            state.session_state.known_stream_len = Some(input_dim);
+        }
+        let output = state.run(tvec!(chunk.into()))?;
+        outputs.push(output[0].to_array_view::<f32>()?.to_owned());
+    }
+    let views: Vec<_> = outputs.iter().map(|o| o.view()).collect();
+    let result = tract_ndarray::stack(tract_ndarray::Axis(output_fact.axis), &views)?;
+    let result = result
+        .slice_axis(tract_ndarray::Axis(output_fact.axis), (output_fact.delay..needed).into());
+    Ok(result.to_owned().into_tensor())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dump_on_failed_assertion() {
+        let asserts = Assertions {
+            assert_outputs: Some(vec![Some(rctensor1(&[1f32])), None]),
+            assert_output_bundles: None,
+            assert_output_facts: None,
+        };
+        let outputs = tvec!(rctensor1(&[2f32]), rctensor1(&[3i32]));
+        assert!(check_assertions(&outputs, &asserts).is_err());
+        let path = std::env::temp_dir().join(format!("tract-dump-{}.npz", std::process::id()));
+        let names = vec!["y".to_string(), "z".to_string()];
+        dump_outputs(path.to_str().unwrap(), &outputs, &names).unwrap();
+        let mut npz = ndarray_npy::NpzReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut found = npz.names().unwrap();
+        found.sort();
+        assert_eq!(found, vec!("y.npy", "z.npy"));
+        assert_eq!(crate::tensor::for_npz(&mut npz, "y.npy").unwrap(), tensor1(&[2f32]));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn any_output_bundle_may_match() {
+        let names = vec!["y".to_string(), "z".to_string()];
+        let outputs = tvec!(rctensor1(&[2f32]), rctensor1(&[3i32]));
+        let paths: Vec<String> = (0..2)
+            .map(|ix| {
+                let name = format!("tract-bundle-{}-{}.npz", std::process::id(), ix);
+                std::env::temp_dir().join(name).to_str().unwrap().to_string()
+            })
+            .collect();
+        dump_outputs(&paths[0], &[rctensor1(&[1f32]), rctensor1(&[3i32])], &names).unwrap();
+        dump_outputs(&paths[1], &outputs, &names).unwrap();
+        let bundles: Vec<_> =
+            paths.iter().map(|p| Assertions::read_bundle(p, &names).unwrap()).collect();
+        let asserts = |bundles: &[Vec<Option<Arc<Tensor>>>]| Assertions {
+            assert_outputs: None,
+            assert_output_bundles: Some(bundles.to_vec()),
+            assert_output_facts: None,
+        };
+        assert!(check_assertions(&outputs, &asserts(&bundles[..1])).is_err());
+        assert!(check_assertions(&outputs, &asserts(&bundles[..])).is_ok());
+        for path in paths {
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn empty_output_bundle_is_an_error() {
+        let outputs = tvec!(rctensor1(&[2f32]));
+        let asserts = Assertions {
+            assert_outputs: None,
+            assert_output_bundles: Some(vec![vec![Some(rctensor1(&[2f32]))], vec![None]]),
+            assert_output_facts: None,
+        };
+        assert!(check_assertions(&outputs, &asserts).is_err());
+    }
+
+    fn dilated_conv(len: TDim) -> NormalizedModel {
+        use tract_core::ops::cnn::{ConvUnary, KernelFormat, PaddingSpec, PoolSpec};
+        use tract_core::ops::nn::DataFormat;
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [len, 1.to_dim()].as_ref()).unwrap();
+        let source = model.add_source("source", fact).unwrap();
+        let pool_spec = PoolSpec::new(
+            DataFormat::HWC,
+            tvec!(3),
+            PaddingSpec::Valid,
+            Some(tvec!(2)),
+            None,
+            Some(1),
+        );
+        let kernel = rctensor3(&[[[1f32]], [[2f32]], [[3f32]]]);
+        let conv = ConvUnary::new(pool_spec, KernelFormat::HWIO, kernel, 1, None, None);
+        let wire = model.wire_node("conv", conv, &[source]).unwrap();
+        model.set_output_outlets(&wire).unwrap();
+        model.into_normalized().unwrap()
+    }
+
+    #[test]
+    fn streamed_output_matches_typed_output() {
+        let input = tensor1(&(0..10).map(|i| i as f32).collect::<Vec<_>>());
+        let input = unsafe { input.into_shape(&[10, 1]).unwrap() };
+        let typed = dilated_conv(10.into());
+        let expected = SimplePlan::new(&typed).unwrap().run(tvec!(input.clone())).unwrap();
+
+        let pulsed = PulsedModel::new(&dilated_conv(TDim::s()), 4).unwrap();
+        assert!(pulsed.output_fact(0).unwrap().delay > 0);
+        let found = stream(&pulsed, &input, &HashMap::new()).unwrap();
+        assert_eq!(found, *expected[0]);
+    }
+
+    #[test]
+    fn checksum_is_reproducible() {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [3].as_ref()).unwrap();
+        let x = model.add_source("x", fact).unwrap();
+        let y = model.wire_node("y", tract_core::ops::math::exp(), &[x]).unwrap();
+        model.set_output_outlets(&y).unwrap();
+        let run = |input: Tensor| {
+            let outputs = SimplePlan::new(&model).unwrap().run(tvec!(input)).unwrap();
+            crate::tensor::checksum(&outputs[0]).unwrap()
         };
-        let outputs = state.run(tvec!(input.into()))?;
-        let result_chunk = outputs[0].to_array_view::<f32>()?;
-        result
-            .slice_axis_mut(
-                tract_ndarray::Axis(output_fact.axis),
-                ((output_pulse * ix)..(output_pulse * (ix + 1))).into(),
-            )
-            .assign(&result_chunk);
+        let first = run(tensor1(&[0f32, 1., 2.]));
+        assert_eq!(first, run(tensor1(&[0f32, 1., 2.])));
+        assert_ne!(first, run(tensor1(&[0f32, 1., 3.])));
     }
-    result.slice_axis_inplace(tract_ndarray::Axis(output_fact.axis), (output_fact.delay..).into());
-    result
-        .slice_axis_inplace(tract_ndarray::Axis(output_fact.axis), (..output_dim as usize).into());
-    Ok(tvec!(result.into_arc_tensor()))
 }
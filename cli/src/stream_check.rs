@@ -1,7 +1,7 @@
 use tract_core::itertools::Itertools;
 use tract_core::ndarray::{ArrayD, Axis};
 
-use tract_core::model::{Fact, OutletId};
+use tract_core::model::{Fact, NormalizedModel, OutletId};
 use tract_core::plan::{SimplePlan, SimpleState};
 use tract_core::pulse::PulsedModel;
 
@@ -9,18 +9,91 @@ use crate::display_params::DisplayParams;
 use crate::terminal;
 use crate::{CliResult, Parameters};
 
-pub fn handle(params: &Parameters, options: &DisplayParams) -> CliResult<()> {
+/// Outcome of the stream check for one pulse size.
+#[derive(Debug)]
+pub enum PulseCheck {
+    Passed,
+    /// The model can not be pulsified with this pulse size.
+    Skipped(String),
+    Failed(String),
+}
+
+pub fn handle(
+    params: &Parameters,
+    options: &DisplayParams,
+    pulses: Option<Vec<usize>>,
+) -> CliResult<()> {
+    let fixed = if let Some(normalized) = &params.normalized_model {
+        normalized.clone()
+    } else {
+        params
+            .typed_model
+            .clone()
+            .ok_or("stream-check requires a typed model")?
+            .into_normalized()?
+    };
+
+    if let Some(pulses) = pulses {
+        let checks = check_pulses(&fixed, &pulses, &params.symbols)?;
+        for (pulse, check) in &checks {
+            match check {
+                PulseCheck::Passed => println!("pulse {}: ok", pulse),
+                PulseCheck::Skipped(why) => println!("pulse {}: skipped ({})", pulse, why),
+                PulseCheck::Failed(why) => println!("pulse {}: FAILED ({})", pulse, why),
+            }
+        }
+        if checks
+            .iter()
+            .any(|(_, check)| if let PulseCheck::Failed(_) = check { true } else { false })
+        {
+            bail!("Pulse check failed")
+        }
+        return Ok(());
+    }
+
     let pulsed = params.tract_model.downcast_ref::<PulsedModel>().unwrap();
+    let annotations = crate::annotations::Annotations::from_model(&*params.tract_model)?
+        .with_graph_def(&*params.tract_model, &params.graph)?;
+    check(&fixed, pulsed, &params.symbols, |pulsed_node| {
+        terminal::render_node(&*params.tract_model, pulsed_node, &annotations, options)
+    })
+}
 
-    let fixed = params.normalized_model.clone().unwrap();
+/// Pulsify `fixed` with each of the pulse sizes and compare the streamed
+/// outputs with the regular ones.
+pub fn check_pulses(
+    fixed: &NormalizedModel,
+    pulses: &[usize],
+    symbols: &HashMap<char, i64>,
+) -> CliResult<Vec<(usize, PulseCheck)>> {
+    pulses
+        .iter()
+        .map(|&pulse| {
+            let pulsed = match PulsedModel::new(fixed, pulse) {
+                Ok(pulsed) => pulsed,
+                Err(e) => return Ok((pulse, PulseCheck::Skipped(e.to_string()))),
+            };
+            let result = match check(fixed, &pulsed, symbols, |_| Ok(())) {
+                Ok(()) => PulseCheck::Passed,
+                Err(e) => PulseCheck::Failed(e.to_string()),
+            };
+            Ok((pulse, result))
+        })
+        .collect()
+}
 
+/// Compare every node output of the pulsed model with the regular one,
+/// calling `on_failure` with the failing pulsed node before bailing out.
+fn check(
+    fixed: &NormalizedModel,
+    pulsed: &PulsedModel,
+    symbols: &HashMap<char, i64>,
+    on_failure: impl Fn(usize) -> CliResult<()>,
+) -> CliResult<()> {
     let fixed_input_fact = fixed.input_fact(0)?;
     let pulsed_input_fact = pulsed.input_fact(0)?;
     let input_pulse = pulsed_input_fact.pulse();
 
-    let annotations = crate::annotations::Annotations::from_model(&*params.tract_model)?
-        .with_graph_def(&*params.tract_model, &params.graph)?;
-
     let eval_order = ::tract_core::model::eval_order(&fixed)?;
 
     for &fixed_node in eval_order.iter() {
@@ -46,6 +119,7 @@ pub fn handle(params: &Parameters, options: &DisplayParams) -> CliResult<()> {
             let fixed_input = crate::tensor::tensor_for_fact(
                 &fixed_input_fact.to_typed_fact()?,
                 Some(stream_dim),
+                symbols,
             )?;
 
             let mut fixed = fixed.clone();
@@ -56,6 +130,7 @@ pub fn handle(params: &Parameters, options: &DisplayParams) -> CliResult<()> {
 
             let plan = SimplePlan::new(&pulsed)?;
             let mut state = SimpleState::new(&plan)?;
+            state.session_state.resolved_symbols = symbols.clone();
 
             for i in 0.. {
                 let mut pulsed_input = ArrayD::from_elem(&*pulsed_input_fact.shape, std::f32::NAN);
@@ -100,12 +175,7 @@ pub fn handle(params: &Parameters, options: &DisplayParams) -> CliResult<()> {
                 let valid_fixed_result =
                     fixed_result.slice_axis(Axis(output_axis), (f_o..f_o + count).into());
                 if valid_pulse_result != valid_fixed_result {
-                    terminal::render_node(
-                        &*params.tract_model,
-                        pulsed_node,
-                        &annotations,
-                        options,
-                    )?;
+                    on_failure(pulsed_node)?;
                     println!("pulse: {} ({}..{})", i, i * output_pulse, (i + 1) * output_pulse);
                     println!(
                         "expected: {}",
@@ -121,7 +191,7 @@ pub fn handle(params: &Parameters, options: &DisplayParams) -> CliResult<()> {
                             .map(|s| *s.iter().next().unwrap())
                             .join(" ")
                     );
-                    bail!("Pulse check failed")
+                    bail!("Pulse check failed on {}", fixed.node(fixed_node).name)
                 }
             }
         }
@@ -129,3 +199,30 @@ pub fn handle(params: &Parameters, options: &DisplayParams) -> CliResult<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::internal::*;
+    use tract_core::ops::Downsample;
+
+    #[test]
+    fn downsample_pulses() {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [TDim::s()].as_ref()).unwrap();
+        let source = model.add_source("source", fact).unwrap();
+        let down = model.wire_node("down", Downsample::new(0, 2, 0), &[source]).unwrap();
+        model.set_output_outlets(&down).unwrap();
+        let checks =
+            check_pulses(&model.into_normalized().unwrap(), &[2, 3, 4], &HashMap::new()).unwrap();
+        let outcomes: Vec<(usize, &str)> = checks
+            .iter()
+            .map(|(pulse, check)| match check {
+                PulseCheck::Passed => (*pulse, "passed"),
+                PulseCheck::Skipped(_) => (*pulse, "skipped"),
+                PulseCheck::Failed(_) => (*pulse, "failed"),
+            })
+            .collect();
+        assert_eq!(outcomes, vec!((2, "passed"), (3, "skipped"), (4, "passed")));
+    }
+}
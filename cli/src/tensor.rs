@@ -16,7 +16,7 @@ pub fn parse_spec(size: &str) -> CliResult<InferenceFact> {
     }
 
     let last = splits.last().unwrap();
-    let (datum_type, shape) = if last.ends_with("S") || last.parse::<i32>().is_ok() {
+    let (datum_type, shape) = if last.parse::<TDim>().is_ok() {
         (None, &*splits)
     } else {
         let datum_type = match splits.last().unwrap().to_lowercase().as_str() {
@@ -25,7 +25,8 @@ pub fn parse_spec(size: &str) -> CliResult<InferenceFact> {
             "i32" => DatumType::I32,
             "i8" => DatumType::I8,
             "u8" => DatumType::U8,
-            _ => bail!("Type of the input should be f64, f32, i32, i8 or u8."),
+            "str" => DatumType::String,
+            _ => bail!("Type of the input should be f64, f32, i32, i8, u8 or str."),
         };
         (Some(datum_type), &splits[0..splits.len() - 1])
     };
@@ -72,11 +73,10 @@ fn tensor_for_text_data(filename: &str) -> CliResult<Tensor> {
 
     // We know there is at most one streaming dimension, so we can deduce the
     // missing value with a simple division.
-    let product: usize = shape.iter().map(|o| o.to_integer().unwrap_or(1) as usize).product();
+    let product: usize = shape.iter().map(|o| o.to_usize().unwrap_or(1)).product();
     let missing = values.len() / product;
 
-    let shape: Vec<_> =
-        shape.iter().map(|d| d.to_integer().map(|i| i as usize).unwrap_or(missing)).collect();
+    let shape: Vec<_> = shape.iter().map(|d| d.to_usize().unwrap_or(missing)).collect();
     dispatch_datum!(parse_values(proto.datum_type.concretize().unwrap())(&*shape, values))
 }
 
@@ -106,6 +106,56 @@ pub fn for_data(filename: &str) -> CliResult<(Option<String>, InferenceFact)> {
     }
 }
 
+/// A numpy fixed-width unicode (`<U`) or bytes (`|S`) string.
+#[derive(Clone)]
+struct NpyString(String);
+
+impl ndarray_npy::ReadableElement for NpyString {
+    fn read_vec<R: Read>(
+        mut reader: R,
+        type_desc: &py_literal::Value,
+        len: usize,
+    ) -> Result<Vec<Self>, ndarray_npy::ReadDataError> {
+        use ndarray_npy::ReadDataError;
+        let descr = match type_desc {
+            py_literal::Value::String(descr) => descr,
+            _ => return Err(ReadDataError::WrongDescriptor(type_desc.clone())),
+        };
+        let (char_size, width) = match (descr.get(..2), descr.get(2..).map(|w| w.parse())) {
+            (Some("<U"), Some(Ok(width))) => (4, width),
+            (Some("|S"), Some(Ok(width))) => (1, width),
+            _ => return Err(ReadDataError::WrongDescriptor(type_desc.clone())),
+        };
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).map_err(ReadDataError::Io)?;
+        let expected = len * width * char_size;
+        if bytes.len() < expected {
+            return Err(ReadDataError::MissingData);
+        } else if bytes.len() > expected {
+            return Err(ReadDataError::ExtraBytes(bytes.len() - expected));
+        }
+        if width == 0 {
+            return Ok(vec![NpyString(String::new()); len]);
+        }
+        Ok(bytes
+            .chunks(width * char_size)
+            .map(|item| {
+                let string = if char_size == 4 {
+                    item.chunks(4)
+                        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                        .take_while(|&c| c != 0)
+                        .map(|c| std::char::from_u32(c).unwrap_or(std::char::REPLACEMENT_CHARACTER))
+                        .collect()
+                } else {
+                    let end = item.iter().position(|&b| b == 0).unwrap_or(item.len());
+                    String::from_utf8_lossy(&item[..end]).into_owned()
+                };
+                NpyString(string)
+            })
+            .collect())
+    }
+}
+
 pub fn for_npz(npz: &mut ndarray_npy::NpzReader<fs::File>, name: &str) -> TractResult<Tensor> {
     fn rewrap<T: Datum>(array: tract_ndarray::ArrayD<T>) -> Tensor {
         let shape = array.shape().to_vec();
@@ -129,16 +179,114 @@ pub fn for_npz(npz: &mut ndarray_npy::NpzReader<fs::File>, name: &str) -> TractR
     if let Ok(t) = npz.by_name::<tract_ndarray::OwnedRepr<i32>, tract_ndarray::IxDyn>(name) {
         return Ok(rewrap(t));
     }
+    if let Ok(t) = npz.by_name::<tract_ndarray::OwnedRepr<NpyString>, tract_ndarray::IxDyn>(name) {
+        return Ok(rewrap(t.mapv(|s| s.0)));
+    }
     bail!("Can not extract tensor from {}", name);
 }
 
+/// Writes tensors to a npz file, as `<name>.npy` entries.
+pub fn write_npz<'a>(
+    path: impl AsRef<std::path::Path>,
+    tensors: impl IntoIterator<Item = (&'a str, &'a Tensor)>,
+) -> CliResult<()> {
+    let mut npz = ndarray_npy::NpzWriter::new(fs::File::create(path)?);
+    for (name, tensor) in tensors {
+        let name = format!("{}.npy", name);
+        match tensor.datum_type() {
+            DatumType::F64 => npz.add_array(name, &tensor.to_array_view::<f64>()?)?,
+            DatumType::F32 => npz.add_array(name, &tensor.to_array_view::<f32>()?)?,
+            DatumType::I8 => npz.add_array(name, &tensor.to_array_view::<i8>()?)?,
+            DatumType::U8 => npz.add_array(name, &tensor.to_array_view::<u8>()?)?,
+            DatumType::I32 => npz.add_array(name, &tensor.to_array_view::<i32>()?)?,
+            DatumType::I64 => npz.add_array(name, &tensor.to_array_view::<i64>()?)?,
+            dt => bail!("Can not write {:?} tensor {} to npz", dt, name),
+        }
+    }
+    Ok(())
+}
+
+/// 64-bit FNV-1a. Unlike the std default hasher, its output is specified, so
+/// checksums can be compared across builds.
+struct Fnv1a(u64);
+
+impl std::hash::Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Stable 64-bit checksum of a tensor datum type, shape and values.
+///
+/// Dimensions are hashed as u64 and values as little-endian bytes, so the
+/// checksum does not depend on the platform or on the tensor memory layout.
+pub fn checksum(tensor: &Tensor) -> CliResult<u64> {
+    use std::hash::Hasher;
+    fn write_sized(hasher: &mut Fnv1a, bytes: &[u8]) {
+        hasher.write(&(bytes.len() as u64).to_le_bytes());
+        hasher.write(bytes);
+    }
+    let mut hasher = Fnv1a(0xcbf29ce484222325);
+    write_sized(&mut hasher, format!("{:?}", tensor.datum_type()).as_bytes());
+    hasher.write(&(tensor.rank() as u64).to_le_bytes());
+    for &dim in tensor.shape() {
+        hasher.write(&(dim as u64).to_le_bytes());
+    }
+    macro_rules! le_bytes {
+        ($t:ty, $x:ident => $bits:expr) => {
+            for $x in tensor.as_slice::<$t>()? {
+                hasher.write(&$bits.to_le_bytes())
+            }
+        };
+    }
+    match tensor.datum_type() {
+        DatumType::Bool => le_bytes!(bool, x => (*x as u8)),
+        DatumType::I8 => le_bytes!(i8, x => x),
+        DatumType::I16 => le_bytes!(i16, x => x),
+        DatumType::I32 => le_bytes!(i32, x => x),
+        DatumType::I64 => le_bytes!(i64, x => x),
+        DatumType::U8 => le_bytes!(u8, x => x),
+        DatumType::U16 => le_bytes!(u16, x => x),
+        DatumType::F16 => le_bytes!(f16, x => x.0.to_bits()),
+        DatumType::F32 => le_bytes!(f32, x => x.to_bits()),
+        DatumType::F64 => le_bytes!(f64, x => x.to_bits()),
+        DatumType::TDim => {
+            for d in tensor.as_slice::<TDim>()? {
+                write_sized(&mut hasher, d.to_string().as_bytes())
+            }
+        }
+        DatumType::String => {
+            for s in tensor.as_slice::<String>()? {
+                write_sized(&mut hasher, s.as_bytes())
+            }
+        }
+        DatumType::Blob => {
+            for b in tensor.as_slice::<Blob>()? {
+                write_sized(&mut hasher, &b.0)
+            }
+        }
+    }
+    Ok(hasher.finish())
+}
+
+/// Parses a `[name:]spec[=values]` tensor argument, or `@file`.
+///
+/// A spec without dimensions (`f32`, or `:f32` without name) is a scalar.
 pub fn for_string(value: &str) -> CliResult<(Option<String>, InferenceFact)> {
     if value.starts_with("@") {
         for_data(&value[1..])
     } else {
         let (name, value) = if value.contains(":") {
             let mut splits = value.split(":");
-            (Some(splits.next().unwrap().to_string()), splits.next().unwrap())
+            let name = splits.next().unwrap();
+            (Some(name.to_string()).filter(|n| n.len() > 0), splits.next().unwrap())
         } else {
             (None, value)
         };
@@ -160,29 +308,66 @@ pub fn for_string(value: &str) -> CliResult<(Option<String>, InferenceFact)> {
     }
 }
 
-pub fn make_inputs(values: &[impl std::borrow::Borrow<TypedFact>]) -> CliResult<TVec<Tensor>> {
-    values.iter().map(|v| tensor_for_fact(v.borrow(), None)).collect()
+pub fn make_inputs(
+    values: &[impl std::borrow::Borrow<TypedFact>],
+    symbols: &HashMap<char, i64>,
+) -> CliResult<TVec<Tensor>> {
+    values.iter().map(|v| tensor_for_fact(v.borrow(), None, symbols)).collect()
 }
 
-pub fn make_inputs_for_model(model: &dyn Model) -> CliResult<TVec<Tensor>> {
+pub fn make_inputs_for_model(
+    model: &dyn Model,
+    symbols: &HashMap<char, i64>,
+) -> CliResult<TVec<Tensor>> {
     Ok(make_inputs(
         &*model
             .input_outlets()
             .iter()
             .map(|&t| model.outlet_typedfact(t))
             .collect::<TractResult<Vec<TypedFact>>>()?,
+        symbols,
     )?)
 }
 
-pub fn tensor_for_fact(fact: &TypedFact, streaming_dim: Option<usize>) -> CliResult<Tensor> {
+/// Binds the symbols standing for a whole dimension of `fact` to the length of
+/// that dimension in `value`, keeping the values already bound.
+pub fn bind_symbols(fact: &TypedFact, value: &Tensor, symbols: &mut HashMap<char, i64>) {
+    for (dim, &len) in fact.shape.iter().zip(value.shape()) {
+        if let Some(&s) = dim.symbols().iter().next() {
+            if dim == TDim::sym(s) {
+                symbols.entry(s).or_insert(len as i64);
+            }
+        }
+    }
+}
+
+/// Generates a random tensor for `fact`. The symbols of the streaming axis
+/// take `streaming_dim` if given, the others their value in `symbols`.
+pub fn tensor_for_fact(
+    fact: &TypedFact,
+    streaming_dim: Option<usize>,
+    symbols: &HashMap<char, i64>,
+) -> CliResult<Tensor> {
     if let Some(value) = &fact.konst {
         Ok(value.clone().into_tensor())
     } else {
-        if fact.shape.stream_info.is_some() && streaming_dim.is_none() {
-            Err("random tensor requires a streaming dim")?
+        let mut resolver = symbols.clone();
+        if let (Some(stream), Some(len)) = (fact.shape.stream_info(), streaming_dim) {
+            for s in stream.len.symbols() {
+                resolver.insert(s, len as i64);
+            }
         }
-        let shape = fact.shape.as_finite().unwrap();
-        Ok(random(shape, fact.datum_type))
+        let shape = fact
+            .shape
+            .iter()
+            .map(|d| {
+                if let Some(s) = d.symbols().into_iter().find(|s| !resolver.contains_key(s)) {
+                    bail!("No value for symbol {} (use --set)", s)
+                }
+                Ok(d.eval_with(&resolver)? as usize)
+            })
+            .collect::<CliResult<TVec<usize>>>()?;
+        Ok(random(&shape, fact.datum_type))
     }
 }
 
@@ -217,3 +402,87 @@ pub fn random(sizes: &[usize], datum_type: DatumType) -> Tensor {
         _ => panic!("Can generate random tensor for {:?}", datum_type),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checksum_is_stable() {
+        let a = tensor2(&[[1f32, 2.], [3., 4.]]);
+        let b = tract_ndarray::Array::from_shape_vec((2, 2), vec![1f32, 2., 3., 4.]).unwrap();
+        let b = b.into_tensor();
+        assert_eq!(checksum(&a).unwrap(), checksum(&b).unwrap());
+        assert_ne!(checksum(&a).unwrap(), checksum(&tensor1(&[1f32, 2., 3., 4.])).unwrap());
+        assert_ne!(checksum(&a).unwrap(), checksum(&a.cast_to::<f64>().unwrap()).unwrap());
+    }
+
+    #[test]
+    fn scalar_spec() {
+        for spec in &["f32", ":f32"] {
+            let (name, fact) = for_string(spec).unwrap();
+            assert_eq!(name, None);
+            assert_eq!(fact.datum_type.concretize(), Some(f32::datum_type()));
+            assert_eq!(fact.shape.as_concrete_finite().unwrap(), Some(tvec!()));
+        }
+    }
+
+    #[test]
+    fn scalar_input() {
+        let (name, fact) = for_string("x:f32=2.5").unwrap();
+        assert_eq!(name, Some("x".to_string()));
+        let value = fact.value.concretize().unwrap();
+        assert_eq!(*value, tensor0(2.5f32));
+
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [0usize; 0].as_ref()).unwrap();
+        let x = model.add_source("x", fact).unwrap();
+        let add = tract_core::ops::math::add::unary(rctensor0(1f32));
+        let y = model.wire_node("y", add, &[x]).unwrap();
+        model.set_output_outlets(&y).unwrap();
+        let output = SimplePlan::new(&model).unwrap().run(tvec!(value.into_tensor())).unwrap();
+        assert_eq!(*output[0], tensor0(3.5f32));
+    }
+
+    #[test]
+    fn string_spec() {
+        let (_, fact) = for_string("3xstr=a,bc,d").unwrap();
+        let value = fact.value.concretize().unwrap();
+        assert_eq!(*value, tensor1(&["a".to_string(), "bc".to_string(), "d".to_string()]));
+    }
+
+    #[test]
+    fn string_input_from_npz() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/test_data/strings.npz");
+        let mut npz = ndarray_npy::NpzReader::new(fs::File::open(path).unwrap()).unwrap();
+        let words = for_npz(&mut npz, "words.npy").unwrap();
+        let expected = tensor1(&["hello".to_string(), "tract".to_string(), "a".to_string()]);
+        assert_eq!(words, expected);
+
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(String::datum_type(), [3usize].as_ref()).unwrap();
+        let x = model.add_source("x", fact).unwrap();
+        model.set_output_outlets(&[x]).unwrap();
+        let output = SimplePlan::new(&model).unwrap().run(tvec!(words)).unwrap();
+        assert_eq!(*output[0], expected);
+    }
+
+    #[test]
+    fn random_tensor_with_symbol_values() {
+        let fact = TypedFact::dt_shape(f32::datum_type(), [TDim::sym('N') * 2, 3.into()].as_ref())
+            .unwrap();
+        let symbols = std::iter::once(('N', 4)).collect();
+        assert_eq!(tensor_for_fact(&fact, None, &symbols).unwrap().shape(), &[8, 3]);
+        let symbols = std::iter::once(('S', 2)).collect();
+        assert_eq!(tensor_for_fact(&fact, Some(7), &symbols).unwrap().shape(), &[14, 3]);
+        assert!(tensor_for_fact(&fact, None, &HashMap::new()).is_err());
+        let fact =
+            TypedFact::dt_shape(f32::datum_type(), [TDim::sym('N'), TDim::s(), 3.into()].as_ref())
+                .unwrap();
+        assert!(tensor_for_fact(&fact, Some(7), &HashMap::new()).is_err());
+        let mut symbols = HashMap::new();
+        bind_symbols(&fact, &Tensor::zero::<f32>(&[2, 5, 3]).unwrap(), &mut symbols);
+        assert_eq!(symbols[&'N'], 2);
+        assert_eq!(tensor_for_fact(&fact, Some(7), &symbols).unwrap().shape(), &[2, 7, 3]);
+    }
+}
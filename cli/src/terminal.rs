@@ -16,7 +16,7 @@ pub fn render(
     annotations: &Annotations,
     options: &DisplayParams,
 ) -> CliResult<()> {
-    render_prefixed(model, "", &[], annotations, options)
+    render_prefixed(&mut std::io::stdout(), model, "", &[], annotations, options)
 }
 
 pub fn render_node(
@@ -25,10 +25,23 @@ pub fn render_node(
     annotations: &Annotations,
     options: &DisplayParams,
 ) -> CliResult<()> {
-    render_node_prefixed(model, "", &[], node_id, None, annotations, options)
+    render_node_to(&mut std::io::stdout(), model, node_id, annotations, options)
+}
+
+/// Renders a node like `render_node`, to `out` instead of the standard
+/// output.
+pub fn render_node_to(
+    out: &mut dyn std::io::Write,
+    model: &dyn Model,
+    node_id: usize,
+    annotations: &Annotations,
+    options: &DisplayParams,
+) -> CliResult<()> {
+    render_node_prefixed(out, model, "", &[], node_id, None, annotations, options)
 }
 
 fn render_prefixed(
+    out: &mut dyn std::io::Write,
     model: &dyn Model,
     prefix: &str,
     scope: &[(usize, String)],
@@ -45,6 +58,7 @@ fn render_prefixed(
     for node in node_ids {
         if options.filter(model, scope, node)? {
             render_node_prefixed(
+                out,
                 model,
                 prefix,
                 scope,
@@ -63,6 +77,7 @@ fn render_prefixed(
 }
 
 fn render_node_prefixed(
+    out: &mut dyn std::io::Write,
     model: &dyn Model,
     prefix: &str,
     scope: &[(usize, String)],
@@ -81,7 +96,7 @@ fn render_node_prefixed(
 
     if let Some(ref mut ds) = &mut drawing_state {
         for l in ds.draw_node_vprefix(model, node_id, &options)? {
-            println!("{}{}{}{} ", cost_column_pad, profile_column_pad, prefix, l);
+            writeln!(out, "{}{}{}{} ", cost_column_pad, profile_column_pad, prefix, l)?;
         }
     }
 
@@ -139,12 +154,13 @@ fn render_node_prefixed(
                 .as_mut()
                 .map(|it| it.next().unwrap_or_else(|| profile_column_pad.to_string()))
                 .unwrap_or("".to_string());
-            print!("{}{}{}{} ", cost, profile, prefix, drawing_lines.next().unwrap(),)
+            write!(out, "{}{}{}{} ", cost, profile, prefix, drawing_lines.next().unwrap(),)?;
         };
     };
 
     prefix!();
-    println!(
+    writeln!(
+        out,
         "{} {} {}",
         White.bold().paint(format!("{}", node_id)),
         (if node_name == "UnimplementedOp" {
@@ -158,23 +174,24 @@ fn render_node_prefixed(
         })
         .paint(node_op_name),
         name_color.italic().paint(node_name)
-    );
+    )?;
     for label in tags.labels.iter() {
         prefix!();
-        println!("  * {}", label);
+        writeln!(out, "  * {}", label)?;
     }
     match options.io {
         Io::Long => {
             for (ix, i) in model.node_inputs(node_id).iter().enumerate() {
                 let star = if ix == 0 { '*' } else { ' ' };
                 prefix!();
-                println!(
+                writeln!(
+                    out,
                     "  {} input fact  #{}: {} {}",
                     star,
                     ix,
                     White.bold().paint(format!("{:?}", i)),
                     model.outlet_fact_format(*i),
-                );
+                )?;
             }
             for ix in 0..model.node_output_count(node_id) {
                 let star = if ix == 0 { '*' } else { ' ' };
@@ -200,18 +217,19 @@ fn render_node_prefixed(
                 let outlet = OutletId::new(node_id, ix);
                 let successors = model.outlet_successors(outlet);
                 prefix!();
-                println!(
+                writeln!(
+                    out,
                     "  {} output fact #{}: {} {} {}",
                     star,
                     ix,
                     model.outlet_fact_format(outlet),
                     White.bold().paint(successors.iter().map(|s| format!("{:?}", s)).join(" ")),
                     io
-                );
+                )?;
                 if options.outlet_labels {
                     if let Some(label) = model.outlet_label(OutletId::new(node_id, ix)) {
                         prefix!();
-                        println!("            {} ", White.italic().paint(label));
+                        writeln!(out, "            {} ", White.italic().paint(label))?;
                     }
                 }
             }
@@ -228,13 +246,14 @@ fn render_node_prefixed(
                     .unwrap_or(White.into());
                 for ix in 0..model.node_output_count(node_id) {
                     prefix!();
-                    println!(
+                    writeln!(
+                        out,
                         "  {}{}{} {}",
                         style.paint(box_drawing::heavy::HORIZONTAL),
                         style.paint(box_drawing::heavy::HORIZONTAL),
                         style.paint(box_drawing::heavy::HORIZONTAL),
                         model.outlet_fact_format((node_id, ix).into())
-                    );
+                    )?;
                 }
             }
         }
@@ -243,29 +262,29 @@ fn render_node_prefixed(
     if options.info {
         for info in model.node_op(node_id).info()? {
             prefix!();
-            println!("  * {}", info);
+            writeln!(out, "  * {}", info)?;
         }
     }
     if options.invariants {
         if let Some(typed) = model.downcast_ref::<TypedModel>() {
             let node = typed.node(node_id);
             prefix!();
-            println!("  * {:?}", node.op().as_typed().unwrap().invariants(&typed, &node)?);
+            writeln!(out, "  * {:?}", node.op().as_typed().unwrap().invariants(&typed, &node)?)?;
         }
     }
     if options.debug_op {
         prefix!();
-        println!("  * {:?}", model.node_op(node_id));
+        writeln!(out, "  * {:?}", model.node_op(node_id))?;
     }
     for section in tags.sections {
         if section.is_empty() {
             continue;
         }
         prefix!();
-        println!("  * {}", section[0]);
+        writeln!(out, "  * {}", section[0])?;
         for s in &section[1..] {
             prefix!();
-            println!("    {}", s);
+            writeln!(out, "    {}", s)?;
         }
     }
     for (label, sub, _, _) in model.nested_models(node_id) {
@@ -273,6 +292,7 @@ fn render_node_prefixed(
         let mut scope: TVec<_> = scope.into();
         scope.push((node_id, label.to_string()));
         render_prefixed(
+            out,
             sub,
             &format!("{} [{}] ", prefix, label),
             &*scope,
@@ -282,7 +302,7 @@ fn render_node_prefixed(
     }
     while cost_column.as_mut().map(|cost| cost.peek().is_some()).unwrap_or(false) {
         prefix!();
-        println!("");
+        writeln!(out)?;
     }
     Ok(())
 }
@@ -299,6 +319,8 @@ pub fn render_summaries(
         for (c, i) in &total.cost {
             println!(" * {:?}: {}", c, render_tdim(i));
         }
+        let equivalent = crate::cost::equivalent_f32_fmas(&total.cost);
+        println!(" * Equivalent FMA(F32): {}", render_tdim(&equivalent));
     }
 
     if options.profile {
@@ -396,7 +418,7 @@ pub fn dur_avg_ratio(measure: Duration, global: Duration) -> String {
 
 fn render_tdim(d: &TDim) -> ANSIString<'static> {
     if let Ok(i) = d.to_integer() {
-        render_big_integer(i as i64)
+        render_big_integer(i)
     } else {
         d.to_string().into()
     }
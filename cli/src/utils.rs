@@ -1,6 +1,50 @@
 use crate::CliResult;
 use tract_hir::internal::*;
 
+/// Turns the model input `name` into a Const holding `value`, so that
+/// everything downstream of it can be constant-folded.
+///
+/// Returns the position the input had in the model inputs.
+pub fn freeze_input(
+    model: &mut InferenceModel,
+    name: &str,
+    value: Arc<Tensor>,
+) -> CliResult<usize> {
+    let id = model.node_by_name(name)?.id;
+    let ix = model
+        .input_outlets()?
+        .iter()
+        .position(|o| o.node == id)
+        .ok_or_else(|| format!("Node {} is not a model input", name))?;
+    model.node_mut(id).op = Box::new(tract_core::ops::konst::Const::new(value.clone()));
+    model.set_outlet_fact(OutletId::new(id, 0), value.into())?;
+    model.inputs.remove(ix);
+    Ok(ix)
+}
+
+/// Labels an outlet from a `node:slot=label` spec. The slot defaults to 0
+/// when omitted.
+pub fn set_outlet_label(model: &mut InferenceModel, spec: &str) -> CliResult<()> {
+    let mut split = spec.rsplitn(2, "=");
+    let label = split.next().unwrap();
+    let outlet = split
+        .next()
+        .ok_or_else(|| format!("--set-outlet-label expects node:slot=label, got {}", spec))?;
+    let (name, slot) = match outlet.rfind(':') {
+        Some(colon) if outlet[colon + 1..].parse::<usize>().is_ok() => {
+            (&outlet[..colon], outlet[colon + 1..].parse::<usize>()?)
+        }
+        _ => (outlet, 0),
+    };
+    let node = model.node_by_name(name)?;
+    if slot >= node.outputs.len() {
+        bail!("Node {} has no output #{}", name, slot);
+    }
+    let outlet = OutletId::new(node.id, slot);
+    model.set_outlet_label(outlet, label.to_string())?;
+    Ok(())
+}
+
 /// Compares the outputs of a node in tract and tensorflow.
 pub fn check_outputs(got: &[Arc<Tensor>], expected: &[Option<Arc<Tensor>>]) -> CliResult<()> {
     if got.len() != expected.len() {
@@ -50,3 +94,65 @@ pub fn check_inferred(got: &[InferenceFact], expected: &[InferenceFact]) -> CliR
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn frozen_input_folds_downstream() {
+        let mut model = InferenceModel::default();
+        let fact = InferenceFact::dt_shape(f32::datum_type(), [2usize].as_ref());
+        let x = model.add_source("x", fact).unwrap();
+        let k = model.add_const("k", tensor1(&[1f32, 2.])).unwrap();
+        let add = model
+            .add_node("add", tract_hir::ops::math::add::bin(), tvec!(InferenceFact::default()))
+            .unwrap();
+        model.add_edge(x, InletId::new(add, 0)).unwrap();
+        model.add_edge(k, InletId::new(add, 1)).unwrap();
+        model.set_output_outlets(&[OutletId::new(add, 0)]).unwrap();
+
+        assert_eq!(freeze_input(&mut model, "x", rctensor1(&[3f32, 4.])).unwrap(), 0);
+        assert_eq!(model.input_outlets().unwrap().len(), 0);
+        let model = model.into_typed().unwrap().declutter().unwrap();
+        assert_eq!(model.output_fact(0).unwrap().konst, Some(rctensor1(&[4f32, 6.])));
+    }
+
+    #[test]
+    fn outlet_label_from_spec() {
+        let mut model = InferenceModel::default();
+        let fact = InferenceFact::dt_shape(f32::datum_type(), [2usize].as_ref());
+        let x = model.add_source("x", fact).unwrap();
+        let y = model.wire_node("y:exp", tract_hir::ops::math::exp(), &[x]).unwrap();
+        model.set_output_outlets(&y).unwrap();
+
+        set_outlet_label(&mut model, "x=input").unwrap();
+        set_outlet_label(&mut model, "y:exp:0=activation").unwrap();
+        assert!(set_outlet_label(&mut model, "x:1=nope").is_err());
+        assert!(set_outlet_label(&mut model, "x").is_err());
+        let model = model.into_typed().unwrap();
+        let outlet = |name| OutletId::new(model.node_by_name(name).unwrap().id, 0);
+        assert_eq!(model.outlet_label(outlet("x")), Some("input"));
+        assert_eq!(model.outlet_label(outlet("y:exp")), Some("activation"));
+    }
+
+    #[test]
+    fn outlet_label_in_dump() {
+        use crate::display_params::{DisplayParams, Io};
+        let mut model = InferenceModel::default();
+        let fact = InferenceFact::dt_shape(f32::datum_type(), [2usize].as_ref());
+        let x = model.add_source("x", fact).unwrap();
+        let y = model.wire_node("y", tract_hir::ops::math::exp(), &[x]).unwrap();
+        model.set_output_outlets(&y).unwrap();
+        set_outlet_label(&mut model, "y=activation").unwrap();
+        let model = model.into_typed().unwrap();
+
+        let annotations = crate::annotations::Annotations::from_model(&model).unwrap();
+        let options =
+            DisplayParams { outlet_labels: true, io: Io::Long, ..DisplayParams::default() };
+        let mut dump = vec![];
+        let y = model.node_by_name("y").unwrap().id;
+        crate::terminal::render_node_to(&mut dump, &model, y, &annotations, &options).unwrap();
+        assert!(String::from_utf8(dump).unwrap().contains("activation"));
+    }
+}
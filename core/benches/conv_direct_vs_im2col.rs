@@ -62,7 +62,9 @@ impl Problem {
 
         let mut model = TypedModel::default();
         let input = model.add_source("input", self.image_type()).unwrap();
-        let output = unsafe { conv.wire_as_im2col_pair(&mut model, "", input, direct).unwrap() };
+        let output = unsafe {
+            conv.wire_as_im2col_pair(&mut model, "", input, direct, tract_linalg::ops()).unwrap()
+        };
         model.set_output_outlets(&[output]).unwrap();
         SimplePlan::new(model).unwrap()
     }
@@ -46,7 +46,7 @@ fn b(
         .add_source("", TypedFact::dt_shape(f32::datum_type(), [1, h, w, ci].as_ref()).unwrap())
         .unwrap();
     unsafe {
-        unary.wire_as_im2col_pair(&mut m, "", wire, false).unwrap();
+        unary.wire_as_im2col_pair(&mut m, "", wire, false, tract_linalg::ops()).unwrap();
     }
     let im2col = m.node(1).op_as::<Im2Col<f32>>().unwrap();
     let args = tvec!(image.into());
@@ -2,7 +2,7 @@
 use crate::dim::TDim;
 use crate::tensor::litteral::*;
 use crate::tensor::Tensor;
-use crate::TractResult;
+use crate::{TractResult, TractResultExt};
 use std::{fmt, ops};
 use std::hash::Hash;
 
@@ -274,13 +274,16 @@ impl TryInto<TDim> for i64 {
 
 impl TryInto<i32> for TDim {
     fn try_into(&self) -> TractResult<i32> {
-        self.to_integer().map(|i| i as i32)
+        let value = TryInto::<i64>::try_into(self)?;
+        Ok(std::convert::TryFrom::try_from(value)
+            .map_err(|_| format!("Dimension {} overflows an i32", self))?)
     }
 }
 
 impl TryInto<i64> for TDim {
     fn try_into(&self) -> TractResult<i64> {
-        self.to_integer().map(|i| i as i64)
+        self.to_integer()
+            .chain_err(|| format!("Can not cast symbolic dimension {} to an integer", self))
     }
 }
 
@@ -421,6 +424,20 @@ mod tests {
         assert_eq!(t_dim, t_dim_2);
     }
 
+    #[test]
+    fn test_cast_concrete_dim_to_i64() {
+        let t_dim: Tensor = tensor1(&[12isize.to_dim(), 42isize.to_dim()]);
+        let t_i64 = t_dim.cast_to::<i64>().unwrap();
+        assert_eq!(*t_i64, tensor1(&[12i64, 42]));
+    }
+
+    #[test]
+    fn test_cast_symbolic_dim_to_i64() {
+        let t_dim: Tensor = tensor1(&[12isize.to_dim(), TDim::s() * 2]);
+        let err = t_dim.cast_to::<i64>().unwrap_err();
+        assert!(err.iter().any(|e| e.to_string().contains("symbolic")));
+    }
+
     #[test]
     fn test_cast_i32_to_dim() {
         let t_i32: Tensor = tensor1(&[0i32, 0]);
@@ -4,7 +4,7 @@ use std::ops;
 
 mod tree;
 
-pub use self::tree::TDim;
+pub use self::tree::{SymbolAssumptions, TDim};
 use crate::TractResult;
 
 /// A super-trait for value acting as tensor dimensions in tract.
@@ -45,10 +45,19 @@ pub trait DimLike:
     }
 
     /// Convert to regular integer.
-    fn to_integer(&self) -> TractResult<i32>;
+    fn to_integer(&self) -> TractResult<i64>;
+
+    /// Convert to a usize, failing on symbols and on negative values.
+    fn to_usize(&self) -> TractResult<usize>;
 
     /// do not use num_traits::Mul as it implies a regular Mul
     fn one() -> Self;
+
+    /// Greatest of the two dimensions (Ord::max would compare TDim trees).
+    fn maxi(self, other: Self) -> Self;
+
+    /// Smallest of the two dimensions.
+    fn mini(self, other: Self) -> Self;
 }
 
 impl DimLike for TDim {
@@ -62,13 +71,25 @@ impl DimLike for TDim {
         }
     }
 
-    fn to_integer(&self) -> TractResult<i32> {
+    fn to_integer(&self) -> TractResult<i64> {
         TDim::to_integer(self)
     }
 
+    fn to_usize(&self) -> TractResult<usize> {
+        TDim::to_usize(self)
+    }
+
     fn one() -> Self {
         Self::from(1)
     }
+
+    fn maxi(self, other: Self) -> Self {
+        TDim::Max(vec![self, other]).reduce()
+    }
+
+    fn mini(self, other: Self) -> Self {
+        TDim::Min(vec![self, other]).reduce()
+    }
 }
 
 impl DimLike for usize {
@@ -76,13 +97,25 @@ impl DimLike for usize {
         Ok(self * other)
     }
 
-    fn to_integer(&self) -> TractResult<i32> {
-        Ok(*self as i32)
+    fn to_integer(&self) -> TractResult<i64> {
+        Ok(*self as i64)
+    }
+
+    fn to_usize(&self) -> TractResult<usize> {
+        Ok(*self)
     }
 
     fn one() -> usize {
         1
     }
+
+    fn maxi(self, other: Self) -> Self {
+        std::cmp::max(self, other)
+    }
+
+    fn mini(self, other: Self) -> Self {
+        std::cmp::min(self, other)
+    }
 }
 
 pub trait MaybeProduct<D> {
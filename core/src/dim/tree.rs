@@ -1,7 +1,9 @@
 use crate::prelude::TractResult;
 use itertools::Itertools;
 use num_traits::{AsPrimitive, Zero};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::convert::TryFrom;
 use std::{fmt, ops};
 
 macro_rules! b( ($e:expr) => { Box::new($e) } );
@@ -9,14 +11,36 @@ macro_rules! b( ($e:expr) => { Box::new($e) } );
 #[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
 pub enum TDim {
     Sym(char),
-    Val(i32),
+    Val(i64),
     Add(Vec<TDim>),
-    Mul(i32, Box<TDim>),
+    Mul(i64, Box<TDim>),
     Div(Box<TDim>, u32),
+    Max(Vec<TDim>),
+    Min(Vec<TDim>),
 }
 
 use TDim::*;
 
+/// Lower bounds on the symbol values, for comparing expressions.
+///
+/// Symbols stand for dimensions, so they are always assumed non-negative.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SymbolAssumptions {
+    pub lower_bounds: HashMap<char, i64>,
+}
+
+impl SymbolAssumptions {
+    /// Assume `sym >= bound`.
+    pub fn with_lower_bound(mut self, sym: char, bound: i64) -> SymbolAssumptions {
+        self.lower_bounds.insert(sym, bound);
+        self
+    }
+
+    pub fn lower_bound(&self, sym: char) -> i64 {
+        self.lower_bounds.get(&sym).cloned().unwrap_or(0).max(0)
+    }
+}
+
 impl fmt::Display for TDim {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match &self {
@@ -25,6 +49,8 @@ impl fmt::Display for TDim {
             Add(it) => write!(fmt, "{}", it.iter().map(|x| format!("{}", x)).join("+")),
             Mul(a, b) => write!(fmt, "{}.{}", a, b),
             Div(a, b) => write!(fmt, "({})/{}", a, b),
+            Max(it) => write!(fmt, "max({})", it.iter().join(",")),
+            Min(it) => write!(fmt, "min({})", it.iter().join(",")),
         }
     }
 }
@@ -34,9 +60,14 @@ impl TDim {
         self.as_const().map(|i| i == 1).unwrap_or(false)
     }
 
+    /// A symbolic dimension, named by a single letter.
+    pub fn sym(name: char) -> TDim {
+        TDim::Sym(name)
+    }
+
     /// The special value S, for streaming.
     pub fn s() -> TDim {
-        TDim::Sym('S')
+        Self::sym('S')
     }
 
     /// The special value S, for streaming.
@@ -45,7 +76,7 @@ impl TDim {
     }
 
     /// Try to convert the value to an integer, if it does not contains S.
-    pub fn as_const(&self) -> Option<i32> {
+    pub fn as_const(&self) -> Option<i64> {
         self.to_integer().ok()
     }
 
@@ -53,26 +84,144 @@ impl TDim {
         self.to_integer().is_err()
     }
 
-    pub fn to_integer(&self) -> TractResult<i32> {
+    pub fn to_integer(&self) -> TractResult<i64> {
         self.eval_with(&hashmap!())
     }
 
-    pub fn eval(&self, s: i32) -> Option<i32> {
+    /// Convert to a usize, failing on symbols and on negative values.
+    pub fn to_usize(&self) -> TractResult<usize> {
+        let value = self.to_integer()?;
+        Ok(usize::try_from(value).map_err(|_| format!("{} is not a valid usize", value))?)
+    }
+
+    pub fn eval(&self, s: i64) -> Option<i64> {
         self.eval_with(&hashmap!('S' => s)).ok()
     }
 
-    fn eval_with(&self, values: &HashMap<char, i32>) -> TractResult<i32> {
+    /// Evaluate the expression, looking up each symbol in `values`.
+    pub fn eval_with(&self, values: &HashMap<char, i64>) -> TractResult<i64> {
         Ok(match self {
             Sym(v) => *values.get(v).ok_or(format!("Unresolved value {:?}", v))?,
             Val(v) => *v,
-            Add(terms) => terms.iter().try_fold(0i32, |acc, it| -> TractResult<i32> {
+            Add(terms) => terms.iter().try_fold(0i64, |acc, it| -> TractResult<i64> {
                 Ok(acc + it.eval_with(values)?)
             })?,
-            Div(a, q) => a.eval_with(values)? / *q as i32,
+            Div(a, q) => a.eval_with(values)? / *q as i64,
             Mul(p, a) => p * a.eval_with(values)?,
+            Max(terms) | Min(terms) if terms.is_empty() => bail!("Empty extremum {:?}", self),
+            Max(terms) => terms.iter().try_fold(std::i64::MIN, |acc, it| -> TractResult<i64> {
+                Ok(acc.max(it.eval_with(values)?))
+            })?,
+            Min(terms) => terms.iter().try_fold(std::i64::MAX, |acc, it| -> TractResult<i64> {
+                Ok(acc.min(it.eval_with(values)?))
+            })?,
         })
     }
 
+    /// Names of the symbols appearing in the expression.
+    pub fn symbols(&self) -> BTreeSet<char> {
+        match self {
+            Sym(v) => std::iter::once(*v).collect(),
+            Val(_) => BTreeSet::new(),
+            Add(terms) | Max(terms) | Min(terms) => {
+                terms.iter().flat_map(|t| t.symbols()).collect()
+            }
+            Mul(_, a) | Div(a, _) => a.symbols(),
+        }
+    }
+
+    /// Order the values of the two expressions, when it holds for all the
+    /// symbol values allowed by the assumptions. None if it can not be proven.
+    pub fn compare_with(&self, other: &TDim, assumptions: &SymbolAssumptions) -> Option<Ordering> {
+        match (self.clone() - other).bounds(assumptions) {
+            (Some(low), _) if low > 0 => Some(Ordering::Greater),
+            (_, Some(high)) if high < 0 => Some(Ordering::Less),
+            (Some(0), Some(0)) => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+
+    /// Lower and upper bounds of the expression, None meaning unbounded.
+    fn bounds(&self, assumptions: &SymbolAssumptions) -> (Option<i64>, Option<i64>) {
+        match self {
+            Val(v) => (Some(*v), Some(*v)),
+            Sym(s) => (Some(assumptions.lower_bound(*s)), None),
+            Add(terms) => Self::add_bounds(terms, assumptions),
+            Mul(p, a) => {
+                let (low, high) = a.bounds(assumptions);
+                if *p >= 0 {
+                    (low.map(|l| l * p), high.map(|h| h * p))
+                } else {
+                    (high.map(|h| h * p), low.map(|l| l * p))
+                }
+            }
+            Div(a, q) => {
+                let (low, high) = a.bounds(assumptions);
+                (low.map(|l| l / *q as i64), high.map(|h| h / *q as i64))
+            }
+            Max(terms) => {
+                let bounds = terms.iter().map(|t| t.bounds(assumptions)).collect::<Vec<_>>();
+                let low = bounds.iter().filter_map(|b| b.0).max();
+                let high = bounds.iter().map(|b| b.1).collect::<Option<Vec<_>>>();
+                (low, high.and_then(|h| h.into_iter().max()))
+            }
+            Min(terms) => {
+                let bounds = terms.iter().map(|t| t.bounds(assumptions)).collect::<Vec<_>>();
+                let low = bounds.iter().map(|b| b.0).collect::<Option<Vec<_>>>();
+                let high = bounds.iter().filter_map(|b| b.1).min();
+                (low.and_then(|l| l.into_iter().min()), high)
+            }
+        }
+    }
+
+    /// Bounds of a sum. Opposite quotients with the same divisor, like in
+    /// `(S+3)/2 - S/2`, are bounded together: for non-negative x and y,
+    /// `x/q - y/q` lies between the floor and the ceiling of `(x-y)/q`.
+    fn add_bounds(terms: &[TDim], assumptions: &SymbolAssumptions) -> (Option<i64>, Option<i64>) {
+        use num_integer::Integer;
+        let quotient = |t: &TDim| match t {
+            Div(a, q) => Some((1, (**a).clone(), *q)),
+            Mul(p, d) => match &**d {
+                Div(a, q) => Some((*p, (**a).clone(), *q)),
+                _ => None,
+            },
+            _ => None,
+        };
+        let non_negative = |t: &TDim| t.bounds(assumptions).0.map(|l| l >= 0).unwrap_or(false);
+        let mut terms: Vec<Option<&TDim>> = terms.iter().map(Some).collect();
+        let (mut low, mut high) = (Some(0), Some(0));
+        for i in 0..terms.len() {
+            let (p, x, q) = match terms[i].and_then(quotient) {
+                Some(it) => it,
+                None => continue,
+            };
+            for j in i + 1..terms.len() {
+                let (p2, y, q2) = match terms[j].and_then(quotient) {
+                    Some(it) => it,
+                    None => continue,
+                };
+                if p2 != -p || q2 != q || !non_negative(&x) || !non_negative(&y) {
+                    continue;
+                }
+                if let Val(k) = x.clone() - &y {
+                    let (l, h) = (k.div_floor(&(q as i64)), k.div_ceil(&(q as i64)));
+                    let (l, h) = if p >= 0 { (l * p, h * p) } else { (h * p, l * p) };
+                    low = low.map(|low| low + l);
+                    high = high.map(|high| high + h);
+                    terms[i] = None;
+                    terms[j] = None;
+                    break;
+                }
+            }
+        }
+        for term in terms.into_iter().filter_map(|t| t) {
+            let (l, h) = term.bounds(assumptions);
+            low = low.and_then(|low| l.map(|l| low + l));
+            high = high.and_then(|high| h.map(|h| high + h));
+        }
+        (low, high)
+    }
+
     pub fn reduce(self) -> TDim {
         self.simplify()
             .wiggle()
@@ -88,7 +237,7 @@ impl TDim {
         use self::TDim::*;
         match self {
             Sym(_) | Val(_) => 1,
-            Add(terms) => 2 * terms.iter().map(TDim::cost).sum::<usize>(),
+            Add(terms) | Max(terms) | Min(terms) => 2 * terms.iter().map(TDim::cost).sum::<usize>(),
             Div(a, _) => 3 * a.cost(),
             Mul(_, a) => 2 * a.cost(),
         }
@@ -115,7 +264,7 @@ impl TDim {
                             .enumerate()
                             .map(|(ix2, t)| {
                                 if ix2 != ix {
-                                    Mul(*q as i32, b!(t.clone()))
+                                    Mul(*q as i64, b!(t.clone()))
                                 } else {
                                     (**num).clone()
                                 }
@@ -128,12 +277,18 @@ impl TDim {
                 forms
             }
             Mul(p, a) => a.wiggle().into_iter().map(|a| Mul(*p, b!(a))).collect(),
+            Max(terms) => {
+                terms.iter().map(|e| e.wiggle()).multi_cartesian_product().map(Max).collect()
+            }
+            Min(terms) => {
+                terms.iter().map(|e| e.wiggle()).multi_cartesian_product().map(Min).collect()
+            }
             Div(a, q) => {
                 let mut forms = vec![];
                 for num in a.wiggle() {
                     if let Add(terms) = &num {
                         let (integer, non_integer): (Vec<_>, Vec<_>) =
-                            terms.into_iter().cloned().partition(|a| a.gcd() % q == 0);
+                            terms.into_iter().cloned().partition(|a| a.gcd() % *q as u64 == 0);
                         let mut new_terms = integer.iter().map(|i| i.div(*q)).collect::<Vec<_>>();
                         if non_integer.len() > 0 {
                             new_terms.push(Div(b!(Add(non_integer)), *q));
@@ -152,7 +307,7 @@ impl TDim {
         use num_integer::Integer;
         match self {
             Add(mut terms) => {
-                let mut reduced: HashMap<TDim, i32> = HashMap::new();
+                let mut reduced: HashMap<TDim, i64> = HashMap::new();
                 // factorize common sub-expr
                 while let Some(item) = terms.pop() {
                     let term = item.simplify();
@@ -221,7 +376,7 @@ impl TDim {
                 }
                 let a = a.simplify();
                 if let Val(a) = a {
-                    Val(a / q as i32)
+                    Val(a / q as i64)
                 } else if let Mul(-1, a) = a {
                     Mul(-1, b!(Div(a, q)))
                 } else if let Add(mut terms) = a {
@@ -230,15 +385,15 @@ impl TDim {
                         .filter_map(|t| if let Val(v) = t { Some(*v) } else { None })
                         .next()
                     {
-                        let offset = if v >= q as i32 {
-                            Some(v / q as i32)
+                        let offset = if v >= q as i64 {
+                            Some(v / q as i64)
                         } else if v < 0 {
-                            Some(-(-v).div_ceil(&(q as i32)))
+                            Some(-(-v).div_ceil(&(q as i64)))
                         } else {
                             None
                         };
                         if let Some(val) = offset {
-                            terms.push(Val(-val * q as i32));
+                            terms.push(Val(-val * q as i64));
                             Add(vec![Val(val), Div(b!(Add(terms).simplify()), q)])
                         } else {
                             Div(b!(Add(terms)), q)
@@ -247,13 +402,13 @@ impl TDim {
                         Div(b!(Add(terms)), q)
                     }
                 } else if let Mul(p, a) = a {
-                    if p == q as i32 {
+                    if p == q as i64 {
                         a.simplify()
                     } else {
-                        let gcd = p.abs().gcd(&(q as i32));
+                        let gcd = p.abs().gcd(&(q as i64));
                         if gcd == p {
                             Div(a, q / gcd as u32)
-                        } else if gcd == q as i32 {
+                        } else if gcd == q as i64 {
                             Mul(p / gcd, a)
                         } else if gcd > 1 {
                             Div(b!(Mul(p / gcd, a)), q / gcd as u32).simplify()
@@ -265,24 +420,84 @@ impl TDim {
                     Div(b!(a), q)
                 }
             }
+            Max(terms) => Self::simplify_extremum(terms, true),
+            Min(terms) => Self::simplify_extremum(terms, false),
             _ => self,
         }
     }
 
-    fn gcd(&self) -> u32 {
+    /// Flattens nested extrema, folds the constant members, and drops the
+    /// members another one provably dominates.
+    fn simplify_extremum(mut terms: Vec<TDim>, max: bool) -> TDim {
+        let mut konst: Option<i64> = None;
+        let mut members: Vec<TDim> = vec![];
+        while let Some(item) = terms.pop() {
+            match item.simplify() {
+                Val(v) => {
+                    konst = Some(konst.map(|k| if max { k.max(v) } else { k.min(v) }).unwrap_or(v))
+                }
+                Max(items) if max => terms.extend(items.into_iter()),
+                Min(items) if !max => terms.extend(items.into_iter()),
+                term => members.push(term),
+            }
+        }
+        members.extend(konst.map(Val));
+        members.sort();
+        members.dedup();
+        // a dominates b if a - b is non-negative for max, b - a for min
+        let dominates = |a: &TDim, b: &TDim| {
+            let diff = if max { a.clone() - b } else { b.clone() - a };
+            diff.prove_positive_or_zero()
+        };
+        let mut kept: Vec<TDim> = vec![];
+        for member in members {
+            if kept.iter().any(|k| dominates(k, &member)) {
+                continue;
+            }
+            kept.retain(|k| !dominates(&member, k));
+            kept.push(member);
+        }
+        kept.sort();
+        if kept.len() == 1 {
+            kept.remove(0)
+        } else if max {
+            Max(kept)
+        } else {
+            Min(kept)
+        }
+    }
+
+    /// True if the expression is known to be non-negative whatever the
+    /// (non-negative) values of its symbols.
+    pub fn prove_positive_or_zero(&self) -> bool {
+        match self {
+            Val(v) => *v >= 0,
+            Sym(_) => true,
+            Add(terms) | Min(terms) => terms.iter().all(|t| t.prove_positive_or_zero()),
+            Max(terms) => terms.iter().any(|t| t.prove_positive_or_zero()),
+            Mul(p, a) => *p >= 0 && a.prove_positive_or_zero(),
+            Div(a, _) => a.prove_positive_or_zero(),
+        }
+    }
+
+    fn gcd(&self) -> u64 {
         use self::TDim::*;
         use num_integer::Integer;
         match self {
-            Val(v) => v.abs() as u32,
+            Val(v) => v.wrapping_abs() as u64,
             Sym(_) => 1,
-            Add(terms) => {
+            Add(terms) | Max(terms) | Min(terms) => {
                 let (head, tail) = terms.split_first().unwrap();
                 tail.iter().fold(head.gcd(), |a, b| a.gcd(&b.gcd()))
             }
-            Mul(p, a) => a.gcd() * p.abs() as u32,
+            Mul(p, a) => {
+                // on overflow, |p| alone still divides every value
+                let p = p.wrapping_abs() as u64;
+                a.gcd().checked_mul(p).unwrap_or(p)
+            }
             Div(a, q) => {
-                if a.gcd() % *q == 0 {
-                    a.gcd() / *q
+                if a.gcd() % *q as u64 == 0 {
+                    a.gcd() / *q as u64
                 } else {
                     1
                 }
@@ -297,15 +512,17 @@ impl TDim {
             return self.clone();
         }
         match self {
-            Val(v) => Val(v / d as i32),
+            Val(v) => Val(v / d as i64),
             Sym(_) => panic!(),
             Add(terms) => Add(terms.iter().map(|t| t.div(d)).collect()),
+            Max(terms) => Max(terms.iter().map(|t| t.div(d)).collect()),
+            Min(terms) => Min(terms.iter().map(|t| t.div(d)).collect()),
             Mul(p, a) => {
-                if *p == d as i32 {
+                if *p == d as i64 {
                     (**a).clone()
                 } else {
-                    let gcd = (p.abs() as u32).gcd(&d);
-                    Mul(p / gcd as i32, b!(a.div(d / gcd)))
+                    let gcd = p.abs().gcd(&(d as i64));
+                    Mul(p / gcd, b!(a.div(d / gcd as u32)))
                 }
             }
             Div(a, q) => Div(a.clone(), q * d),
@@ -313,7 +530,7 @@ impl TDim {
     }
 
     pub fn div_ceil(self, rhs: u32) -> TDim {
-        TDim::Div(Box::new(Add(vec![self, Val(rhs as i32 - 1)])), rhs).reduce()
+        TDim::Div(Box::new(Add(vec![self, Val(rhs as i64 - 1)])), rhs).reduce()
     }
 }
 
@@ -438,15 +655,15 @@ impl<'a> ops::Sub<&'a TDim> for TDim {
     }
 }
 
-impl ops::MulAssign<i32> for TDim {
-    fn mul_assign(&mut self, rhs: i32) {
+impl ops::MulAssign<i64> for TDim {
+    fn mul_assign(&mut self, rhs: i64) {
         let mut me = TDim::Val(0);
         std::mem::swap(&mut me, self);
         *self = TDim::Mul(rhs, Box::new(me)).reduce()
     }
 }
 
-impl<I: AsPrimitive<i32>> ops::Mul<I> for TDim {
+impl<I: AsPrimitive<i64>> ops::Mul<I> for TDim {
     type Output = Self;
     fn mul(mut self, rhs: I) -> Self {
         self *= rhs.as_();
@@ -487,14 +704,16 @@ impl<I: AsPrimitive<u32>> ops::Rem<I> for TDim {
 impl std::str::FromStr for TDim {
     type Err = std::num::ParseIntError;
     fn from_str(s: &str) -> Result<TDim, Self::Err> {
-        if s == "S" {
-            Ok(TDim::s())
-        } else if s.ends_with("S") {
-            let number: String = s.chars().take_while(|c| c.is_digit(10)).collect();
-            let number: i32 = number.parse::<i32>().map(|i| i.into())?;
-            Ok(TDim::s() * number)
-        } else {
-            s.parse::<i32>().map(|i| i.into())
+        match s.chars().last() {
+            Some(sym) if sym.is_ascii_alphabetic() => {
+                let number = &s[..s.len() - 1];
+                if number.is_empty() {
+                    Ok(TDim::sym(sym))
+                } else {
+                    Ok(TDim::sym(sym) * number.parse::<i64>()?)
+                }
+            }
+            _ => s.parse::<i64>().map(|i| i.into()),
         }
     }
 }
@@ -513,7 +732,7 @@ mod tests {
         TDim::Add(vec![a.clone(), b.clone()])
     }
 
-    fn mul(a: i32, b: &TDim) -> TDim {
+    fn mul(a: i64, b: &TDim) -> TDim {
         TDim::Mul(a, b![b.clone()])
     }
 
@@ -688,4 +907,106 @@ mod tests {
         let e = (TDim::Sym('S') - 3 + 1).div_ceil(1);
         assert_eq!(e, TDim::Sym('S') + -2);
     }
+
+    #[test]
+    fn distinct_symbols_stay_apart() {
+        let e = TDim::sym('N') + TDim::sym('S') - TDim::sym('N');
+        assert_eq!(e, TDim::sym('S'));
+        let e = TDim::sym('N') * 2 + TDim::sym('S') * 3 + TDim::sym('N');
+        assert_eq!(e, TDim::sym('N') * 3 + TDim::sym('S') * 3);
+        assert_ne!(TDim::sym('N') + TDim::sym('S'), TDim::sym('S') * 2);
+        assert_eq!(e.symbols().into_iter().collect::<Vec<_>>(), vec!['N', 'S']);
+    }
+
+    #[test]
+    fn eval_two_symbols() {
+        let e = (TDim::sym('N') * 2 + TDim::sym('S') - 1) / 2;
+        assert_eq!(e.eval_with(&hashmap! {'N' => 3, 'S' => 5}).unwrap(), 5);
+        assert!(e.eval_with(&hashmap! {'S' => 5}).is_err());
+        assert_eq!(e.eval(5), None);
+    }
+
+    #[test]
+    fn parse_symbols() {
+        assert_eq!("S".parse::<TDim>().unwrap(), TDim::s());
+        assert_eq!("N".parse::<TDim>().unwrap(), TDim::sym('N'));
+        assert_eq!("2N".parse::<TDim>().unwrap(), TDim::sym('N') * 2);
+        assert_ne!("N".parse::<TDim>().unwrap(), "S".parse::<TDim>().unwrap());
+        assert_eq!("12".parse::<TDim>().unwrap(), 12.into());
+        assert!("xN".parse::<TDim>().is_err());
+    }
+
+    #[test]
+    fn max_with_zero_of_symbol() {
+        assert_eq!(Max(vec![Sym('S'), Val(0)]).reduce(), Sym('S'));
+        assert_eq!(Min(vec![Sym('S'), Val(0)]).reduce(), Val(0));
+        let e = Max(vec![Sym('S') - 3, Val(0)]).reduce();
+        assert_eq!(e, Max(vec![Val(0), Sym('S') - 3]));
+        assert_eq!(e.eval(1), Some(0));
+        assert_eq!(e.eval(5), Some(2));
+    }
+
+    #[test]
+    fn extrema_of_constants_fold() {
+        assert_eq!(Max(vec![Val(2), Val(-1), Val(5)]).reduce(), Val(5));
+        assert_eq!(Min(vec![Val(2), Min(vec![Val(-1), Val(5)])]).reduce(), Val(-1));
+        assert_eq!(Max(vec![Sym('S') + 2, Sym('S'), Val(1)]).reduce(), Sym('S') + 2);
+    }
+
+    #[test]
+    fn extrema_gcd_and_div() {
+        let e = Max(vec![Sym('S') * 4, Val(8)]);
+        assert_eq!(e.gcd(), 4);
+        assert_eq!((e.clone() / 4).eval(1), Some(2));
+        assert_eq!((e / 4).eval(5), Some(5));
+    }
+
+    #[test]
+    fn div_with_large_coefficient() {
+        let e = (Sym('S') * (1i64 << 32) + Sym('N')) / 3;
+        let values = hashmap!('S' => 3, 'N' => 0);
+        assert_eq!(e.eval_with(&values).unwrap(), 1 << 32);
+        assert_eq!((Sym('S') * (3i64 << 32)).gcd(), 3 << 32);
+    }
+
+    #[test]
+    fn compare_linear_forms() {
+        let none = SymbolAssumptions::default();
+        let s = || TDim::s();
+        assert_eq!((s() + 1).compare_with(&s(), &none), Some(Ordering::Greater));
+        assert_eq!(s().compare_with(&(s() + 1), &none), Some(Ordering::Less));
+        assert_eq!((s() * 2).compare_with(&s(), &none), None);
+        let positive = SymbolAssumptions::default().with_lower_bound('S', 1);
+        assert_eq!((s() * 2).compare_with(&s(), &positive), Some(Ordering::Greater));
+        assert_eq!(s().compare_with(&3.into(), &none), None);
+        assert_eq!(Max(vec![s(), Val(0)]).compare_with(&s(), &none), Some(Ordering::Equal));
+        assert_eq!((s() / 2 * 2).compare_with(&s(), &none), None);
+    }
+
+    #[test]
+    fn large_dims_do_not_overflow() {
+        let e = TDim::s() * 65536 * 65536;
+        assert_eq!(e.eval(2), Some(1 << 33));
+        assert_eq!((e / 4096).eval(3), Some(3 << 20));
+        assert_eq!(TDim::from(3_000_000_000usize).to_usize().unwrap(), 3_000_000_000);
+        assert!(TDim::from(-1).to_usize().is_err());
+        assert!(TDim::s().to_usize().is_err());
+    }
+
+    #[test]
+    fn compare_quotients() {
+        let none = SymbolAssumptions::default();
+        let e = (TDim::s() + 3) / 2;
+        assert_eq!(e.compare_with(&(TDim::s() / 2), &none), Some(Ordering::Greater));
+        assert_eq!((TDim::s() / 2).compare_with(&e, &none), Some(Ordering::Less));
+        let e = (TDim::s() + 1) / 2;
+        assert_eq!(e.compare_with(&(TDim::s() / 2), &none), None);
+    }
+
+    #[test]
+    fn empty_extremum_has_no_value() {
+        assert!(Max(vec![]).to_integer().is_err());
+        assert!(Min(vec![]).eval(3).is_none());
+        assert_eq!(Max(vec![Val(2), Sym('S')]).eval(3), Some(3));
+    }
 }
@@ -116,7 +116,7 @@ pub mod prelude {
 
 /// This prelude is meant for code extending tract (like implementing new ops).
 pub mod internal {
-    pub use crate::dim::{DimLike, TDim, ToDim, MaybeProduct};
+    pub use crate::dim::{DimLike, SymbolAssumptions, TDim, ToDim, MaybeProduct};
     pub use tract_linalg::hash::{ hash_f32, DynHash };
     pub use crate::model::*;
     pub use crate::ops::change_axes::*;
@@ -28,6 +28,9 @@ where
                     new.set_outlet_label(o, label.to_string())?;
                 }
             }
+            if let (Some(metadata), Some(o)) = (old.node_metadata.get(&old_id), outlets.first()) {
+                new.node_metadata.entry(o.node).or_default().extend(metadata.clone());
+            }
             if old.input_outlets()?.contains(&OutletId::new(old_node.id, 0)) {
                 continue;
             }
@@ -47,6 +50,7 @@ where
         // maintaining order of i/o interface
         new.inputs = old.input_outlets()?.iter().map(|i| map[&i]).collect();
         new.outputs = old.output_outlets()?.iter().map(|o| map[&o]).collect();
+        new.fusions = old.fusions.clone();
         Ok(new)
     }
 }
@@ -29,127 +29,107 @@ pub struct StreamFact {
     pub len: TDim,
 }
 
+impl StreamFact {
+    /// Finds the streaming axis of a shape: the axis depending on the stream
+    /// symbol `S` or, failing that, its only symbolic axis, whatever its
+    /// symbol (so a model can be pulsed along `N`).
+    ///
+    /// Next to `S`, other symbolic axes (a batch size `N`, say) are not
+    /// streaming, and a shape with several symbolic axes but no `S` has no
+    /// streaming axis.
+    pub fn for_dims(dims: &[TDim]) -> TractResult<Option<StreamFact>> {
+        let mut streaming = dims.iter().enumerate().filter(|(_, d)| d.symbols().contains(&'S'));
+        let found = match (streaming.next(), streaming.next()) {
+            (Some(_), Some(_)) => bail!("Shape with two streaming dims are invalid: {:?}", dims),
+            (Some(found), None) => Some(found),
+            _ => {
+                let mut symbolic = dims.iter().enumerate().filter(|(_, d)| d.is_stream());
+                match (symbolic.next(), symbolic.next()) {
+                    (Some(found), None) => Some(found),
+                    _ => None,
+                }
+            }
+        };
+        Ok(found.map(|(axis, len)| StreamFact { axis, len: len.clone() }))
+    }
+}
+
 /// Fully determined dimension of a tensor.
 ///
-/// Tensors in tract can have one streaming dimension. TDim generalize the
-/// regular tensor dimensions (usize) to arithmetic expressions of `S`, the
-/// (sometimes hypothetical) tensor length on the streaming axis.
+/// TDim generalize the regular tensor dimensions (usize) to arithmetic
+/// expressions of symbols: `S`, the (sometimes hypothetical) tensor length on
+/// the streaming axis, or other dynamic dimensions like a batch size `N`.
+/// Tensors in tract can have one streaming dimension, but any number of
+/// symbolic ones.
 #[derive(Clone, PartialEq, Hash)]
 pub struct ShapeFact {
-    shape: TVec<usize>,
+    dims: TVec<TDim>,
+    /// Concrete dimensions, if none of them is symbolic.
+    concrete: Option<TVec<usize>>,
     /// Optional information for streaming tensors. None for regular tensors.
-    pub stream_info: Option<StreamFact>,
+    stream_info: Option<StreamFact>,
 }
 
 impl ShapeFact {
     /// Rank of the tensor.
     pub fn rank(&self) -> usize {
-        self.shape.len()
+        self.dims.len()
     }
 
     /// Extended dimension of the i-th axis.
     ///
     /// The TDim will wrap a plain integer for regular (non-streaming) tensors.
     pub fn dim(&self, i: usize) -> TDim {
-        if let Some(ref stream) = self.stream_info {
-            if stream.axis == i {
-                return stream.len.clone();
-            }
-        }
-        self.shape[i].to_dim()
+        self.dims[i].clone()
     }
 
     /// Set the i-th axis dimension.
     pub fn set_dim(&mut self, i: usize, dim: TDim) -> TractResult<()> {
-        if let Some(ref stream) = self.stream_info {
-            if let Ok(int) = dim.to_integer() {
-                self.shape[i] = int as _;
-                if stream.axis == i {
-                    self.stream_info = None;
-                }
-            } else {
-                if stream.axis != i {
-                    bail!("Attempt at building a shape with two streaming dim")
-                } else {
-                    self.stream_info = Some(StreamFact { len: dim, axis: i })
-                }
-            }
-        } else {
-            if let Ok(int) = dim.to_integer() {
-                self.shape[i] = int as _;
-            } else {
-                self.shape[i] = 0;
-                self.stream_info = Some(StreamFact { len: dim, axis: i })
-            }
-        }
+        let mut dims = self.dims.clone();
+        dims[i] = dim;
+        *self = ShapeFact::from_dims(dims)?;
         Ok(())
     }
 
     pub fn insert_axis(&mut self, axis: usize) -> TractResult<()> {
-        self.shape.insert(axis, 1);
-        if let Some(s) = self.stream_info.as_mut() {
-            if s.axis >= axis {
-                s.axis += 1;
-            }
-        }
+        let mut dims = self.dims.clone();
+        dims.insert(axis, 1.to_dim());
+        *self = ShapeFact::from_dims(dims)?;
         Ok(())
     }
 
     pub fn remove_axis(&mut self, axis: usize) -> TractResult<()> {
-        self.shape.remove(axis);
-        if let Some(s) = self.stream_info.as_mut() {
-            if s.axis > axis {
-                s.axis -= 1;
-            }
-        }
+        let mut dims = self.dims.clone();
+        dims.remove(axis);
+        *self = ShapeFact::from_dims(dims)?;
         Ok(())
     }
 
-    /// Shape of the tensor, unless it is streaming.
+    /// Streaming axis and length, for streaming tensors.
+    pub fn stream_info(&self) -> Option<&StreamFact> {
+        self.stream_info.as_ref()
+    }
+
+    /// Shape of the tensor, unless it has a symbolic dimension.
     pub fn as_finite(&self) -> Option<&[usize]> {
-        match self.stream_info {
-            None => Some(&*self.shape),
-            _ => None,
-        }
+        self.concrete.as_ref().map(|s| &**s)
     }
 
     /// Iterator over dimension of the shape.
     pub fn iter<'a>(&'a self) -> impl Iterator<Item = TDim> + 'a {
-        self.shape.clone().into_iter().enumerate().map(move |(ix, d)| {
-            if let Some(ref info) = self.stream_info {
-                if ix == info.axis {
-                    return info.len.clone();
-                }
-            }
-            (d as i64).to_dim()
-        })
+        self.dims.iter().cloned()
     }
 
     /// Convert the shape to an array of extended dimensions.
     pub fn to_tvec(&self) -> TVec<TDim> {
-        self.iter().collect::<TVec<TDim>>()
+        self.dims.clone()
     }
 
     pub fn from_dims<T: AsRef<[TDim]> + std::fmt::Debug>(it: T) -> TractResult<ShapeFact> {
-        let count = it.as_ref().iter().filter(|t| t.is_stream()).count();
-        if count > 1 {
-            bail!("Shape with two streaming dims are invalid: {:?}", it)
-        } else {
-            let stream_info = it
-                .as_ref()
-                .iter()
-                .enumerate()
-                .find(|(_ix, d)| d.is_stream())
-                .map(|(ix, d)| StreamFact { axis: ix, len: d.clone() });
-            Ok(ShapeFact {
-                shape: it
-                    .as_ref()
-                    .iter()
-                    .map(|t| t.to_integer().map(|i| i as usize).unwrap_or(0))
-                    .collect(),
-                stream_info,
-            })
-        }
+        let dims: TVec<TDim> = it.as_ref().into();
+        let stream_info = StreamFact::for_dims(&dims)?;
+        let concrete = dims.iter().map(|d| d.to_usize()).collect::<TractResult<_>>().ok();
+        Ok(ShapeFact { dims, concrete, stream_info })
     }
 }
 
@@ -170,7 +150,11 @@ impl TryFrom<&[TDim]> for ShapeFact {
 impl TryFrom<&[usize]> for ShapeFact {
     type Error = TractError;
     fn try_from(it: &[usize]) -> TractResult<ShapeFact> {
-        Ok(ShapeFact { shape: it.into(), stream_info: None })
+        Ok(ShapeFact {
+            dims: it.iter().map(|d| d.to_dim()).collect(),
+            concrete: Some(it.into()),
+            stream_info: None,
+        })
     }
 }
 
@@ -256,7 +240,7 @@ impl From<Arc<Tensor>> for TypedFact {
     fn from(t: Arc<Tensor>) -> TypedFact {
         TypedFact {
             datum_type: t.datum_type(),
-            shape: ShapeFact { shape: t.shape().into(), stream_info: None },
+            shape: t.shape().try_into().unwrap(),
             konst: Some(t),
         }
     }
@@ -350,3 +334,27 @@ impl<'t> From<&'t Tensor> for NormalizedFact {
         NormalizedFact { datum_type: t.datum_type(), shape: t.shape().try_into().unwrap() }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn stream_axis(dims: &[TDim]) -> TractResult<Option<usize>> {
+        Ok(StreamFact::for_dims(dims)?.map(|s| s.axis))
+    }
+
+    #[test]
+    fn stream_axis_prefers_s() -> TractResult<()> {
+        assert_eq!(stream_axis(&[TDim::sym('N'), TDim::s(), 3.into()])?, Some(1));
+        assert!(stream_axis(&[TDim::s(), TDim::s() * 2]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn lone_symbolic_axis_is_streaming() -> TractResult<()> {
+        assert_eq!(stream_axis(&[TDim::sym('N'), 10.into()])?, Some(0));
+        assert_eq!(stream_axis(&[TDim::sym('N'), TDim::sym('M'), 10.into()])?, None);
+        assert_eq!(stream_axis(&[2.into(), 10.into()])?, None);
+        Ok(())
+    }
+}
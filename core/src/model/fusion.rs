@@ -0,0 +1,225 @@
+//! User-registered fusions, replacing a recognized subgraph by a single op.
+use crate::internal::*;
+use std::fmt;
+
+/// A subgraph to recognize, as op names and edges between them.
+///
+/// Edges are `(from, to)` pairs of indices in `ops`, meaning the first output
+/// of `from` is an input of `to`. The last op is the sink of the pattern:
+/// every other op must feed a later one, and only ops of the pattern.
+#[derive(Clone, Debug, new, PartialEq)]
+pub struct FusionPattern {
+    pub ops: Vec<String>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl FusionPattern {
+    /// A chain of ops, each one feeding the next one.
+    pub fn chain(ops: &[&str]) -> FusionPattern {
+        let edges = (1..ops.len()).map(|ix| (ix - 1, ix)).collect();
+        FusionPattern::new(ops.iter().map(|s| s.to_string()).collect(), edges)
+    }
+
+    /// Nodes matching the pattern ops with `sink` as last one, if any.
+    pub fn matches<'m>(
+        &self,
+        model: &'m TypedModel,
+        sink: &'m TypedNode,
+    ) -> TractResult<Option<Vec<&'m TypedNode>>> {
+        if self.ops.is_empty() || sink.op.name() != self.ops[self.ops.len() - 1] {
+            return Ok(None);
+        }
+        let mut assigned = vec![None; self.ops.len()];
+        assigned[self.ops.len() - 1] = Some(sink.id);
+        if !self.assign(model, &mut assigned, self.ops.len() - 1)? {
+            return Ok(None);
+        }
+        let nodes: Vec<&TypedNode> = assigned.iter().map(|id| model.node(id.unwrap())).collect();
+        for node in &nodes[..nodes.len() - 1] {
+            for (slot, output) in node.outputs.iter().enumerate() {
+                if model.output_outlets()?.contains(&OutletId::new(node.id, slot))
+                    || output.successors.iter().any(|s| !assigned.contains(&Some(s.node)))
+                {
+                    return Ok(None);
+                }
+            }
+        }
+        Ok(Some(nodes))
+    }
+
+    // assigns the pattern ops before `done`, backtracking on the candidates
+    fn assign(
+        &self,
+        model: &TypedModel,
+        assigned: &mut Vec<Option<usize>>,
+        done: usize,
+    ) -> TractResult<bool> {
+        if done == 0 {
+            return Ok(self.edges.iter().all(|&(from, to)| {
+                model
+                    .node(assigned[to].unwrap())
+                    .inputs
+                    .contains(&OutletId::new(assigned[from].unwrap(), 0))
+            }));
+        }
+        let ix = done - 1;
+        let to = self
+            .edges
+            .iter()
+            .find(|&&(from, to)| from == ix && to > ix)
+            .map(|e| e.1)
+            .ok_or_else(|| format!("Op #{} of {:?} feeds no later op", ix, self))?;
+        let consumer = model.node(assigned[to].unwrap());
+        for input in &consumer.inputs {
+            let candidate = model.node(input.node);
+            if input.slot != 0
+                || candidate.op.name() != self.ops[ix]
+                || assigned.contains(&Some(candidate.id))
+            {
+                continue;
+            }
+            assigned[ix] = Some(candidate.id);
+            if self.assign(model, assigned, ix)? {
+                return Ok(true);
+            }
+        }
+        assigned[ix] = None;
+        Ok(false)
+    }
+}
+
+/// Makes the op replacing the nodes matching a pattern, or None to leave them.
+pub type FusionBuilder =
+    dyn Fn(&TypedModel, &[&TypedNode]) -> TractResult<Option<Box<dyn TypedOp>>> + Send + Sync;
+
+/// A pattern and the builder of the op replacing it.
+///
+/// The fused op gets the inputs of the matched nodes that come from outside
+/// the pattern, in pattern order, and takes over the outputs of the sink.
+#[derive(Clone)]
+pub struct Fusion {
+    pub pattern: FusionPattern,
+    pub builder: Arc<FusionBuilder>,
+}
+
+impl fmt::Debug for Fusion {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Fusion {:?}", self.pattern)
+    }
+}
+
+impl Fusion {
+    /// A patch replacing the subgraph ending at `sink`, if it matches.
+    pub fn patch(
+        &self,
+        model: &TypedModel,
+        sink: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let nodes = if let Some(nodes) = self.pattern.matches(model, sink)? {
+            nodes
+        } else {
+            return Ok(None);
+        };
+        let op = if let Some(op) = (self.builder)(model, &nodes)? {
+            op
+        } else {
+            return Ok(None);
+        };
+        let mut patch = TypedModelPatch::default();
+        let mut inputs = tvec!();
+        for node in &nodes {
+            for input in &node.inputs {
+                if !nodes.iter().any(|n| n.id == input.node) {
+                    inputs.push(patch.tap_model(model, *input)?);
+                }
+            }
+        }
+        let wires = patch.wire_node(&*sink.name, op, &inputs)?;
+        if wires.len() != sink.outputs.len() {
+            bail!(
+                "Fusing {:?} gives {} outputs instead of {}",
+                self,
+                wires.len(),
+                sink.outputs.len()
+            )
+        }
+        for (ix, wire) in wires.into_iter().enumerate() {
+            patch.shunt_outside(model, OutletId::new(sink.id, ix), wire)?;
+        }
+        Ok(Some(patch))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Clone, Hash)]
+    struct AddRelu;
+    tract_linalg::impl_dyn_hash!(AddRelu);
+
+    impl Op for AddRelu {
+        fn name(&self) -> Cow<str> {
+            "AddRelu".into()
+        }
+
+        op_core_mir!();
+        op_as_typed_op!();
+        not_a_pulsed_op!();
+    }
+
+    impl StatelessOp for AddRelu {
+        fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+            let (a, b) = args_2!(inputs);
+            let mut sum = a.to_array_view::<f32>()?.to_owned();
+            sum.zip_mut_with(&b.to_array_view::<f32>()?, |a, b| *a = (*a + b).max(0.));
+            Ok(tvec!(sum.into_arc_tensor()))
+        }
+    }
+
+    impl TypedOp for AddRelu {
+        as_op!();
+
+        fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+            Ok(tvec!(inputs[0].clone()))
+        }
+    }
+
+    #[test]
+    fn add_relu_is_fused() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [3].as_ref())?;
+        let a = model.add_source("a", fact.clone())?;
+        let b = model.add_source("b", fact)?;
+        let add = model.wire_node("add", crate::ops::math::add::bin_typed(), &[a, b])?;
+        let relu = model.wire_node("relu", crate::ops::math::scalar_max(tensor0(0f32)), &add)?;
+        model.set_output_outlets(&relu)?;
+        model.register_fusion(FusionPattern::chain(&["Add", "ScalarMax"]), |_, nodes| {
+            assert_eq!(nodes.len(), 2);
+            Ok(Some(Box::new(AddRelu)))
+        });
+
+        let model = model.declutter()?;
+        assert_eq!(model.nodes().len(), 3);
+        assert_eq!(model.node(model.output_outlets()?[0].node).op.name(), "AddRelu");
+        let output = model
+            .into_runnable()?
+            .run(tvec!(tensor1(&[1f32, -2., 3.]), tensor1(&[-3f32, 1., 1.])))?;
+        assert_eq!(*output[0], tensor1(&[0f32, 0., 4.]));
+        Ok(())
+    }
+
+    #[test]
+    fn escaping_intermediate_is_not_fused() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [3].as_ref())?;
+        let a = model.add_source("a", fact.clone())?;
+        let b = model.add_source("b", fact)?;
+        let add = model.wire_node("add", crate::ops::math::add::bin_typed(), &[a, b])?;
+        let relu = model.wire_node("relu", crate::ops::math::scalar_max(tensor0(0f32)), &add)?;
+        model.set_output_outlets(&[add[0], relu[0]])?;
+        let pattern = FusionPattern::chain(&["Add", "ScalarMax"]);
+        assert!(pattern.matches(&model, model.node(relu[0].node))?.is_none());
+        Ok(())
+    }
+}
@@ -48,6 +48,7 @@ use itertools::Itertools;
 pub mod compact;
 pub mod dsl;
 mod fact;
+pub mod fusion;
 mod model;
 mod node;
 pub mod order;
@@ -56,16 +57,18 @@ pub mod translator;
 
 pub use self::dsl::*;
 pub use self::fact::*;
+pub use self::fusion::{Fusion, FusionPattern};
 pub use self::model::*;
 pub use self::node::*;
 pub use self::order::eval_order;
 pub use self::patch::ModelPatch;
 pub use crate::ops::{Op, TypedOp};
+pub use crate::optim::CodegenConfig;
 
 use crate::model::translator::Translate;
 use crate::ops::invariants;
 use crate::plan::{SimplePlan, SimpleState};
-use crate::TractResult;
+use crate::{TractResult, TractResultExt};
 use tract_linalg::hash::DynHash;
 
 /// Common methods for all variants of model.
@@ -109,6 +112,16 @@ pub trait Model: downcast_rs::Downcast + std::fmt::Debug + dyn_clone::DynClone {
     /// Outputs of the model
     fn output_outlets(&self) -> &[OutletId];
 
+    /// Names of the nodes of the model inputs
+    fn input_names(&self) -> Vec<&str> {
+        self.input_outlets().iter().map(|o| self.node_name(o.node)).collect()
+    }
+
+    /// Names of the nodes of the model outputs
+    fn output_names(&self) -> Vec<&str> {
+        self.output_outlets().iter().map(|o| self.node_name(o.node)).collect()
+    }
+
     /// Tensorfact for an outlet
     fn outlet_typedfact(&self, outlet: OutletId) -> TractResult<TypedFact>;
 
@@ -161,6 +174,38 @@ impl TypedModel {
         hasher.finish()
     }
 
+    /// `n` symbols no fact of the model uses yet, for dimensions known only
+    /// at runtime. The stream symbol `S` is never picked.
+    pub fn fresh_symbols(&self, n: usize) -> TractResult<TVec<char>> {
+        let used: std::collections::BTreeSet<char> = self
+            .nodes()
+            .iter()
+            .flat_map(|n| n.outputs.iter())
+            .flat_map(|o| o.fact.shape.iter())
+            .flat_map(|d| d.symbols())
+            .collect();
+        let fresh: TVec<char> = (b'A'..=b'Z')
+            .rev()
+            .map(|c| c as char)
+            .filter(|c| *c != 'S' && !used.contains(c))
+            .take(n)
+            .collect();
+        if fresh.len() < n {
+            bail!("No symbol left for {} runtime dimensions", n)
+        }
+        Ok(fresh)
+    }
+
+    /// Quantize the activations of float matmuls with quantized constant
+    /// weights at runtime, then declutter. This trades precision for speed,
+    /// so it is left out of `declutter` and `into_optimized`.
+    pub fn dynamic_quantize(self) -> TractResult<TypedModel> {
+        use crate::optim::TypedPass;
+        let mut model = self;
+        crate::optim::DynamicQuantizeMatMul.pass(&mut model)?;
+        model.declutter()
+    }
+
     /// Perform declutter pass on the network.
     pub fn declutter(self) -> TractResult<TypedModel> {
         let started = self.signature();
@@ -187,12 +232,111 @@ impl TypedModel {
         compact::compact(&model)
     }
 
+    /// Apply at most one operator declutter rule.
+    ///
+    /// Returns the resulting model, and the name of the operator whose rule
+    /// was applied, if any.
+    pub fn declutter_once(self) -> TractResult<(TypedModel, Option<String>)> {
+        let mut model = self;
+        for id in model.eval_order()? {
+            let node = &model.nodes()[id];
+            let patch =
+                node.op.declutter(&model, node).chain_err(|| format!("declutter node {}", node))?;
+            if let Some(patch) = patch {
+                let rule = node.op.name().to_string();
+                debug!("Apply a model patch for declutter {}", node);
+                patch.apply(&mut model)?;
+                return Ok((compact::compact(&model)?, Some(rule)));
+            }
+        }
+        Ok((model, None))
+    }
+
+    /// Check that all outlet facts are fully resolved.
+    ///
+    /// Typed facts always carry a datum type, so this checks that no shape
+    /// is left with a symbolic dimension, naming the first outlet that does.
+    pub fn assert_fully_typed(&self) -> TractResult<()> {
+        for node in self.nodes() {
+            for (ix, output) in node.outputs.iter().enumerate() {
+                if output.fact.shape.as_finite().is_none() {
+                    bail!(
+                        "Outlet {:?} of {} is not fully typed: {:?}",
+                        OutletId::new(node.id, ix),
+                        node,
+                        output.fact
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Register a fusion, applied by declutter: subgraphs matching `pattern`
+    /// are replaced by the op `builder` makes from their nodes.
+    pub fn register_fusion<B>(&mut self, pattern: FusionPattern, builder: B)
+    where
+        B: Fn(&TypedModel, &[&TypedNode]) -> TractResult<Option<Box<dyn TypedOp>>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.fusions.push(Fusion { pattern, builder: std::sync::Arc::new(builder) })
+    }
+
+    /// Outlets with a data-dependent shape.
+    ///
+    /// These are the outlets whose shape involves a symbol none of the model
+    /// inputs uses (like the count of a NonZero), as opposed to the streaming
+    /// symbols, so the model can not be fully specialized on input shapes.
+    pub fn dynamic_outputs(&self) -> Vec<OutletId> {
+        let input_symbols: std::collections::BTreeSet<char> = self
+            .inputs
+            .iter()
+            .flat_map(|i| self.nodes[i.node].outputs[i.slot].fact.shape.iter())
+            .flat_map(|d| d.symbols())
+            .collect();
+        let mut dynamic = vec![];
+        for node in self.nodes() {
+            for (ix, output) in node.outputs.iter().enumerate() {
+                if output
+                    .fact
+                    .shape
+                    .iter()
+                    .any(|d| d.symbols().iter().any(|s| !input_symbols.contains(s)))
+                {
+                    dynamic.push(OutletId::new(node.id, ix));
+                }
+            }
+        }
+        dynamic
+    }
+
     /// Translate the graph to optimized operators.
     pub fn codegen(self) -> TractResult<TypedModel> {
+        self.run_codegen_passes(crate::optim::codegen)
+    }
+
+    /// Translate the graph to optimized operators, following the options of
+    /// `config`.
+    pub fn codegen_with_config(self, config: &CodegenConfig) -> TractResult<TypedModel> {
+        self.run_codegen_passes(|| crate::optim::codegen_with_config(config))
+    }
+
+    /// Translate the graph to operators, preferring the ones with a smaller
+    /// memory footprint (like direct convolutions instead of im2col).
+    pub fn codegen_for_size(self) -> TractResult<TypedModel> {
+        self.run_codegen_passes(crate::optim::codegen_for_size)
+    }
+
+    fn run_codegen_passes(
+        self,
+        passes: impl Fn() -> Vec<Box<dyn crate::optim::TypedPass>>,
+    ) -> TractResult<TypedModel> {
         let mut model = self;
         loop {
             let mut done_something = false;
-            for p in crate::optim::codegen() {
+            for p in passes() {
                 done_something = done_something || p.pass(&mut model)?;
                 if cfg!(debug_assertions) {
                     model.check_edges()?;
@@ -210,6 +354,52 @@ impl TypedModel {
         invariants::for_model(self)
     }
 
+    /// Invariants (axis-tracking information) of a single node.
+    ///
+    /// Tells which input axes are mapped to which output axes by the node
+    /// operator, and whether the operator is element-wise.
+    pub fn node_invariants(&self, id: usize) -> TractResult<invariants::Invariants> {
+        let node = self.node(id);
+        node.op
+            .invariants(self, node)
+            .chain_err(|| format!("Computing invariants for {}", node))
+    }
+
+    /// Stable textual form of the graph, for review and diffing.
+    ///
+    /// One line per node, in id order: `id name = Op[info](inputs) : facts`,
+    /// followed by the full values of the constants.
+    pub fn to_text(&self) -> TractResult<String> {
+        use itertools::Itertools;
+        let outlet = |o: &OutletId| format!("{}.{}", o.node, o.slot);
+        let mut lines = vec![];
+        lines.push(format!("inputs: {}", self.input_outlets()?.iter().map(outlet).join(", ")));
+        lines.push(format!("outputs: {}", self.output_outlets()?.iter().map(outlet).join(", ")));
+        let mut consts = vec![];
+        for node in self.nodes() {
+            let info = node.op.info()?;
+            let info = if !info.is_empty() { format!("[{}]", info.join("; ")) } else { "".into() };
+            lines.push(format!(
+                "{} {} = {}{}({}) : {}",
+                node.id,
+                node.name,
+                node.op.name(),
+                info,
+                node.inputs.iter().map(outlet).join(", "),
+                node.outputs.iter().map(|o| o.fact.format_dt_shape()).join(", ")
+            ));
+            if let Some(k) = node.op_as::<crate::ops::konst::Const>() {
+                consts.push(format!("{} {} = {}", node.id, node.name, k.0.dump(true)?));
+            }
+        }
+        if !consts.is_empty() {
+            lines.push("constants:".into());
+            lines.extend(consts);
+        }
+        lines.push("".into());
+        Ok(lines.join("\n"))
+    }
+
     /// Attempt to convert the network to a NormalizedModel.
     pub fn into_normalized(self) -> TractResult<NormalizedModel> {
         crate::model::translator::IntoTranslator.translate_model(&self)
@@ -222,6 +412,15 @@ impl TypedModel {
         let model = compact::compact(&model)?;
         Ok(model)
     }
+
+    /// Declutter as much as possible, then translate to operators optimized
+    /// for size rather than speed.
+    pub fn optimize_for_size(self) -> TractResult<TypedModel> {
+        let model = self.declutter()?;
+        let model = model.codegen_for_size()?;
+        let model = compact::compact(&model)?;
+        Ok(model)
+    }
 }
 
 impl NormalizedModel {
@@ -243,4 +442,123 @@ mod test {
         is_sync::<TypedModel>();
         is_sync::<NormalizedModel>();
     }
+
+    #[test]
+    fn dequantize_invariants() -> TractResult<()> {
+        use crate::internal::*;
+        let mut model = TypedModel::default();
+        let source = model.add_source(
+            "source",
+            TypedFact::dt_shape(u8::datum_type(), [2usize, 3, 4].as_ref())?,
+        )?;
+        let dequant = model.wire_node(
+            "dequant",
+            crate::ops::quant::DequantizeLinearF32::new(0.1, 128),
+            &[source],
+        )?;
+        let invariants = model.node_invariants(dequant[0].node)?;
+        assert!(invariants.element_wise());
+        assert_eq!(invariants.axes.len(), 3);
+        for axis in 0..3 {
+            assert_eq!(invariants.unary_track_axis_up(axis, false), Some(axis));
+            assert_eq!(invariants.unary_track_axis_down(axis, false), Some(axis));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn text_form() -> TractResult<()> {
+        use crate::internal::*;
+        let build = |k: f32| -> TractResult<String> {
+            let mut model = TypedModel::default();
+            let source =
+                model.add_source("source", TypedFact::dt_shape(f32::datum_type(), [2].as_ref())?)?;
+            let k = model.add_const("k", tensor1(&[1f32, k]))?;
+            let add = model.wire_node("add", crate::ops::math::add::bin_typed(), &[source, k])?;
+            model.set_output_outlets(&add)?;
+            model.to_text()
+        };
+        assert_eq!(build(2.)?, build(2.)?);
+        let diff = build(2.)?
+            .lines()
+            .zip(build(3.)?.lines())
+            .filter(|(a, b)| a != b)
+            .map(|(_, b)| b.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(diff, vec!["1 k = 2xF32 1, 3"]);
+        Ok(())
+    }
+
+    #[test]
+    fn assert_fully_typed() -> TractResult<()> {
+        use crate::internal::*;
+        let build = |len: TDim| -> TractResult<TypedModel> {
+            let mut model = TypedModel::default();
+            let fact = TypedFact::dt_shape(f32::datum_type(), [len, 2.to_dim()].as_ref())?;
+            let source = model.add_source("source", fact)?;
+            let neg = model.wire_node("neg", crate::ops::math::neg(), &[source])?;
+            model.set_output_outlets(&neg)?;
+            Ok(model)
+        };
+        build(3.to_dim())?.assert_fully_typed()?;
+        let err = build(TDim::s())?.assert_fully_typed().unwrap_err().to_string();
+        assert!(err.contains("\"source\""), "{}", err);
+        assert!(build(TDim::s())?.dynamic_outputs().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn batch_and_stream_symbols() -> TractResult<()> {
+        use crate::internal::*;
+        let mut model = TypedModel::default();
+        let shape = [TDim::sym('N'), TDim::s(), 3.to_dim()];
+        let fact = TypedFact::dt_shape(f32::datum_type(), shape.as_ref())?;
+        assert_eq!(fact.shape.stream_info().map(|s| s.axis), Some(1));
+        let source = model.add_source("source", fact)?;
+        let neg = model.wire_node("neg", crate::ops::math::neg(), &[source])?;
+        model.set_output_outlets(&neg)?;
+        assert_eq!(&*model.output_fact(0)?.shape.to_tvec(), &shape);
+        assert!(crate::pulse::PulsedModel::new(&model.clone().into_normalized()?, 4).is_err());
+
+        let model = model.into_optimized()?;
+        let input = Tensor::zero::<f32>(&[2, 5, 3])?;
+        let output = model.into_runnable()?.run(tvec!(input))?;
+        assert_eq!(output[0].shape(), &[2, 5, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn declutter_once_applies_one_rule() -> TractResult<()> {
+        use crate::internal::*;
+        use crate::ops::array::{Pad, PadMode};
+        use crate::ops::cnn::{ConvUnary, KernelFormat, PaddingSpec, PoolSpec};
+        use crate::ops::nn::DataFormat;
+        let conv = ConvUnary::new(
+            PoolSpec::new(DataFormat::NCHW, tvec!(3, 3), PaddingSpec::Valid, None, None, Some(1)),
+            KernelFormat::OIHW,
+            rctensor4(&[[[[1f32, 2., 3.], [4., 5., 6.], [7., 8., 9.]]]]),
+            1,
+            None,
+            None,
+        );
+        let pad = Pad::new(vec![(0, 0), (0, 0), (1, 1), (1, 1)], PadMode::default());
+        let mut model = TypedModel::default();
+        let mut wire = model.add_source(
+            "source",
+            TypedFact::dt_shape(f32::datum_type(), [1usize, 1, 4, 4].as_ref())?,
+        )?;
+        for i in 0..2 {
+            wire = model.wire_node(format!("pad-{}", i), pad.clone(), &[wire])?[0];
+            wire = model.wire_node(format!("conv-{}", i), conv.clone(), &[wire])?[0];
+        }
+        model.set_output_outlets(&[wire])?;
+
+        let (once, rule) = model.declutter_once()?;
+        assert_eq!(rule.as_ref().map(|s| s.as_str()), Some("Conv"));
+        assert_eq!(once.nodes().iter().filter(|n| n.op_is::<Pad>()).count(), 1);
+        let (twice, rule) = once.declutter_once()?;
+        assert_eq!(rule.as_ref().map(|s| s.as_str()), Some("Conv"));
+        assert_eq!(twice.nodes().iter().filter(|n| n.op_is::<Pad>()).count(), 0);
+        Ok(())
+    }
 }
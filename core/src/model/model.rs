@@ -26,6 +26,12 @@ where
     /// outlet labels
     #[educe(Hash(method = "hash_outlet_labels"))]
     pub outlet_labels: HashMap<OutletId, String>,
+    /// user metadata attached to nodes, by node id
+    #[educe(Hash(ignore))]
+    pub node_metadata: HashMap<usize, HashMap<String, String>>,
+    /// fusions registered by the user, applied by declutter on typed models
+    #[educe(Hash(ignore))]
+    pub fusions: Vec<crate::model::fusion::Fusion>,
 }
 
 fn hash_outlet_labels<H: std::hash::Hasher>(it: &HashMap<OutletId, String>, state: &mut H) {
@@ -55,6 +61,8 @@ where
             inputs: vec![],
             outputs: vec![],
             outlet_labels: HashMap::new(),
+            node_metadata: HashMap::new(),
+            fusions: vec![],
         }
     }
 }
@@ -394,6 +402,27 @@ where
         self.outlet_labels.iter().find(|(_k, v)| &**v == label).map(|(k, _v)| *k)
     }
 
+    // node metadata
+
+    /// Get the user metadata attached to a node.
+    pub fn node_metadata(&self, node: usize) -> Option<&HashMap<String, String>> {
+        self.node_metadata.get(&node)
+    }
+
+    /// Attach a user metadata entry to a node.
+    pub fn set_node_metadata(
+        &mut self,
+        node: usize,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> TractResult<()> {
+        if node >= self.nodes.len() {
+            bail!("Invalid node id {}", node)
+        }
+        self.node_metadata.entry(node).or_default().insert(key.into(), value.into());
+        Ok(())
+    }
+
     // misc
 
     /// Computes an evalutation order for the graph inputs and outputs
@@ -401,6 +430,45 @@ where
         eval_order(&self)
     }
 
+    /// Evaluation order, with the outlets live at each step.
+    ///
+    /// For each node in `eval_order()`, gives the outlets that have been
+    /// computed (including the node own outputs) and are still needed by the
+    /// node itself, a later node, or as a model output.
+    pub fn eval_order_with_scopes(&self) -> TractResult<Vec<(usize, Vec<OutletId>)>> {
+        let order = self.eval_order()?;
+        let mut step_of = vec![None; self.nodes.len()];
+        for (step, &node) in order.iter().enumerate() {
+            step_of[node] = Some(step);
+        }
+        let outputs = self.output_outlets()?;
+        let mut last_use: HashMap<OutletId, usize> = HashMap::new();
+        for (step, &node) in order.iter().enumerate() {
+            for (slot, output) in self.nodes[node].outputs.iter().enumerate() {
+                let outlet = OutletId::new(node, slot);
+                let last = if outputs.contains(&outlet) {
+                    order.len()
+                } else {
+                    output
+                        .successors
+                        .iter()
+                        .filter_map(|succ| step_of[succ.node])
+                        .max()
+                        .unwrap_or(step)
+                };
+                last_use.insert(outlet, last);
+            }
+        }
+        let mut live = std::collections::BTreeSet::new();
+        let mut scopes = Vec::with_capacity(order.len());
+        for (step, &node) in order.iter().enumerate() {
+            live.extend((0..self.nodes[node].outputs.len()).map(|slot| OutletId::new(node, slot)));
+            scopes.push((node, live.iter().cloned().collect()));
+            live = live.into_iter().filter(|outlet| last_use[outlet] > step).collect();
+        }
+        Ok(scopes)
+    }
+
     /// Performs a sanity check on network connections.
     pub fn check_edges(&self) -> TractResult<()> {
         for node in self.eval_order()? {
@@ -596,4 +664,90 @@ mod test {
         let mut hasher = std::collections::hash_map::DefaultHasher::default();
         model.hash(&mut hasher);
     }
+
+    #[test]
+    fn scopes_on_a_chain() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let mut wire =
+            model.add_source("source", TypedFact::dt_shape(DatumType::F32, [2].as_ref())?)?;
+        for i in 0..4 {
+            wire = model.wire_node(format!("neg-{}", i), crate::ops::math::neg(), &[wire])?[0];
+        }
+        model.set_output_outlets(&[wire])?;
+        let scopes = model.eval_order_with_scopes()?;
+        assert_eq!(scopes.iter().map(|s| s.0).collect::<Vec<_>>(), model.eval_order()?);
+        assert!(scopes.iter().all(|s| s.1.len() <= 2));
+        assert_eq!(scopes[0].1, vec!(OutletId::new(0, 0)));
+        assert_eq!(scopes[4].1, vec!(OutletId::new(3, 0), OutletId::new(4, 0)));
+        Ok(())
+    }
+
+    #[test]
+    fn input_and_output_names() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(DatumType::F32, [2].as_ref())?;
+        let a = model.add_source("a", fact.clone())?;
+        let b = model.add_source("b", fact)?;
+        let neg = model.wire_node("neg", crate::ops::math::neg(), &[a])?;
+        let abs = model.wire_node("abs", crate::ops::math::abs(), &[b])?;
+        model.set_output_outlets(&[neg[0], abs[0]])?;
+        model.set_input_names(&["b", "a"])?;
+        model.set_output_names(&["abs", "neg"])?;
+        assert_eq!(model.input_names(), vec!("b", "a"));
+        assert_eq!(model.output_names(), vec!("abs", "neg"));
+        Ok(())
+    }
+
+    #[test]
+    fn node_metadata_survives_declutter() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(DatumType::F32, [2].as_ref())?)?;
+        let id = model.wire_node("id", crate::ops::identity::Identity, &[source])?;
+        let neg = model.wire_node("neg", crate::ops::math::neg(), &id)?;
+        model.set_output_outlets(&neg)?;
+        model.set_node_metadata(neg[0].node, "origin", "layer-1")?;
+        let model = model.declutter()?;
+        assert_eq!(model.nodes().len(), 2);
+        let neg = model.node_by_name("neg")?.id;
+        assert_eq!(model.node_metadata(neg).unwrap()["origin"], "layer-1");
+        Ok(())
+    }
+
+    #[test]
+    fn node_metadata_stays_off_shunted_nodes() -> TractResult<()> {
+        use crate::model::translator::{IntoTranslator, Translate};
+        #[derive(Debug)]
+        struct DropNeg;
+        impl Translate<TypedFact, Box<dyn TypedOp>, TypedFact, Box<dyn TypedOp>> for DropNeg {
+            fn translate_node(
+                &self,
+                source: &TypedModel,
+                node: &TypedNode,
+                target: &mut TypedModel,
+                mapping: &HashMap<OutletId, OutletId>,
+            ) -> TractResult<TVec<OutletId>> {
+                if node.op.name() == "Neg" {
+                    Ok(tvec!(mapping[&node.inputs[0]]))
+                } else {
+                    IntoTranslator.translate_node(source, node, target, mapping)
+                }
+            }
+        }
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(DatumType::F32, [2].as_ref())?)?;
+        let abs = model.wire_node("abs", crate::ops::math::abs(), &[source])?;
+        let neg = model.wire_node("neg", crate::ops::math::neg(), &abs)?;
+        model.set_output_outlets(&neg)?;
+        model.set_node_metadata(abs[0].node, "origin", "layer-1")?;
+        model.set_node_metadata(neg[0].node, "origin", "layer-2")?;
+        model.set_node_metadata(neg[0].node, "extra", "dropped")?;
+        let model = DropNeg.translate_model(&model)?;
+        let abs = model.node_by_name("abs")?.id;
+        let metadata = model.node_metadata(abs).unwrap();
+        assert_eq!(metadata["origin"], "layer-1");
+        assert!(!metadata.contains_key("extra"));
+        Ok(())
+    }
 }
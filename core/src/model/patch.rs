@@ -204,7 +204,9 @@ where
     pub fn apply(self, target: &mut ModelImpl<F, O>) -> TractResult<()> {
         let prior_target_inputs = target.input_outlets()?.len();
         let prior_target_outputs = target.output_outlets()?.len();
-        let ModelPatch { model: patch, incoming: mut mapping, shunt_outlet_by, obliterate } = self;
+        let ModelPatch { model: mut patch, incoming: mut mapping, shunt_outlet_by, obliterate } =
+            self;
+        let mut patch_metadata = std::mem::replace(&mut patch.node_metadata, Default::default());
         let mut all_inputs = HashMap::new(); // new_id -> [ old_inputs ]
         for node in patch.nodes {
             if <ModelImpl<F, O>>::is_source(node.op()) {
@@ -217,6 +219,9 @@ where
             for ix in 0..n_outputs {
                 mapping.insert(OutletId::new(id, ix), OutletId::new(added_node_id, ix));
             }
+            if let Some(metadata) = patch_metadata.remove(&id) {
+                target.node_metadata.insert(added_node_id, metadata);
+            }
             all_inputs.insert(added_node_id, inputs);
         }
         debug_assert_eq!(target.input_outlets()?.len(), prior_target_inputs);
@@ -235,6 +240,14 @@ where
             if let Some(label) = target.outlet_label(outlet).map(|s| s.to_string()) {
                 target.set_outlet_label(fixed_by, label)?;
             }
+            if all_inputs.contains_key(&fixed_by.node) {
+                if let Some(metadata) = target.node_metadata(outlet.node).cloned() {
+                    let carried = target.node_metadata.entry(fixed_by.node).or_default();
+                    for (k, v) in metadata {
+                        carried.entry(k).or_insert(v);
+                    }
+                }
+            }
         }
         debug_assert_eq!(target.input_outlets()?.len(), prior_target_inputs);
         debug_assert_eq!(target.output_outlets()?.len(), prior_target_outputs);
@@ -253,3 +266,126 @@ where
         Ok(())
     }
 }
+
+impl TypedModelPatch {
+    /// Check the patch against the model it is meant to be applied to.
+    ///
+    /// Verifies that the tapped and shunted outlets facts agree with the
+    /// target model, and that the facts declared for each new node match
+    /// what its op computes. Meant to catch broken rewrites before `apply`.
+    pub fn check(&self, target: &TypedModel) -> TractResult<()> {
+        for (inside, outside) in &self.incoming {
+            let inside_fact = self.model.outlet_fact(*inside)?;
+            let outside_fact = target.outlet_fact(*outside)?;
+            if !inside_fact.same_as(outside_fact) {
+                bail!(
+                    "Patch taps {:?} as {:?}, but target model has {:?}",
+                    outside,
+                    inside_fact,
+                    outside_fact
+                );
+            }
+        }
+        for (outlet, by) in &self.shunt_outlet_by {
+            let original_fact = target.outlet_fact(*outlet)?;
+            let new_fact = self.model.outlet_fact(*by)?;
+            if !original_fact.same_as(new_fact) {
+                bail!(
+                    "Patch shunts {:?} ({:?}) by {} output #{} ({:?})",
+                    outlet,
+                    original_fact,
+                    self.model.node(by.node),
+                    by.slot,
+                    new_fact
+                );
+            }
+        }
+        for node in self.model.nodes() {
+            if self.incoming.contains_key(&OutletId::new(node.id, 0)) {
+                continue;
+            }
+            let inputs = self.model.node_input_facts(node.id)?;
+            let computed = node
+                .op
+                .output_facts(&*inputs)
+                .chain_err(|| format!("Computing output facts for patch node {}", node))?;
+            if computed.len() != node.outputs.len() {
+                bail!(
+                    "Patch node {} declares {} outputs, but its op computes {}",
+                    node,
+                    node.outputs.len(),
+                    computed.len()
+                );
+            }
+            for (ix, (declared, computed)) in node.outputs.iter().zip(computed.iter()).enumerate() {
+                if !declared.fact.same_as(computed) {
+                    bail!(
+                        "Patch node {} declares output #{} as {:?}, but its op computes {:?}",
+                        node,
+                        ix,
+                        declared.fact,
+                        computed
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn check_rejects_mismatched_facts() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [2].as_ref())?;
+        let source = model.add_source("source", fact.clone())?;
+        let neg = model.wire_node("neg", crate::ops::math::neg(), &[source])?;
+        model.set_output_outlets(&neg)?;
+
+        let patch = TypedModelPatch::intercept(
+            &model,
+            neg[0],
+            "abs",
+            crate::ops::math::abs(),
+            fact.clone(),
+        )?;
+        patch.check(&model)?;
+
+        let patch = TypedModelPatch::intercept(
+            &model,
+            neg[0],
+            "cast",
+            crate::ops::cast::cast(i32::datum_type()),
+            fact,
+        )?;
+        let err = patch.check(&model).unwrap_err().to_string();
+        assert!(err.contains("\"cast\""), "{}", err);
+        assert!(err.contains("declares output #0 as 2xF32"), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn apply_carries_node_metadata() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [2].as_ref())?;
+        let source = model.add_source("source", fact)?;
+        let neg = model.wire_node("neg", crate::ops::math::neg(), &[source])?;
+        model.set_output_outlets(&neg)?;
+        model.set_node_metadata(neg[0].node, "origin", "layer-1")?;
+
+        let patch = TypedModelPatch::replace_single_op(
+            &model,
+            model.node(neg[0].node),
+            &[source],
+            crate::ops::math::abs(),
+        )?;
+        patch.apply(&mut model)?;
+        let abs = model.output_outlets()?[0].node;
+        assert_ne!(abs, neg[0].node);
+        assert_eq!(model.node_metadata(abs).unwrap()["origin"], "layer-1");
+        Ok(())
+    }
+}
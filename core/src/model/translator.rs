@@ -32,9 +32,20 @@ where
         for old_id in source.eval_order()? {
             let node = source.node(old_id);
             debug!("Translating {} {:?}", node, self);
+            let first_new_node = target.nodes().len();
             let outlets = self
                 .translate_node(&source, node, &mut target, &mapping)
                 .chain_err(|| format!("Translating node {} {:?}", node, self))?;
+            // a node dropped or shunted to an existing one leaves no metadata
+            if let (Some(metadata), Some(o)) = (source.node_metadata.get(&old_id), outlets.first())
+            {
+                if o.node >= first_new_node {
+                    let target_metadata = target.node_metadata.entry(o.node).or_default();
+                    for (k, v) in metadata {
+                        target_metadata.entry(k.clone()).or_insert_with(|| v.clone());
+                    }
+                }
+            }
             for (ix, outlet) in outlets.into_iter().enumerate() {
                 mapping.insert(OutletId::new(node.id, ix), outlet);
                 if let Some(label) = source.outlet_label(OutletId::new(node.id, ix)) {
@@ -56,6 +67,7 @@ where
         // maintaining order of i/o interface
         target.inputs = source.input_outlets()?.iter().map(|i| mapping[&i]).collect();
         target.outputs = source.output_outlets()?.iter().map(|o| mapping[&o]).collect();
+        target.fusions = source.fusions.clone();
         Ok((target, mapping))
     }
 }
@@ -7,10 +7,35 @@ pub struct MultiBroadcastTo {
 tract_linalg::impl_dyn_hash!(MultiBroadcastTo);
 
 impl MultiBroadcastTo {
-    pub fn eval_t<T: Datum>(input: &Tensor, shape: &[usize]) -> TractResult<TVec<Arc<Tensor>>> {
-        let input = input.to_array_view::<T>()?;
-        let output = input.broadcast(&*shape).ok_or("incompatible shapes")?;
-        Ok(tvec![output.to_owned().into_arc_tensor()])
+    /// Shape of `input` broadcast to `target`, right-aligned. An input dim
+    /// must be 1 or match the target one, and a target dim of 1 keeps the
+    /// input dim.
+    pub fn broadcast_shape<D: DimLike>(input: &[D], target: &[D]) -> TractResult<TVec<D>> {
+        let rank = input.len().max(target.len());
+        let left_pad = |shape: &[D]| -> TVec<D> {
+            std::iter::repeat(D::one())
+                .take(rank - shape.len())
+                .chain(shape.iter().cloned())
+                .collect()
+        };
+        let mut shape: TVec<D> = tvec!();
+        for (axis, (i, t)) in left_pad(input).into_iter().zip(left_pad(target)).enumerate() {
+            if t == D::one() || i == t {
+                shape.push(i);
+            } else if i == D::one() {
+                shape.push(t);
+            } else {
+                bail!(
+                    "Can not broadcast {:?} to {:?}: axis {} has dim {:?}, expected 1 or {:?}",
+                    input,
+                    target,
+                    axis,
+                    i,
+                    t
+                );
+            }
+        }
+        Ok(shape)
     }
 }
 
@@ -29,15 +54,43 @@ impl StatelessOp for MultiBroadcastTo {
     fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
         let input = args_1!(inputs);
         let dims: Vec<usize> =
-            self.shape.iter().map(|d| Ok(d.to_integer()? as usize)).collect::<TractResult<_>>()?;
-        dispatch_datum!(Self::eval_t(input.datum_type())(&*input, &*dims))
+            self.shape.iter().map(|d| d.to_usize()).collect::<TractResult<_>>()?;
+        let dims = Self::broadcast_shape(input.shape(), &*dims)?;
+        Ok(tvec!(input.broadcast_to(&*dims)?.into_arc_tensor()))
     }
 }
 
 impl TypedOp for MultiBroadcastTo {
     fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
-        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*self.shape)?))
+        let shape = Self::broadcast_shape(&*inputs[0].shape.to_tvec(), &*self.shape)?;
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*shape)?))
     }
 
     as_op!();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn expand(input: &[usize], target: &[usize]) -> TractResult<TVec<usize>> {
+        let fact = TypedFact::dt_shape(f32::datum_type(), input)?;
+        let op = MultiBroadcastTo::new(target.iter().map(|d| d.to_dim()).collect());
+        let output = op.output_facts(&[&fact])?.remove(0);
+        Ok(output.shape.as_finite().unwrap().into())
+    }
+
+    #[test]
+    fn expand_rejects_incompatible_dim() {
+        let err = expand(&[2, 3], &[2, 4]).unwrap_err().to_string();
+        assert!(err.contains("axis 1"), "{}", err);
+    }
+
+    #[test]
+    fn expand_compatible_dims() -> TractResult<()> {
+        assert_eq!(&*expand(&[3], &[3])?, &[3]);
+        assert_eq!(&*expand(&[1], &[4])?, &[4]);
+        assert_eq!(&*expand(&[3], &[2, 1])?, &[2, 3]);
+        Ok(())
+    }
+}
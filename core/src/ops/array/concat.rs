@@ -84,17 +84,31 @@ impl TypedOp for TypedConcat {
                 }
             })
             .unwrap();
-        for input in inputs {
-            if input.rank() != fact.rank()
-                || input
-                    .shape
-                    .iter()
-                    .zip(fact.shape.iter())
-                    .enumerate()
-                    .filter(|(ax, _)| *ax != self.axis)
-                    .any(|(_, (i, f))| i != f)
-            {
-                bail!("Inconsistent concat {:?} inputs: {:?}", self, inputs);
+        let shapes = inputs.iter().map(|f| f.shape.to_tvec()).chain(
+            self.slices
+                .iter()
+                .filter_map(|s| s.as_const())
+                .map(|t| t.shape().iter().map(|d| d.to_dim()).collect()),
+        );
+        for shape in shapes {
+            if shape.len() != fact.rank() {
+                bail!(
+                    "Concat on axis {}: inputs have different ranks ({} vs {})",
+                    self.axis,
+                    fact.rank(),
+                    shape.len()
+                );
+            }
+            for (axis, dim) in shape.iter().enumerate() {
+                if axis != self.axis && *dim != fact.shape.dim(axis) {
+                    bail!(
+                        "Concat on axis {}: inputs disagree on axis {} ({} vs {})",
+                        self.axis,
+                        axis,
+                        fact.shape.dim(axis),
+                        dim
+                    );
+                }
             }
         }
         let dim = inputs.iter().map(|f| f.shape.dim(self.axis)).sum::<TDim>()
@@ -120,6 +134,31 @@ impl TypedOp for TypedConcat {
         }
     }
 
+    fn quantize(
+        &self,
+        _model: &TypedModel,
+        _node: &TypedNode,
+        dt: DatumType,
+        scale: f32,
+        zero_point: i32,
+    ) -> TractResult<Option<Box<dyn TypedOp>>> {
+        if self.slices.iter().filter(|s| s.is_var()).count() != 1 {
+            return Ok(None);
+        }
+        let slices = self
+            .slices
+            .iter()
+            .map(|s| match s {
+                ConcatSlice::Const(t) => Ok(ConcatSlice::Const(
+                    crate::ops::quant::quantize_linear_f32(t, dt, scale, zero_point)?
+                        .into_arc_tensor(),
+                )),
+                ConcatSlice::Var => Ok(ConcatSlice::Var),
+            })
+            .collect::<TractResult<_>>()?;
+        Ok(Some(Box::new(TypedConcat { axis: self.axis, slices })))
+    }
+
     fn change_axes(
         &self,
         model: &TypedModel,
@@ -163,7 +202,7 @@ impl TypedOp for TypedConcat {
             let offsets = self
                 .offsets(&inputs)?
                 .iter()
-                .map(|x| x.to_integer().map(|i| i as usize))
+                .map(|x| x.to_usize())
                 .collect::<TractResult<Vec<usize>>>()?;
             for (ix, slice) in self.slices.iter().enumerate() {
                 if start >= offsets[ix] && end <= offsets[ix + 1] {
@@ -431,7 +470,8 @@ impl OpState for PulsedSameAxisConcatState {
             pre_offset
         ))?;
         if let Some(l) = session.known_stream_len {
-            let input_length = op.input_len.eval(l as i32).unwrap() as usize;
+            let input_length =
+                crate::pulse::eval_stream_dim(&op.input_len, l, &session.resolved_symbols)?;
             let post_offset = op.input_delay + input_length;
             dispatch_datum!(overwrite_part_of_pulse(data.datum_type())(
                 op.axis,
@@ -466,3 +506,17 @@ fn slices<'a, 'i: 'a, 'o: 'a>(
     }
     Ok(mats)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concat_mismatching_dims() {
+        let op = TypedConcat::concat_vars(0, 2);
+        let a = TypedFact::dt_shape(f32::datum_type(), [2, 3].as_ref()).unwrap();
+        let b = TypedFact::dt_shape(f32::datum_type(), [2, 4].as_ref()).unwrap();
+        let err = op.output_facts(&[&a, &b]).unwrap_err();
+        assert!(err.to_string().contains("axis 1"));
+    }
+}
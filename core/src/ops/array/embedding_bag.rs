@@ -0,0 +1,123 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// How the rows gathered in a bag are reduced.
+#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+pub enum EmbeddingBagMode {
+    Sum,
+    Mean,
+}
+
+/// Gathers rows of an embedding table and reduces them by bags.
+///
+/// Inputs are the `[rows, dim]` table, a flat vector of row indices, and a
+/// vector of offsets in the indices, one per bag: bag `i` spans the indices
+/// from `offsets[i]` to `offsets[i + 1]` (or to the end for the last bag).
+/// The output is `[bags, dim]`, empty bags giving zeroes.
+#[derive(Debug, Clone, new, Hash)]
+pub struct EmbeddingBag {
+    pub mode: EmbeddingBagMode,
+}
+
+tract_linalg::impl_dyn_hash!(EmbeddingBag);
+
+impl EmbeddingBag {
+    fn eval_t<T: Datum + num_traits::Float + num_traits::FromPrimitive>(
+        &self,
+        table: &Tensor,
+        indices: &[i64],
+        offsets: &[i64],
+    ) -> TractResult<Arc<Tensor>> {
+        let table = table.to_array_view::<T>()?.into_dimensionality::<Ix2>()?;
+        let mut output = Array2::<T>::zeros((offsets.len(), table.shape()[1]));
+        for (bag, mut row) in output.outer_iter_mut().enumerate() {
+            let start = offsets[bag];
+            let end = offsets.get(bag + 1).cloned().unwrap_or(indices.len() as i64);
+            if start < 0 || end < start || end > indices.len() as i64 {
+                bail!("Invalid offsets {:?} for {} indices", offsets, indices.len());
+            }
+            for &index in &indices[start as usize..end as usize] {
+                if index < 0 || index >= table.shape()[0] as i64 {
+                    bail!("Index {} is out of a table of {} rows", index, table.shape()[0]);
+                }
+                row.zip_mut_with(&table.index_axis(Axis(0), index as usize), |r, &x| *r = *r + x);
+            }
+            if self.mode == EmbeddingBagMode::Mean && end > start {
+                let count = T::from_i64(end - start).unwrap();
+                row.mapv_inplace(|x| x / count);
+            }
+        }
+        Ok(output.into_arc_tensor())
+    }
+}
+
+impl Op for EmbeddingBag {
+    fn name(&self) -> Cow<str> {
+        "EmbeddingBag".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("mode: {:?}", self.mode)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+    not_a_pulsed_op!();
+}
+
+impl StatelessOp for EmbeddingBag {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (table, indices, offsets) = args_3!(inputs);
+        let indices = indices.cast_to::<i64>()?;
+        let offsets = offsets.cast_to::<i64>()?;
+        let (indices, offsets) = (indices.as_slice::<i64>()?, offsets.as_slice::<i64>()?);
+        let result =
+            dispatch_floatlike!(Self::eval_t(table.datum_type())(self, &table, indices, offsets))?;
+        Ok(tvec!(result))
+    }
+}
+
+impl TypedOp for EmbeddingBag {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs[0].rank() != 2 || inputs[1].rank() != 1 || inputs[2].rank() != 1 {
+            bail!("EmbeddingBag expects a 2D table, 1D indices and offsets, got {:?}", inputs);
+        }
+        let shape = [inputs[2].shape.dim(0), inputs[0].shape.dim(1)];
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, shape.as_ref())?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sum_two_bags() -> TractResult<()> {
+        let table = (0..15).map(|i| i as f32).collect::<Vec<_>>();
+        let table = unsafe { tensor1(&table).into_shape(&[5, 3])? };
+        let mut model = TypedModel::default();
+        let table = model.add_const("table", table)?;
+        let fact = TypedFact::dt_shape(i64::datum_type(), [TDim::s()].as_ref())?;
+        let indices = model.add_source("indices", fact.clone())?;
+        let offsets = model.add_source("offsets", fact)?;
+        let op = EmbeddingBag::new(EmbeddingBagMode::Sum);
+        let bags = model.wire_node("bags", op.clone(), &[table, indices, offsets])?;
+        assert_eq!(model.outlet_fact(bags[0])?.shape.to_tvec(), tvec!(TDim::s(), 3.to_dim()));
+
+        let table = model.outlet_fact(table)?.konst.clone().unwrap();
+        let found = op.eval(tvec!(table, rctensor1(&[0i64, 4, 1, 1, 3]), rctensor1(&[0i64, 2])))?;
+        assert_eq!(*found[0], tensor2(&[[12f32, 14., 16.], [15., 18., 21.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn mean_with_empty_bag() -> TractResult<()> {
+        let table = rctensor2(&[[1f32, 2.], [3., 4.]]);
+        let op = EmbeddingBag::new(EmbeddingBagMode::Mean);
+        let found = op.eval(tvec!(table, rctensor1(&[0i64, 1]), rctensor1(&[0i64, 0])))?;
+        assert_eq!(*found[0], tensor2(&[[0f32, 0.], [2., 3.]]));
+        Ok(())
+    }
+}
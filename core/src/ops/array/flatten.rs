@@ -4,7 +4,7 @@ use crate::internal::*;
 
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct Flatten {
-    axis: usize,
+    axis: isize,
 }
 tract_linalg::impl_dyn_hash!(Flatten);
 
@@ -22,7 +22,13 @@ impl Flatten {
         if shape.iter().filter(|d| d.to_integer().is_err()).count() > 1 {
             bail!("Can not compute a shape with square of symbols")
         }
-        Ok([shape[..self.axis].iter().maybe_product()?, shape[self.axis..].iter().maybe_product()?])
+        // axis can be the rank itself, flattening to (N, 1)
+        let axis = if self.axis == shape.len() as isize {
+            shape.len()
+        } else {
+            crate::ops::normalize_axis(self.axis, shape.len())?
+        };
+        Ok([shape[..axis].iter().maybe_product()?, shape[axis..].iter().maybe_product()?])
     }
 }
 
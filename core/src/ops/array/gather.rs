@@ -1,17 +1,41 @@
 use crate::internal::*;
+use crate::ops::array::Slice;
 use ndarray::*;
 
-#[derive(Debug, Clone, new, Hash)]
+#[derive(Debug, Clone, Hash)]
 pub struct Gather {
     axis: i64,
+    proved: Option<Arc<Tensor>>,
 }
 tract_linalg::impl_dyn_hash!(Gather);
 
+impl Gather {
+    pub fn new(axis: i64) -> Gather {
+        Gather { axis, proved: None }
+    }
+
+    /// A Gather using `indices`, already wrapped and checked against the
+    /// gathered axis, instead of validating its indices input at each eval.
+    ///
+    /// Only meant for constant indices (see `declutter`).
+    pub(crate) fn with_proved_indices(axis: i64, indices: Arc<Tensor>) -> Gather {
+        Gather { axis, proved: Some(indices) }
+    }
+}
+
 impl Op for Gather {
     fn name(&self) -> Cow<str> {
         "Gather".into()
     }
 
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "axis: {}{}",
+            self.axis,
+            if self.proved.is_some() { " (proved)" } else { "" }
+        )])
+    }
+
     op_core_mir!();
     op_as_typed_op!();
     not_a_pulsed_op!();
@@ -19,13 +43,7 @@ impl Op for Gather {
 
 impl Gather {
     fn resolved_axis(&self, rank: usize) -> TractResult<usize> {
-        if 0 <= self.axis && self.axis <= rank as i64 - 1 {
-            Ok(self.axis as usize)
-        } else if -(rank as i64) <= self.axis && self.axis < 0 {
-            Ok((self.axis + rank as i64) as usize)
-        } else {
-            bail!("Illegal combination of values for rank and axis")
-        }
+        crate::ops::normalize_axis(self.axis as isize, rank)
     }
 
     pub fn compute_output_shape<D: DimLike>(
@@ -47,6 +65,19 @@ impl Gather {
         Ok(output_shape)
     }
 
+    /// Wrap negative indices, and check they all fall in the axis.
+    fn checked_indices(indices: &Tensor, len: usize) -> TractResult<Tensor> {
+        let mut indices = indices.cast_to::<i64>()?.into_owned();
+        for index in indices.as_slice_mut::<i64>()? {
+            let wrapped = if *index < 0 { *index + len as i64 } else { *index };
+            if wrapped < 0 || wrapped >= len as i64 {
+                bail!("Gather index {} is out of an axis of length {}", index, len);
+            }
+            *index = wrapped;
+        }
+        Ok(indices)
+    }
+
     fn eval_t<T: Datum>(
         &self,
         data: Arc<Tensor>,
@@ -54,12 +85,15 @@ impl Gather {
     ) -> TractResult<Arc<Tensor>> {
         let data_view = data.to_array_view::<T>()?;
         let axis = self.resolved_axis(data.shape().len())?;
-        let indices = indices.cast_to::<i64>()?;
+        let checked;
+        let indices: &Tensor = if let Some(proved) = &self.proved {
+            proved
+        } else {
+            checked = Self::checked_indices(indices, data.shape()[axis])?;
+            &checked
+        };
         if indices.shape().len() == 0 {
-            let mut index = *indices.to_scalar::<i64>()?;
-            if index < 0 {
-                index += data_view.shape()[0] as i64;
-            }
+            let index = *indices.to_scalar::<i64>()?;
             return Ok(data_view
                 .index_axis(Axis(axis), index as usize)
                 .to_owned()
@@ -94,6 +128,50 @@ impl TypedOp for Gather {
                 .compute_output_shape(&*inputs[0].shape.to_tvec(), &*inputs[1].shape.to_tvec())?
         )?))
     }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let data_fact = model.outlet_fact(node.inputs[0])?;
+        let indices_fact = model.outlet_fact(node.inputs[1])?;
+        let indices = if let Some(indices) = &indices_fact.konst {
+            indices
+        } else {
+            return Ok(None);
+        };
+        let axis = self.resolved_axis(data_fact.rank())?;
+        // indices can only be checked against a known axis
+        let dim = if let Ok(dim) = data_fact.shape.dim(axis).to_integer() {
+            dim
+        } else {
+            return Ok(None);
+        };
+        if indices.rank() == 1 && indices.len() > 0 {
+            let indices = indices.cast_to::<i64>()?;
+            let indices = indices
+                .as_slice::<i64>()?
+                .iter()
+                .map(|&ix| if ix < 0 { ix + dim } else { ix })
+                .collect::<Vec<i64>>();
+            // a contiguous ascending range of indices is a slice
+            let (start, end) = (indices[0], indices[0] + indices.len() as i64);
+            if indices.windows(2).all(|w| w[1] == w[0] + 1) && start >= 0 && end <= dim {
+                let slice = Slice::new(axis, start as usize, end as usize);
+                return Ok(Some(TypedModelPatch::single_unary_op(model, node, slice)?));
+            }
+        }
+        // constant indices proved in range do not need checking at runtime
+        if self.proved.is_none() {
+            if let Ok(checked) = Self::checked_indices(indices, dim as usize) {
+                let op = Gather::with_proved_indices(self.axis, checked.into_arc_tensor());
+                let patch = TypedModelPatch::replace_single_op(model, node, &node.inputs, op)?;
+                return Ok(Some(patch));
+            }
+        }
+        Ok(None)
+    }
 }
 
 impl StatelessOp for Gather {
@@ -108,6 +186,42 @@ impl StatelessOp for Gather {
 mod tests {
     use super::*;
 
+    #[test]
+    fn contiguous_gather_to_slice() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let data =
+            model.add_source("data", TypedFact::dt_shape(f32::datum_type(), [2, 6].as_ref())?)?;
+        let indices = model.add_const("indices", tensor1(&[2i64, 3, 4]))?;
+        let gather = model.wire_node("gather", Gather::new(1), &[data, indices])?;
+        model.set_output_outlets(&gather)?;
+        let input = tensor2(&[[0f32, 1., 2., 3., 4., 5.], [6., 7., 8., 9., 10., 11.]]);
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone()))?;
+        let decluttered = model.declutter()?;
+        let node = decluttered.node(decluttered.output_outlets()?[0].node);
+        let slice = node.op_as::<Slice<usize>>().unwrap();
+        assert_eq!((slice.axis, slice.start, slice.end), (1, 2, 5));
+        let found = decluttered.into_runnable()?.run(tvec!(input))?;
+        assert_eq!(found, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn symbolic_axis_gather_is_not_a_slice() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let shape = [TDim::s(), 2.to_dim()];
+        let data =
+            model.add_source("data", TypedFact::dt_shape(f32::datum_type(), shape.as_ref())?)?;
+        let indices = model.add_const("indices", tensor1(&[2i64, 3, 4]))?;
+        let gather = model.wire_node("gather", Gather::new(0), &[data, indices])?;
+        model.set_output_outlets(&gather)?;
+        let decluttered = model.declutter()?;
+        let node = decluttered.node(decluttered.output_outlets()?[0].node);
+        assert!(node.op_as::<Gather>().unwrap().proved.is_none());
+        let input = tensor2(&[[0f32, 1.], [2., 3.], [4., 5.]]);
+        assert!(decluttered.into_runnable()?.run(tvec!(input)).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_should_gather_scalar_index() {
         let data = Tensor::from(arr1(&[1i64, 2, 3]));
@@ -120,4 +234,33 @@ mod tests {
             assert_eq!(*output.to_scalar::<i64>().unwrap(), idx + 1);
         }
     }
+
+    #[test]
+    fn proved_in_range_gather() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let data =
+            model.add_source("data", TypedFact::dt_shape(f32::datum_type(), [5, 2].as_ref())?)?;
+        let indices = model.add_const("indices", tensor2(&[[4i64, 0], [-3, 2]]))?;
+        let gather = model.wire_node("gather", Gather::new(0), &[data, indices])?;
+        model.set_output_outlets(&gather)?;
+        let input = tensor2(&[[0f32, 1.], [2., 3.], [4., 5.], [6., 7.], [8., 9.]]);
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone()))?;
+        let decluttered = model.declutter()?;
+        let node = decluttered.node(decluttered.output_outlets()?[0].node);
+        let proved = node.op_as::<Gather>().unwrap().proved.as_ref().unwrap();
+        assert_eq!(**proved, tensor2(&[[4i64, 0], [2, 2]]));
+        let found = decluttered.into_runnable()?.run(tvec!(input))?;
+        assert_eq!(found, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn checked_gather() -> TractResult<()> {
+        let data: Arc<Tensor> = rctensor1(&[1i64, 2, 3]);
+        let found = Gather::new(0).eval(tvec!(data.clone(), rctensor1(&[-1i64, 0])))?;
+        assert_eq!(*found[0], tensor1(&[3i64, 1]));
+        assert!(Gather::new(0).eval(tvec!(data.clone(), rctensor1(&[0i64, 3]))).is_err());
+        assert!(Gather::new(0).eval(tvec!(data, rctensor0(-4i64))).is_err());
+        Ok(())
+    }
 }
@@ -1,10 +1,13 @@
 /// # Operators on array and shapes
 mod broadcast;
 pub(crate) mod concat;
+mod embedding_bag;
 mod flatten;
 mod gather;
+mod non_zero;
 mod pad;
 mod reshape;
+mod sequence_mask;
 mod shape;
 mod size;
 mod slice;
@@ -12,10 +15,13 @@ mod tile;
 
 pub use self::broadcast::MultiBroadcastTo;
 pub use self::concat::{ConcatSlice, TypedConcat};
+pub use self::embedding_bag::{EmbeddingBag, EmbeddingBagMode};
 pub use self::flatten::Flatten;
 pub use self::gather::Gather;
+pub use self::non_zero::NonZero;
 pub use self::pad::{Pad, PadMode};
-pub use self::reshape::{FiniteReshape, TypedReshape};
+pub use self::reshape::{FiniteReshape, PulsedReshape, TypedReshape};
+pub use self::sequence_mask::SequenceMask;
 pub use self::shape::Shape;
 pub use self::size::Size;
 pub use self::slice::Slice;
@@ -0,0 +1,111 @@
+use crate::internal::*;
+
+/// Indices of the non-zero elements of the input.
+///
+/// The output is a `[rank, count]` i64 tensor, holding one column of
+/// coordinates per non-zero element, in row-major order. As `count` depends
+/// on the input values, the output fact uses the `count` symbol for it, which
+/// must not be used anywhere else in the model (see `NonZero::fresh`).
+#[derive(Debug, Clone, new, Hash)]
+pub struct NonZero {
+    pub count: char,
+}
+
+tract_linalg::impl_dyn_hash!(NonZero);
+
+impl NonZero {
+    /// A NonZero whose count symbol is not used yet in `model`.
+    pub fn fresh(model: &TypedModel) -> TractResult<NonZero> {
+        let count = model.fresh_symbols(1)?[0];
+        Ok(NonZero { count })
+    }
+
+    fn eval_t<T: Datum + num_traits::Zero>(input: &Tensor) -> TractResult<Tensor> {
+        let input = input.to_array_view::<T>()?;
+        let coords = input
+            .indexed_iter()
+            .filter(|(_, x)| !x.is_zero())
+            .map(|(coords, _)| coords)
+            .collect::<Vec<_>>();
+        let mut output = ndarray::Array2::<i64>::zeros((input.ndim(), coords.len()));
+        for (ix, coords) in coords.iter().enumerate() {
+            for axis in 0..input.ndim() {
+                output[(axis, ix)] = coords[axis] as i64;
+            }
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for NonZero {
+    fn name(&self) -> Cow<str> {
+        "NonZero".into()
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+    not_a_pulsed_op!();
+}
+
+impl StatelessOp for NonZero {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let input = if input.datum_type() == bool::datum_type() {
+            input.cast_to::<u8>()?.into_owned()
+        } else {
+            input.into_tensor()
+        };
+        let output = dispatch_numbers!(Self::eval_t(input.datum_type())(&input))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for NonZero {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let shape = [inputs[0].rank().to_dim(), TDim::sym(self.count)];
+        Ok(tvec!(TypedFact::dt_shape(i64::datum_type(), shape.as_ref())?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn non_zero_is_dynamic() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(f32::datum_type(), [2, 3].as_ref())?)?;
+        let neg = model.wire_node("neg", crate::ops::math::neg(), &[source])?;
+        let non_zero = model.wire_node("non_zero", NonZero::fresh(&model)?, &neg)?;
+        model.set_output_outlets(&non_zero)?;
+        assert_eq!(model.dynamic_outputs(), vec![non_zero[0]]);
+
+        let input = tensor2(&[[0f32, 1., 0.], [2., 0., 3.]]);
+        let found = model.into_runnable()?.run(tvec!(input))?;
+        assert_eq!(*found[0], tensor2(&[[0i64, 1, 1], [1, 0, 2]]));
+        Ok(())
+    }
+
+    #[test]
+    fn non_zero_counts_are_distinct() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [2, 3].as_ref())?;
+        let a = model.add_source("a", fact.clone())?;
+        let b = model.add_source("b", fact)?;
+        let nz_a = model.wire_node("nz_a", NonZero::fresh(&model)?, &[a])?;
+        let nz_b = model.wire_node("nz_b", NonZero::fresh(&model)?, &[b])?;
+        model.set_output_outlets(&[nz_a[0], nz_b[0]])?;
+        let count_a = model.outlet_fact(nz_a[0])?.shape.dim(1);
+        assert_ne!(count_a, model.outlet_fact(nz_b[0])?.shape.dim(1));
+
+        let a = tensor2(&[[0f32, 1., 0.], [0., 0., 0.]]);
+        let b = tensor2(&[[1f32, 1., 0.], [0., 0., 1.]]);
+        let found = model.into_runnable()?.run(tvec!(a, b))?;
+        assert_eq!(*found[0], tensor2(&[[0i64], [1]]));
+        assert_eq!(*found[1], tensor2(&[[0i64, 0, 1], [0, 1, 2]]));
+        Ok(())
+    }
+}
@@ -18,7 +18,7 @@ impl Default for PadMode {
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct Pad {
     pub pads: Vec<(usize, usize)>,
-    mode: PadMode,
+    pub mode: PadMode,
 }
 tract_linalg::impl_dyn_hash!(Pad);
 
@@ -124,10 +124,51 @@ impl TypedOp for Pad {
         node: &TypedNode,
     ) -> TractResult<Option<TypedModelPatch>> {
         if self.pads.iter().all(|p| p.0 == 0 && p.1 == 0) {
-            Ok(Some(TypedModelPatch::shunt_one_op(model, node)?))
-        } else {
-            Ok(None)
+            return Ok(Some(TypedModelPatch::shunt_one_op(model, node)?));
         }
+        // padding is separable, so two pads with the same mode on different
+        // axes (or on any axes with the same constant) make a single one
+        let prec = model.node(node.inputs[0].node);
+        if let Some(other) = prec.op_as::<Pad>() {
+            let disjoint =
+                self.pads.iter().zip(other.pads.iter()).all(|(a, b)| *a == (0, 0) || *b == (0, 0));
+            let constant = if let PadMode::Constant(_) = self.mode { true } else { false };
+            if other.mode == self.mode
+                && (disjoint || constant)
+                && prec.outputs[0].successors.len() == 1
+            {
+                let pads = self
+                    .pads
+                    .iter()
+                    .zip(other.pads.iter())
+                    .map(|(a, b)| (a.0 + b.0, a.1 + b.1))
+                    .collect();
+                let mut patch = TypedModelPatch::default();
+                let tap = patch.tap_model(model, prec.inputs[0])?;
+                let op = Pad::new(pads, self.mode.clone());
+                let wire = patch.wire_node(&*node.name, op, &[tap])?[0];
+                patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
+                return Ok(Some(patch));
+            }
+        }
+        Ok(None)
+    }
+
+    fn quantize(
+        &self,
+        _model: &TypedModel,
+        _node: &TypedNode,
+        dt: DatumType,
+        scale: f32,
+        zero_point: i32,
+    ) -> TractResult<Option<Box<dyn TypedOp>>> {
+        let mode = if let PadMode::Constant(c) = &self.mode {
+            let c = crate::ops::quant::quantize_linear_f32(c, dt, scale, zero_point)?;
+            PadMode::Constant(c.into_arc_tensor())
+        } else {
+            self.mode.clone()
+        };
+        Ok(Some(Box::new(Pad { mode, ..self.clone() })))
     }
 
     fn pulsify(
@@ -207,10 +248,10 @@ impl PulsePadOpState {
         let pulse_begin = self.current_pos;
         let pulse_end = self.current_pos + op.pulse;
         self.current_pos += op.pulse;
-        let end_input = session
-            .known_stream_len
-            .map(|s| op.end_input.eval(s as i32).unwrap() as usize)
-            .unwrap_or(std::usize::MAX);
+        let end_input = match session.known_stream_len {
+            Some(s) => crate::pulse::eval_stream_dim(&op.end_input, s, &session.resolved_symbols)?,
+            None => std::usize::MAX,
+        };
 
         if let PadMode::Edge = op.mode {
             if op.after > 0 && pulse_begin < end_input {
@@ -335,3 +376,52 @@ impl PulsedOp for PulsePad {
     as_op!();
     pulsed_op_to_typed_op!();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pad_twice(mode: PadMode) -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(f32::datum_type(), [2, 3].as_ref())?)?;
+        let pad0 =
+            model.wire_node("pad0", Pad::new(vec![(1, 2), (0, 0)], mode.clone()), &[source])?;
+        let pad1 = model.wire_node("pad1", Pad::new(vec![(0, 0), (2, 1)], mode.clone()), &pad0)?;
+        model.set_output_outlets(&pad1)?;
+        let input = tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]);
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone()))?;
+        let decluttered = model.declutter()?;
+        let pads = decluttered.nodes().iter().filter_map(|n| n.op_as::<Pad>()).collect::<Vec<_>>();
+        assert_eq!(pads.len(), 1);
+        assert_eq!(pads[0].pads, vec![(1, 2), (2, 1)]);
+        let found = decluttered.into_runnable()?.run(tvec!(input))?;
+        assert_eq!(found, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn merge_constant_pads() -> TractResult<()> {
+        pad_twice(PadMode::Constant(rctensor0(0.5f32)))
+    }
+
+    #[test]
+    fn merge_reflect_pads() -> TractResult<()> {
+        pad_twice(PadMode::Reflect)
+    }
+
+    #[test]
+    fn keep_pads_with_different_constants() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(f32::datum_type(), [2, 3].as_ref())?)?;
+        let pad0 = Pad::new(vec![(1, 0), (0, 0)], PadMode::Constant(rctensor0(1f32)));
+        let pad0 = model.wire_node("pad0", pad0, &[source])?;
+        let pad1 = Pad::new(vec![(0, 0), (0, 1)], PadMode::Constant(rctensor0(2f32)));
+        let pad1 = model.wire_node("pad1", pad1, &pad0)?;
+        model.set_output_outlets(&pad1)?;
+        let decluttered = model.declutter()?;
+        assert_eq!(decluttered.nodes().iter().filter(|n| n.op_is::<Pad>()).count(), 2);
+        Ok(())
+    }
+}
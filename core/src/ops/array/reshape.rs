@@ -27,12 +27,118 @@ impl StatelessOp for TypedReshape {
     fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
         let input = args_1!(inputs);
         let shape: TVec<usize> =
-            self.shape.iter().map(|d| Ok(d.to_integer()? as usize)).collect::<TractResult<_>>()?;
+            self.shape.iter().map(|d| d.to_usize()).collect::<TractResult<_>>()?;
         let o = unsafe { input.into_tensor().into_shape(&*shape)?.into_arc_tensor() };
         Ok(tvec!(o))
     }
 }
 
+impl TypedReshape {
+    /// Look for a Reshape, Permute, Reshape sequence ending on this node that
+    /// is, net, a pure axis permutation, and return the equivalent permutation
+    /// together with the input of the first Reshape.
+    fn net_permutation(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<(OutletId, TVec<usize>)>> {
+        let permute = model.node(node.inputs[0].node);
+        let axes = if let Some(AxisOp::Permute(axes)) = permute.op_as::<AxisOp>() {
+            axes
+        } else {
+            return Ok(None);
+        };
+        let reshape = model.node(permute.inputs[0].node);
+        if !reshape.op_is::<TypedReshape>() {
+            return Ok(None);
+        }
+        let input = reshape.inputs[0];
+        let shapes = (
+            model.outlet_fact(input)?.shape.as_finite(),
+            model.outlet_fact(reshape.id.into())?.shape.as_finite(),
+            model.outlet_fact(node.id.into())?.shape.as_finite(),
+        );
+        let (input_shape, reshaped, output_shape) = match shapes {
+            (Some(i), Some(r), Some(o)) => (i, r, o),
+            _ => return Ok(None),
+        };
+        if input_shape.len() != output_shape.len() || input_shape.iter().any(|&d| d == 0) {
+            return Ok(None);
+        }
+        // express the permuted intermediate as a strided view of the input...
+        let reshaped_strides = natural_strides(reshaped);
+        let permuted: TVec<(usize, isize)> =
+            axes.iter().map(|&ax| (reshaped[ax], reshaped_strides[ax])).collect();
+        // ... then the output, when the last reshape does not need a copy
+        let output = if let Some(output) = strided_reshape(&permuted, output_shape) {
+            output
+        } else {
+            return Ok(None);
+        };
+        // finally, match output axes with input axes of the same size and stride
+        let input_strides = natural_strides(input_shape);
+        let mut perm: TVec<usize> = tvec!();
+        for &(dim, stride) in &output {
+            let candidate = (0..input_shape.len()).find(|&ax| {
+                !perm.contains(&ax)
+                    && input_shape[ax] == dim
+                    && (dim == 1 || input_strides[ax] == stride)
+            });
+            if let Some(ax) = candidate {
+                perm.push(ax);
+            } else {
+                return Ok(None);
+            }
+        }
+        Ok(Some((input, perm)))
+    }
+}
+
+fn natural_strides(shape: &[usize]) -> TVec<isize> {
+    let mut strides: TVec<isize> = tvec!(1; shape.len());
+    for ix in (0..shape.len().saturating_sub(1)).rev() {
+        strides[ix] = strides[ix + 1] * shape[ix + 1] as isize;
+    }
+    strides
+}
+
+/// Compute the strides of `shape` when reshaping a strided view of
+/// (dim, stride) pairs, or None if the reshape would require a copy.
+fn strided_reshape(view: &[(usize, isize)], shape: &[usize]) -> Option<TVec<(usize, isize)>> {
+    let old: TVec<(usize, isize)> = view.iter().cloned().filter(|&(d, _)| d != 1).collect();
+    let new: TVec<usize> = shape.iter().cloned().filter(|&d| d != 1).collect();
+    let mut strides: TVec<isize> = tvec!(0; new.len());
+    let (mut oi, mut ni) = (0, 0);
+    while oi < old.len() && ni < new.len() {
+        let (mut oj, mut nj) = (oi + 1, ni + 1);
+        let (mut op, mut np) = (old[oi].0, new[ni]);
+        while op != np {
+            if np < op {
+                np *= *new.get(nj)?;
+                nj += 1;
+            } else {
+                op *= old.get(oj)?.0;
+                oj += 1;
+            }
+        }
+        // the merged old axes must be contiguous with each other
+        if (oi..oj - 1).any(|ok| old[ok].1 != old[ok + 1].0 as isize * old[ok + 1].1) {
+            return None;
+        }
+        strides[nj - 1] = old[oj - 1].1;
+        for nk in (ni + 1..nj).rev() {
+            strides[nk - 1] = strides[nk] * new[nk] as isize;
+        }
+        oi = oj;
+        ni = nj;
+    }
+    if oi != old.len() || ni != new.len() {
+        return None;
+    }
+    let mut strides = strides.into_iter();
+    Some(shape.iter().map(|&d| (d, if d == 1 { 0 } else { strides.next().unwrap() })).collect())
+}
+
 impl TypedOp for TypedReshape {
     as_op!();
 
@@ -40,6 +146,56 @@ impl TypedOp for TypedReshape {
         Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*self.shape)?))
     }
 
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        let input = mapping[&node.inputs[0]];
+        let fact = target.outlet_fact(input)?.clone();
+        // the stream axis must stay a standalone dim, with the same element
+        // count before and after it
+        let axes: TVec<usize> =
+            (0..self.shape.len()).filter(|&ix| self.shape[ix] == fact.dim).collect();
+        if axes.len() != 1 {
+            bail!("Can not pulsify reshape merging or splitting the streaming axis")
+        }
+        let axis = axes[0];
+        let mut shape = tvec!();
+        for (ix, d) in self.shape.iter().enumerate() {
+            shape.push(if ix == axis { fact.pulse() } else { d.to_usize()? });
+        }
+        if shape[..axis].iter().product::<usize>() != fact.shape[..fact.axis].iter().product()
+            || shape[axis..].iter().product::<usize>() != fact.shape[fact.axis..].iter().product()
+        {
+            bail!("Can not pulsify reshape merging or splitting the streaming axis")
+        }
+        target.wire_node(&*node.name, PulsedReshape::new(axis, shape), &[input])
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if model.outlet_fact(node.inputs[0])?.shape.to_tvec() == self.shape {
+            return Ok(Some(TypedModelPatch::shunt_one_op(model, node)?));
+        }
+        if let Some((input, perm)) = self.net_permutation(model, node)? {
+            let mut patch = TypedModelPatch::default();
+            let mut wire = patch.tap_model(model, input)?;
+            if perm.iter().enumerate().any(|(ix, &ax)| ix != ax) {
+                wire = patch.wire_node(&*node.name, AxisOp::Permute(perm), &[wire])?[0];
+            }
+            patch.shunt_outside(model, node.id.into(), wire)?;
+            return Ok(Some(patch));
+        }
+        Ok(None)
+    }
+
     fn codegen(
         &self,
         model: &TypedModel,
@@ -49,7 +205,7 @@ impl TypedOp for TypedReshape {
         if input_fact.shape.to_tvec() == self.shape {
             return Ok(Some(TypedModelPatch::shunt_one_op(model, node)?));
         } else if let Ok(shape) =
-            self.shape.iter().map(|d| Ok(d.to_integer()? as usize)).collect::<TractResult<_>>()
+            self.shape.iter().map(|d| d.to_usize()).collect::<TractResult<_>>()
         {
             return Ok(Some(TypedModelPatch::single_unary_op(
                 model,
@@ -97,3 +253,156 @@ impl TypedOp for FiniteReshape {
 
     as_op!();
 }
+
+/// Reshape of a pulsed tensor, leaving the streaming axis alone.
+#[derive(Debug, Clone, new, Default, Hash)]
+pub struct PulsedReshape {
+    pub axis: usize,
+    pub shape: TVec<usize>,
+}
+
+tract_linalg::impl_dyn_hash!(PulsedReshape);
+
+impl Op for PulsedReshape {
+    fn name(&self) -> Cow<str> {
+        "PulsedReshape".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("to shape: {} (stream axis: {})", self.shape.iter().join("x"), self.axis)])
+    }
+
+    op_core_lir_mir!();
+    not_a_typed_op!();
+    op_as_pulsed_op!();
+}
+
+impl StatelessOp for PulsedReshape {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let o = unsafe { input.into_tensor().into_shape(&*self.shape)?.into_arc_tensor() };
+        Ok(tvec!(o))
+    }
+}
+
+impl PulsedOp for PulsedReshape {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        fact.shape = self.shape.clone();
+        fact.axis = self.axis;
+        Ok(tvec!(fact))
+    }
+
+    fn to_typed(&self) -> Box<dyn TypedOp> {
+        Box::new(FiniteReshape::new(self.shape.clone()))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn reshape_permute_reshape(
+        input_shape: &[usize],
+        reshaped: &[usize],
+        axes: &[usize],
+        output_shape: &[usize],
+    ) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let input =
+            model.add_source("input", TypedFact::dt_shape(f32::datum_type(), input_shape)?)?;
+        let dims = |shape: &[usize]| shape.iter().map(|&d| d.to_dim()).collect();
+        let wire = model.wire_node("reshape1", TypedReshape::new(dims(reshaped)), &[input])?;
+        let wire = model.wire_node("permute", AxisOp::Permute(axes.into()), &wire)?;
+        let wire = model.wire_node("reshape2", TypedReshape::new(dims(output_shape)), &wire)?;
+        model.set_output_outlets(&wire)?;
+        Ok(model)
+    }
+
+    fn check(model: TypedModel, input_shape: &[usize], collapses: bool) -> TractResult<()> {
+        let len = input_shape.iter().product::<usize>();
+        let input = tensor1(&(0..len).map(|i| i as f32).collect::<Vec<_>>());
+        let input = unsafe { input.into_shape(input_shape)? };
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone()))?;
+        let decluttered = model.declutter()?;
+        let reshapes = decluttered.nodes().iter().filter(|n| n.op_is::<TypedReshape>()).count();
+        assert_eq!(reshapes == 0, collapses);
+        let found = decluttered.into_runnable()?.run(tvec!(input))?;
+        assert_eq!(found, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn nhwc_to_nchw_collapses_to_permute() -> TractResult<()> {
+        // NHWC to NCHW, going through a flattened spatial axis
+        let model = reshape_permute_reshape(&[2, 3, 4, 5], &[2, 12, 5], &[0, 2, 1], &[2, 5, 3, 4])?;
+        check(model, &[2, 3, 4, 5], true)
+    }
+
+    #[test]
+    fn channel_shuffle_with_one_group_collapses() -> TractResult<()> {
+        let model = reshape_permute_reshape(
+            &[1, 6, 2, 2],
+            &[1, 1, 6, 2, 2],
+            &[0, 2, 1, 3, 4],
+            &[1, 6, 2, 2],
+        )?;
+        check(model, &[1, 6, 2, 2], true)
+    }
+
+    #[test]
+    fn channel_shuffle_does_not_collapse() -> TractResult<()> {
+        let model = reshape_permute_reshape(
+            &[1, 6, 2, 2],
+            &[1, 2, 3, 2, 2],
+            &[0, 2, 1, 3, 4],
+            &[1, 6, 2, 2],
+        )?;
+        check(model, &[1, 6, 2, 2], false)
+    }
+
+    #[test]
+    fn identity_reshape_is_removed() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let shape = tvec!(TDim::s(), 3.to_dim(), 4.to_dim());
+        let source =
+            model.add_source("source", TypedFact::dt_shape(f32::datum_type(), &*shape)?)?;
+        let reshape = model.wire_node("reshape", TypedReshape::new(shape), &[source])?;
+        model.set_output_outlets(&reshape)?;
+        let decluttered = model.declutter()?;
+        assert!(!decluttered.nodes().iter().any(|n| n.op_is::<TypedReshape>()));
+        Ok(())
+    }
+
+    fn streaming_model(shape: TVec<TDim>) -> TractResult<NormalizedModel> {
+        let mut model = TypedModel::default();
+        let fact =
+            TypedFact::dt_shape(f32::datum_type(), [TDim::s(), 2.to_dim(), 3.to_dim()].as_ref())?;
+        let source = model.add_source("source", fact)?;
+        let reshape = model.wire_node("reshape", TypedReshape::new(shape), &[source])?;
+        model.set_output_outlets(&reshape)?;
+        model.into_normalized()
+    }
+
+    #[test]
+    fn pulsify_reshape_keeping_stream_axis() -> TractResult<()> {
+        let model = streaming_model(tvec!(TDim::s(), 6.to_dim()))?;
+        let pulsed = PulsedModel::new(&model, 4)?;
+        let fact = pulsed.output_fact(0)?;
+        assert_eq!((&*fact.shape, fact.axis, fact.delay), (&[4, 6][..], 0, 0));
+        let input = tensor1(&(0..24).map(|i| i as f32).collect::<Vec<_>>());
+        let input = unsafe { input.into_shape(&[4, 2, 3])? };
+        let output = pulsed.into_typed()?.into_runnable()?.run(tvec!(input.clone()))?;
+        assert_eq!(*output[0], unsafe { input.into_shape(&[4, 6])? });
+        Ok(())
+    }
+
+    #[test]
+    fn pulsify_reshape_merging_stream_axis_fails() -> TractResult<()> {
+        let model = streaming_model(tvec!(TDim::s() * 2, 3.to_dim()))?;
+        assert!(PulsedModel::new(&model, 4).is_err());
+        Ok(())
+    }
+}
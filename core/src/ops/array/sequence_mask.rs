@@ -0,0 +1,87 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// Zeroes the padded positions of a batch of sequences.
+///
+/// Inputs are the data and an i32 vector of sequence lengths, one per batch
+/// entry. Along `axis`, positions greater or equal to the entry length are set
+/// to zero. The batch axis is the first axis that is not `axis`.
+#[derive(Debug, Clone, new, Default, Hash)]
+pub struct SequenceMask {
+    pub axis: usize,
+}
+
+tract_linalg::impl_dyn_hash!(SequenceMask);
+
+impl SequenceMask {
+    fn batch_axis(&self) -> usize {
+        if self.axis == 0 {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn eval_t<T: Datum>(&self, data: Arc<Tensor>, lengths: &[i32]) -> TractResult<Arc<Tensor>> {
+        let mut data = data.into_tensor().into_array::<T>()?;
+        let batch_axis = self.batch_axis();
+        if data.shape()[batch_axis] != lengths.len() {
+            bail!("Expected {} sequence lengths, got {}", data.shape()[batch_axis], lengths.len());
+        }
+        let axis = if self.axis > batch_axis { self.axis - 1 } else { self.axis };
+        for (ix, &len) in lengths.iter().enumerate() {
+            let mut sequence = data.index_axis_mut(Axis(batch_axis), ix);
+            let len = (len.max(0) as usize).min(sequence.shape()[axis]);
+            sequence.slice_axis_mut(Axis(axis), (len..).into()).fill(T::default());
+        }
+        Ok(data.into_arc_tensor())
+    }
+}
+
+impl Op for SequenceMask {
+    fn name(&self) -> Cow<str> {
+        "SequenceMask".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {}", self.axis)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+    not_a_pulsed_op!();
+}
+
+impl StatelessOp for SequenceMask {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (data, lengths) = args_2!(inputs);
+        let lengths = lengths.cast_to::<i32>()?;
+        let lengths = lengths.as_slice::<i32>()?;
+        let result = dispatch_datum!(Self::eval_t(data.datum_type())(self, data, lengths))?;
+        Ok(tvec!(result))
+    }
+}
+
+impl TypedOp for SequenceMask {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs[0].rank() < 2 || self.axis >= inputs[0].rank() {
+            bail!("Can not mask along axis {} of {:?}", self.axis, inputs[0]);
+        }
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mask_2x5() {
+        let data = rctensor2(&[[1f32, 2., 3., 4., 5.], [6., 7., 8., 9., 10.]]);
+        let output =
+            SequenceMask::new(1).eval(tvec!(data, rctensor1(&[3i32, 5]))).unwrap().remove(0);
+        assert_eq!(*output, tensor2(&[[1f32, 2., 3., 0., 0.], [6., 7., 8., 9., 10.]]));
+    }
+}
@@ -19,7 +19,7 @@ impl<D: DimLike + ToDim + Hash> Slice<D> {
         let mut input = input.to_array_view_unchecked::<T>();
         input.slice_axis_inplace(
             Axis(self.axis),
-            ::ndarray::Slice::from((self.start.to_integer()?)..(self.end.to_integer()?)),
+            ::ndarray::Slice::from((self.start.to_usize()?)..(self.end.to_usize()?)),
         );
         Ok(Tensor::from(input.to_owned()).into())
     }
@@ -113,9 +113,12 @@ impl<D: DimLike + ToDim + Hash> TypedOp for Slice<D> {
                 )?));
             }
         }
-        if self.start == D::zero()
-            && (self.end.clone().to_dim()
-                == model.outlet_fact(node.inputs[0])?.shape.dim(self.axis))
+        let assumptions = SymbolAssumptions::default();
+        let dim = model.outlet_fact(node.inputs[0])?.shape.dim(self.axis);
+        if self.start.clone().to_dim().compare_with(&0.into(), &assumptions)
+            == Some(std::cmp::Ordering::Equal)
+            && self.end.clone().to_dim().compare_with(&dim, &assumptions)
+                == Some(std::cmp::Ordering::Equal)
         {
             return Ok(Some(TypedModelPatch::shunt_one_op(model, node)?));
         }
@@ -158,7 +161,7 @@ impl<D: DimLike + ToDim + Hash> TypedOp for Slice<D> {
         let input = mapping[&node.inputs[0]];
         let fact = target.outlet_fact(input)?.clone();
         let op: Box<dyn PulsedOp> = if self.axis == fact.axis {
-            let skip = self.start.to_integer()? as usize;
+            let skip = self.start.to_usize()?;
             let take = (self.end.clone() - &self.start).to_dim();
             PulsedAxisSlice::new(self.axis, skip, take).into()
         } else {
@@ -196,7 +199,7 @@ impl<D: DimLike + ToDim + Hash> TypedOp for Slice<D> {
 impl<D: DimLike + ToDim + Hash> PulsedOp for Slice<D> {
     fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
         let mut fact = inputs[0].clone();
-        fact.delay += self.start.to_integer()? as usize;
+        fact.delay += self.start.to_usize()?;
         fact.dim = (self.end.clone() - &self.start).to_dim();
         Ok(tvec!(fact))
     }
@@ -248,3 +251,22 @@ impl PulsedOp for PulsedAxisSlice {
 
     as_op!();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn provably_full_slice_is_removed() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [TDim::s(), 2.to_dim()].as_ref())?;
+        let source = model.add_source("source", fact)?;
+        let end = TDim::Max(vec![TDim::s(), 0.into()]);
+        assert_ne!(end, TDim::s());
+        let slice = model.wire_node("slice", Slice::new(0, 0.to_dim(), end), &[source])?;
+        model.set_output_outlets(&slice)?;
+        let model = model.declutter()?;
+        assert_eq!(model.nodes().len(), 1);
+        Ok(())
+    }
+}
@@ -299,6 +299,36 @@ pub struct UnaryOp {
 }
 tract_linalg::impl_dyn_hash!(UnaryOp);
 
+impl UnaryOp {
+    /// Adding zero or multiplying by one does nothing, unless broadcasting
+    /// the constant changes the shape or type of the input.
+    fn declutter_neutral(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        use crate::ops::math::{Add, Mul};
+        let neutral = if self.mini_op.is::<Add>() {
+            0.0
+        } else if self.mini_op.is::<Mul>() {
+            1.0
+        } else {
+            return Ok(None);
+        };
+        let input = model.outlet_fact(node.inputs[0])?;
+        let output = &node.outputs[0].fact;
+        if input.shape != output.shape || input.datum_type != output.datum_type {
+            return Ok(None);
+        }
+        if let Ok(a) = self.a.cast_to::<f64>() {
+            if a.as_slice::<f64>()?.iter().all(|x| *x == neutral) {
+                return Ok(Some(TypedModelPatch::shunt_one_op(model, node)?));
+            }
+        }
+        Ok(None)
+    }
+}
+
 impl Op for UnaryOp {
     fn name(&self) -> Cow<str> {
         self.mini_op.name().into()
@@ -415,9 +445,40 @@ impl TypedOp for UnaryOp {
         model: &TypedModel,
         node: &TypedNode,
     ) -> TractResult<Option<TypedModelPatch>> {
+        if let Some(patch) = self.declutter_neutral(model, node)? {
+            return Ok(Some(patch));
+        }
         self.mini_op.declutter_unary(model, node, &self.a)
     }
 
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        use crate::ops::math::{scalar_add, scalar_mul, Add, Mul};
+        if self.a.len() != 1 || self.a.datum_type() != model.outlet_fact(node.inputs[0])?.datum_type
+        {
+            return Ok(None);
+        }
+        // the scalar ops are only instantiated for these types
+        match self.a.datum_type() {
+            DatumType::F16 | DatumType::F32 | DatumType::F64 => (),
+            DatumType::I8 | DatumType::I16 | DatumType::I32 | DatumType::I64 => (),
+            DatumType::U8 | DatumType::U16 => (),
+            _ => return Ok(None),
+        }
+        let a = unsafe { self.a.clone().into_tensor().into_shape(&[])? };
+        let op = if self.mini_op.is::<Add>() {
+            scalar_add(a)
+        } else if self.mini_op.is::<Mul>() {
+            scalar_mul(a)
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(TypedModelPatch::single_unary_op(model, node, op)?))
+    }
+
     fn change_axes(
         &self,
         model: &TypedModel,
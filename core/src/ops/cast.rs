@@ -31,9 +31,88 @@ impl ElementWiseMiniOp for Cast {
         node: &TypedNode,
     ) -> TractResult<Option<TypedModelPatch>> {
         if model.outlet_fact(node.inputs[0])?.datum_type == self.to {
-            Ok(Some(TypedModelPatch::shunt_one_op(model, node)?))
-        } else {
-            Ok(None)
+            return Ok(Some(TypedModelPatch::shunt_one_op(model, node)?));
         }
+        // two lossless casts in a row are one lossless cast. A lossy second
+        // cast must stay: float to int saturates, but int to int wraps.
+        let prec = model.node(node.inputs[0].node);
+        if let Some(prec_op) =
+            prec.op_as::<ElementWiseOp>().and_then(|op| op.0.downcast_ref::<Cast>())
+        {
+            let from = model.outlet_fact(prec.inputs[0])?.datum_type;
+            if is_lossless(from, prec_op.to) && is_lossless(prec_op.to, self.to) {
+                let mut patch = TypedModelPatch::default();
+                let wire = patch.tap_model(model, prec.inputs[0])?;
+                let wire = patch.wire_node(&*node.name, cast(self.to), &[wire])?[0];
+                patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
+                return Ok(Some(patch));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Whether all values of `from` are exactly represented in `to`.
+fn is_lossless(from: DatumType, to: DatumType) -> bool {
+    use DatumType::*;
+    match (from, to) {
+        (Bool, I8) | (Bool, I16) | (Bool, I32) | (Bool, I64) | (Bool, F32) | (Bool, F64) => true,
+        (I8, I16) | (I8, I32) | (I8, I64) | (I16, I32) | (I16, I64) | (I32, I64) => true,
+        (U8, U16) | (U8, I16) | (U8, I32) | (U8, I64) | (U16, I32) | (U16, I64) => true,
+        (I8, F32) | (I8, F64) | (I16, F32) | (I16, F64) | (I32, F64) => true,
+        (U8, F32) | (U8, F64) | (U16, F32) | (U16, F64) => true,
+        (F16, F32) | (F16, F64) | (F32, F64) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn casts(types: &[DatumType]) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let mut wire = model.add_source("source", TypedFact::dt_shape(types[0], [3].as_ref())?)?;
+        for (ix, &dt) in types[1..].iter().enumerate() {
+            wire = model.wire_node(format!("cast-{}", ix), cast(dt), &[wire])?[0];
+        }
+        model.set_output_outlets(&[wire])?;
+        Ok(model)
+    }
+
+    fn count_casts(model: &TypedModel) -> usize {
+        model.nodes().iter().filter(|n| n.op_as::<ElementWiseOp>().is_some()).count()
+    }
+
+    #[test]
+    fn fold_widening_chain() -> TractResult<()> {
+        let model = casts(&[DatumType::I8, DatumType::I32, DatumType::I64])?.declutter()?;
+        assert_eq!(count_casts(&model), 1);
+        assert_eq!(model.output_fact(0)?.datum_type, DatumType::I64);
+        let output = model.into_runnable()?.run(tvec!(tensor1(&[-128i8, 0, 127])))?;
+        assert_eq!(*output[0], tensor1(&[-128i64, 0, 127]));
+        Ok(())
+    }
+
+    #[test]
+    fn keep_saturating_cast_through_float() -> TractResult<()> {
+        let model = casts(&[DatumType::I32, DatumType::F64, DatumType::I8])?;
+        let input = tensor1(&[300i32, -300, 5]);
+        let expected = model.clone().into_runnable()?.run(tvec!(input.clone()))?;
+        let model = model.declutter()?;
+        assert_eq!(count_casts(&model), 2);
+        let output = model.into_runnable()?.run(tvec!(input))?;
+        assert_eq!(output, expected);
+        assert_eq!(*output[0], tensor1(&[127i8, -128, 5]));
+        Ok(())
+    }
+
+    #[test]
+    fn keep_lossy_intermediate() -> TractResult<()> {
+        let model = casts(&[DatumType::F32, DatumType::I32, DatumType::F32])?.declutter()?;
+        assert_eq!(count_casts(&model), 2);
+        let output = model.into_runnable()?.run(tvec!(tensor1(&[0.5f32, 1.5, -2.5])))?;
+        assert_eq!(*output[0], tensor1(&[0f32, 1., -2.]));
+        Ok(())
     }
 }
@@ -115,27 +115,20 @@ impl AxisOp {
             }
             Permute(perm) => {
                 assert_eq!(perm.len(), shape.rank());
-                let orig = shape.clone();
-                for (ix, &from) in perm.iter().enumerate() {
-                    shape.set_dim(ix, orig.dim(from).to_integer().unwrap_or(1).to_dim())?;
-                }
-                if let Some(info) = orig.stream_info {
-                    shape.set_dim(perm.iter().position(|&i| i == info.axis).unwrap(), info.len)?;
-                }
+                *shape = ShapeFact::from_dims(
+                    perm.iter().map(|&from| shape.dim(from)).collect::<TVec<_>>(),
+                )?;
                 Ok(())
             }
         }
     }
 
     pub fn change_tensor(&self, tensor: &mut Tensor) -> TractResult<()> {
-        fn permute<T: Datum>(axes: &[usize], input: Tensor) -> TractResult<Tensor> {
-            Ok(input.into_array::<T>()?.permuted_axes(axes).into_tensor())
-        }
         match self {
             Add(ix) => tensor.insert_axis(*ix),
             Rm(ix) => tensor.remove_axis(*ix),
             Permute(axes) => {
-                let mut tmp = dispatch_datum!(permute(tensor.datum_type())(axes, tensor.clone()))?;
+                let mut tmp = tensor.permute_axes(axes)?;
                 std::mem::swap(tensor, &mut tmp);
                 Ok(())
             }
@@ -225,6 +218,46 @@ impl StatelessOp for AxisOp {
     }
 }
 
+impl AxisOp {
+    /// Commute a permutation with the element-wise op consuming it, so it
+    /// can meet another permutation or a convolution further down.
+    fn declutter_permute_through_element_wise(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        use crate::ops::binary::UnaryOp;
+        use crate::ops::element_wise::ElementWiseOp;
+        let is_permute = match self {
+            Permute(_) => true,
+            _ => false,
+        };
+        if !is_permute || self.is_noop() {
+            return Ok(None);
+        }
+        if model.output_outlets()?.contains(&OutletId::new(node.id, 0)) {
+            return Ok(None);
+        }
+        let succ =
+            if let Some(succ) = model.single_succ(node.id)? { succ } else { return Ok(None) };
+        let op: Box<dyn TypedOp> = if succ.op_is::<ElementWiseOp>() {
+            succ.op.clone()
+        } else if let Some(unary) = succ.op_as::<UnaryOp>() {
+            let mut a = unary.a.clone().into_tensor();
+            self.recip().change_tensor(&mut a)?;
+            Box::new(UnaryOp::new(unary.mini_op.clone(), a.into_arc_tensor()))
+        } else {
+            return Ok(None);
+        };
+        let mut patch = TypedModelPatch::default();
+        let wire = patch.tap_model(model, node.inputs[0])?;
+        let wire = patch.wire_node(&*succ.name, op, &[wire])?;
+        let wire = patch.wire_node(&*node.name, self.clone(), &wire)?[0];
+        patch.shunt_outside(model, OutletId::new(succ.id, 0), wire)?;
+        Ok(Some(patch))
+    }
+}
+
 impl TypedOp for AxisOp {
     as_op!();
 
@@ -249,6 +282,14 @@ impl TypedOp for AxisOp {
         Ok(axes.into_iter().collect())
     }
 
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        self.declutter_permute_through_element_wise(model, node)
+    }
+
     fn suggested_axis_changes(&self) -> TractResult<TVec<(InOut, AxisOp)>> {
         Ok(tvec!((InOut::Out(0), self.recip()), (InOut::In(0), self.clone())))
     }
@@ -584,6 +625,34 @@ mod test {
         let op = Permute(tvec!(2, 0, 1));
         assert_eq!(op.merge_incoming_change(&change), Some((Some(p!(1, 0)), Some(Rm(1)))));
     }
+
+    fn permute_then(op: Box<dyn TypedOp>) -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(f32::datum_type(), [2, 3].as_ref())?)?;
+        let wire = model.wire_node("transpose", p!(1, 0), &[source])?;
+        let wire = model.wire_node("op", op, &wire)?;
+        model.set_output_outlets(&wire)?;
+        let decluttered = model.clone().declutter()?;
+        let order = decluttered.eval_order()?;
+        assert_eq!(decluttered.node(order[1]).name, "op");
+        assert_eq!(decluttered.node(order[2]).op_as::<AxisOp>(), Some(&p!(1, 0)));
+        let input = tensor2(&[[-1f32, 2., -3.], [4., -5., 6.]]);
+        let expected = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+        let found = SimplePlan::new(&decluttered)?.run(tvec!(input))?;
+        assert_eq!(found, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn transpose_relu_is_relu_transpose() -> TractResult<()> {
+        permute_then(Box::new(crate::ops::math::max::unary(rctensor2(&[[0f32]]))))
+    }
+
+    #[test]
+    fn transpose_per_channel_add_is_add_transpose() -> TractResult<()> {
+        permute_then(Box::new(crate::ops::math::add::unary(rctensor2(&[[1f32], [2.], [3.]]))))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,158 @@
+use crate::internal::*;
+
+/// Passes its input through, failing if it contains NaN or infinite values.
+///
+/// `name` is the name of the guarded node, used in the error message.
+#[derive(Debug, Clone, new, Hash)]
+pub struct CheckFinite {
+    pub name: String,
+}
+
+tract_linalg::impl_dyn_hash!(CheckFinite);
+
+impl CheckFinite {
+    fn check<T: Datum + num_traits::Float>(&self, input: &Tensor) -> TractResult<()> {
+        if let Some(v) = input.as_slice::<T>()?.iter().find(|v| !v.is_finite()) {
+            bail!("Non-finite value {:?} in output of {}", v, self.name);
+        }
+        Ok(())
+    }
+}
+
+impl Op for CheckFinite {
+    fn name(&self) -> Cow<str> {
+        "CheckFinite".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("guarding: {}", self.name)])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+    op_as_pulsed_op!();
+}
+
+impl StatelessOp for CheckFinite {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        match inputs[0].datum_type() {
+            DatumType::F16 => self.check::<f32>(&*inputs[0].cast_to::<f32>()?)?,
+            DatumType::F32 => self.check::<f32>(&inputs[0])?,
+            DatumType::F64 => self.check::<f64>(&inputs[0])?,
+            _ => (),
+        }
+        Ok(inputs)
+    }
+}
+
+impl TypedOp for CheckFinite {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        let input = mapping[&node.inputs[0]];
+        target.wire_node(&*node.name, self.clone(), &[input])
+    }
+
+    as_op!();
+}
+
+impl PulsedOp for CheckFinite {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+/// Guards every floating point output computed by `model` with a CheckFinite
+/// op. Constants and sources are left alone.
+///
+/// Guards on model outputs take over the guarded node name and outlet label,
+/// so outputs can still be looked up by name.
+pub fn check_finite_outputs(model: &TypedModel) -> TractResult<TypedModel> {
+    use crate::ops::konst::Const;
+    use crate::ops::source::TypedSource;
+    let mut model = model.clone();
+    let floats = [DatumType::F16, DatumType::F32, DatumType::F64];
+    for id in 0..model.nodes().len() {
+        if model.node(id).op_is::<Const>() || model.node(id).op_is::<TypedSource>() {
+            continue;
+        }
+        let name = model.node(id).name.clone();
+        for slot in 0..model.node(id).outputs.len() {
+            let outlet = OutletId::new(id, slot);
+            if !floats.contains(&model.outlet_fact(outlet)?.datum_type) {
+                continue;
+            }
+            let is_output = model.output_outlets()?.contains(&outlet);
+            let guard_name = if is_output && slot == 0 {
+                model.rename_node(id, &format!("{}.unchecked", name))?;
+                name.clone()
+            } else {
+                format!("{}.check-finite-{}", name, slot)
+            };
+            let mut patch = TypedModelPatch::default();
+            let tap = patch.tap_model(&model, outlet)?;
+            let guard = patch.wire_node(guard_name, CheckFinite::new(name.clone()), &[tap])?[0];
+            patch.shunt_outside(&model, outlet, guard)?;
+            patch.apply(&mut model)?;
+            if is_output {
+                model.outlet_labels.remove(&outlet);
+            }
+        }
+    }
+    Ok(model)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::konst::Const;
+    use crate::ops::math::{add, mul};
+    use crate::ops::source::TypedSource;
+
+    #[test]
+    fn guard_names_the_faulty_node() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(f32::datum_type(), [2].as_ref())?)?;
+        let one = model.add_const("one", rctensor1(&[1f32]))?;
+        let ok = model.wire_node("ok", add::bin_typed(), &[source, one])?;
+        let boom = model.wire_node("boom", mul::unary(rctensor1(&[std::f32::INFINITY])), &ok)?;
+        model.set_output_outlets(&boom)?;
+        model.set_outlet_label(boom[0], "output".to_string())?;
+        let guarded = check_finite_outputs(&model)?;
+        // the output keeps its name and label
+        let output = guarded.output_outlets()?[0];
+        assert!(guarded.node(output.node).op_is::<CheckFinite>());
+        assert_eq!(guarded.node_by_name("boom")?.id, output.node);
+        assert_eq!(guarded.find_outlet_label("output"), Some(output));
+        // only "ok" and "boom" are computed
+        let guards =
+            guarded.nodes().iter().filter(|n| n.op_is::<CheckFinite>()).collect::<Vec<_>>();
+        assert_eq!(guards.len(), 2);
+        for guard in guards {
+            let guarded_node = guarded.node(guard.inputs[0].node);
+            assert!(!guarded_node.op_is::<Const>() && !guarded_node.op_is::<TypedSource>());
+        }
+
+        let plan = SimplePlan::new(&guarded)?;
+        let err = plan.run(tvec!(tensor1(&[1f32, 2.]))).unwrap_err();
+        assert!(err.to_string().contains("boom"), "{}", err);
+        assert!(plan.run(tvec!(tensor1(&[-1f32, 2.]))).is_err());
+
+        let plan = SimplePlan::new(&model)?;
+        assert!(plan.run(tvec!(tensor1(&[1f32, 2.]))).is_ok());
+        Ok(())
+    }
+}
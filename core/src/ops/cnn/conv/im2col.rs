@@ -195,7 +195,8 @@ impl Patcher {
         pad_value: T,
     ) {
         let ptr = input.as_ptr();
-        let mut mega_matrix = unsafe { Array2::<T>::uninitialized((im2col.k, im2col.n)) };
+        let mut mega_matrix: Array2<T> =
+            unsafe { crate::tensor::uninitialized_array((im2col.k, im2col.n)) };
         let shape = &im2col.input_shape;
         unsafe {
             let ptr = ptr.offset((shape.c_stride() * (g * im2col.ci_per_group)) as isize);
@@ -12,11 +12,12 @@ use super::depth_wise::DepthWise;
 use super::im2col::Im2Col;
 use crate::ops::array::TypedReshape;
 use crate::ops::cnn::conv::KernelFormat;
-use crate::ops::cnn::PoolSpec;
+use crate::ops::cnn::{PaddingSpec, PoolSpec};
 use crate::ops::matmul;
 use crate::ops::matmul::mmm_wrapper::MMMWrapper;
 use crate::ops::nn::{DataFormat, DataShape};
 use crate::ops::quant::QParams;
+use tract_linalg::Ops;
 
 use tract_linalg::frame::mmm::FusedSpec;
 use tract_linalg::frame::PackA;
@@ -38,6 +39,10 @@ pub struct ConvUnary {
 tract_linalg::impl_dyn_hash!(ConvUnary);
 
 impl ConvUnary {
+    fn accumulates_in_i32(&self) -> bool {
+        self.q_params.as_ref().map(|q| q.accumulator_datum_type == i32::datum_type()).unwrap_or(true)
+    }
+
     fn input_channels(&self) -> usize {
         match self.kernel_fmt {
             KernelFormat::OIHW => self.kernel.shape()[1],
@@ -133,33 +138,83 @@ impl ConvUnary {
         name: &str,
         wire: OutletId,
         direct: bool,
+        ops: &Ops,
         ) -> TractResult<OutletId> {
         let a = self.kernel.datum_type();
         let b = model.outlet_fact(wire)?.datum_type;
+        if !self.accumulates_in_i32() {
+            return self.wire_as_im2col_pair_i64(model, name, wire, direct);
+        }
         if (a, b) == (f32::datum_type(), f32::datum_type()) {
             return self.wire_as_im2col_pair_t(model, name, wire, direct, &|m, k, n| {
-                MMMWrapper::Plain((tract_linalg::ops().mmm_f32)(m, k, n))
+                MMMWrapper::Plain((ops.mmm_f32)(m, k, n))
             });
         } else if (a, b) == (u8::datum_type(), u8::datum_type()) {
             return self.wire_as_im2col_pair_t(model, name, wire, direct, &|m, k, n| {
-                MMMWrapper::Quant((tract_linalg::ops().qmmm_u8_i32)(m, k, n))
+                MMMWrapper::Quant((ops.qmmm_u8_i32)(m, k, n))
             });
         } else if (a, b) == (i8::datum_type(), i8::datum_type()) {
             if let Some(q) = &self.q_params {
                 if q.c_datum_type == i8::datum_type() {
                     return self.wire_as_im2col_pair_t(model, name, wire, direct, &|m, k, n| {
-                        MMMWrapper::Quant((tract_linalg::ops().qmmm_i8_i8)(m, k, n))
+                        MMMWrapper::Quant((ops.qmmm_i8_i8)(m, k, n))
                     });
                 }
             } else {
                 return self.wire_as_im2col_pair_t(model, name, wire, direct, &|m, k, n| {
-                    MMMWrapper::Quant((tract_linalg::ops().qmmm_i8_i32)(m, k, n))
+                    MMMWrapper::Quant((ops.qmmm_i8_i32)(m, k, n))
                 });
             }
         }
         bail!("Unsupported combination for Conv (filters: {:?}, data:{:?})", a, b);
     }
 
+    unsafe fn wire_as_im2col_pair_i64(
+        &self,
+        model: &mut TypedModel,
+        name: &str,
+        wire: OutletId,
+        direct: bool,
+        ) -> TractResult<OutletId> {
+        use tract_linalg::generic::GenericMmm4x4;
+        use tract_linalg::mmm::{MatMatMulImpl, QMatMatMulImpl};
+        macro_rules! wire_i64 {
+            ($ta: ty, $tb: ty, $tc: ty) => {
+                self.wire_as_im2col_pair_t(model, name, wire, direct, &|m, k, n| {
+                    MMMWrapper::Quant(Box::new(QMatMatMulImpl::from(MatMatMulImpl::<
+                        GenericMmm4x4<$ta, $tb, $tc, i64>,
+                        $ta,
+                        $tb,
+                        $tc,
+                        i64,
+                    >::new(m, k, n))))
+                })
+            };
+        }
+        let q = self.q_params.as_ref().unwrap();
+        let a = self.kernel.datum_type();
+        let b = model.outlet_fact(wire)?.datum_type;
+        if q.accumulator_datum_type != i64::datum_type() {
+            bail!("Unsupported accumulator type for Conv: {:?}", q.accumulator_datum_type);
+        }
+        match (a, b, q.c_datum_type) {
+            (DatumType::U8, DatumType::U8, DatumType::U8) => wire_i64!(u8, u8, u8),
+            (DatumType::U8, DatumType::U8, DatumType::I32) => wire_i64!(u8, u8, i32),
+            (DatumType::U8, DatumType::U8, DatumType::I64) => wire_i64!(u8, u8, i64),
+            (DatumType::I8, DatumType::I8, DatumType::I8) => wire_i64!(i8, i8, i8),
+            (DatumType::I8, DatumType::I8, DatumType::I32) => wire_i64!(i8, i8, i32),
+            (DatumType::I8, DatumType::I8, DatumType::I64) => wire_i64!(i8, i8, i64),
+            (DatumType::I16, DatumType::I16, DatumType::I32) => wire_i64!(i16, i16, i32),
+            (DatumType::I16, DatumType::I16, DatumType::I64) => wire_i64!(i16, i16, i64),
+            (a, b, c) => bail!(
+                "Unsupported combination for Conv with i64 accumulator (filters: {:?}, data:{:?}, output:{:?})",
+                a,
+                b,
+                c
+            ),
+        }
+    }
+
     unsafe fn wire_as_im2col_pair_t<TA, TB, TC, TI>(
         &self,
         model: &mut TypedModel,
@@ -285,6 +340,163 @@ impl ConvUnary {
             Ok(Box::new(op))
         }
 
+    fn codegen_with_config_impl(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        config: &CodegenConfig,
+        ) -> TractResult<Option<TypedModelPatch>> {
+        let ops = config.linalg_ops();
+        let full_input_shape = model.outlet_fact(node.inputs[0])?.shape.to_tvec();
+        let input_fact = model.outlet_fact(node.inputs[0])?;
+        let input_shape = self.pool_spec.data_format.shape(&full_input_shape)?;
+        let spatial_rank = input_shape.hw_rank();
+        let kernel_spatial_shape = &self.kernel.shape()[self.kernel_fmt.h_axis()..][..spatial_rank];
+        if let Some(shape) = input_fact.shape.as_finite() {
+            unsafe {
+                let dt = input_fact.datum_type;
+                if kernel_spatial_shape.iter().product::<usize>() == 1
+                    && self.accumulates_in_i32()
+                    && (0..spatial_rank).all(|i| {
+                        self.pool_spec.stride(i) == 1
+                            && self.pool_spec.dilation(i) == 1
+                            && self.pool_spec.padding.valid_dim(i)
+                    })
+                        && self.group == 1
+                        {
+                            use crate::ops::matmul::MatMulUnary;
+                            let mut patch = TypedModelPatch::default();
+                            let mut wire = patch.tap_model(model, node.inputs[0])?;
+                            let input_c_is_last = input_shape.c_axis() == input_shape.rank() - 1;
+                            let mut reshaped_input = tvec!(
+                                input_shape.n().cloned().unwrap_or(1.to_dim()),
+                                input_shape.hw_dims().iter().cloned().maybe_product()?,
+                                input_shape.c().clone(),
+                                );
+                            if !input_c_is_last {
+                                reshaped_input.swap(1, 2);
+                            }
+                            wire =
+                                patch.wire_node(&*node.name, TypedReshape::new(reshaped_input), &[wire])?
+                                [0];
+                            let kernel_shape = match self.kernel_fmt {
+                                KernelFormat::HWIO => &self.kernel.shape()[spatial_rank..],
+                                KernelFormat::OIHW => &self.kernel.shape()[..2],
+                            };
+                            let kernel = self.kernel.as_ref().clone().into_shape(&kernel_shape)?;
+                            wire = patch.wire_node(
+                                &*node.name,
+                                MatMulUnary::new(
+                                    kernel.into_arc_tensor(),
+                                    self.kernel_fmt == KernelFormat::HWIO,
+                                    input_c_is_last,
+                                    input_c_is_last,
+                                    self.q_params.clone(),
+                                    ),
+                                    &[wire],
+                                    )?[0];
+                            if let Some(ref bias) = self.bias {
+                                let bias_shape =
+                                    if input_c_is_last { [1, 1, bias.len()] } else { [1, bias.len(), 1] };
+                                let bias =
+                                    bias.clone().into_tensor().into_shape(&bias_shape)?.into_arc_tensor();
+                                wire = patch.wire_node(
+                                    format!("{}.bias", node.name),
+                                    crate::ops::math::add::unary(bias),
+                                    &[wire],
+                                    )?[0];
+                            }
+                            wire = patch.wire_node(
+                                &*node.name,
+                                TypedReshape::new(node.outputs[0].fact.shape.to_tvec()),
+                                &[wire],
+                                )?[0];
+                            patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
+                            return Ok(Some(patch));
+                        } else if let Some(patch) =
+                            self.codegen_capped_im2col(model, node, shape, config.max_im2col_bytes, ops)?
+                        {
+                            return Ok(Some(patch));
+                        } else if should_use_direct(
+                            &self.pool_spec.data_format.shape(shape.into())?,
+                            &self.pool_spec,
+                            self.group,
+                            ) {
+                            let mut patch = TypedModelPatch::default();
+                            let wire = patch.tap_model(model, node.inputs[0])?;
+                            let wire = self.wire_as_im2col_pair(&mut patch, &*node.name, wire, true, ops)?;
+                            patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
+                            return Ok(Some(patch));
+                        } else if self.group != 1 && self.group == self.output_channels() {
+                            return Ok(Some(TypedModelPatch::single_unary_op(
+                                        model,
+                                        node,
+                                        dispatch_floatlike!(Self::to_depth_wise(dt)(self, &shape))?,
+                                        )?));
+                        } else {
+                            let mut patch = TypedModelPatch::default();
+                            let wire = patch.tap_model(model, node.inputs[0])?;
+                            let wire = self.wire_as_im2col_pair(&mut patch, &*node.name, wire, false, ops)?;
+                            patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
+                            return Ok(Some(patch));
+                        }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Size in bytes of the buffer the im2col transformation would produce.
+    fn im2col_bytes(&self, input_full_shape: &[usize], dt: DatumType) -> TractResult<usize> {
+        let (input_shape, geo, _) = self.pool_spec.compute_geo(input_full_shape)?;
+        let k = self.kernel.len() / self.output_channels();
+        let n = geo.output_shape.iter().cloned().product::<usize>();
+        Ok(input_shape.n().cloned().unwrap_or(1) * self.group * k * n * dt.size_of())
+    }
+
+    /// Wire the convolution directly on the input when im2col would need more
+    /// than `max_im2col_bytes`, padding the input explicitly if needed.
+    unsafe fn codegen_capped_im2col(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        input_full_shape: &[usize],
+        max_im2col_bytes: Option<usize>,
+        ops: &Ops,
+        ) -> TractResult<Option<TypedModelPatch>> {
+        use crate::ops::array::{Pad, PadMode};
+        let dt = model.outlet_fact(node.inputs[0])?.datum_type;
+        match max_im2col_bytes {
+            Some(max) if self.im2col_bytes(input_full_shape, dt)? > max => (),
+            _ => return Ok(None),
+        }
+        if self.group != 1 {
+            return Ok(None);
+        }
+        let mut patch = TypedModelPatch::default();
+        let mut wire = patch.tap_model(model, node.inputs[0])?;
+        let spatial_rank = self.pool_spec.kernel_shape.len();
+        if !(0..spatial_rank).all(|ax| self.pool_spec.padding.valid_dim(ax)) {
+            let pad_value = match self.q_params.as_ref().and_then(|q| q.zero_point_b.as_ref()) {
+                Some(zp) if zp.rank() == 0 => zp.clone(),
+                Some(_) => return Ok(None),
+                None => Tensor::zero_dt(dt, &[])?.into_arc_tensor(),
+            };
+            let (input_shape, geo, _) = self.pool_spec.compute_geo(input_full_shape)?;
+            let h_axis = input_shape.h_axis();
+            let mut pads = vec![(0, 0); input_full_shape.len()];
+            for ax in 0..spatial_rank {
+                pads[h_axis + ax] = (geo.pad_before[ax], geo.pad_after[ax]);
+            }
+            let pad = Pad::new(pads, PadMode::Constant(pad_value));
+            wire = patch.wire_node(format!("{}.pad", node.name), pad, &[wire])?[0];
+        }
+        let mut valid = self.clone();
+        valid.pool_spec.padding = PaddingSpec::Valid;
+        let wire = valid.wire_as_im2col_pair(&mut patch, &*node.name, wire, true, ops)?;
+        patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
+        Ok(Some(patch))
+    }
+
     fn declutter_stride_slice_to_downsample(
         &self,
         model: &TypedModel,
@@ -321,6 +533,51 @@ impl ConvUnary {
         Ok(None)
     }
 
+    fn declutter_absorb_pad(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        ) -> TractResult<Option<TypedModelPatch>> {
+        use crate::ops::array::{Pad, PadMode};
+        let prec = model.node(node.inputs[0].node);
+        let pad = if let Some(pad) = prec.op_as::<Pad>() { pad } else { return Ok(None) };
+        if self.q_params.is_some() {
+            return Ok(None);
+        }
+        if let PadMode::Constant(c) = &pad.mode {
+            if c.cast_to_scalar::<f64>()? != 0.0 {
+                return Ok(None);
+            }
+        } else {
+            return Ok(None);
+        }
+        let input_fact = model.outlet_fact(prec.inputs[0])?;
+        let shape = self.pool_spec.data_format.shape(input_fact.shape.to_tvec())?;
+        let spatial_rank = shape.hw_rank();
+        let h_axis = shape.h_axis();
+        if pad.pads.iter().enumerate().any(|(ax, &(a, b))| {
+            (ax < h_axis || ax >= h_axis + spatial_rank) && (a != 0 || b != 0)
+        }) {
+            return Ok(None);
+        }
+        let (mut before, mut after) = match &self.pool_spec.padding {
+            PaddingSpec::Valid => (tvec!(0; spatial_rank), tvec!(0; spatial_rank)),
+            PaddingSpec::Explicit(b, a) => (b.clone(), a.clone()),
+            _ => return Ok(None),
+        };
+        for ax in 0..spatial_rank {
+            before[ax] += pad.pads[ax + h_axis].0;
+            after[ax] += pad.pads[ax + h_axis].1;
+        }
+        let mut new_op = self.clone();
+        new_op.pool_spec.padding = PaddingSpec::Explicit(before, after);
+        let mut patch = TypedModelPatch::default();
+        let tap = patch.tap_model(model, prec.inputs[0])?;
+        let wire = patch.wire_node(&*node.name, new_op, &[tap])?[0];
+        patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
+        Ok(Some(patch))
+    }
+
     fn declutter_as_matmul(
         &self,
         model: &TypedModel,
@@ -331,6 +588,7 @@ impl ConvUnary {
         let full_input_shape = input_fact.shape.to_tvec();
         let input_shape = self.pool_spec.data_format.shape(&full_input_shape)?;
         if input_shape.hw_rank() == 1
+            && self.accumulates_in_i32()
             && self.group == 1
                 && self.pool_spec.stride(0) == 1
                 && self.pool_spec.dilation(0) == 1
@@ -440,7 +698,9 @@ impl StatelessOp for ConvUnary {
         let mut model = TypedModel::default();
         let dt = inputs[0].datum_type();
         let wire = model.add_source("source", TypedFact::dt_shape(dt, inputs[0].shape())?)?;
-        let wire = unsafe { self.wire_as_im2col_pair(&mut model, "im2col-adhoc", wire, false)? };
+        let wire = unsafe {
+            self.wire_as_im2col_pair(&mut model, "im2col-adhoc", wire, false, tract_linalg::ops())?
+        };
         model.set_output_outlets(&[wire])?;
         let plan = SimplePlan::new(model)?;
         plan.run(inputs.into_iter().map(|t| t.into_tensor()).collect())
@@ -479,7 +739,11 @@ impl TypedOp for ConvUnary {
         model: &TypedModel,
         node: &TypedNode,
         ) -> TractResult<Option<TypedModelPatch>> {
-        for d in &[Self::declutter_stride_slice_to_downsample, Self::declutter_as_matmul] {
+        for d in &[
+            Self::declutter_absorb_pad,
+            Self::declutter_stride_slice_to_downsample,
+            Self::declutter_as_matmul,
+        ] {
             if let Some(p) = d(&self, model, node)? {
                 return Ok(Some(p));
             }
@@ -640,94 +904,38 @@ impl TypedOp for ConvUnary {
         model: &TypedModel,
         node: &TypedNode,
         ) -> TractResult<Option<TypedModelPatch>> {
-        let full_input_shape = model.outlet_fact(node.inputs[0])?.shape.to_tvec();
+        self.codegen_with_config(model, node, &CodegenConfig::default())
+    }
+
+    fn codegen_with_config(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        config: &CodegenConfig,
+        ) -> TractResult<Option<TypedModelPatch>> {
+        self.codegen_with_config_impl(model, node, config)
+    }
+
+    fn codegen_for_size(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        ) -> TractResult<Option<TypedModelPatch>> {
         let input_fact = model.outlet_fact(node.inputs[0])?;
-        let input_shape = self.pool_spec.data_format.shape(&full_input_shape)?;
-        let spatial_rank = input_shape.hw_rank();
-        let kernel_spatial_shape = &self.kernel.shape()[self.kernel_fmt.h_axis()..][..spatial_rank];
-        if let Some(shape) = input_fact.shape.as_finite() {
-            unsafe {
-                let dt = input_fact.datum_type;
-                if kernel_spatial_shape.iter().product::<usize>() == 1
-                    && (0..spatial_rank)
-                        .all(|i| self.pool_spec.stride(i) == 1 && self.pool_spec.dilation(i) == 1)
-                        && self.group == 1
-                        {
-                            use crate::ops::matmul::MatMulUnary;
-                            let mut patch = TypedModelPatch::default();
-                            let mut wire = patch.tap_model(model, node.inputs[0])?;
-                            let input_c_is_last = input_shape.c_axis() == input_shape.rank() - 1;
-                            let mut reshaped_input = tvec!(
-                                input_shape.n().cloned().unwrap_or(1.to_dim()),
-                                input_shape.hw_dims().iter().cloned().maybe_product()?,
-                                input_shape.c().clone(),
-                                );
-                            if !input_c_is_last {
-                                reshaped_input.swap(1, 2);
-                            }
-                            wire =
-                                patch.wire_node(&*node.name, TypedReshape::new(reshaped_input), &[wire])?
-                                [0];
-                            let kernel_shape = match self.kernel_fmt {
-                                KernelFormat::HWIO => &self.kernel.shape()[spatial_rank..],
-                                KernelFormat::OIHW => &self.kernel.shape()[..2],
-                            };
-                            let kernel = self.kernel.as_ref().clone().into_shape(&kernel_shape)?;
-                            wire = patch.wire_node(
-                                &*node.name,
-                                MatMulUnary::new(
-                                    kernel.into_arc_tensor(),
-                                    self.kernel_fmt == KernelFormat::HWIO,
-                                    input_c_is_last,
-                                    input_c_is_last,
-                                    self.q_params.clone(),
-                                    ),
-                                    &[wire],
-                                    )?[0];
-                            if let Some(ref bias) = self.bias {
-                                let bias_shape =
-                                    if input_c_is_last { [1, 1, bias.len()] } else { [1, bias.len(), 1] };
-                                let bias =
-                                    bias.clone().into_tensor().into_shape(&bias_shape)?.into_arc_tensor();
-                                wire = patch.wire_node(
-                                    format!("{}.bias", node.name),
-                                    crate::ops::math::add::unary(bias),
-                                    &[wire],
-                                    )?[0];
-                            }
-                            wire = patch.wire_node(
-                                &*node.name,
-                                TypedReshape::new(node.outputs[0].fact.shape.to_tvec()),
-                                &[wire],
-                                )?[0];
-                            patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
-                            return Ok(Some(patch));
-                        } else if should_use_direct(
-                            &self.pool_spec.data_format.shape(shape.into())?,
-                            &self.pool_spec,
-                            self.group,
-                            ) {
-                            let mut patch = TypedModelPatch::default();
-                            let wire = patch.tap_model(model, node.inputs[0])?;
-                            let wire = self.wire_as_im2col_pair(&mut patch, &*node.name, wire, true)?;
-                            patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
-                            return Ok(Some(patch));
-                        } else if self.group != 1 && self.group == self.output_channels() {
-                            return Ok(Some(TypedModelPatch::single_unary_op(
-                                        model,
-                                        node,
-                                        dispatch_floatlike!(Self::to_depth_wise(dt)(self, &shape))?,
-                                        )?));
-                        } else {
-                            let mut patch = TypedModelPatch::default();
-                            let wire = patch.tap_model(model, node.inputs[0])?;
-                            let wire = self.wire_as_im2col_pair(&mut patch, &*node.name, wire, false)?;
-                            patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
-                            return Ok(Some(patch));
-                        }
-            }
-        }
-        Ok(None)
+        let spatial_rank = self.kernel.rank() - 2;
+        if input_fact.shape.as_finite().is_some()
+            && self.group == 1
+                && (0..spatial_rank).all(|ax| self.pool_spec.padding.valid_dim(ax))
+                {
+                    let mut patch = TypedModelPatch::default();
+                    let wire = patch.tap_model(model, node.inputs[0])?;
+                    let wire = unsafe {
+                        self.wire_as_im2col_pair(&mut patch, &*node.name, wire, true, tract_linalg::ops())?
+                    };
+                    patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
+                    return Ok(Some(patch));
+                }
+        self.codegen(model, node)
     }
 
     as_op!();
@@ -762,7 +970,7 @@ fn should_use_direct(input_shape: &DataShape, pool_spec: &PoolSpec, group: usize
 mod test {
     use super::*;
     use crate::ops::cnn::PaddingSpec;
-    use DataFormat::{ HWC, NHWC };
+    use DataFormat::{ HWC, NCHW, NHWC };
 
     #[test]
     fn conv_vs_direct_arm_ml_kws_cnn_m_0() {
@@ -879,4 +1087,252 @@ mod test {
         // assert!(!use_direct(24, 3)); // tdnn3 is neutral
         assert!(!use_direct(10, 1)); // tdnn4,5
     }
+
+    #[test]
+    fn optimize_for_size_uses_direct_conv() -> TractResult<()> {
+        let kernel = tensor4(&[[[[1f32, 0., -1.], [2., 0., -2.], [1., 0., -1.]]]]);
+        let conv = ConvUnary::new(
+            PoolSpec::new(DataFormat::NCHW, tvec!(3, 3), PaddingSpec::Valid, None, None, Some(1)),
+            KernelFormat::OIHW,
+            kernel.into_arc_tensor(),
+            1,
+            None,
+            None,
+            );
+        let mut model = TypedModel::default();
+        let source = model
+            .add_source("source", TypedFact::dt_shape(f32::datum_type(), [1usize, 1, 5, 5].as_ref())?)?;
+        let wire = model.wire_node("conv", conv, &[source])?;
+        model.set_output_outlets(&wire)?;
+        let optimized = model.clone().optimize_for_size()?;
+        assert!(!optimized
+            .nodes()
+            .iter()
+            .any(|n| n.op_is::<Im2Col<f32>>() || n.op().name() == "MatMatMulPackB"));
+
+        let input = tensor1(&(0..25).map(|i| (i * i) as f32).collect::<Vec<_>>());
+        let input = unsafe { input.into_shape(&[1, 1, 5, 5])? };
+        let found = SimplePlan::new(&optimized)?.run(tvec!(input.clone()))?;
+        let expected = SimplePlan::new(&model)?.run(tvec!(input))?;
+        assert_eq!(found, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn pad_then_valid_conv_is_same_conv() -> TractResult<()> {
+        use crate::ops::array::{Pad, PadMode};
+        let kernel = tensor4(&[[[[1f32, 2., 3.], [4., 5., 6.], [7., 8., 9.]]]]);
+        let conv = |padding| ConvUnary::new(
+            PoolSpec::new(DataFormat::NCHW, tvec!(3, 3), padding, None, None, Some(1)),
+            KernelFormat::OIHW,
+            kernel.clone().into_arc_tensor(),
+            1,
+            None,
+            None,
+            );
+        let fact = TypedFact::dt_shape(f32::datum_type(), [1usize, 1, 4, 4].as_ref())?;
+        let mut model = TypedModel::default();
+        let source = model.add_source("source", fact.clone())?;
+        let pad = Pad::new(vec![(0, 0), (0, 0), (1, 1), (1, 1)], PadMode::default());
+        let wire = model.wire_node("pad", pad, &[source])?;
+        let wire = model.wire_node("conv", conv(PaddingSpec::Valid), &wire)?;
+        model.set_output_outlets(&wire)?;
+        let model = model.declutter()?;
+        assert!(!model.nodes().iter().any(|n| n.op_is::<Pad>()));
+
+        let mut reference = TypedModel::default();
+        let source = reference.add_source("source", fact)?;
+        let wire = reference.wire_node("conv", conv(PaddingSpec::SameUpper), &[source])?;
+        reference.set_output_outlets(&wire)?;
+
+        let input = tensor1(&(0..16).map(|i| i as f32).collect::<Vec<_>>());
+        let input = unsafe { input.into_shape(&[1, 1, 4, 4])? };
+        let found = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+        let expected = SimplePlan::new(&reference)?.run(tvec!(input))?;
+        assert_eq!(found, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn capped_im2col_falls_back_to_direct() -> TractResult<()> {
+        let kernel = tensor1(&(0..54).map(|i| (i % 5) as f32 - 2.).collect::<Vec<_>>());
+        let kernel = unsafe { kernel.into_shape(&[3, 2, 3, 3])? };
+        let input = tensor1(&(0..128).map(|i| (i % 7) as f32).collect::<Vec<_>>());
+        let input = unsafe { input.into_shape(&[1, 2, 8, 8])? };
+        for padding in vec![PaddingSpec::Valid, PaddingSpec::SameUpper] {
+            let conv = ConvUnary::new(
+                PoolSpec::new(DataFormat::NCHW, tvec!(3, 3), padding, None, None, Some(3)),
+                KernelFormat::OIHW,
+                kernel.clone().into_arc_tensor(),
+                1,
+                None,
+                None,
+            );
+            let mut model = TypedModel::default();
+            let fact = TypedFact::dt_shape(f32::datum_type(), [1, 2, 8, 8].as_ref())?;
+            let source = model.add_source("source", fact)?;
+            let wire = model.wire_node("conv", conv.clone(), &[source])?;
+            model.set_output_outlets(&wire)?;
+            let expected = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+            for &(max, uses_im2col) in &[(None, true), (Some(1 << 20), true), (Some(16), false)] {
+                let node = model.node(wire[0].node);
+                let config = CodegenConfig { max_im2col_bytes: max, ..CodegenConfig::default() };
+                let patch = conv.codegen_with_config(&model, node, &config)?.unwrap();
+                let mut optimized = model.clone();
+                patch.apply(&mut optimized)?;
+                let im2col = optimized.nodes().iter().any(|n| n.op_is::<Im2Col<f32>>());
+                assert_eq!(im2col, uses_im2col);
+                let found = SimplePlan::new(&optimized)?.run(tvec!(input.clone()))?;
+                found[0].close_enough(&expected[0], true)?;
+                let optimized = model.clone().codegen_with_config(&config)?;
+                let im2col = optimized.nodes().iter().any(|n| n.op_is::<Im2Col<f32>>());
+                assert_eq!(im2col, uses_im2col);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn one_by_one_conv_as_matmul() -> TractResult<()> {
+        let kernel = tensor1(&(0..12).map(|i| i as f32 - 5.).collect::<Vec<_>>());
+        let kernel = unsafe { kernel.into_shape(&[3, 4, 1, 1])? };
+        let conv = |padding| {
+            ConvUnary::new(
+                PoolSpec::new(DataFormat::NCHW, tvec!(1, 1), padding, None, None, Some(3)),
+                KernelFormat::OIHW,
+                kernel.clone().into_arc_tensor(),
+                1,
+                Some(rctensor1(&[1f32, 2., 3.])),
+                None,
+            )
+        };
+        let input = tensor1(&(0..256).map(|i| (i % 7) as f32).collect::<Vec<_>>());
+        let input = unsafe { input.into_shape(&[1, 4, 8, 8])? };
+        for &pad in &[0, 1] {
+            let mut model = TypedModel::default();
+            let fact = TypedFact::dt_shape(f32::datum_type(), [1, 4, 8, 8].as_ref())?;
+            let source = model.add_source("source", fact)?;
+            let padding = PaddingSpec::Explicit(tvec!(pad, pad), tvec!(pad, pad));
+            let wire = model.wire_node("conv", conv(padding), &[source])?;
+            model.set_output_outlets(&wire)?;
+            let optimized = model.clone().into_optimized()?;
+            let im2col = optimized.nodes().iter().any(|n| n.op_is::<Im2Col<f32>>());
+            assert_eq!(im2col, pad != 0);
+            assert!(optimized.nodes().iter().any(|n| n.op().name() == "MatMatMul"));
+            let found = SimplePlan::new(&optimized)?.run(tvec!(input.clone()))?;
+            let expected = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+            assert_eq!(found[0].shape(), &[1, 3, 8 + 2 * pad, 8 + 2 * pad]);
+            assert_eq!(found, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn nhwc_bias_is_per_channel() -> TractResult<()> {
+        let bias = rctensor1(&[1f32, 2., 3.]);
+        for &(k, group, ci) in &[(1, 1, 2), (3, 1, 2), (3, 1, 1), (3, 3, 3)] {
+            let kernel = (0..k * k * ci / group * 3).map(|i| (i % 5) as f32 - 2.);
+            let kernel = tensor1(&kernel.collect::<Vec<_>>());
+            let kernel = unsafe { kernel.into_shape(&[3, ci / group, k, k])? };
+            // HWIO for plain convolutions, OIHW for grouped ones
+            let (kernel_fmt, kernel) = if group == 1 {
+                (KernelFormat::HWIO, kernel.permute_axes(&[2, 3, 1, 0])?)
+            } else {
+                (KernelFormat::OIHW, kernel)
+            };
+            let conv = |fmt| {
+                ConvUnary::new(
+                    PoolSpec::new(fmt, tvec!(k, k), PaddingSpec::SameUpper, None, None, Some(3)),
+                    kernel_fmt.clone(),
+                    kernel.clone().into_arc_tensor(),
+                    group,
+                    Some(bias.clone()),
+                    None,
+                )
+            };
+            let run = |fmt, input: &Tensor| -> TractResult<Tensor> {
+                let mut model = TypedModel::default();
+                let fact = TypedFact::dt_shape(f32::datum_type(), input.shape())?;
+                let source = model.add_source("source", fact)?;
+                let wire = model.wire_node("conv", conv(fmt), &[source])?;
+                model.set_output_outlets(&wire)?;
+                let found = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+                let optimized = model.into_optimized()?;
+                let optimized = SimplePlan::new(&optimized)?.run(tvec!(input.clone()))?;
+                optimized[0].close_enough(&found[0], true)?;
+                Ok(found[0].clone().into_tensor())
+            };
+            let zeros = Tensor::zero::<f32>(&[1, 4, 4, ci])?;
+            let found = run(NHWC, &zeros)?;
+            for pixel in found.to_array_view::<f32>()?.into_shape((16, 3))?.outer_iter() {
+                assert_eq!(pixel.as_slice().unwrap(), bias.as_slice::<f32>()?);
+            }
+            let input = tensor1(&(0..16 * ci).map(|i| (i % 7) as f32).collect::<Vec<_>>());
+            let input = unsafe { input.into_shape(&[1, 4, 4, ci])? };
+            let nhwc = run(NHWC, &input)?;
+            let nchw = run(NCHW, &input.permute_axes(&[0, 3, 1, 2])?)?;
+            nhwc.close_enough(&nchw.permute_axes(&[0, 2, 3, 1])?, true)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn i64_accumulator_for_large_k() -> TractResult<()> {
+        // 40000 * 255 * 255 does not fit in an i32
+        let k = 40000;
+        let kernel = unsafe { tensor1(&vec![255u8; k]).into_shape(&[1, k, 1, 1])? };
+        let conv = ConvUnary::new(
+            PoolSpec::new(DataFormat::NCHW, tvec!(1, 1), PaddingSpec::Valid, None, None, Some(1)),
+            KernelFormat::OIHW,
+            kernel.into_arc_tensor(),
+            1,
+            None,
+            Some(QParams::new(i64::datum_type()).with_accumulator_datum_type(i64::datum_type())),
+        );
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(u8::datum_type(), [1, k, 1, 1].as_ref())?)?;
+        let wire = model.wire_node("conv", conv, &[source])?;
+        model.set_output_outlets(&wire)?;
+        let input = unsafe { tensor1(&vec![255u8; k]).into_shape(&[1, k, 1, 1])? };
+        let expected = unsafe { tensor1(&[k as i64 * 255 * 255]).into_shape(&[1, 1, 1, 1])? };
+        assert!(k as i64 * 255 * 255 > i32::max_value() as i64);
+        let found = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+        assert_eq!(*found[0], expected);
+        let optimized = model.into_optimized()?;
+        let found = SimplePlan::new(&optimized)?.run(tvec!(input))?;
+        assert_eq!(*found[0], expected);
+        Ok(())
+    }
+
+    #[test]
+    fn pulsed_dilated_conv_delay() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [TDim::s(), 1.to_dim()].as_ref())?;
+        let source = model.add_source("source", fact)?;
+        let pool_spec =
+            PoolSpec::new(HWC, tvec!(3), PaddingSpec::Valid, Some(tvec!(2)), None, Some(1));
+        let kernel = rctensor3(&[[[1f32]], [[1f32]], [[1f32]]]);
+        let conv = ConvUnary::new(pool_spec, KernelFormat::HWIO, kernel, 1, None, None);
+        let wire = model.wire_node("conv", conv, &[source])?;
+        model.set_output_outlets(&wire)?;
+
+        let pulsed = PulsedModel::new(&model.into_normalized()?, 4)?;
+        let output_fact = pulsed.output_fact(0)?;
+        assert_eq!(output_fact.delay, 4);
+        assert_eq!(output_fact.dim, TDim::s() - 4);
+
+        let plan = SimplePlan::new(pulsed)?;
+        let mut state = SimpleState::new(plan)?;
+        let mut outputs = vec![];
+        for i in 0..3 {
+            let input: Vec<f32> = (4 * i..4 * (i + 1)).map(|x| x as f32).collect();
+            let input = arr1(&input).into_shape((4, 1))?.into_tensor();
+            let output = state.run(tvec!(input))?;
+            outputs.extend(output[0].as_slice::<f32>()?.iter().cloned());
+        }
+        let expected: Vec<f32> = (0..8).map(|t| 3. * t as f32 + 6.).collect();
+        assert_eq!(&outputs[4..], &*expected);
+        Ok(())
+    }
 }
@@ -124,9 +124,10 @@ impl MaxPoolFixed {
         let input: ArrayViewD<T> = input.to_array_view()?;
         let input_ptr = input.as_ptr();
 
-        let mut values = unsafe { ArrayD::<T>::uninitialized(&*self.output_shape.shape) };
+        let mut values: ArrayD<T> =
+            unsafe { crate::tensor::uninitialized_array(&*self.output_shape.shape) };
         let mut indices = if self.with_index_outputs.is_some() {
-            Some(unsafe { ArrayD::<i32>::uninitialized(&*self.output_shape.shape) })
+            Some(unsafe { crate::tensor::uninitialized_array::<i64, _>(&*self.output_shape.shape) })
         } else {
             None
         };
@@ -141,9 +142,11 @@ impl MaxPoolFixed {
                     for c in 0..*self.input_shape.c() {
                         let input_offset = input_offset + self.input_shape.c_stride() * c;
                         let output_offset = output_offset + self.output_shape.c_stride() * c;
+                        // keep the flat offset in the input along with the value
                         let max = visitor
                             .valid_offsets()
-                            .map(|v| (v, *input_ptr.offset(v + input_offset as isize)))
+                            .map(|v| v + input_offset as isize)
+                            .map(|v| (v, *input_ptr.offset(v)))
                             .fold((0, T::min_value()), |acc, v| if acc.1 < v.1 { v } else { acc });
                         *values
                             .as_mut_ptr()
@@ -152,7 +155,7 @@ impl MaxPoolFixed {
                             *indices
                                 .as_mut_ptr()
                                 .offset(output_offset as isize + visitor.output_offset) =
-                                max.0 as i32 / self.patch.spec.output_inner_stride as i32;
+                                max.0 as i64;
                         }
                     }
                 }
@@ -188,3 +191,49 @@ impl TypedOp for MaxPoolFixed {
         Ok(facts)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::cnn::PaddingSpec;
+    use crate::ops::nn::DataFormat;
+
+    fn max_pool_2x2(input: Tensor) -> TractResult<TVec<Arc<Tensor>>> {
+        let pool_spec = PoolSpec::new(
+            DataFormat::NCHW,
+            tvec!(2, 2),
+            PaddingSpec::Valid,
+            None,
+            Some(tvec!(2, 2)),
+            None,
+        );
+        MaxPool::new(pool_spec, Some(i64::datum_type())).eval(tvec!(input.into_arc_tensor()))
+    }
+
+    #[test]
+    fn argmax_indices() -> TractResult<()> {
+        let input = tensor4(&[[[
+            [1f32, 8., 2., 0.],
+            [3., 4., 9., 5.],
+            [7., 0., 1., 1.],
+            [2., 6., 3., 4.],
+        ]]]);
+        let outputs = max_pool_2x2(input.clone())?;
+        assert_eq!(*outputs[0], tensor4(&[[[[8f32, 9.], [7., 4.]]]]));
+        assert_eq!(*outputs[1], tensor4(&[[[[1i64, 6], [8, 15]]]]));
+        let flat = input.as_slice::<f32>()?;
+        for (&ix, &max) in outputs[1].as_slice::<i64>()?.iter().zip(outputs[0].as_slice::<f32>()?) {
+            assert_eq!(flat[ix as usize], max);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn argmax_indices_are_flat_in_input() -> TractResult<()> {
+        let data = (0..32).map(|x| x as f32).collect::<Vec<_>>();
+        let input = tract_ndarray::ArrayD::from_shape_vec(vec![1, 2, 4, 4], data)?.into_tensor();
+        let outputs = max_pool_2x2(input)?;
+        assert_eq!(*outputs[1], tensor4(&[[[[5i64, 7], [13, 15]], [[21, 23], [29, 31]]]]));
+        Ok(())
+    }
+}
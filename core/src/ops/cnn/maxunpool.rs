@@ -0,0 +1,168 @@
+use crate::internal::*;
+
+/// Scatters pooled values back to the positions given by MaxPool argmax
+/// indices, zero-filling the rest.
+///
+/// Inputs are the pooled values, their flat indices in the output, and the
+/// output shape. When the output shape is only known at runtime, the output
+/// keeps the batch and channel dimensions of the values, and each spatial
+/// axis is named by one of the `spatial` symbols, which must not be used
+/// anywhere else in the model (see `MaxUnpool::fresh`).
+#[derive(Debug, Clone, Default, Hash)]
+pub struct MaxUnpool {
+    pub spatial: TVec<char>,
+}
+
+tract_linalg::impl_dyn_hash!(MaxUnpool);
+
+impl MaxUnpool {
+    /// A MaxUnpool naming its `spatial_rank` output spatial axes with symbols
+    /// not used yet in `model`.
+    pub fn fresh(model: &TypedModel, spatial_rank: usize) -> TractResult<MaxUnpool> {
+        Ok(MaxUnpool { spatial: model.fresh_symbols(spatial_rank)? })
+    }
+
+    fn eval_t<T: Datum + num_traits::Zero>(
+        &self,
+        values: &Tensor,
+        indices: &Tensor,
+        shape: &[usize],
+    ) -> TractResult<Tensor> {
+        let mut output = Tensor::zero::<T>(shape)?;
+        let len = output.len();
+        let scattered = output.as_slice_mut::<T>()?;
+        for (value, &ix) in values.as_slice::<T>()?.iter().zip(indices.as_slice::<i64>()?) {
+            if ix < 0 || ix as usize >= len {
+                bail!("MaxUnpool index {} is out of output of shape {:?}", ix, shape);
+            }
+            scattered[ix as usize] = value.clone();
+        }
+        Ok(output)
+    }
+}
+
+impl Op for MaxUnpool {
+    fn name(&self) -> Cow<str> {
+        "MaxUnpool".into()
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+    not_a_pulsed_op!();
+}
+
+impl StatelessOp for MaxUnpool {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (values, indices, shape) = args_3!(inputs);
+        if values.shape() != indices.shape() {
+            bail!("MaxUnpool values {:?} and indices {:?} mismatch", values, indices);
+        }
+        let indices = indices.cast_to::<i64>()?;
+        let shape = shape.cast_to::<i64>()?;
+        let shape = shape.as_slice::<i64>()?.iter().map(|&d| d as usize).collect::<TVec<_>>();
+        let output =
+            dispatch_numbers!(Self::eval_t(values.datum_type())(self, &values, &indices, &shape))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for MaxUnpool {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if let Some(shape) = &inputs[2].konst {
+            let shape = shape.cast_to::<TDim>()?;
+            return Ok(tvec!(TypedFact::dt_shape(
+                inputs[0].datum_type,
+                shape.as_slice::<TDim>()?
+            )?));
+        }
+        let values = inputs[0].shape.to_tvec();
+        let rank = inputs[2].shape.dim(0).to_usize()?;
+        if rank != values.len() {
+            bail!("MaxUnpool output rank {} does not match values {:?}", rank, values);
+        }
+        if rank < 2 || self.spatial.len() != rank - 2 {
+            bail!(
+                "MaxUnpool with a dynamic output shape needs a symbol per spatial axis, got {:?}",
+                self.spatial
+            );
+        }
+        let mut shape: TVec<TDim> = values[..2].into();
+        shape.extend(self.spatial.iter().map(|&s| TDim::sym(s)));
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*shape)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::cnn::{MaxPool, PaddingSpec, PoolSpec};
+    use crate::ops::nn::DataFormat;
+
+    #[test]
+    fn max_pool_round_trip() -> TractResult<()> {
+        let input = tensor4(&[[[
+            [1f32, 8., 2., 0.],
+            [3., 4., 9., 5.],
+            [7., 0., 1., 1.],
+            [2., 6., 3., 4.],
+        ]]]);
+        let pool_spec = PoolSpec::new(
+            DataFormat::NCHW,
+            tvec!(2, 2),
+            PaddingSpec::Valid,
+            None,
+            Some(tvec!(2, 2)),
+            None,
+        );
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("input", TypedFact::dt_shape(f32::datum_type(), input.shape())?)?;
+        let pooled =
+            model.wire_node("pool", MaxPool::new(pool_spec, Some(i64::datum_type())), &[source])?;
+        let shape = model.add_const("shape", tensor1(&[1i64, 1, 4, 4]))?;
+        let unpooled =
+            model.wire_node("unpool", MaxUnpool::default(), &[pooled[0], pooled[1], shape])?;
+        model.set_output_outlets(&unpooled)?;
+        assert_eq!(model.output_fact(0)?.shape.as_finite(), Some(&[1usize, 1, 4, 4][..]));
+        let output = model.into_runnable()?.run(tvec!(input))?;
+        assert_eq!(
+            *output[0],
+            tensor4(&[[[
+                [0f32, 8., 0., 0.],
+                [0., 0., 9., 0.],
+                [7., 0., 0., 0.],
+                [0., 0., 0., 4.],
+            ]]])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic_output_shape() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [1, 1, 2, 2].as_ref())?;
+        let values = model.add_source("values", fact)?;
+        let fact = TypedFact::dt_shape(i64::datum_type(), [1, 1, 2, 2].as_ref())?;
+        let indices = model.add_source("indices", fact)?;
+        let fact = TypedFact::dt_shape(i64::datum_type(), [4].as_ref())?;
+        let shape = model.add_source("shape", fact)?;
+        let op = MaxUnpool::fresh(&model, 2)?;
+        assert!(!op.spatial.contains(&'S'));
+        let unpooled = model.wire_node("unpool", op.clone(), &[values, indices, shape])?;
+        model.set_output_outlets(&unpooled)?;
+        let output_fact = model.output_fact(0)?.clone();
+        assert_eq!(output_fact.shape.dim(2), TDim::sym(op.spatial[0]));
+        assert_eq!(output_fact.shape.dim(3), TDim::sym(op.spatial[1]));
+        assert_ne!(op.spatial[0], op.spatial[1]);
+        assert!(output_fact.shape.stream_info().is_none());
+        let output = model.into_runnable()?.run(tvec!(
+            tensor4(&[[[[1f32, 2.], [3., 4.]]]]),
+            tensor4(&[[[[0i64, 2], [7, 8]]]]),
+            tensor1(&[1i64, 1, 3, 3]),
+        ))?;
+        assert_eq!(*output[0], tensor4(&[[[[1f32, 0., 2.], [0., 0., 0.], [0., 3., 4.]]]]));
+        Ok(())
+    }
+}
@@ -1,15 +1,23 @@
 mod avgpool;
 pub mod conv;
 mod maxpool;
+mod maxunpool;
 mod padding;
 mod patch_axis;
 mod patches;
 pub mod pools;
+mod receptive_field;
+mod space_to_batch;
 
 pub use self::avgpool::AvgPool;
 pub use self::conv::{ConvUnary, KernelFormat};
 pub use self::maxpool::MaxPool;
+pub use self::maxunpool::MaxUnpool;
 pub use self::padding::PaddingSpec;
 pub use self::patch_axis::PatchAxis;
 pub use self::patches::{Patch, PatchSpec};
 pub use self::pools::PoolSpec;
+pub use self::receptive_field::{receptive_fields, ReceptiveField};
+pub use self::space_to_batch::{
+    batch_to_space, space_to_batch, BatchToSpaceND, PaddingStrat, SpaceToBatchND,
+};
@@ -113,7 +113,7 @@ impl PaddingSpec {
                 .max(0);
             (pad as usize).into()
         } else {
-            (output.clone() - 1) * stride + kernel_field - input
+            ((output.clone() - 1) * stride + kernel_field - input).maxi(D::zero())
         };
         let lower_pad = pad.clone() / 2;
         let higher_pad = pad - &lower_pad;
@@ -183,4 +183,23 @@ mod tests {
     fn same_upper() {
         assert_eq!(PaddingSpec::same(&7usize, 1usize, 1, 2, true), ComputedPaddedDim::new(4, 0, 0));
     }
+
+    #[test]
+    fn same_symbolic_matches_concrete() {
+        for &(kernel, stride) in &[(1, 2), (2, 2), (3, 1), (3, 2), (5, 3)] {
+            let sym = PaddingSpec::same(&TDim::s(), kernel, 1, stride, true);
+            for len in 1..12usize {
+                let concrete = PaddingSpec::same(&len, kernel, 1, stride, true);
+                let eval = |d: &TDim| d.eval(len as i64).unwrap() as usize;
+                assert_eq!(
+                    ComputedPaddedDim::new(
+                        eval(&sym.output),
+                        eval(&sym.pad_before),
+                        eval(&sym.pad_after)
+                    ),
+                    concrete
+                );
+            }
+        }
+    }
 }
@@ -95,14 +95,13 @@ impl PoolSpec {
         } else {
             let geo_axis = fact.axis - input_shape.h_axis();
             let stride = self.strides.as_ref().and_then(|v| v.get(geo_axis).cloned()).unwrap_or(1);
-            let pulse = fact.pulse();
             if fact.pulse() % stride != 0 {
                 bail!("Pulsificaton requires pulse to be a stride multiple")
             }
             let dilation = self.dilations.as_ref().map(|d| d[geo_axis]).unwrap_or(1);
             let kernel_len = (self.kernel_shape[geo_axis] - 1) * dilation;
             let overlap = (kernel_len + 1).saturating_sub(stride);
-            let misalignment = fact.delay % pulse;
+            let misalignment = fact.delay % stride;
             let mut wire = input;
 
             if overlap > 0 || misalignment > 0 {
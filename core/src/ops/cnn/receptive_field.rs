@@ -0,0 +1,123 @@
+use crate::internal::*;
+use crate::ops::cnn::{AvgPool, ConvUnary, MaxPool, PoolSpec};
+
+/// Receptive field along one spatial axis: an output position depends on
+/// `size` consecutive input positions, and moving by one output position
+/// moves the field by `stride` input positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, new)]
+pub struct ReceptiveField {
+    pub size: usize,
+    pub stride: usize,
+}
+
+impl ReceptiveField {
+    /// Compose with a convolution or a pooling on the same axis.
+    pub fn then(&self, kernel: usize, stride: usize, dilation: usize) -> ReceptiveField {
+        let kernel_field = (kernel - 1) * dilation + 1;
+        ReceptiveField::new(self.size + (kernel_field - 1) * self.stride, self.stride * stride)
+    }
+}
+
+fn pool_spec(node: &TypedNode) -> Option<&PoolSpec> {
+    if let Some(conv) = node.op_as::<ConvUnary>() {
+        Some(&conv.pool_spec)
+    } else if let Some(pool) = node.op_as::<MaxPool>() {
+        Some(&pool.pool_spec)
+    } else if let Some(pool) = node.op_as::<AvgPool>() {
+        Some(&pool.pool_spec)
+    } else {
+        None
+    }
+}
+
+/// Compute the receptive field of each model output, per spatial axis, at
+/// the model inputs.
+///
+/// ConvUnary, MaxPool and AvgPool are composed, any other operator is assumed
+/// to be pointwise on the spatial axes. When several inputs of a node have a
+/// receptive field, the largest one is kept.
+pub fn receptive_fields(model: &TypedModel) -> TractResult<TVec<TVec<ReceptiveField>>> {
+    // None stands for a pointwise field, of any rank
+    let mut fields: HashMap<OutletId, Option<TVec<ReceptiveField>>> = HashMap::new();
+    for node_id in model.eval_order()? {
+        let node = model.node(node_id);
+        let mut field: Option<TVec<ReceptiveField>> = None;
+        for input in &node.inputs {
+            if let Some(Some(input_field)) = fields.get(input) {
+                field = Some(match field {
+                    None => input_field.clone(),
+                    Some(field) if field.len() == input_field.len() => field
+                        .iter()
+                        .zip(input_field.iter())
+                        .map(|(a, b)| {
+                            ReceptiveField::new(a.size.max(b.size), a.stride.max(b.stride))
+                        })
+                        .collect(),
+                    _ => bail!("Inconsistent spatial ranks at {}", node),
+                });
+            }
+        }
+        if let Some(spec) = pool_spec(node) {
+            let rank = spec.kernel_shape.len();
+            let input_field = field.unwrap_or_else(|| tvec!(ReceptiveField::new(1, 1); rank));
+            if input_field.len() != rank {
+                bail!("Inconsistent spatial ranks at {}", node);
+            }
+            field = Some(
+                input_field
+                    .iter()
+                    .enumerate()
+                    .map(|(ax, f)| {
+                        f.then(spec.kernel_shape[ax], spec.stride(ax), spec.dilation(ax))
+                    })
+                    .collect(),
+            );
+        }
+        for slot in 0..node.outputs.len() {
+            fields.insert(OutletId::new(node_id, slot), field.clone());
+        }
+    }
+    Ok(model
+        .output_outlets()?
+        .iter()
+        .map(|o| fields.get(o).and_then(|f| f.clone()).unwrap_or_default())
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::cnn::{KernelFormat, PaddingSpec};
+    use crate::ops::nn::DataFormat;
+
+    #[test]
+    fn two_convs() -> TractResult<()> {
+        let conv = |strides, dilations| {
+            ConvUnary::new(
+                PoolSpec::new(
+                    DataFormat::NCHW,
+                    tvec!(3),
+                    PaddingSpec::Valid,
+                    dilations,
+                    strides,
+                    Some(1),
+                ),
+                KernelFormat::OIHW,
+                rctensor3(&[[[1f32, 1., 1.]]]),
+                1,
+                None,
+                None,
+            )
+        };
+        let mut model = TypedModel::default();
+        let source = model
+            .add_source("source", TypedFact::dt_shape(f32::datum_type(), [1, 1, 32].as_ref())?)?;
+        let wire = model.wire_node("conv1", conv(Some(tvec!(2)), None), &[source])?;
+        let wire = model.wire_node("relu", crate::ops::math::scalar_max(tensor0(0f32)), &wire)?;
+        let wire = model.wire_node("conv2", conv(None, Some(tvec!(2))), &wire)?;
+        model.set_output_outlets(&wire)?;
+        // conv1: 3 inputs, stride 2. conv2: 3 conv1 outputs 2 apart, 2 * 2 * 2 inputs more
+        assert_eq!(receptive_fields(&model)?, tvec!(tvec!(ReceptiveField::new(11, 2))));
+        Ok(())
+    }
+}
@@ -0,0 +1,358 @@
+use crate::internal::*;
+use crate::ops::cnn::{ConvUnary, PaddingSpec, PoolSpec};
+use crate::ops::nn::DataFormat;
+use ndarray::prelude::*;
+use num_traits::Zero;
+
+/// Padding (or cropping) of one block axis. The "flex" side absorbs whatever
+/// is needed to make the padded axis a multiple of the block size.
+#[derive(Debug, Copy, Clone, Hash)]
+pub enum PaddingStrat {
+    FlexFixed(usize),
+    FixedFlex(usize),
+    FixedFixed(usize, usize),
+}
+
+fn resolve_paddings(
+    space_shape: &[TDim],
+    batch_shape: &[TDim],
+    block_shape: &Array1<i32>,
+    pad: &[PaddingStrat],
+) -> TractResult<TVec<(usize, usize)>> {
+    pad.iter()
+        .enumerate()
+        .map(|(ax, &strat)| {
+            Ok(match strat {
+                PaddingStrat::FixedFixed(a, b) => (a, b),
+                _ => {
+                    let spread = (batch_shape[1 + ax].clone() * block_shape[ax]
+                        - &space_shape[1 + ax])
+                        .to_usize()?;
+                    match strat {
+                        PaddingStrat::FlexFixed(f) => (spread - f, f),
+                        PaddingStrat::FixedFlex(f) => (f, spread - f),
+                        PaddingStrat::FixedFixed(..) => unreachable!(),
+                    }
+                }
+            })
+        })
+        .collect()
+}
+
+fn paddings_array(paddings: &[(usize, usize)]) -> Array2<i32> {
+    Array2::from_shape_fn((paddings.len(), 2), |(ax, side)| {
+        if side == 0 {
+            paddings[ax].0 as i32
+        } else {
+            paddings[ax].1 as i32
+        }
+    })
+}
+
+/// Pad the block axes (1 to block_shape.len()), then move each block
+/// position to the batch axis.
+pub fn space_to_batch<T: Copy + Datum + Zero>(
+    input: Arc<Tensor>,
+    block_shape: &ArrayView1<i32>,
+    paddings: &ArrayView2<i32>,
+) -> TractResult<Arc<Tensor>> {
+    let mut data = input.into_tensor().into_array::<T>()?;
+
+    for (ix, pad) in paddings.view().outer_iter().enumerate() {
+        if pad[0] != 0 {
+            let mut pad_shape = data.shape().to_vec();
+            pad_shape[ix + 1] = pad[0] as usize;
+            let tmp = ndarray::stack(
+                ndarray::Axis(ix + 1),
+                &[ndarray::ArrayD::zeros(pad_shape).view(), data.view()],
+            )?;
+            data = tmp;
+        }
+        if pad[1] != 0 {
+            let mut pad_shape = data.shape().to_vec();
+            pad_shape[ix + 1] = pad[1] as usize;
+            let tmp = ndarray::stack(
+                ndarray::Axis(ix + 1),
+                &[data.view(), ndarray::ArrayD::zeros(pad_shape).view()],
+            )?;
+            data = tmp;
+        }
+    }
+    let mut reshaped = vec![data.shape()[0]];
+    let block_size = block_shape.iter().map(|a| *a as usize).product::<usize>();
+    let mut final_shape = vec![block_size * data.shape()[0]];
+    for (m, &block_shape_dim) in block_shape.iter().enumerate() {
+        reshaped.push(data.shape()[m + 1] / block_shape_dim as usize);
+        reshaped.push(block_shape_dim as usize);
+        final_shape.push(data.shape()[m + 1] / block_shape_dim as usize);
+    }
+    reshaped.extend(&data.shape()[block_shape.len() + 1..]);
+    final_shape.extend(&data.shape()[block_shape.len() + 1..]);
+    let data = data.into_shape(reshaped)?;
+
+    let mut permuted_axes: Vec<_> = (0..block_shape.len()).map(|x| 2 * x + 2).collect();
+    permuted_axes.push(0);
+    permuted_axes.extend((0..block_shape.len()).map(|x| 2 * x + 1));
+    permuted_axes.extend((block_shape.len() * 2 + 1)..data.ndim());
+    let data = data.permuted_axes(permuted_axes);
+    let data: Vec<T> = data.into_iter().map(|x| *x).collect();
+    let data = ndarray::ArrayD::from_shape_vec(final_shape, data)?;
+
+    Ok(data.into_arc_tensor())
+}
+
+/// Inverse of space_to_batch: move the block positions back from the batch
+/// axis to the block axes, then crop them.
+pub fn batch_to_space<T: Copy + Datum + Zero>(
+    input: Arc<Tensor>,
+    block_shape: &ArrayView1<i32>,
+    crops: &ArrayView2<i32>,
+) -> TractResult<Arc<Tensor>> {
+    let data = input.into_tensor().into_array()?;
+    let input_shape = data.shape().to_vec();
+    let crops: ArrayView2<i32> = crops.view().into_dimensionality()?;
+
+    let block_size = block_shape.iter().map(|a| *a as usize).product::<usize>();
+
+    // block_dim_1 .. block_dim_n, batches/bloc_size, dim_1, .. dim_n, chan_1, .., chan_n
+    let mut unflatten_blocked_shape = vec![];
+    unflatten_blocked_shape.extend(block_shape.iter().map(|a| *a as usize));
+    let batches = data.shape()[0] / block_size;
+    unflatten_blocked_shape.push(batches);
+    unflatten_blocked_shape.extend(&data.shape()[1..]);
+    let data = data.into_shape(&*unflatten_blocked_shape)?;
+    let mut permuted_axes = vec![block_shape.len()];
+    let mut padded_shape = vec![batches];
+    for i in 0..block_shape.shape()[0] {
+        permuted_axes.push(block_shape.len() + 1 + i);
+        permuted_axes.push(i);
+        padded_shape.push(block_shape[i] as usize * input_shape[i + 1]);
+    }
+    permuted_axes.extend((1 + block_shape.len() * 2)..data.ndim());
+    padded_shape.extend(&input_shape[1 + block_shape.len()..]);
+    let data = data.permuted_axes(permuted_axes);
+    let data: Vec<T> = data.into_iter().map(|x| *x).collect();
+    let data = ndarray::ArrayD::from_shape_vec(padded_shape, data)?;
+    let mut data = data;
+    for (i, crop) in crops.outer_iter().enumerate() {
+        if crop[0] != 0 || crop[1] != 0 {
+            let end = data.shape()[1 + i] as usize;
+            let range = (crop[0] as usize)..(end - crop[1] as usize);
+            data = data.slice_axis(Axis(i + 1), range.into()).map(|x| *x).to_owned();
+        }
+    }
+    Ok(data.into_arc_tensor())
+}
+
+/// SpaceToBatchND with known block shape and paddings.
+#[derive(Debug, Clone, new, Hash)]
+pub struct SpaceToBatchND {
+    pub datum_type: DatumType,
+    pub space_shape: TVec<TDim>,
+    pub batch_shape: TVec<TDim>,
+    pub block_shape: Array1<i32>,
+    pub pad: TVec<PaddingStrat>,
+}
+
+tract_linalg::impl_dyn_hash!(SpaceToBatchND);
+
+impl SpaceToBatchND {
+    pub fn paddings(&self) -> TractResult<TVec<(usize, usize)>> {
+        resolve_paddings(&self.space_shape, &self.batch_shape, &self.block_shape, &self.pad)
+    }
+}
+
+impl Op for SpaceToBatchND {
+    fn name(&self) -> Cow<str> {
+        "SpaceToBatchND".into()
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+    not_a_pulsed_op!();
+}
+
+impl StatelessOp for SpaceToBatchND {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let paddings = paddings_array(&self.paddings()?);
+        let r = dispatch_numbers!(space_to_batch(input.datum_type())(
+            input,
+            &self.block_shape.view(),
+            &paddings.view()
+        ))?;
+        Ok(tvec!(r))
+    }
+}
+
+impl TypedOp for SpaceToBatchND {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*self.batch_shape)?))
+    }
+
+    /// SpaceToBatchND, a valid undilated NHWC convolution and a BatchToSpaceND
+    /// with the same block shape are a dilated convolution, the padding minus
+    /// the crops becoming the convolution padding.
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let conv_node =
+            if let Some(n) = model.single_succ(node.id)? { n } else { return Ok(None) };
+        let b2s_node =
+            if let Some(n) = model.single_succ(conv_node.id)? { n } else { return Ok(None) };
+        let (conv, b2s) = match (conv_node.op_as::<ConvUnary>(), b2s_node.op_as::<BatchToSpaceND>())
+        {
+            (Some(conv), Some(b2s)) => (conv, b2s),
+            _ => return Ok(None),
+        };
+        let spec = &conv.pool_spec;
+        let rank = self.block_shape.len();
+        if spec.data_format != DataFormat::NHWC
+            || spec.kernel_shape.len() != rank
+            || b2s.block_shape != self.block_shape
+            || spec.padding != PaddingSpec::Valid
+            || (0..rank).any(|ax| spec.stride(ax) != 1 || spec.dilation(ax) != 1)
+        {
+            return Ok(None);
+        }
+        let pads = self.paddings()?;
+        let crops = b2s.paddings()?;
+        if pads.iter().zip(crops.iter()).any(|(p, c)| p.0 < c.0 || p.1 < c.1) {
+            return Ok(None);
+        }
+        let op = ConvUnary {
+            pool_spec: PoolSpec {
+                padding: PaddingSpec::Explicit(
+                    pads.iter().zip(crops.iter()).map(|(p, c)| p.0 - c.0).collect(),
+                    pads.iter().zip(crops.iter()).map(|(p, c)| p.1 - c.1).collect(),
+                ),
+                dilations: Some(self.block_shape.iter().map(|&i| i as usize).collect()),
+                ..spec.clone()
+            },
+            ..conv.clone()
+        };
+        let mut patch = TypedModelPatch::default();
+        let tap = patch.tap_model(&model, node.inputs[0])?;
+        let out = patch.model.wire_node(&*conv_node.name, op, &[tap])?[0];
+        patch.shunt_outside(model, OutletId::new(b2s_node.id, 0), out)?;
+        Ok(Some(patch))
+    }
+
+    as_op!();
+}
+
+/// BatchToSpaceND with known block shape and crops.
+#[derive(Debug, Clone, new, Hash)]
+pub struct BatchToSpaceND {
+    pub datum_type: DatumType,
+    pub batch_shape: TVec<TDim>,
+    pub space_shape: TVec<TDim>,
+    pub block_shape: Array1<i32>,
+    pub pad: TVec<PaddingStrat>,
+}
+
+tract_linalg::impl_dyn_hash!(BatchToSpaceND);
+
+impl BatchToSpaceND {
+    pub fn paddings(&self) -> TractResult<TVec<(usize, usize)>> {
+        resolve_paddings(&self.space_shape, &self.batch_shape, &self.block_shape, &self.pad)
+    }
+}
+
+impl Op for BatchToSpaceND {
+    fn name(&self) -> Cow<str> {
+        "BatchToSpaceND".into()
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+    not_a_pulsed_op!();
+}
+
+impl StatelessOp for BatchToSpaceND {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let paddings = paddings_array(&self.paddings()?);
+        let r = dispatch_numbers!(batch_to_space(input.datum_type())(
+            input,
+            &self.block_shape.view(),
+            &paddings.view()
+        ))?;
+        Ok(tvec!(r))
+    }
+}
+
+impl TypedOp for BatchToSpaceND {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(TypedFact::dt_shape(inputs[0].datum_type, &*self.space_shape)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::cnn::KernelFormat;
+
+    fn dims(shape: &[usize]) -> TVec<TDim> {
+        shape.iter().map(|d| d.to_dim()).collect()
+    }
+
+    // 3x3 conv with atrous rate 2 and SAME padding, the way TF builds it
+    fn sandwich() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let source = model
+            .add_source("source", TypedFact::dt_shape(f32::datum_type(), [1, 5, 5, 2].as_ref())?)?;
+        let s2b = SpaceToBatchND::new(
+            f32::datum_type(),
+            dims(&[1, 5, 5, 2]),
+            dims(&[4, 5, 5, 2]),
+            arr1(&[2, 2]),
+            tvec!(PaddingStrat::FixedFixed(2, 3); 2),
+        );
+        let wire = model.wire_node("s2b", s2b, &[source])?;
+        let kernel = ArrayD::from_shape_vec(
+            vec![3, 3, 2, 1],
+            (0..18).map(|i| i as f32 / 10. - 0.5).collect(),
+        )?
+        .into_tensor();
+        let conv = ConvUnary::new(
+            PoolSpec::new(DataFormat::NHWC, tvec!(3, 3), PaddingSpec::Valid, None, None, Some(1)),
+            KernelFormat::HWIO,
+            kernel.into_arc_tensor(),
+            1,
+            Some(rctensor1(&[0.25f32])),
+            None,
+        );
+        let wire = model.wire_node("conv", conv, &wire)?;
+        let b2s = BatchToSpaceND::new(
+            f32::datum_type(),
+            dims(&[4, 3, 3, 1]),
+            dims(&[1, 5, 5, 1]),
+            arr1(&[2, 2]),
+            tvec!(PaddingStrat::FixedFixed(0, 1); 2),
+        );
+        let wire = model.wire_node("b2s", b2s, &wire)?;
+        model.set_output_outlets(&wire)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn sandwich_folds_to_dilated_conv() -> TractResult<()> {
+        let model = sandwich()?;
+        let input =
+            ArrayD::from_shape_vec(vec![1, 5, 5, 2], (0..50).map(|i| (i as f32).sin()).collect())?
+                .into_tensor();
+        let expected = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+        let decluttered = model.declutter()?;
+        assert_eq!(decluttered.nodes().len(), 2);
+        let conv = decluttered.node(1).op_as::<ConvUnary>().unwrap();
+        assert_eq!(conv.pool_spec.dilations, Some(tvec!(2, 2)));
+        assert_eq!(conv.pool_spec.padding, PaddingSpec::Explicit(tvec!(2, 2), tvec!(2, 2)));
+        let found = SimplePlan::new(&decluttered)?.run(tvec!(input))?;
+        found[0].close_enough(&expected[0], true)
+    }
+}
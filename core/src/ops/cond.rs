@@ -0,0 +1,278 @@
+use crate::internal::*;
+
+/// Conditional evaluation of one of two sub-models.
+///
+/// Input 0 is a boolean scalar selecting the branch. The other inputs are the
+/// values the branches may use: `then_input_mapping[i]` (resp. else) is the
+/// slot of the outer input feeding the i-th input of the branch body.
+#[derive(Debug, Clone, Hash)]
+pub struct If {
+    then_plan: Arc<TypedSimplePlan<TypedModel>>,
+    pub then_input_mapping: Vec<usize>,
+    else_plan: Arc<TypedSimplePlan<TypedModel>>,
+    pub else_input_mapping: Vec<usize>,
+}
+
+tract_linalg::impl_dyn_hash!(If);
+
+impl If {
+    pub fn new(
+        then_body: TypedModel,
+        then_input_mapping: Vec<usize>,
+        else_body: TypedModel,
+        else_input_mapping: Vec<usize>,
+    ) -> TractResult<If> {
+        for (name, body, mapping) in
+            &[("then", &then_body, &then_input_mapping), ("else", &else_body, &else_input_mapping)]
+        {
+            if body.input_outlets()?.len() != mapping.len() {
+                bail!(
+                    "If {} branch has {} inputs, but its mapping has {}",
+                    name,
+                    body.input_outlets()?.len(),
+                    mapping.len()
+                );
+            }
+            if mapping.contains(&0) {
+                bail!("If {} branch maps an input to the condition slot", name);
+            }
+        }
+        Ok(If {
+            then_plan: Arc::new(SimplePlan::new(then_body)?),
+            then_input_mapping,
+            else_plan: Arc::new(SimplePlan::new(else_body)?),
+            else_input_mapping,
+        })
+    }
+
+    pub fn then_body(&self) -> &TypedModel {
+        self.then_plan.model()
+    }
+
+    pub fn else_body(&self) -> &TypedModel {
+        self.else_plan.model()
+    }
+
+    fn branch(&self, cond: bool) -> (&TypedSimplePlan<TypedModel>, &[usize]) {
+        if cond {
+            (&self.then_plan, &self.then_input_mapping)
+        } else {
+            (&self.else_plan, &self.else_input_mapping)
+        }
+    }
+
+    fn unify_facts(then_fact: &TypedFact, else_fact: &TypedFact) -> TractResult<TypedFact> {
+        if then_fact.datum_type != else_fact.datum_type || then_fact.shape != else_fact.shape {
+            bail!("Incompatible branch outputs: {:?} and {:?}", then_fact, else_fact);
+        }
+        let mut fact = then_fact.clone();
+        if then_fact.konst != else_fact.konst {
+            fact.konst = None;
+        }
+        Ok(fact)
+    }
+}
+
+impl Op for If {
+    fn name(&self) -> Cow<str> {
+        "If".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("then inputs: {:?}", self.then_input_mapping),
+            format!("else inputs: {:?}", self.else_input_mapping),
+        ])
+    }
+
+    fn nested_models(&self) -> Vec<(Cow<str>, &dyn Model, Vec<String>, Vec<String>)> {
+        let inputs = |mapping: &[usize]| mapping.iter().map(|i| format!("input #{}", i)).collect();
+        let outputs = |body: &TypedModel| {
+            (0..body.output_outlets().unwrap().len()).map(|o| format!("output #{}", o)).collect()
+        };
+        vec![
+            (
+                "then".into(),
+                self.then_body(),
+                inputs(&self.then_input_mapping),
+                outputs(self.then_body()),
+            ),
+            (
+                "else".into(),
+                self.else_body(),
+                inputs(&self.else_input_mapping),
+                outputs(self.else_body()),
+            ),
+        ]
+    }
+
+    op_core_mir!();
+    canonic!();
+    op_as_typed_op!();
+    not_a_pulsed_op!();
+}
+
+impl StatelessOp for If {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let cond = *inputs[0].to_scalar::<bool>()?;
+        let (plan, mapping) = self.branch(cond);
+        let body_inputs = mapping
+            .iter()
+            .map(|&slot| {
+                Ok(inputs.get(slot).ok_or("If input mapping out of range")?.clone().into_tensor())
+            })
+            .collect::<TractResult<_>>()?;
+        plan.run(body_inputs)
+    }
+}
+
+impl TypedOp for If {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if inputs[0].datum_type != bool::datum_type() || inputs[0].rank() != 0 {
+            bail!("If condition must be a boolean scalar, got {:?}", inputs[0]);
+        }
+        for (name, body, mapping) in &[
+            ("then", self.then_body(), &self.then_input_mapping),
+            ("else", self.else_body(), &self.else_input_mapping),
+        ] {
+            for (ix, &slot) in mapping.iter().enumerate() {
+                let outer = inputs.get(slot).ok_or_else(|| {
+                    format!("If {} input #{} maps to missing slot {}", name, ix, slot)
+                })?;
+                let inner = body.input_fact(ix)?;
+                if outer.datum_type != inner.datum_type || outer.shape != inner.shape {
+                    bail!(
+                        "If {} input #{} expects {:?}, got {:?} on slot {}",
+                        name,
+                        ix,
+                        inner,
+                        outer,
+                        slot
+                    );
+                }
+            }
+        }
+        let outputs = self.then_body().output_outlets()?.len();
+        if outputs != self.else_body().output_outlets()?.len() {
+            bail!("Branches of If must have the same number of outputs");
+        }
+        (0..outputs)
+            .map(|ix| {
+                Self::unify_facts(
+                    self.then_body().output_fact(ix)?,
+                    self.else_body().output_fact(ix)?,
+                )
+                .chain_err(|| format!("Unifying If output #{}", ix))
+            })
+            .collect()
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let cond = if let Some(cond) = &model.outlet_fact(node.inputs[0])?.konst {
+            *cond.to_scalar::<bool>()?
+        } else {
+            return Ok(None);
+        };
+        let (plan, mapping) = self.branch(cond);
+        let body = plan.model();
+        let mut patch = TypedModelPatch::default();
+        let mut mapped: HashMap<OutletId, OutletId> = HashMap::new();
+        for (input, &slot) in body.input_outlets()?.iter().zip(mapping.iter()) {
+            mapped.insert(*input, patch.tap_model(model, node.inputs[slot])?);
+        }
+        for id in body.eval_order()? {
+            let inner = body.node(id);
+            if mapped.contains_key(&OutletId::new(id, 0)) {
+                continue;
+            }
+            let inputs = inner.inputs.iter().map(|i| mapped[i]).collect::<TVec<_>>();
+            let outputs = patch.wire_node(
+                format!("{}.{}", node.name, inner.name),
+                inner.op.clone(),
+                &inputs,
+            )?;
+            for (ix, o) in outputs.into_iter().enumerate() {
+                mapped.insert(OutletId::new(id, ix), o);
+            }
+        }
+        for (ix, output) in body.output_outlets()?.iter().enumerate() {
+            patch.shunt_outside(model, OutletId::new(node.id, ix), mapped[output])?;
+        }
+        Ok(Some(patch))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn branch(op: Box<dyn TypedOp>) -> TypedModel {
+        let mut body = TypedModel::default();
+        let x = body
+            .add_source("x", TypedFact::dt_shape(f32::datum_type(), [3].as_ref()).unwrap())
+            .unwrap();
+        let y = body.wire_node("op", op, &[x]).unwrap();
+        body.set_output_outlets(&y).unwrap();
+        body
+    }
+
+    fn if_op() -> If {
+        If::new(
+            branch(Box::new(crate::ops::math::add::unary(rctensor1(&[1f32])))),
+            vec![1],
+            branch(Box::new(crate::ops::math::mul::unary(rctensor1(&[2f32])))),
+            vec![1],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn select_branch() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let cond = model
+            .add_source("cond", TypedFact::dt_shape(bool::datum_type(), [0usize; 0].as_ref())?)?;
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [3].as_ref())?)?;
+        let y = model.wire_node("if", if_op(), &[cond, x])?;
+        model.set_output_outlets(&y)?;
+        let plan = SimplePlan::new(&model)?;
+        let output = plan.run(tvec!(tensor0(true), tensor1(&[1f32, 2., 3.])))?;
+        assert_eq!(*output[0], tensor1(&[2f32, 3., 4.]));
+        let output = plan.run(tvec!(tensor0(false), tensor1(&[1f32, 2., 3.])))?;
+        assert_eq!(*output[0], tensor1(&[2f32, 4., 6.]));
+        Ok(())
+    }
+
+    #[test]
+    fn const_cond_is_inlined() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let cond = model.add_const("cond", tensor0(false))?;
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [3].as_ref())?)?;
+        let y = model.wire_node("if", if_op(), &[cond, x])?;
+        model.set_output_outlets(&y)?;
+        let model = model.declutter()?;
+        assert!(!model.nodes().iter().any(|n| n.op_is::<If>()));
+        let output = SimplePlan::new(&model)?.run(tvec!(tensor1(&[1f32, 2., 3.])))?;
+        assert_eq!(*output[0], tensor1(&[2f32, 4., 6.]));
+        Ok(())
+    }
+
+    #[test]
+    fn bad_mapping_is_an_error() -> TractResult<()> {
+        let add = || Box::new(crate::ops::math::add::unary(rctensor1(&[1f32])));
+        assert!(If::new(branch(add()), vec![0], branch(add()), vec![1]).is_err());
+        assert!(If::new(branch(add()), vec![1, 2], branch(add()), vec![1]).is_err());
+        let mut model = TypedModel::default();
+        let cond = model
+            .add_source("cond", TypedFact::dt_shape(bool::datum_type(), [0usize; 0].as_ref())?)?;
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [3].as_ref())?)?;
+        let op = If::new(branch(add()), vec![1], branch(add()), vec![2])?;
+        assert!(model.wire_node("if", op, &[cond, x]).is_err());
+        Ok(())
+    }
+}
@@ -15,15 +15,20 @@ where
     if down_op.axis != slice_op.axis {
         return Ok(None);
     }
-    let modulo = (down_op.modulo + slice_op.start.to_integer()? as usize) % down_op.stride;
-    let left = (down_op.modulo + slice_op.start.to_integer()? as usize) / down_op.stride;
+    let final_len = down_node.outputs[0].fact.shape.dim(down_op.axis);
+    let (modulo, left) = if final_len == 0.to_dim() {
+        // the modulo may point beyond the input end, any valid one will do
+        (0, 0)
+    } else {
+        let first = down_op.modulo + slice_op.start.to_usize()?;
+        (first % down_op.stride, first / down_op.stride)
+    };
     let mut patch = TypedModelPatch::default();
     let tap = patch.tap_model(model, slice_node.inputs[0])?;
-    let final_len = down_node.outputs[0].fact.shape.dim(down_op.axis);
     let new_down = Downsample::new(down_op.axis, down_op.stride, modulo);
     let ds = patch.wire_node(&*down_node.name, new_down, [tap].as_ref())?;
     let new_start = left;
-    let new_end = (final_len.to_dim() + left).to_integer()? as usize;
+    let new_end = (final_len.to_dim() + left).to_usize()?;
     let op = ops::array::Slice::new(slice_op.axis, new_start, new_end);
     let new_slice = patch.wire_node(&*slice_node.name, op, &*ds)?[0];
     patch.shunt_outside(model, OutletId::new(down_node.id, 0), new_slice)?;
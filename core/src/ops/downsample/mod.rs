@@ -16,6 +16,7 @@ pub struct Downsample {
 impl Downsample {
     fn eval_t<T: Datum>(&self, input: &Tensor) -> TractResult<Arc<Tensor>> {
         let input = input.to_array_view::<T>()?;
+        self.check_modulo(input.shape()[self.axis])?;
         let sampled = if self.modulo < input.shape()[self.axis] {
             input
                 .slice_axis(
@@ -32,6 +33,21 @@ impl Downsample {
         Ok(sampled)
     }
 
+    /// Sampling a non-empty axis from beyond its end is almost certainly a
+    /// mistake in the upstream graph, so reject it instead of silently
+    /// producing an empty tensor.
+    fn check_modulo(&self, len: usize) -> TractResult<()> {
+        if len > 0 && self.modulo >= len {
+            bail!(
+                "Downsample modulo {} is out of axis {} of length {}",
+                self.modulo,
+                self.axis,
+                len
+            );
+        }
+        Ok(())
+    }
+
     pub(crate) fn transform_dim(&self, input_dim: &TDim) -> TDim {
         (input_dim.clone() - self.modulo).div_ceil(self.stride as u32)
     }
@@ -70,6 +86,9 @@ impl StatelessOp for Downsample {
 
 impl TypedOp for Downsample {
     fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if let Ok(len) = inputs[0].shape.dim(self.axis).to_integer() {
+            self.check_modulo(len as usize)?;
+        }
         let mut downed = inputs[0].clone();
         let down_len = self.transform_dim(&downed.shape.dim(self.axis));
         downed.shape.set_dim(self.axis, down_len.clone())?;
@@ -153,3 +172,27 @@ fn pull_downsample_up(
     }
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modulo_out_of_axis() -> TractResult<()> {
+        let op = Downsample::new(0, 2, 3);
+        assert!(op.eval(tvec!(rctensor1(&[0i32, 1, 2]))).is_err());
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(i32::datum_type(), [3].as_ref())?)?;
+        assert!(model.wire_node("down", op, &[source]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn modulo_on_empty_axis() -> TractResult<()> {
+        let op = Downsample::new(0, 2, 1);
+        let output = op.eval(tvec!(rctensor1::<i32>(&[])))?;
+        assert_eq!(output[0].shape(), &[0]);
+        Ok(())
+    }
+}
@@ -62,7 +62,7 @@ pub fn pull_downsample_over_scan(
                 }
             }
             InputMapping::Scan { ref mut chunk, .. } => {
-                if chunk.to_integer()? as usize % down_op.stride != 0 {
+                if chunk.to_usize()? % down_op.stride != 0 {
                     return Ok(None);
                 }
                 *chunk = chunk.clone().div_ceil(down_op.stride as u32)
@@ -71,7 +71,7 @@ pub fn pull_downsample_over_scan(
         }
     }
     for output in &mut new_scan.output_mapping {
-        if output.chunk.to_integer()? as usize % down_op.stride != 0 {
+        if output.chunk.to_usize()? % down_op.stride != 0 {
             return Ok(None);
         }
         output.full_dim_hint.as_mut().map(|d| *d = down_op.transform_dim(d));
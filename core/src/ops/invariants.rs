@@ -133,6 +133,12 @@ impl Invariants {
         self.axes.iter().find(|conn| conn.outputs.get(output) == Some(&Some(axis)))
     }
 
+    /// Find the input axis matching `axis` of the output of a single-input,
+    /// single-output operator.
+    ///
+    /// Returns `None` if the axis is not preserved by the operator (or is
+    /// only preserved with a period other than 1). If `only_disposable` is
+    /// set, axes the operator can not work without are ignored too.
     pub fn unary_track_axis_up(&self, axis: usize, only_disposable: bool) -> Option<usize> {
         // TODO use track_input_axis
         if self.element_wise {
@@ -149,6 +155,10 @@ impl Invariants {
         }
     }
 
+    /// Find the output axis matching `axis` of the input of a single-input,
+    /// single-output operator.
+    ///
+    /// This is the reverse of `unary_track_axis_up`.
     pub fn unary_track_axis_down(&self, axis: usize, only_disposable: bool) -> Option<usize> {
         // TODO use track_input_axis
         if self.element_wise {
@@ -3,6 +3,9 @@ use num_traits::{Float, Zero};
 
 use super::binary::*;
 
+mod nary;
+pub use nary::{Mean, Sum};
+
 bin_to_super_type!(add, Add,
                    flip:commute,
                    validation: Validation::Rounding,
@@ -12,6 +15,7 @@ bin_to_super_type!(sub, Sub, flip:flip_sub,
 
 bin_to_super_type!(mul, Mul,
                    cost: |dt| tvec!((Cost::FMA(dt), 1)),
+                   declutter_bin: declutter_bin_mul,
                    declutter_unary: declutter_unary_mul,
                    flip: commute,
                    out_of_place: |c:&mut Tensor, a:&Tensor, b: &Tensor| -> TractResult<bool> {
@@ -30,25 +34,189 @@ bin_to_super_type!(mul, Mul,
                    [f32, i8, i16, i32, i64, u8, u16, f16, f64] => |c, a, b| *c = a.clone() * b
                   );
 
-bin_to_super_type!(div, Div,
-                   cost: |dt| tvec!((Cost::Div(dt), 1)),
-                   declutter_bin: declutter_bin_div,
-                   flip: flip_div,
-                   out_of_place: |c:&mut Tensor, a:&Tensor, b: &Tensor| -> TractResult<bool> {
-                       if c.datum_type() == TDim::datum_type() &&
-                           a.datum_type() == TDim::datum_type() && b.datum_type() == TDim::datum_type() {
-                               let a = a.to_array_view::<TDim>()?;
-                               let b = b.cast_to::<i32>()?;
-                               let b = b.to_array_view::<i32>()?;
-                               let c = c.to_array_view_mut::<TDim>()?;
-                               crate::ndarray::Zip::from(c).and_broadcast(a).and_broadcast(b).apply(|c,a,b| *c = a.clone() / *b);
-                               Ok(true)
-                           } else {
-                               Ok(false)
-                           }
-                   },
-                   [f32, i8, i16, i32, i64, u8, u16, f16, f64] => |c, a, b| *c = a.clone() / b
-                  );
+/// Division.
+///
+/// Integer division by a zero divisor is an error, unless `int_zero_divisor`
+/// is set: the output is then this value instead. Float division follows
+/// IEEE 754, giving infinities and NaNs.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct Div {
+    pub int_zero_divisor: Option<i64>,
+}
+tract_linalg::impl_dyn_hash!(Div);
+
+impl Div {
+    fn zero_divisor_value<T: Datum + num_traits::PrimInt>(&self, b: &Tensor) -> TractResult<T> {
+        if let Some(v) = self.int_zero_divisor {
+            return T::from(v)
+                .ok_or_else(|| format!("{} does not fit in {:?}", v, T::datum_type()).into());
+        }
+        if b.as_slice::<T>()?.iter().any(|b| b.is_zero()) {
+            bail!("Integer division by zero")
+        }
+        Ok(T::zero())
+    }
+
+    fn overflow<T: Datum>(a: T, b: T) -> TractError {
+        format!("Integer overflow dividing {:?} by {:?}", a, b).into()
+    }
+
+    fn eval_in_place_int<T: Datum + num_traits::PrimInt>(
+        &self,
+        a: &Tensor,
+        b: &mut Tensor,
+    ) -> TractResult<()> {
+        let on_zero = self.zero_divisor_value::<T>(b)?;
+        let a = a.as_slice::<T>()?;
+        for (a, b) in a.iter().zip(b.as_slice_mut::<T>()?.iter_mut()) {
+            *b = if b.is_zero() {
+                on_zero
+            } else {
+                a.checked_div(b).ok_or_else(|| Self::overflow(*a, *b))?
+            };
+        }
+        Ok(())
+    }
+
+    fn eval_out_of_place_int<T: Datum + num_traits::PrimInt>(
+        &self,
+        c: &mut Tensor,
+        a: &Tensor,
+        b: &Tensor,
+    ) -> TractResult<()> {
+        let on_zero = self.zero_divisor_value::<T>(b)?;
+        let a = a.to_array_view::<T>()?;
+        let b = b.to_array_view::<T>()?;
+        let c = c.to_array_view_mut::<T>()?;
+        let mut overflow = None;
+        crate::ndarray::Zip::from(c).and_broadcast(a).and_broadcast(b).apply(|c, a, b| {
+            *c = if b.is_zero() {
+                on_zero
+            } else if let Some(q) = a.checked_div(b) {
+                q
+            } else {
+                overflow = Some((*a, *b));
+                T::zero()
+            }
+        });
+        if let Some((a, b)) = overflow {
+            return Err(Self::overflow(a, b));
+        }
+        Ok(())
+    }
+
+    fn eval_in_place_float<T: Datum + Copy + std::ops::Div<Output = T>>(
+        a: &Tensor,
+        b: &mut Tensor,
+    ) -> TractResult<()> {
+        let a = a.as_slice::<T>()?;
+        for (a, b) in a.iter().zip(b.as_slice_mut::<T>()?.iter_mut()) {
+            *b = *a / *b;
+        }
+        Ok(())
+    }
+
+    fn eval_out_of_place_float<T: Datum + Copy + std::ops::Div<Output = T>>(
+        c: &mut Tensor,
+        a: &Tensor,
+        b: &Tensor,
+    ) -> TractResult<()> {
+        let a = a.to_array_view::<T>()?;
+        let b = b.to_array_view::<T>()?;
+        let c = c.to_array_view_mut::<T>()?;
+        crate::ndarray::Zip::from(c)
+            .and_broadcast(a)
+            .and_broadcast(b)
+            .apply(|c, a, b| *c = *a / *b);
+        Ok(())
+    }
+}
+
+impl BinMiniOp for Div {
+    fn name(&self) -> &'static str {
+        "Div"
+    }
+
+    fn eval_in_place(&self, a: &Tensor, b: &mut Tensor) -> TractResult<()> {
+        match a.datum_type() {
+            DatumType::F16 => Self::eval_in_place_float::<f16>(a, b),
+            DatumType::F32 => Self::eval_in_place_float::<f32>(a, b),
+            DatumType::F64 => Self::eval_in_place_float::<f64>(a, b),
+            DatumType::U8 => self.eval_in_place_int::<u8>(a, b),
+            DatumType::U16 => self.eval_in_place_int::<u16>(a, b),
+            DatumType::I8 => self.eval_in_place_int::<i8>(a, b),
+            DatumType::I16 => self.eval_in_place_int::<i16>(a, b),
+            DatumType::I32 => self.eval_in_place_int::<i32>(a, b),
+            DatumType::I64 => self.eval_in_place_int::<i64>(a, b),
+            dt => bail!("{} does not support {:?} (inplace)", self.name(), dt),
+        }
+    }
+
+    fn eval_out_of_place(&self, c: &mut Tensor, a: &Tensor, b: &Tensor) -> TractResult<()> {
+        match c.datum_type() {
+            DatumType::TDim
+                if a.datum_type() == TDim::datum_type() && b.datum_type() == TDim::datum_type() =>
+            {
+                let a = a.to_array_view::<TDim>()?;
+                let b = b.cast_to::<i32>()?;
+                let b = b.to_array_view::<i32>()?;
+                let c = c.to_array_view_mut::<TDim>()?;
+                crate::ndarray::Zip::from(c)
+                    .and_broadcast(a)
+                    .and_broadcast(b)
+                    .apply(|c, a, b| *c = a.clone() / *b);
+                Ok(())
+            }
+            DatumType::F16 => Self::eval_out_of_place_float::<f16>(c, a, b),
+            DatumType::F32 => Self::eval_out_of_place_float::<f32>(c, a, b),
+            DatumType::F64 => Self::eval_out_of_place_float::<f64>(c, a, b),
+            DatumType::U8 => self.eval_out_of_place_int::<u8>(c, a, b),
+            DatumType::U16 => self.eval_out_of_place_int::<u16>(c, a, b),
+            DatumType::I8 => self.eval_out_of_place_int::<i8>(c, a, b),
+            DatumType::I16 => self.eval_out_of_place_int::<i16>(c, a, b),
+            DatumType::I32 => self.eval_out_of_place_int::<i32>(c, a, b),
+            DatumType::I64 => self.eval_out_of_place_int::<i64>(c, a, b),
+            dt => bail!("{} does not support {:?} (out of place)", self.name(), dt),
+        }
+    }
+
+    fn operating_datum_type(&self, a: DatumType, b: DatumType) -> TractResult<DatumType> {
+        a.common_super_type(b)
+            .ok_or_else(|| format!("No super type for {:?} and {:?}", a, b).into())
+    }
+
+    fn result_datum_type(&self, a: DatumType, b: DatumType) -> TractResult<DatumType> {
+        self.operating_datum_type(a, b)
+    }
+
+    fn unary_with_b_const(&self, b: &Arc<Tensor>) -> Option<UnaryOp> {
+        flip_div(self, b)
+    }
+
+    fn declutter_bin(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        declutter_bin_div(self, model, node)
+    }
+
+    fn cost_per_element(&self, dt: DatumType) -> TVec<(Cost, usize)> {
+        tvec!((Cost::Div(dt), 1))
+    }
+}
+
+pub mod div {
+    pub fn bin() -> crate::ops::binary::InferenceBinOp {
+        crate::ops::binary::InferenceBinOp(Box::new(super::Div::default()))
+    }
+    pub fn bin_typed() -> crate::ops::binary::TypedBinOp {
+        crate::ops::binary::TypedBinOp(Box::new(super::Div::default()))
+    }
+    pub fn unary(t: std::sync::Arc<crate::prelude::Tensor>) -> crate::ops::binary::UnaryOp {
+        crate::ops::binary::UnaryOp::new(Box::new(super::Div::default()), t)
+    }
+}
 
 bin_to_super_type!(rem, Rem,
                    out_of_place: |c:&mut Tensor, a:&Tensor, b: &Tensor| -> TractResult<bool> {
@@ -65,11 +233,34 @@ bin_to_super_type!(rem, Rem,
                            }
                    },
                    [f32, i8, i16, i32, i64, u8, u16, f16, f64] => |c, a, b| *c = a.clone() % b);
+// NaN policy: min and max operators (Min, Max, ScalarMin, ScalarMax,
+// ScalarMinMax and the Min and Max reducers) propagate NaN, like numpy
+// `minimum`/`maximum`/`min`/`max` and ONNX. `f32::min` and `f32::max` would
+// ignore them instead.
+
+/// Minimum of two floats, NaN if either of them is NaN.
+pub fn propagating_min<T: Float>(a: T, b: T) -> T {
+    if a.is_nan() || b.is_nan() {
+        T::nan()
+    } else {
+        a.min(b)
+    }
+}
+
+/// Maximum of two floats, NaN if either of them is NaN.
+pub fn propagating_max<T: Float>(a: T, b: T) -> T {
+    if a.is_nan() || b.is_nan() {
+        T::nan()
+    } else {
+        a.max(b)
+    }
+}
+
 bin_to_super_type!(min, Min, flip:commute,
-                   [f32, f64] => |c,a,b| *c = a.min(*b),
+                   [f32, f64] => |c,a,b| *c = propagating_min(*a, *b),
                    [i8, i16, i32, i64, u8, u16] => |c, a, b| *c = *a.min(b));
 bin_to_super_type!(max, Max, flip:commute,
-                   [f32, f64] => |c,a,b| *c = a.max(*b),
+                   [f32, f64] => |c,a,b| *c = propagating_max(*a, *b),
                    [i8, i16, i32, i64, u8, u16] => |c, a, b| *c = *a.max(b));
 bin_to_super_type!(pow, Pow,
                    [f32, f64] => |c,a,b| *c = a.powf(*b));
@@ -97,6 +288,10 @@ fn flip_sub(_op: &dyn BinMiniOp, t: &Arc<Tensor>) -> Option<UnaryOp> {
 }
 
 fn flip_div(_op: &dyn BinMiniOp, t: &Arc<Tensor>) -> Option<UnaryOp> {
+    // integer division does not turn into a product
+    if ![f16::datum_type(), f32::datum_type(), f64::datum_type()].contains(&t.datum_type()) {
+        return None;
+    }
     let mut t = t.clone().into_tensor();
     fn inverse<T: Datum + num_traits::Float>(t: &mut Tensor) {
         t.as_slice_mut::<T>().unwrap().iter_mut().for_each(|p| *p = p.recip());
@@ -109,6 +304,36 @@ fn flip_div(_op: &dyn BinMiniOp, t: &Arc<Tensor>) -> Option<UnaryOp> {
     Some(UnaryOp::new(Box::new(Mul), Arc::new(t)))
 }
 
+fn declutter_bin_mul(
+    _op: &Mul,
+    model: &TypedModel,
+    node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+    use crate::ops::element_wise::ElementWiseOp;
+    use crate::ops::nn::{HardSigmoid, Sigmoid};
+    // x * sigmoid(x) is swish(x), x * hard_sigmoid(x) is hard_swish(x)
+    for (sig_ix, x_ix) in &[(0, 1), (1, 0)] {
+        let sigmoid = model.node(node.inputs[*sig_ix].node);
+        if sigmoid.inputs.len() != 1 || sigmoid.inputs[0] != node.inputs[*x_ix] {
+            continue;
+        }
+        let mini_op = if let Some(op) = sigmoid.op_as::<ElementWiseOp>() { &op.0 } else { continue };
+        let swish = if mini_op.is::<Sigmoid>() {
+            crate::ops::nn::swish()
+        } else if let Some(hs) = mini_op.downcast_ref::<HardSigmoid>() {
+            crate::ops::nn::hard_swish(hs.alpha, hs.beta)
+        } else {
+            continue;
+        };
+        let mut patch = TypedModelPatch::default();
+        let x = patch.tap_model(model, node.inputs[*x_ix])?;
+        let out = patch.wire_node(&node.name, swish, &[x])?[0];
+        patch.shunt_outside(model, node.id.into(), out)?;
+        return Ok(Some(patch))
+    }
+    Ok(None)
+}
+
 fn declutter_unary_mul(
     _op: &Mul,
     model: &TypedModel,
@@ -129,9 +354,7 @@ fn declutter_unary_mul_magic_values(
     node: &TypedNode,
     a: &Arc<Tensor>,
     ) -> TractResult<Option<TypedModelPatch>> {
-    if a.cast_to::<f64>()?.as_slice::<f64>()?.iter().all(|v| *v == 1.0) {
-        return Ok(Some(TypedModelPatch::shunt_one_op(model, node)?))
-    } else if a.cast_to::<f64>()?.as_slice::<f64>()?.iter().all(|v| *v == 0.0) {
+    if a.cast_to::<f64>()?.as_slice::<f64>()?.iter().all(|v| *v == 0.0) {
         let fact = model.outlet_fact(node.inputs[0])?;
         if let Some(shape) = fact.shape.as_finite() {
             let zeros = Tensor::zero_dt(fact.datum_type, shape)?;
@@ -146,6 +369,9 @@ fn declutter_bin_div(
     model: &TypedModel,
     node: &TypedNode,
     ) -> TractResult<Option<TypedModelPatch>> {
+    if let Some(p) = declutter_div_by_one(model, node)? {
+        return Ok(Some(p))
+    }
     if let Some(p) = declutter_div_as_shift(model, node)? {
         return Ok(Some(p))
     }
@@ -162,6 +388,25 @@ fn declutter_bin_div(
     Ok(None)
 }
 
+fn declutter_div_by_one(
+    model: &TypedModel,
+    node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+    let input = model.outlet_fact(node.inputs[0])?;
+    let output = &node.outputs[0].fact;
+    if input.shape != output.shape || input.datum_type != output.datum_type {
+        return Ok(None)
+    }
+    if let Some(b) = &model.outlet_fact(node.inputs[1])?.konst {
+        if let Ok(b) = b.cast_to::<f64>() {
+            if b.as_slice::<f64>()?.iter().all(|v| *v == 1.0) {
+                return Ok(Some(TypedModelPatch::shunt_one_op(model, node)?))
+            }
+        }
+    }
+    Ok(None)
+}
+
 fn declutter_div_as_shift(
     model: &TypedModel,
     node: &TypedNode,
@@ -266,11 +511,29 @@ element_wise!(round, Round, [f16, f32, f64] => |_, xs| {
     Ok(())
 });
 
+element_wise!(scalar_add, ScalarAdd { a: Tensor },
+              [f16, f32, f64, i8, i16, i32, i64, u8, u16] =>
+                  |m, xs| scalar_op_t(&m.a, xs, |a, x| a + x);
+              cost: |dt| tvec!((Cost::FMA(dt), 1))
+             );
+
+element_wise!(scalar_mul, ScalarMul { a: Tensor },
+              [f16, f32, f64, i8, i16, i32, i64, u8, u16] =>
+                  |m, xs| scalar_op_t(&m.a, xs, |a, x| a * x);
+              cost: |dt| tvec!((Cost::FMA(dt), 1))
+             );
+
+fn scalar_op_t<T: Datum + Copy>(a: &Tensor, xs: &mut [T], f: impl Fn(T, T) -> T) -> TractResult<()> {
+    let a = *a.to_scalar::<T>()?;
+    xs.iter_mut().for_each(|x| *x = f(a, *x));
+    Ok(())
+}
+
 element_wise!(scalar_min_max, ScalarMinMax { min: Tensor, max: Tensor },
               [f32, f64] => |m, xs| {
                   let max = m.max.cast_to_scalar()?;
                   let min = m.min.cast_to_scalar()?;
-                  xs.iter_mut().for_each(|x| { *x = x.max(max).min(min) });
+                  xs.iter_mut().for_each(|x| *x = propagating_min(propagating_max(*x, max), min));
                   Ok(())
               },
               [i8, u8] => |m, xs| {
@@ -308,7 +571,7 @@ fn quantize_scalar_min_max(
 element_wise!(scalar_min, ScalarMin { min: Tensor },
               [f32, f64] => |m, xs| {
                   let min = m.min.cast_to_scalar()?;
-                  xs.iter_mut().for_each(|x| *x = x.min(min));
+                  xs.iter_mut().for_each(|x| *x = propagating_min(*x, min));
                   Ok(())
               },
               [i8, u8] => |m, xs| {
@@ -338,7 +601,7 @@ fn quantize_scalar_min(
 element_wise!(scalar_max, ScalarMax { max: Tensor },
               [f32, f64] => |m, xs| {
                   let max = m.max.cast_to_scalar()?;
-                  xs.iter_mut().for_each(|x| *x = x.max(max));
+                  xs.iter_mut().for_each(|x| *x = propagating_max(*x, max));
                   Ok(())
               },
               [i8, u8] => |m, xs| {
@@ -444,6 +707,100 @@ mod tests {
         assert_eq!(a.dot(&b), arr2(&[[1., 0.], [3., 0.]]));
     }
 
+    #[test]
+    fn add_scalar_uses_scalar_op() -> TractResult<()> {
+        use crate::ops::element_wise::ElementWiseOp;
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [2, 3].as_ref())?)?;
+        let k = model.add_const("k", tensor0(0.5f32))?;
+        let add = model.wire_node("add", add::bin_typed(), &[x, k])?;
+        model.set_output_outlets(&add)?;
+        let input = tensor2(&[[0f32, 1., 2.], [3., 4., 5.]]);
+        let expected = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+        let optimized = model.into_optimized()?;
+        let scalar = optimized.node(optimized.output_outlets()?[0].node);
+        assert!(scalar.op_as::<ElementWiseOp>().unwrap().0.is::<ScalarAdd>());
+        let found = SimplePlan::new(&optimized)?.run(tvec!(input))?;
+        assert_eq!(found, expected);
+        assert_eq!(*found[0], tensor2(&[[0.5f32, 1.5, 2.5], [3.5, 4.5, 5.5]]));
+        Ok(())
+    }
+
+    #[test]
+    fn add_tdim_scalar_after_codegen() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(TDim::datum_type(), [2].as_ref())?)?;
+        let add = model.wire_node("add", add::unary(rctensor0(TDim::from(1))), &[x])?;
+        model.set_output_outlets(&add)?;
+        let model = model.codegen()?;
+        assert!(model.node(model.output_outlets()?[0].node).op_is::<UnaryOp>());
+        let input = tensor1(&[TDim::from(2), TDim::from(3)]);
+        let found = SimplePlan::new(&model)?.run(tvec!(input))?;
+        assert_eq!(*found[0], tensor1(&[TDim::from(3), TDim::from(4)]));
+        Ok(())
+    }
+
+    #[test]
+    fn add_f16_scalar_after_optimization() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f16::datum_type(), [3].as_ref())?)?;
+        let k = model.add_const("k", tensor0(0.5f32).cast_to::<f16>()?.into_owned())?;
+        let add = model.wire_node("add", add::bin_typed(), &[x, k])?;
+        model.set_output_outlets(&add)?;
+        let optimized = model.into_optimized()?;
+        let input = tensor1(&[0f32, 1., 2.]).cast_to::<f16>()?.into_owned();
+        let found = SimplePlan::new(&optimized)?.run(tvec!(input))?;
+        assert_eq!(*found[0], tensor1(&[0.5f32, 1.5, 2.5]).cast_to::<f16>()?.into_owned());
+        Ok(())
+    }
+
+    #[test]
+    fn mul_scalar_fuses_in_matmul() -> TractResult<()> {
+        use crate::ops::element_wise::ElementWiseOp;
+        use crate::ops::matmul::MatMulUnary;
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [3, 2].as_ref())?)?;
+        let a = rctensor2(&[[1f32, 2., 3.], [4., 5., 6.]]);
+        let mm = model.wire_node("mm", MatMulUnary::new(a, false, false, false, None), &[x])?;
+        let k = model.add_const("k", tensor0(2f32))?;
+        let mul = model.wire_node("mul", mul::bin_typed(), &[mm[0], k])?;
+        model.set_output_outlets(&mul)?;
+        let input = tensor2(&[[1f32, 0.], [0., 1.], [1., 1.]]);
+        let expected = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+        let optimized = model.into_optimized()?;
+        assert!(!optimized.nodes().iter().any(|n| n.op_is::<ElementWiseOp>()));
+        let found = SimplePlan::new(&optimized)?.run(tvec!(input))?;
+        assert_eq!(found, expected);
+        Ok(())
+    }
+
+    fn assert_nan_then(found: &Tensor, rest: &[f32]) -> TractResult<()> {
+        let found = found.as_slice::<f32>()?;
+        assert!(found[0].is_nan());
+        assert_eq!(&found[1..], rest);
+        Ok(())
+    }
+
+    #[test]
+    fn scalar_max_min_propagate_nan() -> TractResult<()> {
+        let x = rctensor1(&[std::f32::NAN, -1., 1.]);
+        assert_nan_then(&scalar_max(tensor0(0f32)).eval(tvec!(x.clone()))?[0], &[0., 1.])?;
+        assert_nan_then(&scalar_min(tensor0(0f32)).eval(tvec!(x))?[0], &[-1., 0.])
+    }
+
+    #[test]
+    fn max_min_propagate_nan() -> TractResult<()> {
+        let a = rctensor1(&[1f32, std::f32::NAN, 1.]);
+        let b = rctensor1(&[std::f32::NAN, 2., 2.]);
+        for op in &[max::bin_typed(), min::bin_typed()] {
+            let found = op.eval(tvec!(a.clone(), b.clone()))?.remove(0);
+            let found = found.as_slice::<f32>()?;
+            assert!(found[0].is_nan() && found[1].is_nan());
+            assert!(!found[2].is_nan());
+        }
+        Ok(())
+    }
+
     #[test]
     fn mul_as_shift() -> TractResult<()> {
         let mut model = TypedModel::default();
@@ -461,6 +818,89 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn int_div_by_zero_gives_configured_value() -> TractResult<()> {
+        let op = TypedBinOp(Box::new(Div { int_zero_divisor: Some(0) }));
+        let result = op.eval(tvec!(rctensor1(&[6i32, 7, 8]), rctensor1(&[2i32, 0, 4])))?;
+        assert_eq!(result[0], rctensor1(&[3i32, 0, 2]));
+        let op = TypedBinOp(Box::new(Div { int_zero_divisor: Some(-1) }));
+        let result = op.eval(tvec!(rctensor1(&[6i32, 7, 8]), rctensor1(&[0i32])))?;
+        assert_eq!(result[0], rctensor1(&[-1i32, -1, -1]));
+        assert!(div::bin_typed().eval(tvec!(rctensor1(&[6i32]), rctensor1(&[0i32]))).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn int_div_overflow_is_an_error() {
+        let op = div::bin_typed();
+        let min = rctensor1(&[std::i32::MIN, 6]);
+        assert!(op.eval(tvec!(min.clone(), rctensor1(&[-1i32, 2]))).is_err());
+        assert!(op.eval(tvec!(min, rctensor1(&[-1i32]))).is_err());
+    }
+
+    #[test]
+    fn int_div_by_const_survives_declutter() -> TractResult<()> {
+        let cases = [(rctensor1(&[2i32, 0, 4]), [3i32, 0, 2]), (rctensor0(3i32), [2, 2, 2])];
+        for (divisor, expected) in &cases {
+            let mut model = TypedModel::default();
+            let x = model.add_source("x", TypedFact::dt_shape(i32::datum_type(), [3].as_ref())?)?;
+            let d = model.add_const("d", divisor.clone())?;
+            let op = TypedBinOp(Box::new(Div { int_zero_divisor: Some(0) }));
+            let y = model.wire_node("y", op, &[x, d])?;
+            model.set_output_outlets(&y)?;
+            let model = model.declutter()?;
+            let result = SimplePlan::new(&model)?.run(tvec!(tensor1(&[6i32, 7, 8])))?;
+            assert_eq!(result[0], rctensor1(expected));
+        }
+        Ok(())
+    }
+
+    fn declutter_single_op(
+        shape: &[usize],
+        op: impl Into<Box<dyn TypedOp>>,
+    ) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), shape)?)?;
+        let y = model.wire_node("y", op, &[x])?;
+        model.set_output_outlets(&y)?;
+        model.declutter()
+    }
+
+    #[test]
+    fn add_zero_is_removed() -> TractResult<()> {
+        let model = declutter_single_op(&[2, 2], add::unary(rctensor2(&[[0f32, 0.]])))?;
+        assert_eq!(model.nodes().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn mul_one_is_removed() -> TractResult<()> {
+        let model = declutter_single_op(&[2, 2], mul::unary(rctensor2(&[[1f32]])))?;
+        assert_eq!(model.nodes().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn div_one_is_removed() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(i32::datum_type(), [2usize].as_ref())?)?;
+        let one = model.add_const("one", tensor1(&[1i32]))?;
+        let y = model.wire_node("y", div::bin_typed(), &[x, one])?;
+        model.set_output_outlets(&y)?;
+        let model = model.declutter()?;
+        assert_eq!(model.nodes().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn broadcasting_mul_one_is_kept() -> TractResult<()> {
+        let model = declutter_single_op(&[1, 2], mul::unary(rctensor2(&[[1f32, 1.], [1., 1.]])))?;
+        assert_eq!(model.nodes().len(), 2);
+        let output = SimplePlan::new(&model)?.run(tvec!(tensor2(&[[3f32, 4.]])))?;
+        assert_eq!(*output[0], tensor2(&[[3f32, 4.], [3., 4.]]));
+        Ok(())
+    }
+
     #[test]
     fn div_as_shift() -> TractResult<()> {
         let mut model = TypedModel::default();
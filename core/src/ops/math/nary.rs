@@ -0,0 +1,243 @@
+use crate::internal::*;
+use num_traits::AsPrimitive;
+use std::ops::{AddAssign, DivAssign};
+
+fn output_fact(inputs: &[&TypedFact]) -> TractResult<TypedFact> {
+    let dt = inputs[0].datum_type;
+    if inputs.iter().any(|i| i.datum_type != dt) {
+        bail!("Inconsistent datum types: {:?}", inputs);
+    }
+    let shapes = inputs.iter().map(|i| i.shape.to_tvec()).collect::<Vec<_>>();
+    let shape = crate::broadcast::multi_broadcast(&shapes)
+        .ok_or_else(|| format!("Can not broadcast shapes {:?}", inputs))?;
+    TypedFact::dt_shape(dt, &*shape)
+}
+
+/// Rewrite a two-input Sum or Mean as a binary addition, which the optimizer
+/// and the pulsifier know about, and a division for Mean. Sums of more inputs
+/// stay n-ary.
+fn declutter_as_binary(
+    model: &TypedModel,
+    node: &TypedNode,
+    mean: bool,
+) -> TractResult<Option<TypedModelPatch>> {
+    if node.inputs.len() != 2 {
+        return Ok(None);
+    }
+    let facts = model.node_input_facts(node.id)?;
+    let dt = facts[0].datum_type;
+    let rank = facts.iter().map(|f| f.rank()).max().unwrap();
+    let mut patch = TypedModelPatch::default();
+    let mut inputs = tvec!();
+    for (ix, &input) in node.inputs.iter().enumerate() {
+        let mut wire = patch.tap_model(model, input)?;
+        for axis in facts[ix].rank()..rank {
+            wire = patch.wire_node(
+                format!("{}-input-{}-axis-{}", node.name, ix, axis),
+                AxisOp::Add(0),
+                &[wire],
+            )?[0];
+        }
+        inputs.push(wire);
+    }
+    let name = if mean { format!("{}-add", node.name) } else { node.name.clone() };
+    let mut wire = patch.wire_node(name, super::add::bin_typed(), &inputs)?[0];
+    if mean {
+        let n = tensor0(inputs.len() as i64).cast_to_dt(dt)?.into_owned();
+        let n = unsafe { n.into_shape(&*vec![1; rank])? };
+        let n = patch.add_const(format!("{}-count", node.name), n)?;
+        wire = patch.wire_node(&*node.name, super::div::bin_typed(), &[wire, n])?[0];
+    }
+    patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
+    Ok(Some(patch))
+}
+
+fn sum_t<T>(inputs: &[Arc<Tensor>], shape: &[usize], mean: bool) -> TractResult<Tensor>
+where
+    T: Datum + AddAssign + DivAssign + Copy,
+    usize: AsPrimitive<T>,
+{
+    let mut acc = inputs[0].broadcast_to(shape)?;
+    {
+        let mut view = acc.to_array_view_mut::<T>()?;
+        for input in &inputs[1..] {
+            view += &input.to_array_view::<T>()?;
+        }
+        if mean {
+            let n: T = inputs.len().as_();
+            view /= &ndarray::arr0(n);
+        }
+    }
+    Ok(acc)
+}
+
+fn eval(inputs: TVec<Arc<Tensor>>, mean: bool) -> TractResult<TVec<Arc<Tensor>>> {
+    let dt = inputs[0].datum_type();
+    if inputs.iter().any(|i| i.datum_type() != dt) {
+        bail!("Inconsistent datum types: {:?}", inputs);
+    }
+    let shapes = inputs.iter().map(|i| i.shape()).collect::<Vec<_>>();
+    let shape = crate::broadcast::multi_broadcast(&shapes)
+        .ok_or_else(|| format!("Can not broadcast shapes {:?}", shapes))?;
+    let output = dispatch_numbers!(sum_t(dt)(&inputs, &shape, mean))?;
+    Ok(tvec!(output.into_arc_tensor()))
+}
+
+/// Element-wise sum of any number of inputs, with broadcasting.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct Sum;
+tract_linalg::impl_dyn_hash!(Sum);
+
+impl Op for Sum {
+    fn name(&self) -> Cow<str> {
+        "Sum".into()
+    }
+
+    fn validation(&self) -> Validation {
+        Validation::Rounding
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+    not_a_pulsed_op!();
+}
+
+impl StatelessOp for Sum {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        eval(inputs, false)
+    }
+}
+
+impl TypedOp for Sum {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(output_fact(inputs)?))
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        declutter_as_binary(model, node, false)
+    }
+
+    as_op!();
+}
+
+/// Element-wise mean of any number of inputs, with broadcasting.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct Mean;
+tract_linalg::impl_dyn_hash!(Mean);
+
+impl Op for Mean {
+    fn name(&self) -> Cow<str> {
+        "Mean".into()
+    }
+
+    fn validation(&self) -> Validation {
+        Validation::Rounding
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+    not_a_pulsed_op!();
+}
+
+impl StatelessOp for Mean {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        eval(inputs, true)
+    }
+}
+
+impl TypedOp for Mean {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(output_fact(inputs)?))
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        declutter_as_binary(model, node, true)
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sum_three() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [2, 3].as_ref())?;
+        let a = model.add_source("a", fact.clone())?;
+        let b = model.add_source("b", fact.clone())?;
+        let c = model.add_source("c", fact)?;
+        let sum = model.wire_node("sum", Sum, &[a, b, c])?;
+        model.set_output_outlets(&sum)?;
+        let model = model.declutter()?;
+        assert_eq!(model.nodes().len(), 4);
+        assert!(model.node(model.output_outlets()?[0].node).op_is::<Sum>());
+        let output = SimplePlan::new(&model)?.run(tvec!(
+            tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]),
+            tensor2(&[[10f32, 20., 30.], [40., 50., 60.]]),
+            tensor2(&[[100f32, 200., 300.], [400., 500., 600.]]),
+        ))?;
+        assert_eq!(*output[0], tensor2(&[[111f32, 222., 333.], [444., 555., 666.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn pulse_decluttered_sum() -> TractResult<()> {
+        use crate::pulse::PulsedModel;
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [TDim::s(), 2.to_dim()].as_ref())?;
+        let a = model.add_source("a", fact.clone())?;
+        let b = model.add_source("b", fact)?;
+        let sum = model.wire_node("sum", Sum, &[a, b])?;
+        model.set_output_outlets(&sum)?;
+        let model = model.declutter()?;
+        assert!(model.nodes().iter().all(|n| !n.op_is::<Sum>()));
+        let pulsed = PulsedModel::new(&model.into_normalized()?, 4)?;
+        let output = SimplePlan::new(pulsed.into_typed()?)?.run(tvec!(
+            tensor2(&[[1f32, 2.], [3., 4.], [5., 6.], [7., 8.]]),
+            tensor2(&[[10f32, 20.], [30., 40.], [50., 60.], [70., 80.]]),
+        ))?;
+        assert_eq!(*output[0], tensor2(&[[11f32, 22.], [33., 44.], [55., 66.], [77., 88.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn decluttered_mean_broadcast() -> TractResult<()> {
+        for dt in &[f32::datum_type(), i32::datum_type()] {
+            let mut model = TypedModel::default();
+            let a = model.add_source("a", TypedFact::dt_shape(*dt, [2, 3].as_ref())?)?;
+            let b = model.add_source("b", TypedFact::dt_shape(*dt, [3].as_ref())?)?;
+            let mean = model.wire_node("mean", Mean, &[a, b])?;
+            model.set_output_outlets(&mean)?;
+            let model = model.declutter()?;
+            assert!(model.nodes().iter().all(|n| !n.op_is::<Mean>()));
+            let output = SimplePlan::new(&model)?.run(tvec!(
+                tensor2(&[[1f32, 2., 3.], [5., 6., 7.]]).cast_to_dt(*dt)?.into_owned(),
+                tensor1(&[3f32, 4., 5.]).cast_to_dt(*dt)?.into_owned(),
+            ))?;
+            let expected = tensor2(&[[2f32, 3., 4.], [4., 5., 6.]]);
+            assert_eq!(*output[0], expected.cast_to_dt(*dt)?.into_owned());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn mean_broadcast() -> TractResult<()> {
+        let output = Mean.eval(tvec!(
+            rctensor2(&[[1f32, 2., 3.], [4., 5., 6.]]),
+            rctensor1(&[2f32, 4., 6.]),
+            rctensor0(3f32),
+        ))?;
+        assert_eq!(*output[0], tensor2(&[[2f32, 3., 4.], [3., 4., 5.]]));
+        Ok(())
+    }
+}
@@ -144,7 +144,13 @@ where
         )];
         infos.push(format!("Mult: {}", self.mmm));
         if let Some(f) = &self.fused_ops {
-            infos.push(format!("{:?}", f));
+            if let Some(chain) = f.iter().next() {
+                if f.len() > 1 {
+                    infos.push(format!("Fused: {:?} (x{})", chain, f.len()));
+                } else {
+                    infos.push(format!("Fused: {:?}", chain));
+                }
+            }
         }
         Ok(infos)
     }
@@ -230,6 +236,15 @@ where
         Ok(tvec!((Cost::FMA(TI::datum_type()), (mul * mmm.m() * mmm.n() * mmm.k()).to_dim())))
     }
 
+    fn fused_ops(&self) -> Vec<&dyn fmt::Debug> {
+        // all tiles fuse the same chain, only their per-row/col values differ
+        self.fused_ops
+            .as_ref()
+            .and_then(|f| f.iter().next())
+            .map(|chain| chain.iter().map(|spec| spec as &dyn fmt::Debug).collect())
+            .unwrap_or(vec![])
+    }
+
     fn fuse(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Option<TypedModelPatch>> {
         use crate::ops;
         if let Some(succ) = model.single_succ(node.id)? {
@@ -269,6 +284,12 @@ where
                             FusedSpec::Min(op.min.cast_to_scalar()?),
                             FusedSpec::Max(op.max.cast_to_scalar()?),
                         )));
+                    } else if TC::datum_type() == TI::datum_type() {
+                        if let Some(op) = op.0.downcast_ref::<ops::math::ScalarAdd>() {
+                            return Ok(Some(tvec!(FusedSpec::ScalarAdd(*op.a.to_scalar()?))));
+                        } else if let Some(op) = op.0.downcast_ref::<ops::math::ScalarMul>() {
+                            return Ok(Some(tvec!(FusedSpec::ScalarMul(*op.a.to_scalar()?))));
+                        }
                     }
                 }
                 Ok(None)
@@ -9,6 +9,65 @@ use ndarray::*;
 
 use itertools::Itertools;
 
+/// Quantized multiplier accumulating in i64 with the generic kernel, for the
+/// very large K that would overflow an i32 accumulator.
+macro_rules! qmmm_i64 {
+    ($ta: ty, $tb: ty, $tc: ty) => {
+        &|m, k, n| {
+            use tract_linalg::generic::GenericMmm4x4;
+            use tract_linalg::mmm::{MatMatMulImpl, QMatMatMulImpl};
+            MMMWrapper::Quant(Box::new(QMatMatMulImpl::from(MatMatMulImpl::<
+                GenericMmm4x4<$ta, $tb, $tc, i64>,
+                $ta,
+                $tb,
+                $tc,
+                i64,
+            >::new(m, k, n))))
+        }
+    };
+}
+
+/// Check the accumulator type asked for by `q_params`: true for i64, false
+/// for the default i32.
+fn accumulates_in_i64(q_params: Option<&QParams>) -> TractResult<bool> {
+    match q_params.map(|q| q.accumulator_datum_type) {
+        None | Some(DatumType::I32) => Ok(false),
+        Some(DatumType::I64) => Ok(true),
+        Some(dt) => bail!("Unsupported accumulator type for MatMul: {:?}", dt),
+    }
+}
+
+fn eval_i64(
+    a: &Tensor,
+    b: &Tensor,
+    a_trans: bool,
+    b_trans: bool,
+    c_trans: bool,
+    q: &QParams,
+) -> TractResult<Tensor> {
+    macro_rules! eval_i64 {
+        ($ta: ty, $tb: ty, $tc: ty) => {
+            eval_t(a, b, a_trans, b_trans, c_trans, Some(q), qmmm_i64!($ta, $tb, $tc))
+        };
+    }
+    match (a.datum_type(), b.datum_type(), q.c_datum_type) {
+        (DatumType::U8, DatumType::U8, DatumType::U8) => eval_i64!(u8, u8, u8),
+        (DatumType::U8, DatumType::U8, DatumType::I32) => eval_i64!(u8, u8, i32),
+        (DatumType::U8, DatumType::U8, DatumType::I64) => eval_i64!(u8, u8, i64),
+        (DatumType::I8, DatumType::I8, DatumType::I8) => eval_i64!(i8, i8, i8),
+        (DatumType::I8, DatumType::I8, DatumType::I32) => eval_i64!(i8, i8, i32),
+        (DatumType::I8, DatumType::I8, DatumType::I64) => eval_i64!(i8, i8, i64),
+        (DatumType::I16, DatumType::I16, DatumType::I32) => eval_i64!(i16, i16, i32),
+        (DatumType::I16, DatumType::I16, DatumType::I64) => eval_i64!(i16, i16, i64),
+        (a, b, c) => bail!(
+            "Unsupported combination for MatMul with i64 accumulator (a: {:?}, b: {:?}, c: {:?})",
+            a,
+            b,
+            c
+        ),
+    }
+}
+
 fn eval(
     a: &Tensor,
     b: &Tensor,
@@ -18,6 +77,9 @@ fn eval(
     q_params: Option<&QParams>,
 ) -> TractResult<Tensor> {
     if let Some(q) = q_params {
+        if accumulates_in_i64(q_params)? {
+            return eval_i64(a, b, a_trans, b_trans, c_trans, q);
+        }
         if (a.datum_type(), b.datum_type()) == (i8::datum_type(), i8::datum_type()) {
             if q.c_datum_type == i32::datum_type() {
                 return eval_t(a, b, a_trans, b_trans, c_trans, q_params, &|m, k, n| {
@@ -38,6 +100,12 @@ fn eval(
                     MMMWrapper::Quant((tract_linalg::ops().qmmm_u8_u8)(m, k, n))
                 });
             }
+        } else if (a.datum_type(), b.datum_type(), q.c_datum_type)
+            == (i16::datum_type(), i16::datum_type(), i32::datum_type())
+        {
+            return eval_t(a, b, a_trans, b_trans, c_trans, q_params, &|m, k, n| {
+                MMMWrapper::Quant((tract_linalg::ops().qmmm_i16_i32)(m, k, n))
+            });
         }
     } else if (a.datum_type(), b.datum_type()) == (f32::datum_type(), f32::datum_type()) {
         return eval_t(a, b, a_trans, b_trans, c_trans, q_params, &|m, k, n| {
@@ -81,7 +149,7 @@ where
     }
     let a = a.into_shape(&*geo.bc_a_shape)?;
     let b = b.into_shape(&*geo.bc_b_shape)?;
-    let mut c = unsafe { Array::<TC, IxDyn>::uninitialized(&*geo.bc_c_shape) };
+    let mut c: ArrayD<TC> = unsafe { crate::tensor::uninitialized_array(&*geo.bc_c_shape) };
 
     let b_pack = geo.mm.as_mmm().b_pack();
 
@@ -408,15 +476,58 @@ impl TypedOp for MatMul {
 
 #[derive(Debug, Clone, new, Hash)]
 pub struct MatMulUnary {
-    a: Arc<Tensor>,
-    a_trans: bool,
-    b_trans: bool,
-    c_trans: bool,
-    q_params: Option<QParams>,
+    pub a: Arc<Tensor>,
+    pub a_trans: bool,
+    pub b_trans: bool,
+    pub c_trans: bool,
+    pub q_params: Option<QParams>,
 }
 
 tract_linalg::impl_dyn_hash!(MatMulUnary);
 
+impl MatMulUnary {
+    fn codegen_i64(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        b_dt: DatumType,
+        b_shape: &[usize],
+    ) -> TractResult<TypedModelPatch> {
+        let q = self.q_params.as_ref().unwrap();
+        macro_rules! codegen_i64 {
+            ($ta: ty, $tb: ty, $tc: ty) => {
+                new_mat_mul_unary_finite(
+                    model,
+                    node,
+                    self.a.clone(),
+                    b_shape,
+                    self.a_trans,
+                    self.b_trans,
+                    self.c_trans,
+                    Some(q),
+                    qmmm_i64!($ta, $tb, $tc),
+                )
+            };
+        }
+        match (self.a.datum_type(), b_dt, q.c_datum_type) {
+            (DatumType::U8, DatumType::U8, DatumType::U8) => codegen_i64!(u8, u8, u8),
+            (DatumType::U8, DatumType::U8, DatumType::I32) => codegen_i64!(u8, u8, i32),
+            (DatumType::U8, DatumType::U8, DatumType::I64) => codegen_i64!(u8, u8, i64),
+            (DatumType::I8, DatumType::I8, DatumType::I8) => codegen_i64!(i8, i8, i8),
+            (DatumType::I8, DatumType::I8, DatumType::I32) => codegen_i64!(i8, i8, i32),
+            (DatumType::I8, DatumType::I8, DatumType::I64) => codegen_i64!(i8, i8, i64),
+            (DatumType::I16, DatumType::I16, DatumType::I32) => codegen_i64!(i16, i16, i32),
+            (DatumType::I16, DatumType::I16, DatumType::I64) => codegen_i64!(i16, i16, i64),
+            (a, b, c) => bail!(
+                "Unsupported MatMul codegen with i64 accumulator (a: {:?}, b: {:?}, c: {:?})",
+                a,
+                b,
+                c
+            ),
+        }
+    }
+}
+
 impl Op for MatMulUnary {
     fn name(&self) -> Cow<str> {
         "MatMul".into()
@@ -579,7 +690,7 @@ impl TypedOp for MatMulUnary {
                 let offsets = concat
                     .offsets(&model.node_input_facts(concat_node.id)?)?
                     .iter()
-                    .map(|x| x.to_integer().map(|i| i as usize))
+                    .map(|x| x.to_usize())
                     .collect::<TractResult<Vec<usize>>>()?;
                 let mut wires = vec![];
                 for (ix, slice) in concat.slices.iter().enumerate() {
@@ -676,9 +787,22 @@ impl TypedOp for MatMulUnary {
         &self,
         model: &TypedModel,
         node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        self.codegen_with_config(model, node, &CodegenConfig::default())
+    }
+
+    fn codegen_with_config(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        config: &CodegenConfig,
     ) -> TractResult<Option<TypedModelPatch>> {
         let b = args_1!(model.node_input_facts(node.id)?);
+        let ops = config.linalg_ops();
         if let Some(b_shape) = b.shape.as_finite() {
+            if accumulates_in_i64(self.q_params.as_ref())? {
+                return self.codegen_i64(model, node, b.datum_type, b_shape).map(Some);
+            }
             let patch =
                 if (self.a.datum_type(), b.datum_type) == (f32::datum_type(), f32::datum_type()) {
                     new_mat_mul_unary_finite(
@@ -690,7 +814,7 @@ impl TypedOp for MatMulUnary {
                         self.b_trans,
                         self.c_trans,
                         self.q_params.as_ref(),
-                        &|m, k, n| MMMWrapper::Plain((tract_linalg::ops().mmm_f32)(m, k, n)),
+                        &|m, k, n| MMMWrapper::Plain((ops.mmm_f32)(m, k, n)),
                     )?
                 } else if (
                     self.a.datum_type(),
@@ -707,7 +831,7 @@ impl TypedOp for MatMulUnary {
                         self.b_trans,
                         self.c_trans,
                         self.q_params.as_ref(),
-                        &|m, k, n| MMMWrapper::Quant((tract_linalg::ops().qmmm_i8_i8)(m, k, n)),
+                        &|m, k, n| MMMWrapper::Quant((ops.qmmm_i8_i8)(m, k, n)),
                     )?
                 } else if (
                     self.a.datum_type(),
@@ -724,7 +848,24 @@ impl TypedOp for MatMulUnary {
                         self.b_trans,
                         self.c_trans,
                         self.q_params.as_ref(),
-                        &|m, k, n| MMMWrapper::Quant((tract_linalg::ops().qmmm_i8_i32)(m, k, n)),
+                        &|m, k, n| MMMWrapper::Quant((ops.qmmm_i8_i32)(m, k, n)),
+                    )?
+                } else if (
+                    self.a.datum_type(),
+                    b.datum_type,
+                    self.q_params.as_ref().map(|q| q.c_datum_type),
+                ) == (i16::datum_type(), i16::datum_type(), Some(i32::datum_type()))
+                {
+                    new_mat_mul_unary_finite(
+                        model,
+                        node,
+                        self.a.clone(),
+                        b_shape,
+                        self.a_trans,
+                        self.b_trans,
+                        self.c_trans,
+                        self.q_params.as_ref(),
+                        &|m, k, n| MMMWrapper::Quant((ops.qmmm_i16_i32)(m, k, n)),
                     )?
                 } else {
                     bail!(
@@ -756,7 +897,7 @@ impl PulsedOp for MatMulUnary {
         )?
         .2
         .iter()
-        .map(|d| d.to_integer().unwrap() as usize)
+        .map(|d| d.to_usize().unwrap())
         .collect::<TVec<_>>();
         Ok(tvec!(fact))
     }
@@ -904,6 +1045,69 @@ fn cost<A: ToDim + Clone, B: ToDim + Clone>(
 mod test {
     use super::*;
 
+    #[cfg(feature = "safe-alloc")]
+    #[test]
+    fn safe_alloc_matmul_output_is_zeroed() -> TractResult<()> {
+        // with an empty k axis, no kernel product touches the output
+        let a = tensor2(&[[0f32; 0]; 16]);
+        let b = unsafe { Tensor::uninitialized::<f32>(&[0, 16])? };
+        for _ in 0..16 {
+            let mut dirty = unsafe { Tensor::uninitialized::<f32>(&[256])? };
+            dirty.as_slice_mut::<f32>()?.iter_mut().for_each(|x| *x = 1.0);
+            drop(dirty);
+            let c = eval(&a, &b, false, false, false, None)?;
+            assert_eq!(c, Tensor::zero::<f32>(&[16, 16])?);
+        }
+        Ok(())
+    }
+
+    fn optimized_matmul_fmas(input_shape: &[usize]) -> TractResult<TDim> {
+        let mut model = TypedModel::default();
+        let wire = model.add_source("s", TypedFact::dt_shape(f32::datum_type(), input_shape)?)?;
+        let a = rctensor2(&[[1f32; 8]; 8]);
+        let wire = model.wire_node("m", MatMulUnary::new(a, false, false, false, None), &[wire])?;
+        model.set_output_outlets(&wire)?;
+        let model = model.into_optimized()?;
+        let node = model
+            .nodes()
+            .iter()
+            .find(|n| n.op_is::<lir::MatMatMulUnaryFinite<f32, f32, f32, f32>>())
+            .unwrap();
+        let inputs = model.node_input_facts(node.id)?;
+        let cost = node.op.cost(&*inputs)?;
+        assert_eq!(cost.len(), 1);
+        assert_eq!(cost[0].0, Cost::FMA(f32::datum_type()));
+        Ok(cost[0].1.clone())
+    }
+
+    #[test]
+    fn optimized_cost() -> TractResult<()> {
+        assert_eq!(optimized_matmul_fmas(&[8, 8])?, 512.to_dim());
+        assert_eq!(optimized_matmul_fmas(&[2, 8, 8])?, 1024.to_dim());
+        Ok(())
+    }
+
+    #[test]
+    fn codegen_honours_kernel_selector() -> TractResult<()> {
+        use tract_linalg::mmm::MatMatMul;
+        let mut model = TypedModel::default();
+        let wire =
+            model.add_source("s", TypedFact::dt_shape(f32::datum_type(), [8, 8].as_ref())?)?;
+        let a = rctensor2(&[[1f32; 8]; 8]);
+        let wire = model.wire_node("m", MatMulUnary::new(a, false, false, false, None), &[wire])?;
+        model.set_output_outlets(&wire)?;
+        let selector = tract_linalg::KernelSelector { deterministic: true, mmm_f32_kernel: None };
+        let config = CodegenConfig { kernel_selector: Some(selector), ..CodegenConfig::default() };
+        let model = model.declutter()?.codegen_with_config(&config)?;
+        let op = model
+            .nodes()
+            .iter()
+            .find_map(|n| n.op_as::<lir::MatMatMulUnaryFinite<f32, f32, f32, f32>>())
+            .unwrap();
+        assert_eq!(op.mmm.as_mmm().kernel_name(), "generic");
+        Ok(())
+    }
+
     #[test]
     fn bin() {
         let a = rctensor2(&[[0f32, 1.0, 2.0], [3.0, 4.0, 5.0]]);
@@ -924,6 +1128,71 @@ mod test {
         c.close_enough(&c_found, true).unwrap();
     }
 
+    fn check_i16_matmul(q_params: QParams, expected: Tensor) -> TractResult<()> {
+        let a = rctensor2(&[[-32768i16, -32768, -32768], [100, 200, 300]]);
+        let zero_point = rctensor0(1i16);
+        let q_params = q_params.with_zero_point_b(&zero_point);
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(i16::datum_type(), [3, 1].as_ref())?;
+        let wire = model.add_source("s", fact)?;
+        let op = MatMulUnary::new(a, false, false, false, Some(q_params));
+        let wire = model.wire_node("m", op, &[wire])?;
+        model.set_output_outlets(&wire)?;
+        let input = tensor2(&[[-32768i16], [-32768], [-32768]]);
+        let found = model.clone().into_runnable()?.run(tvec!(input.clone()))?;
+        assert_eq!(*found[0], expected);
+        let found = model.into_optimized()?.into_runnable()?.run(tvec!(input))?;
+        assert_eq!(*found[0], expected);
+        Ok(())
+    }
+
+    #[test]
+    fn i16_saturates_i32_output() -> TractResult<()> {
+        let expected = tensor2(&[[i32::max_value()], [-19_661_400]]);
+        check_i16_matmul(QParams::new(i32::datum_type()), expected)
+    }
+
+    #[test]
+    fn i16_with_i64_accumulator_is_exact() -> TractResult<()> {
+        // 3 * -32768 * (-32768 - 1) does not fit in an i32
+        let q_params =
+            QParams::new(i64::datum_type()).with_accumulator_datum_type(i64::datum_type());
+        let expected = tensor2(&[[3_221_323_776i64], [-19_661_400]]);
+        check_i16_matmul(q_params, expected)
+    }
+
+    #[test]
+    fn i64_accumulator_for_large_k() -> TractResult<()> {
+        // 40000 * 255 * 255 does not fit in an i32
+        let k = 40000;
+        let a = unsafe { tensor1(&vec![255u8; k]).into_shape(&[1, k])? };
+        let q_params =
+            QParams::new(i64::datum_type()).with_accumulator_datum_type(i64::datum_type());
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(u8::datum_type(), [k, 1].as_ref())?;
+        let wire = model.add_source("s", fact)?;
+        let op = MatMulUnary::new(a.into_arc_tensor(), false, false, false, Some(q_params));
+        let wire = model.wire_node("m", op, &[wire])?;
+        model.set_output_outlets(&wire)?;
+        let input = unsafe { tensor1(&vec![255u8; k]).into_shape(&[k, 1])? };
+        let expected = tensor2(&[[k as i64 * 255 * 255]]);
+        assert!(k as i64 * 255 * 255 > i32::max_value() as i64);
+        let found = model.clone().into_runnable()?.run(tvec!(input.clone()))?;
+        assert_eq!(*found[0], expected);
+        let found = model.into_optimized()?.into_runnable()?.run(tvec!(input))?;
+        assert_eq!(*found[0], expected);
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_accumulator_is_an_error() {
+        let q_params =
+            QParams::new(i32::datum_type()).with_accumulator_datum_type(i16::datum_type());
+        let op = MatMul::default().with_q_params(q_params);
+        let (a, b) = (rctensor2(&[[1i8]]), rctensor2(&[[1i8]]));
+        assert!(op.eval(tvec!(a, b)).is_err());
+    }
+
     #[test]
     fn batch_input() -> TractResult<()> {
         crate::setup_test_logger();
@@ -17,7 +17,9 @@ pub mod invariants;
 pub mod array;
 pub mod cast;
 pub mod change_axes;
+pub mod check_finite;
 pub mod cnn;
+pub mod cond;
 pub mod downsample;
 pub mod dummy;
 pub mod identity;
@@ -30,6 +32,7 @@ pub mod quant;
 pub mod scan;
 pub mod source;
 pub mod unimpl;
+pub mod while_loop;
 
 pub use downsample::Downsample;
 pub use invariants::*;
@@ -54,6 +57,19 @@ pub enum Cost {
 
 use crate::internal::*;
 
+/// Resolve a possibly negative axis (counting from the end, as in ONNX or
+/// numpy) against the rank of a tensor.
+pub fn normalize_axis(axis: isize, rank: usize) -> TractResult<usize> {
+    let rank = rank as isize;
+    if 0 <= axis && axis < rank {
+        Ok(axis as usize)
+    } else if -rank <= axis && axis < 0 {
+        Ok((axis + rank) as usize)
+    } else {
+        bail!("Illegal combination of values for rank and axis: {} and {}", rank, axis)
+    }
+}
+
 pub trait OpState: fmt::Debug + Send {
     fn eval(
         &mut self,
@@ -205,7 +221,7 @@ pub trait TypedOp:
     ) -> TractResult<Option<OutletId>> {
         let outlet = OutletId::new(node.id, output_slot);
         let output = model.outlet_fact(outlet)?;
-        if start == 0 && Some(end as i32) == output.shape.dim(axis).to_integer().ok() {
+        if start == 0 && Some(end) == output.shape.dim(axis).to_usize().ok() {
             Ok(Some(patch.tap_model(model, outlet)?))
         } else {
             let wire = patch.tap_model(model, outlet)?;
@@ -259,11 +275,42 @@ pub trait TypedOp:
         Ok(None)
     }
 
+    /// Same as `codegen`, following the options of `config`.
+    ///
+    /// Defaults to `codegen`.
+    #[allow(unused_variables)]
+    fn codegen_with_config(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        config: &CodegenConfig,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        self.codegen(model, node)
+    }
+
+    /// Translate the op into an executable form, favouring small memory
+    /// footprint and node count over raw speed.
+    ///
+    /// Defaults to `codegen`.
+    fn codegen_for_size(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        self.codegen(model, node)
+    }
+
     /// Nested model multipliers, with label (for profiling).
     #[allow(unused_variables)]
     fn nested_model_multipliers(&self, inputs: &[&TypedFact]) -> Vec<(Cow<str>, f64)> {
         vec![]
     }
+
+    /// Element-wise operations fused in the op at codegen, in application
+    /// order (for profiling).
+    fn fused_ops(&self) -> Vec<&dyn fmt::Debug> {
+        vec![]
+    }
 }
 
 pub trait PulsedOp:
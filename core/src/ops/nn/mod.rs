@@ -2,11 +2,13 @@ mod arg_max_min;
 mod data_formats;
 mod global_pools;
 mod reduce;
+mod running_mean;
 
 pub use self::arg_max_min::ArgMaxMin;
 pub use self::data_formats::{BaseDataShape, DataFormat, DataShape};
 pub use self::global_pools::{GlobalAvgPool, GlobalLpPool, GlobalMaxPool};
 pub use self::reduce::{Reduce, Reducer};
+pub use self::running_mean::RunningMean;
 
 use num_traits::{AsPrimitive, Float};
 
@@ -29,6 +31,15 @@ element_wise!(sigmoid, Sigmoid, [f32] => |_, xs| {
     cost: |dt| {tvec!((Cost::FMA(dt), 11), (Cost::Div(dt), 1))}
 );
 
+element_wise!(swish, Swish, [f32] => |_, xs| {
+    let mut sigmoids = xs.to_vec();
+    (tract_linalg::ops().sigmoid_f32)().run(&mut sigmoids);
+    xs.iter_mut().zip(sigmoids.iter()).for_each(|(x, s)| *x *= s);
+    Ok(())
+};
+    cost: |dt| {tvec!((Cost::FMA(dt), 12), (Cost::Div(dt), 1))}
+);
+
 element_wise!(elu,
     Elu {
         #[educe(Hash(method = "hash_f32"))] alpha: f32
@@ -50,6 +61,18 @@ element_wise!(hard_sigmoid,
         Ok(())
 });
 
+element_wise!(hard_swish,
+    HardSwish {
+        #[educe(Hash(method = "hash_f32"))]
+        alpha: f32,
+        #[educe(Hash(method = "hash_f32"))]
+        beta: f32
+    },
+    [f32, f64] => |e, xs| {
+        xs.iter_mut().for_each(|x| { *x = x.hard_swish(e.alpha, e.beta); });
+        Ok(())
+});
+
 element_wise!(leaky_relu,
     LeakyRelu {
         #[educe(Hash(method = "hash_f32"))]
@@ -109,6 +132,7 @@ element_wise!(threshold_relu,
 trait Activations {
     fn elu(self, alpha: f32) -> Self;
     fn hard_sigmoid(self, alpha: f32, beta: f32) -> Self;
+    fn hard_swish(self, alpha: f32, beta: f32) -> Self;
     fn leaky_relu(self, alpha: f32) -> Self;
     fn parametric_softplus(self, alpha: f32, beta: f32) -> Self;
     fn scaled_tanh(self, alpha: f32, beta: f32) -> Self;
@@ -131,6 +155,9 @@ where
     fn hard_sigmoid(self, alpha: f32, beta: f32) -> Self {
         (alpha.as_() * self + beta.as_()).min(1.0.as_()).max(0.0.as_())
     }
+    fn hard_swish(self, alpha: f32, beta: f32) -> Self {
+        self * self.hard_sigmoid(alpha, beta)
+    }
     fn leaky_relu(self, alpha: f32) -> Self {
         if self < 0.0.as_() {
             alpha.as_() * self
@@ -159,3 +186,59 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::element_wise::ElementWiseOp;
+
+    #[test]
+    fn fold_sigmoid_mul_to_swish() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [2, 3].as_ref())?)?;
+        let sig = model.wire_node("sigmoid", sigmoid(), &[x])?[0];
+        let y = model.wire_node("mul", crate::ops::math::mul::bin_typed(), &[sig, x])?;
+        model.set_output_outlets(&y)?;
+
+        let input = tensor2(&[[-3f32, -0.5, 0.], [0.25, 1., 4.]]);
+        let expected = SimplePlan::new(&model)?.run(tvec!(input.clone()))?.remove(0);
+        let model = model.declutter()?;
+        assert_eq!(model.nodes().len(), 2);
+        let op = model.node(model.output_outlets()?[0].node).op_as::<ElementWiseOp>().unwrap();
+        assert!(op.0.is::<Swish>());
+        let found = SimplePlan::new(&model)?.run(tvec!(input))?.remove(0);
+        found.close_enough(&expected, true)
+    }
+
+    #[test]
+    fn hard_sigmoid_clips() -> TractResult<()> {
+        let input = tensor1(&[-4f32, -3., 0., 1.5, 3., 4.]);
+        let output = hard_sigmoid(1. / 6., 0.5).eval(tvec!(input.into_arc_tensor()))?;
+        output[0].close_enough(&tensor1(&[0f32, 0., 0.5, 0.75, 1., 1.]), false)
+    }
+
+    #[test]
+    fn hard_swish_clips() -> TractResult<()> {
+        let input = tensor1(&[-4f32, -3., 0., 1.5, 3., 4.]);
+        let output = hard_swish(1. / 6., 0.5).eval(tvec!(input.into_arc_tensor()))?;
+        output[0].close_enough(&tensor1(&[0f32, 0., 0., 1.125, 3., 4.]), false)
+    }
+
+    #[test]
+    fn fold_hard_sigmoid_mul_to_hard_swish() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [4].as_ref())?)?;
+        let sig = model.wire_node("hard_sigmoid", hard_sigmoid(1. / 6., 0.5), &[x])?[0];
+        let y = model.wire_node("mul", crate::ops::math::mul::bin_typed(), &[x, sig])?;
+        model.set_output_outlets(&y)?;
+
+        let input = tensor1(&[-4f32, -1., 2., 5.]);
+        let expected = SimplePlan::new(&model)?.run(tvec!(input.clone()))?.remove(0);
+        let model = model.declutter()?;
+        assert_eq!(model.nodes().len(), 2);
+        let op = model.node(model.output_outlets()?[0].node).op_as::<ElementWiseOp>().unwrap();
+        assert!(op.0.is::<HardSwish>());
+        let found = SimplePlan::new(&model)?.run(tvec!(input))?.remove(0);
+        found.close_enough(&expected, false)
+    }
+}
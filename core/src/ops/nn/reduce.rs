@@ -73,18 +73,27 @@ impl Reducer {
     }
 }
 
+// Min and Max propagate NaN, like the min and max element-wise operators:
+// once the accumulator is NaN (the only value not comparable to itself), it
+// sticks.
 fn max_t<'a, T>(v: ArrayViewD<'a, T>) -> T
 where
     T: Copy + Datum + num_traits::Bounded + ::std::cmp::PartialOrd,
 {
-    v.fold(T::min_value(), |acc, &v| if acc > v { acc } else { v })
+    v.fold(
+        T::min_value(),
+        |acc, &v| if acc > v || acc.partial_cmp(&acc).is_none() { acc } else { v },
+    )
 }
 
 fn min_t<'a, T>(v: ArrayViewD<'a, T>) -> T
 where
     T: Copy + Datum + num_traits::Bounded + ::std::cmp::PartialOrd,
 {
-    v.fold(T::max_value(), |acc, &v| if acc < v { acc } else { v })
+    v.fold(
+        T::max_value(),
+        |acc, &v| if acc < v || acc.partial_cmp(&acc).is_none() { acc } else { v },
+    )
 }
 
 fn prod_t<'a, T>(v: ArrayViewD<'a, T>) -> T
@@ -103,8 +112,8 @@ where
 
 #[derive(Clone, Debug, new, Hash)]
 pub struct Reduce {
-    axes: TVec<usize>,
-    reducer: Reducer,
+    pub axes: TVec<usize>,
+    pub reducer: Reducer,
 }
 
 tract_linalg::impl_dyn_hash!(Reduce);
@@ -178,7 +187,7 @@ impl TypedOp for Reduce {
         let input = mapping[&node.inputs[0]];
         let axis = target.outlet_fact(input)?.axis;
         if self.axes.contains(&axis) {
-            bail!("Can not reduce over streaming axis");
+            bail!("Can not reduce over streaming axis: the reduction needs the full sequence");
         }
         target.wire_node(&*node.name, self.clone(), &[input])
     }
@@ -196,3 +205,21 @@ impl PulsedOp for Reduce {
     as_op!();
     pulsed_op_to_typed_op!();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn max_min_propagate_nan() -> TractResult<()> {
+        let nan = std::f32::NAN;
+        let input = tensor2(&[[nan, 1., 2.], [1., 2., nan], [1., 2., 3.]]);
+        for reducer in &[Reducer::Max, Reducer::Min] {
+            let found = reducer.reduce(&[1], &input)?;
+            let found = found.as_slice::<f32>()?;
+            assert!(found[0].is_nan() && found[1].is_nan());
+            assert_eq!(found[2], if let Reducer::Max = reducer { 3. } else { 1. });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use crate::internal::*;
+use crate::pulse::PulsedFact;
+use ndarray::*;
+use num_traits::{Float, FromPrimitive};
+
+/// Cumulative mean along an axis: each output frame is the mean of all the
+/// input frames up to and including itself.
+///
+/// Once pulsified along its axis, the running sum and frame count are kept in
+/// the op state, so each pulse outputs the mean of all the frames seen so far.
+#[derive(Clone, Debug, new, Hash)]
+pub struct RunningMean {
+    pub axis: usize,
+    /// Delay of the stream when pulsing along `axis`. These first frames are
+    /// not part of the signal and are left out of the mean.
+    pub stream_delay: Option<usize>,
+}
+
+tract_linalg::impl_dyn_hash!(RunningMean);
+
+#[derive(Clone, Debug, Default)]
+struct RunningMeanState {
+    sum: Option<Tensor>,
+    count: usize,
+    seen: usize,
+}
+
+impl RunningMeanState {
+    fn eval_t<T: Datum + Float + FromPrimitive>(
+        &mut self,
+        op: &RunningMean,
+        input: Arc<Tensor>,
+    ) -> TractResult<Tensor> {
+        let mut output = input.into_tensor().into_array::<T>()?;
+        for mut frame in output.axis_iter_mut(Axis(op.axis)) {
+            self.seen += 1;
+            if self.seen <= op.stream_delay.unwrap_or(0) {
+                continue;
+            }
+            self.count += 1;
+            if self.sum.is_none() {
+                self.sum = Some(Tensor::zero::<T>(frame.shape())?);
+            }
+            let mut sum = self.sum.as_mut().unwrap().to_array_view_mut::<T>()?;
+            sum.zip_mut_with(&frame, |s, x| *s = *s + *x);
+            let count = T::from_usize(self.count).unwrap();
+            frame.zip_mut_with(&sum, |x, s| *x = *s / count);
+        }
+        Ok(output.into_tensor())
+    }
+}
+
+impl OpState for RunningMeanState {
+    fn eval(
+        &mut self,
+        _state: &mut SessionState,
+        op: &dyn Op,
+        mut inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let op = op.downcast_ref::<RunningMean>().ok_or("Wrong Op type")?;
+        if op.stream_delay.is_none() {
+            // not streaming: every call sees the full sequence
+            *self = RunningMeanState::default();
+        }
+        let output = dispatch_floatlike!(Self::eval_t(input.datum_type())(self, op, input))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl Op for RunningMean {
+    fn name(&self) -> Cow<str> {
+        "RunningMean".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} stream delay: {:?}", self.axis, self.stream_delay)])
+    }
+
+    canonic!();
+    op_core_mir!();
+    op_as_typed_op!();
+    op_as_pulsed_op!();
+}
+
+impl StatefullOp for RunningMean {
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(RunningMeanState::default())))
+    }
+}
+
+impl TypedOp for RunningMean {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        let input = mapping[&node.inputs[0]];
+        let fact = target.outlet_fact(input)?;
+        if fact.axis != self.axis {
+            bail!("RunningMean can only be pulsified along its axis");
+        }
+        let op = RunningMean { stream_delay: Some(fact.delay), ..self.clone() };
+        target.wire_node(&*node.name, op, &[input])
+    }
+}
+
+impl PulsedOp for RunningMean {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn running_mean_over_pulses() {
+        let mut model = TypedModel::default();
+        let fact =
+            TypedFact::dt_shape(f32::datum_type(), [TDim::s(), 2.to_dim()].as_ref()).unwrap();
+        let source = model.add_source("source", fact).unwrap();
+        model.wire_node("mean", RunningMean::new(0, None), &[source]).unwrap();
+        model.auto_outputs().unwrap();
+
+        let pulsed = PulsedModel::new(&model.into_normalized().unwrap(), 3).unwrap();
+        let plan = SimplePlan::new(pulsed.into_typed().unwrap()).unwrap();
+        let mut state = crate::plan::SimpleState::new(plan).unwrap();
+
+        let mut seen = vec![];
+        for i in 0..4 {
+            let frames: Vec<f32> = (0..6).map(|x| ((i * 6 + x) as f32).sin()).collect();
+            seen.extend(frames.iter().cloned());
+            let input = Array2::from_shape_vec((3, 2), frames).unwrap().into_tensor();
+            let output = state.run(tvec!(input)).unwrap().remove(0);
+            let output = output.to_array_view::<f32>().unwrap();
+            let n = seen.len() / 2;
+            for col in 0..2 {
+                let mean = seen.iter().skip(col).step_by(2).sum::<f32>() / n as f32;
+                assert!((output[[2, col]] - mean).abs() < 1e-5);
+            }
+        }
+    }
+}
@@ -13,6 +13,7 @@ pub struct QParams {
     pub zero_point_c: Option<Arc<Tensor>>,
     #[educe(Hash(method = "hash_scale"))]
     pub scale_factor: Option<f32>,
+    pub accumulator_datum_type: DatumType,
 }
 
 fn hash_scale<H: std::hash::Hasher>(it: &Option<f32>, state: &mut H) {
@@ -50,6 +51,7 @@ impl QParams {
             zero_point_b: None,
             zero_point_c: None,
             scale_factor: None,
+            accumulator_datum_type: i32::datum_type(),
         }
     }
 
@@ -69,6 +71,12 @@ impl QParams {
         QParams { scale_factor: Some(scale_factor), ..self }
     }
 
+    /// Integer type for the products accumulators. Defaults to i32, i64 can
+    /// be required for very large K.
+    pub fn with_accumulator_datum_type(self, dt: DatumType) -> QParams {
+        QParams { accumulator_datum_type: dt, ..self }
+    }
+
     pub fn set_zero_point_a(&mut self, zero_point: &Arc<Tensor>) {
         self.zero_point_a = cleanup_zeropoint(zero_point);
     }
@@ -86,18 +94,98 @@ impl QParams {
     }
 }
 
+/// Rounds half to even, as ONNX QuantizeLinear does.
+pub fn round_ties_to_even(x: f32) -> f32 {
+    if (x - x.trunc()).abs() == 0.5 {
+        2. * (x / 2.).round()
+    } else {
+        x.round()
+    }
+}
+
 pub fn quantize_linear_f32_u8(x: f32, scale: f32, zero_point: i32) -> u8 {
-    (((x * scale).round() as i32) + zero_point as i32)
+    ((round_ties_to_even(x * scale) as i32) + zero_point as i32)
         .max(u8::min_value() as i32)
         .min(u8::max_value() as i32) as u8
 }
 
 pub fn quantize_linear_f32_i8(x: f32, scale: f32, zero_point: i32) -> i8 {
-    (((x * scale).round() as i32) + zero_point as i32)
+    ((round_ties_to_even(x * scale) as i32) + zero_point as i32)
         .max(i8::min_value() as i32)
         .min(i8::max_value() as i32) as i8
 }
 
+pub use tract_linalg::mmm::scale_factor_to_fixed_point;
+
+/// Rounding of the fixed-point requantization, as in the `QTowardsEven` and
+/// `QTowardsPlusInf` specs fused in the quantized matrix multiplication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoundingPolicy {
+    /// Round half to even.
+    TowardsEven,
+    /// Round half towards +inf (what the matrix multiplication fuses).
+    TowardsPlusInf,
+}
+
+fn requantize_i32(
+    acc: i32,
+    multiplier: i32,
+    shift: usize,
+    zero_point: i32,
+    rounding: RoundingPolicy,
+) -> i32 {
+    use tract_linalg::generic::mmm::PseudoRightShift;
+    let v = match rounding {
+        RoundingPolicy::TowardsEven => acc.q_even(multiplier, shift),
+        RoundingPolicy::TowardsPlusInf => acc.q_to_plus_inf(multiplier, shift),
+    };
+    v.saturating_add(zero_point)
+}
+
+/// Scalar reference of the requantization fused in the quantized matrix
+/// multiplication: scale an i32 accumulator by `multiplier * 2^-(31 + shift)`
+/// with the given rounding, add the zero point, and saturate to i8.
+pub fn requantize_i32_to_i8(
+    acc: i32,
+    multiplier: i32,
+    shift: usize,
+    zero_point: i32,
+    rounding: RoundingPolicy,
+) -> i8 {
+    requantize_i32(acc, multiplier, shift, zero_point, rounding)
+        .max(i8::min_value() as i32)
+        .min(i8::max_value() as i32) as i8
+}
+
+/// Same as `requantize_i32_to_i8`, saturating to u8.
+pub fn requantize_i32_to_u8(
+    acc: i32,
+    multiplier: i32,
+    shift: usize,
+    zero_point: i32,
+    rounding: RoundingPolicy,
+) -> u8 {
+    requantize_i32(acc, multiplier, shift, zero_point, rounding)
+        .max(u8::min_value() as i32)
+        .min(u8::max_value() as i32) as u8
+}
+
+/// Quantizes all the values of a tensor to u8 or i8.
+pub fn quantize_linear_f32(
+    t: &Tensor,
+    dt: DatumType,
+    scale: f32,
+    zero_point: i32,
+) -> TractResult<Tensor> {
+    let t = t.cast_to::<f32>()?;
+    let t = t.to_array_view::<f32>()?;
+    match dt {
+        DatumType::U8 => Ok(t.mapv(|x| quantize_linear_f32_u8(x, scale, zero_point)).into_tensor()),
+        DatumType::I8 => Ok(t.mapv(|x| quantize_linear_f32_i8(x, scale, zero_point)).into_tensor()),
+        dt => bail!("Unsupported Q type: {:?}", dt),
+    }
+}
+
 element_wise_oop!(quantize_linear_u8,
     QuantizeLinearU8 {
         #[educe(Hash(method="hash_f32"))]
@@ -150,11 +238,68 @@ fn info_quantize_linear_i8(q: &QuantizeLinearI8) -> TractResult<Vec<String>> {
 #[educe(Hash)]
 pub struct DequantizeLinearF32 {
     #[educe(Hash(method="hash_f32"))]
-    scale: f32,
-    zero_point: i32,
+    pub scale: f32,
+    pub zero_point: i32,
 }
 
 impl DequantizeLinearF32 {
+    /// Merge into an identical dequantization of the same outlet, if any.
+    fn declutter_merge_duplicates(
+        &self,
+        model: &TypedModel,
+        dequant: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let input = dequant.inputs[0];
+        for succ in &model.node(input.node).outputs[input.slot].successors {
+            if succ.node >= dequant.id {
+                continue;
+            }
+            let other = model.node(succ.node);
+            if let Some(op) = other.op_as::<DequantizeLinearF32>() {
+                if op.scale == self.scale && op.zero_point == self.zero_point {
+                    let mut patch = TypedModelPatch::default();
+                    let wire = patch.tap_model(model, OutletId::new(other.id, 0))?;
+                    patch.shunt_outside(model, OutletId::new(dequant.id, 0), wire)?;
+                    return Ok(Some(patch));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Fuse with a directly following quantization into a Requantize, when
+    /// a lookup table can not do it.
+    fn declutter_as_requantize(
+        &self,
+        model: &TypedModel,
+        dequant: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let quant = if let Some(quant) = model.single_succ(dequant.id)? {
+            quant
+        } else {
+            return Ok(None);
+        };
+        let (scale, zero_point, dt) =
+            if let Some(params) = quantize_params(quant) { params } else { return Ok(None) };
+        let incoming_dt = model.outlet_fact(dequant.inputs[0])?.datum_type;
+        if incoming_dt == dt {
+            return Ok(None);
+        }
+        let op = Requantize::new(
+            0,
+            rctensor0(self.scale),
+            rctensor0(self.zero_point),
+            rctensor0(scale.recip()),
+            rctensor0(zero_point),
+            dt,
+        );
+        let mut patch = TypedModelPatch::default();
+        let wire = patch.tap_model(model, dequant.inputs[0])?;
+        let wire = patch.wire_node(&*quant.name, op, &[wire])?[0];
+        patch.shunt_outside(model, OutletId::new(quant.id, 0), wire)?;
+        Ok(Some(patch))
+    }
+
     fn eval_t<T: Datum + AsPrimitive<i32>>(&self, input: &Tensor) -> TractResult<Tensor> {
         let mut output = unsafe { Tensor::uninitialized::<f32>(input.shape())? };
         input
@@ -225,21 +370,17 @@ impl TypedOp for DequantizeLinearF32 {
         model: &TypedModel,
         dequant: &TypedNode,
     ) -> TractResult<Option<TypedModelPatch>> {
+        if let Some(patch) = self.declutter_merge_duplicates(model, dequant)? {
+            return Ok(Some(patch));
+        }
+        if let Some(patch) = self.declutter_as_requantize(model, dequant)? {
+            return Ok(Some(patch));
+        }
         let mut current = dequant;
+        let mut element_wise = true;
         let incoming_dt = model.node_input_facts(dequant.id)?[0].datum_type;
         while let Some(quant) = model.single_succ(current.id)? {
-            let q_params = if let Some(op) = quant.op_as::<ElementWiseOp>() {
-                if let Some(mop) = op.0.downcast_ref::<QuantizeLinearU8>() {
-                    Some((mop.scale, mop.zero_point as i32, u8::datum_type()))
-                } else if let Some(mop) = op.0.downcast_ref::<QuantizeLinearI8>() {
-                    Some((mop.scale, mop.zero_point as i32, i8::datum_type()))
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-            if let Some((scale, zero_point, dt)) = q_params {
+            if let Some((scale, zero_point, dt)) = quantize_params(quant) {
                 // first, try Op::quantize() on all ops in the chain
                 let mut patch = TypedModelPatch::default();
                 let mut wire: OutletId = patch.tap_model(model, dequant.inputs[0])?.into();
@@ -262,7 +403,11 @@ impl TypedOp for DequantizeLinearF32 {
                     }
                 }
                 // or else make a lookup table
-                if incoming_dt == DatumType::I8 || incoming_dt == DatumType::U8 {
+                // the table maps a byte to a byte of the same type
+                if element_wise
+                    && incoming_dt == dt
+                    && (incoming_dt == DatumType::I8 || incoming_dt == DatumType::U8)
+                {
                     let mut adhoc_model = TypedModel::default();
                     let mut wire = adhoc_model
                         .add_source("ad-hoc", TypedFact::dt_shape(dt, [256].as_ref())?)?;
@@ -310,6 +455,12 @@ impl TypedOp for DequantizeLinearF32 {
             let invariants = quant.op.invariants(model, quant)?;
             if invariants.element_wise() {
                 current = quant;
+            } else if quant.op_is::<crate::ops::array::Pad>()
+                || quant.op_is::<crate::ops::array::TypedConcat>()
+            {
+                // only moving values around: Op::quantize can go through, a LUT can not
+                element_wise = false;
+                current = quant;
             } else {
                 break;
             }
@@ -343,6 +494,156 @@ impl PulsedOp for DequantizeLinearF32 {
     pulsed_op_to_typed_op!();
 }
 
+/// Scale, zero point and type of a quantization node, if `node` is one.
+fn quantize_params(node: &TypedNode) -> Option<(f32, i32, DatumType)> {
+    let op = node.op_as::<ElementWiseOp>()?;
+    if let Some(mop) = op.0.downcast_ref::<QuantizeLinearU8>() {
+        Some((mop.scale, mop.zero_point as i32, u8::datum_type()))
+    } else if let Some(mop) = op.0.downcast_ref::<QuantizeLinearI8>() {
+        Some((mop.scale, mop.zero_point as i32, i8::datum_type()))
+    } else {
+        None
+    }
+}
+
+/// Converts a quantized tensor from one set of quantization parameters to
+/// another in one step, saturating to the output type.
+///
+/// Scales (f32) and zero points (i32) are either scalars, for per-tensor
+/// quantization, or vectors along `axis`, for per-channel quantization.
+#[derive(Clone, Debug, new, Hash)]
+pub struct Requantize {
+    pub axis: usize,
+    pub in_scale: Arc<Tensor>,
+    pub in_zero_point: Arc<Tensor>,
+    pub out_scale: Arc<Tensor>,
+    pub out_zero_point: Arc<Tensor>,
+    pub out_datum_type: DatumType,
+}
+
+tract_linalg::impl_dyn_hash!(Requantize);
+
+impl Requantize {
+    fn params(&self, len: usize) -> TractResult<Vec<(f32, i32, f32, i32)>> {
+        fn channels<T: Datum + Copy>(t: &Tensor, len: usize) -> TractResult<Vec<T>> {
+            let t = t.cast_to::<T>()?;
+            let values = t.as_slice::<T>()?;
+            if values.len() == 1 {
+                Ok(vec![values[0]; len])
+            } else if values.len() == len {
+                Ok(values.to_vec())
+            } else {
+                bail!("Requantize expects scalar parameters or {} channels, got {:?}", len, t)
+            }
+        }
+        let in_scale = channels::<f32>(&self.in_scale, len)?;
+        let in_zero_point = channels::<i32>(&self.in_zero_point, len)?;
+        let out_scale = channels::<f32>(&self.out_scale, len)?;
+        let out_zero_point = channels::<i32>(&self.out_zero_point, len)?;
+        Ok((0..len)
+            .map(|c| (in_scale[c], in_zero_point[c], out_scale[c], out_zero_point[c]))
+            .collect())
+    }
+
+    fn per_channel(&self) -> bool {
+        [&self.in_scale, &self.in_zero_point, &self.out_scale, &self.out_zero_point]
+            .iter()
+            .any(|t| t.len() > 1)
+    }
+}
+
+impl Op for Requantize {
+    fn name(&self) -> Cow<str> {
+        "Requantize".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("in scale: {:?} zero_point: {:?}", self.in_scale, self.in_zero_point),
+            format!("out scale: {:?} zero_point: {:?}", self.out_scale, self.out_zero_point),
+        ])
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+    op_as_pulsed_op!();
+}
+
+impl StatelessOp for Requantize {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (min, max) = match self.out_datum_type {
+            DatumType::I8 => (i8::min_value() as i32, i8::max_value() as i32),
+            DatumType::U8 => (u8::min_value() as i32, u8::max_value() as i32),
+            DatumType::I32 => (i32::min_value(), i32::max_value()),
+            dt => bail!("Unsupported Q type: {:?}", dt),
+        };
+        let input = inputs[0].cast_to::<i32>()?;
+        let input = input.to_array_view::<i32>()?;
+        let per_channel = input.ndim() > self.axis;
+        let params = self.params(if per_channel { input.shape()[self.axis] } else { 1 })?;
+        let mut output = input.to_owned();
+        for (coords, y) in output.indexed_iter_mut() {
+            let c = if per_channel { coords[self.axis] } else { 0 };
+            let (in_scale, in_zero_point, out_scale, out_zero_point) = params[c];
+            let x = (*y - in_zero_point) as f32 * in_scale / out_scale;
+            *y = (round_ties_to_even(x) as i64 + out_zero_point as i64)
+                .max(min as i64)
+                .min(max as i64) as i32;
+        }
+        let output = output.into_tensor().cast_to_dt(self.out_datum_type)?.into_owned();
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl TypedOp for Requantize {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let mut fact = inputs[0].clone();
+        fact.datum_type = self.out_datum_type;
+        fact.konst = None;
+        Ok(tvec!(fact))
+    }
+
+    fn invariants(&self, model: &TypedModel, node: &TypedNode) -> TractResult<Invariants> {
+        if self.per_channel() {
+            let rank = model.outlet_fact(node.inputs[0])?.rank();
+            (0..rank)
+                .filter(|&axis| axis != self.axis)
+                .map(|axis| AxisInfo::for_node(model, node, axis))
+                .collect()
+        } else {
+            Invariants::new_element_wise(model, node)
+        }
+    }
+
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        let input = mapping[&node.inputs[0]];
+        if self.per_channel() && target.outlet_fact(input)?.axis == self.axis {
+            bail!("Can not pulsify a per-channel Requantize along its channel axis")
+        }
+        target.wire_node(&*node.name, self.clone(), &[input])
+    }
+
+    as_op!();
+}
+
+impl PulsedOp for Requantize {
+    fn pulsed_output_facts(&self, inputs: &[&PulsedFact]) -> TractResult<TVec<PulsedFact>> {
+        let mut fact = inputs[0].clone();
+        fact.datum_type = self.out_datum_type;
+        Ok(tvec!(fact))
+    }
+
+    as_op!();
+    pulsed_op_to_typed_op!();
+}
+
 element_wise_oop!(lookup_table,
     LookupTable {
         #[educe(Hash(method="hash_lookup_table"))]
@@ -366,3 +667,338 @@ element_wise_oop!(lookup_table,
 fn hash_lookup_table<H: std::hash::Hasher>(lut: &Box<dyn Lut>, h: &mut H) {
     Hash::hash_slice(lut.table(), h)
 }
+
+/// Quantizes a f32 tensor to u8, computing scale and zero point from its
+/// range (ONNX DynamicQuantizeLinear).
+///
+/// Outputs are the quantized tensor, the scale and the zero point, the two
+/// latter as scalars.
+#[derive(Clone, Debug, new, Default, Hash)]
+pub struct DynamicQuantizeLinearU8;
+
+tract_linalg::impl_dyn_hash!(DynamicQuantizeLinearU8);
+
+impl Op for DynamicQuantizeLinearU8 {
+    fn name(&self) -> Cow<str> {
+        "DynamicQuantizeLinearU8".into()
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+    not_a_pulsed_op!();
+}
+
+impl StatelessOp for DynamicQuantizeLinearU8 {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let input = input.to_array_view::<f32>()?;
+        let (min, max) = input.iter().fold((0f32, 0f32), |(min, max), &x| (min.min(x), max.max(x)));
+        let scale = if max > min { (max - min) / 255. } else { 1. };
+        let zero_point = round_ties_to_even(-min / scale).max(0.).min(255.) as u8;
+        let output = input.mapv(|x| quantize_linear_f32_u8(x, scale.recip(), zero_point as i32));
+        Ok(tvec!(output.into_arc_tensor(), rctensor0(scale), rctensor0(zero_point)))
+    }
+}
+
+impl TypedOp for DynamicQuantizeLinearU8 {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        Ok(tvec!(
+            TypedFact::dt_shape(u8::datum_type(), inputs[0].shape.clone())?,
+            TypedFact::dt_shape(f32::datum_type(), [0usize; 0].as_ref())?,
+            TypedFact::dt_shape(u8::datum_type(), [0usize; 0].as_ref())?
+        ))
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merge_duplicate_dequant() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(u8::datum_type(), [4].as_ref())?)?;
+        let a = model.wire_node("a", DequantizeLinearF32::new(0.1, 128), &[source])?;
+        let b = model.wire_node("b", DequantizeLinearF32::new(0.1, 128), &[source])?;
+        let sum = model.wire_node("sum", crate::ops::math::add::bin_typed(), &[a[0], b[0]])?;
+        model.set_output_outlets(&sum)?;
+        let model = model.declutter()?;
+        assert_eq!(model.nodes().iter().filter(|n| n.op_is::<DequantizeLinearF32>()).count(), 1);
+        let output = SimplePlan::new(&model)?.run(tvec!(tensor1(&[128u8, 138, 118, 128])))?;
+        output[0].close_enough(&tensor1(&[0f32, 2., -2., 0.]), true)
+    }
+
+    #[test]
+    fn requantize_matches_kernel() -> TractResult<()> {
+        use crate::ops::matmul::MatMul;
+        let a = tensor2(&[[127i8], [-3], [1]]);
+        let b = tensor2(&[[-128i8, -77, -5, -1, 0, 1, 2, 3, 50, 127]]);
+        for &(scale, zp) in &[(0.1f32, 0i8), (0.013, -7), (0.5, 3), (0.0007, 100)] {
+            let op = MatMul::default().with_q_params(
+                QParams::new(i8::datum_type())
+                    .with_scale_factor(scale)
+                    .with_zero_point_c(&rctensor0(zp)),
+            );
+            let output =
+                op.eval(tvec!(a.clone().into_arc_tensor(), b.clone().into_arc_tensor()))?;
+            let (mult, shift) = scale_factor_to_fixed_point(scale);
+            let b_data = b.as_slice::<i8>()?;
+            let expected: Vec<i8> = a
+                .as_slice::<i8>()?
+                .iter()
+                .flat_map(|&a| {
+                    b_data.iter().map(move |&b| {
+                        let acc = a as i32 * b as i32;
+                        let rounding = RoundingPolicy::TowardsPlusInf;
+                        requantize_i32_to_i8(acc, mult, shift, zp as i32, rounding)
+                    })
+                })
+                .collect();
+            assert_eq!(output[0].as_slice::<i8>()?, &*expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn requantize_rounding() {
+        use RoundingPolicy::*;
+        // 0.5 is exactly representable: ties show the rounding policy
+        let (mult, shift) = scale_factor_to_fixed_point(0.5);
+        let accs = [1, 3, 5, -1, -3];
+        let round = |policy| -> Vec<i8> {
+            accs.iter().map(|&acc| requantize_i32_to_i8(acc, mult, shift, 0, policy)).collect()
+        };
+        assert_eq!(round(TowardsEven), vec![0, 2, 2, 0, -2]);
+        assert_eq!(round(TowardsPlusInf), vec![1, 2, 3, 0, -1]);
+    }
+
+    #[test]
+    fn dynamic_quantize() -> TractResult<()> {
+        let output = DynamicQuantizeLinearU8.eval(tvec!(rctensor1(&[-1f32, 0., 1.55])))?;
+        assert_eq!(*output[0], tensor1(&[0u8, 100, 255]));
+        assert_eq!(*output[1], tensor0(0.01f32));
+        assert_eq!(*output[2], tensor0(100u8));
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic_quantize_zero_point_tie() -> TractResult<()> {
+        // scale is 1, so the zero point is exactly 2.5 and rounds to even
+        let output = DynamicQuantizeLinearU8.eval(tvec!(rctensor1(&[-2.5f32, 0., 252.5])))?;
+        assert_eq!(*output[0], tensor1(&[0u8, 2, 254]));
+        assert_eq!(*output[1], tensor0(1f32));
+        assert_eq!(*output[2], tensor0(2u8));
+        Ok(())
+    }
+
+    #[test]
+    fn fold_dequant_pad_quant() -> TractResult<()> {
+        use crate::ops::array::{Pad, PadMode};
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(u8::datum_type(), [2].as_ref())?)?;
+        let deq = model.wire_node("deq", DequantizeLinearF32::new(0.1, 128), &[source])?;
+        let pad = Pad::new(vec![(1, 1)], PadMode::Constant(rctensor0(1f32)));
+        let pad = model.wire_node("pad", pad, &deq)?;
+        let quant = model.wire_node("quant", quantize_linear_u8(10., 128), &pad)?;
+        model.set_output_outlets(&quant)?;
+        let model = model.declutter()?;
+        assert_eq!(model.nodes().len(), 2);
+        assert!(model.nodes()[1].op_is::<Pad>());
+        let output = SimplePlan::new(&model)?.run(tvec!(tensor1(&[100u8, 200])))?;
+        assert_eq!(*output[0], tensor1(&[138u8, 100, 200, 138]));
+        Ok(())
+    }
+
+    fn lut_chain(
+        dt: DatumType,
+        activation: ElementWiseOp,
+        out_scale: f32,
+        out_zero_point: i32,
+    ) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let source = model.add_source("source", TypedFact::dt_shape(dt, [256].as_ref())?)?;
+        let deq = model.wire_node("deq", DequantizeLinearF32::new(0.05, 0), &[source])?;
+        let act = model.wire_node("act", activation, &deq)?;
+        let quant = if dt == DatumType::U8 {
+            quantize_linear_u8(out_scale, out_zero_point as u8)
+        } else {
+            quantize_linear_i8(out_scale, out_zero_point as i8)
+        };
+        let quant = model.wire_node("quant", quant, &act)?;
+        model.set_output_outlets(&quant)?;
+        let model = model.declutter()?;
+        assert_eq!(model.nodes().len(), 2);
+        let lut = model.nodes()[1].op_as::<ElementWiseOp>().unwrap();
+        assert!(lut.0.downcast_ref::<LookupTable>().is_some());
+        Ok(model)
+    }
+
+    #[test]
+    fn lut_sigmoid_u8() -> TractResult<()> {
+        let model = lut_chain(u8::datum_type(), crate::ops::nn::sigmoid(), 255., 0)?;
+        let input = (0u8..=255).collect::<Vec<u8>>();
+        let output = SimplePlan::new(&model)?.run(tvec!(tensor1(&input)))?;
+        for (&x, &y) in input.iter().zip(output[0].as_slice::<u8>()?) {
+            let expected = 1. / (1. + (-(x as f32) * 0.05).exp());
+            assert!((y as f32 / 255. - expected).abs() <= 1. / 255., "sigmoid({}) -> {}", x, y);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn lut_tanh_i8() -> TractResult<()> {
+        let model = lut_chain(i8::datum_type(), crate::ops::math::tanh(), 127., 0)?;
+        let input = (-128i8..=127).collect::<Vec<i8>>();
+        let output = SimplePlan::new(&model)?.run(tvec!(tensor1(&input)))?;
+        for (&x, &y) in input.iter().zip(output[0].as_slice::<i8>()?) {
+            let expected = (x as f32 * 0.05).tanh();
+            assert!((y as f32 / 127. - expected).abs() <= 1. / 127., "tanh({}) -> {}", x, y);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn no_lut_across_types() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(u8::datum_type(), [4].as_ref())?)?;
+        let deq = model.wire_node("deq", DequantizeLinearF32::new(0.05, 128), &[source])?;
+        let act = model.wire_node("act", crate::ops::math::tanh(), &deq)?;
+        let quant = model.wire_node("quant", quantize_linear_i8(127., 0), &act)?;
+        model.set_output_outlets(&quant)?;
+        let model = model.declutter()?;
+        assert_eq!(model.output_fact(0)?.datum_type, i8::datum_type());
+        let output = SimplePlan::new(&model)?.run(tvec!(tensor1(&[0u8, 128, 148, 255])))?;
+        assert_eq!(*output[0], tensor1(&[-127i8, 0, 97, 127]));
+        Ok(())
+    }
+
+    #[test]
+    fn requantize_per_tensor_to_per_channel_and_back() -> TractResult<()> {
+        let input = tensor2(&[[-20i8, 10, 40], [5, -60, 127]]);
+        let to_channels = Requantize::new(
+            1,
+            rctensor0(0.1f32),
+            rctensor0(0i32),
+            rctensor1(&[0.05f32, 0.1, 0.025]),
+            rctensor1(&[0i32, 3, -10]),
+            i8::datum_type(),
+        );
+        let channels = to_channels.eval(tvec!(input.clone().into_arc_tensor()))?.remove(0);
+        assert_eq!(*channels, tensor2(&[[-40i8, 13, 127], [10, -57, 127]]));
+        let to_tensor = Requantize::new(
+            1,
+            to_channels.out_scale.clone(),
+            to_channels.out_zero_point.clone(),
+            rctensor0(0.1f32),
+            rctensor0(0i32),
+            i8::datum_type(),
+        );
+        let back = to_tensor.eval(tvec!(channels))?.remove(0);
+        assert_eq!(*back, tensor2(&[[-20i8, 10, 34], [5, -60, 34]]));
+        Ok(())
+    }
+
+    #[test]
+    fn requantize_ties_to_even() -> TractResult<()> {
+        let input = tensor1(&[-3i32, -1, 1, 3, 5]);
+        let op = Requantize::new(
+            0,
+            rctensor0(1f32),
+            rctensor0(0i32),
+            rctensor0(2f32),
+            rctensor0(0i32),
+            i8::datum_type(),
+        );
+        let output = op.eval(tvec!(input.clone().into_arc_tensor()))?;
+        assert_eq!(*output[0], tensor1(&[-2i8, 0, 0, 2, 2]));
+        let quantized = quantize_linear_f32(&input, i8::datum_type(), 0.5, 0)?;
+        assert_eq!(*output[0], quantized);
+        Ok(())
+    }
+
+    #[test]
+    fn fold_dequant_quant_into_requantize() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let source =
+            model.add_source("source", TypedFact::dt_shape(i32::datum_type(), [4].as_ref())?)?;
+        let deq = model.wire_node("deq", DequantizeLinearF32::new(0.01, 0), &[source])?;
+        let quant = model.wire_node("quant", quantize_linear_i8(20., 2), &deq)?;
+        model.set_output_outlets(&quant)?;
+        let input = tensor1(&[-10000i32, -52, 0, 333]);
+        let expected = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+        let model = model.declutter()?;
+        assert_eq!(model.nodes().len(), 2);
+        assert!(model.nodes()[1].op_is::<Requantize>());
+        let found = SimplePlan::new(&model)?.run(tvec!(input))?;
+        assert_eq!(found, expected);
+        assert_eq!(*found[0], tensor1(&[-128i8, -8, 2, 69]));
+        Ok(())
+    }
+
+    fn dequant_sigmoid_quant(len: TDim) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(u8::datum_type(), [len, 2.to_dim()].as_ref())?;
+        let source = model.add_source("source", fact)?;
+        let deq = model.wire_node("deq", DequantizeLinearF32::new(0.05, 128), &[source])?;
+        let sigmoid = model.wire_node("sigmoid", crate::ops::nn::sigmoid(), &deq)?;
+        let quant = model.wire_node("quant", quantize_linear_u8(255., 0), &sigmoid)?;
+        model.set_output_outlets(&quant)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn pulse_requantize() -> TractResult<()> {
+        use crate::pulse::PulsedModel;
+        let model = |len: TDim| -> TractResult<TypedModel> {
+            let mut model = TypedModel::default();
+            let fact = TypedFact::dt_shape(u8::datum_type(), [len, 2.to_dim()].as_ref())?;
+            let source = model.add_source("source", fact)?;
+            let deq = model.wire_node("deq", DequantizeLinearF32::new(0.05, 128), &[source])?;
+            let quant = model.wire_node("quant", quantize_linear_i8(10., 0), &deq)?;
+            model.set_output_outlets(&quant)?;
+            model.declutter()
+        };
+        let input: Vec<u8> = (0..16).map(|i| (i * 17) as u8).collect();
+        let input = unsafe { tensor1(&input).into_shape(&[8, 2])? };
+        let expected = SimplePlan::new(model(8.into())?)?.run(tvec!(input.clone()))?;
+
+        let streaming = model(TDim::s())?;
+        assert!(streaming.nodes().iter().any(|n| n.op_is::<Requantize>()));
+        let pulsed = PulsedModel::new(&streaming.into_normalized()?, 4)?;
+        assert_eq!(pulsed.output_fact(0)?.datum_type, i8::datum_type());
+        let mut state = crate::plan::SimpleState::new(SimplePlan::new(pulsed.into_typed()?)?)?;
+        for chunk in 0..2 {
+            let pulse = input.slice(0, 4 * chunk, 4 * (chunk + 1))?;
+            let output = state.run(tvec!(pulse))?;
+            assert_eq!(*output[0], expected[0].slice(0, 4 * chunk, 4 * (chunk + 1))?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn pulse_dequant_op_quant() -> TractResult<()> {
+        use crate::pulse::PulsedModel;
+        let input: Vec<u8> = (0..24).map(|i| (i * 11) as u8).collect();
+        let input = unsafe { tensor1(&input).into_shape(&[12, 2])? };
+        let expected =
+            SimplePlan::new(dequant_sigmoid_quant(12.into())?)?.run(tvec!(input.clone()))?;
+
+        let streaming = dequant_sigmoid_quant(TDim::s())?.into_normalized()?;
+        let pulsed = PulsedModel::new(&streaming, 4)?;
+        assert_eq!(pulsed.output_fact(0)?.datum_type, u8::datum_type());
+        assert_eq!(pulsed.output_fact(0)?.delay, 0);
+        assert!(pulsed.nodes().iter().any(|n| n.op.name() == "QuantizeLinearU8"));
+        let mut state = crate::plan::SimpleState::new(SimplePlan::new(pulsed.into_typed()?)?)?;
+        for chunk in 0..3 {
+            let pulse = input.slice(0, 4 * chunk, 4 * (chunk + 1))?;
+            let output = state.run(tvec!(pulse))?;
+            assert_eq!(*output[0], expected[0].slice(0, 4 * chunk, 4 * (chunk + 1))?);
+        }
+        Ok(())
+    }
+}
@@ -27,11 +27,9 @@ impl Scan {
             .iter()
             .map(|im| {
                 Ok(match im {
-                    InputMapping::Scan { axis, slot, chunk } => InputMapping::Scan {
-                        axis: *axis,
-                        slot: *slot,
-                        chunk: chunk.to_integer()? as usize,
-                    },
+                    InputMapping::Scan { axis, slot, chunk } => {
+                        InputMapping::Scan { axis: *axis, slot: *slot, chunk: chunk.to_usize()? }
+                    }
                     InputMapping::Full { slot } => InputMapping::Full { slot: *slot },
                     InputMapping::State { initializer } => {
                         InputMapping::State { initializer: initializer.clone() }
@@ -50,7 +48,7 @@ impl Scan {
                     full_slot: im.full_slot,
                     full_dim_hint: im.full_dim_hint.clone(),
                     last_value_slot: im.last_value_slot,
-                    chunk: im.chunk.to_integer()? as usize,
+                    chunk: im.chunk.to_usize()?,
                 })
             })
             .collect::<TractResult<_>>()?;
@@ -84,6 +82,133 @@ impl Scan {
         })
     }
 
+    /// Unrolls the body `n` times, each iteration processing `n` times more
+    /// steps.
+    ///
+    /// The new body slices its scan inputs in `n` parts, threads the states
+    /// through `n` copies of the original body, and concatenates the copies
+    /// full outputs. As the scan inputs are padded to a whole number of
+    /// chunks, states and last values are only preserved when the sequence
+    /// length is a multiple of the new chunk.
+    pub fn with_chunk(&self, n: usize) -> TractResult<Scan> {
+        if n == 0 {
+            bail!("Scan chunk multiplier must be positive")
+        } else if n == 1 {
+            return Ok(self.clone());
+        } else if self.backward {
+            bail!("Can not unroll a backward scan")
+        } else if self.skip % n != 0 {
+            bail!("Can not unroll a scan skipping {} iterations by {}", self.skip, n)
+        }
+        let old_inputs = self.body.input_outlets()?;
+        let old_outputs = self.body.output_outlets()?;
+        let mut body = TypedModel::default();
+        let mut input_mapping = vec![];
+        let mut sources = vec![];
+        for (m, &outlet) in self.input_mapping.iter().zip(old_inputs.iter()) {
+            let mut fact = self.body.outlet_fact(outlet)?.clone();
+            if let InputMapping::Scan { slot, axis, chunk } = m {
+                let chunk = chunk.clone() * n;
+                fact.shape.set_dim(*axis, chunk.clone())?;
+                input_mapping.push(InputMapping::Scan { slot: *slot, axis: *axis, chunk });
+            } else {
+                input_mapping.push(m.clone());
+            }
+            sources.push(body.add_source(&*self.body.node(outlet.node).name, fact)?);
+        }
+
+        let state_inputs =
+            self.input_mapping.iter().enumerate().filter(|(_, m)| m.as_state().is_some());
+        let state_outputs = self.output_mapping.iter().enumerate().filter(|(_, m)| m.state);
+        let state_pairs: Vec<(usize, usize)> =
+            state_inputs.zip(state_outputs).map(|((i, _), (o, _))| (i, o)).collect();
+        let mut states: HashMap<usize, OutletId> =
+            state_pairs.iter().map(|&(i, _)| (i, sources[i])).collect();
+        let mut copies: Vec<TVec<OutletId>> = vec![];
+        for copy in 0..n {
+            let mut mapping = HashMap::<OutletId, OutletId>::new();
+            for (ix, m) in self.input_mapping.iter().enumerate() {
+                let wire = match m {
+                    InputMapping::Scan { axis, chunk, .. } => {
+                        let chunk = chunk.to_usize()?;
+                        let slice =
+                            crate::ops::array::Slice::new(*axis, copy * chunk, (copy + 1) * chunk);
+                        let name = format!("{}.{}", self.body.node(old_inputs[ix].node).name, copy);
+                        body.wire_node(name, slice, &[sources[ix]])?[0]
+                    }
+                    InputMapping::State { .. } => states[&ix],
+                    InputMapping::Full { .. } => sources[ix],
+                };
+                mapping.insert(old_inputs[ix], wire);
+            }
+            for id in self.body.eval_order()? {
+                if old_inputs.iter().any(|i| i.node == id) {
+                    continue;
+                }
+                let node = self.body.node(id);
+                let inputs = node.inputs.iter().map(|i| mapping[i]).collect::<TVec<_>>();
+                let wires =
+                    body.wire_node(format!("{}.{}", node.name, copy), node.op.clone(), &inputs)?;
+                for (slot, wire) in wires.into_iter().enumerate() {
+                    mapping.insert(OutletId::new(id, slot), wire);
+                }
+            }
+            let outputs: TVec<OutletId> = old_outputs.iter().map(|o| mapping[o]).collect();
+            for &(i, o) in &state_pairs {
+                states.insert(i, outputs[o]);
+            }
+            copies.push(outputs);
+        }
+
+        // a full output that is also a state or a last value gets its own body output
+        let mut outputs = vec![];
+        let mut output_mapping = vec![];
+        let mut full_outputs = vec![];
+        let mut full_output_mapping = vec![];
+        for (ix, m) in self.output_mapping.iter().enumerate() {
+            let chunk = m.chunk.clone() * n;
+            let full = if m.full_slot.is_some() {
+                let name = format!("{}.concat", self.body.node(old_outputs[ix].node).name);
+                let op = crate::ops::array::TypedConcat::concat_vars(m.axis, n);
+                Some(body.wire_node(name, op, &copies.iter().map(|c| c[ix]).collect::<Vec<_>>())?)
+            } else {
+                None
+            };
+            if m.state || m.last_value_slot.is_some() {
+                outputs.push(copies[n - 1][ix]);
+                output_mapping.push(OutputMapping {
+                    full_slot: None,
+                    chunk: chunk.clone(),
+                    ..m.clone()
+                });
+                if let Some(full) = full {
+                    full_outputs.push(full[0]);
+                    full_output_mapping.push(OutputMapping {
+                        state: false,
+                        last_value_slot: None,
+                        chunk,
+                        ..m.clone()
+                    });
+                }
+            } else {
+                outputs.push(full.map(|f| f[0]).unwrap_or(copies[n - 1][ix]));
+                output_mapping.push(OutputMapping { chunk, ..m.clone() });
+            }
+        }
+        outputs.extend(full_outputs);
+        output_mapping.extend(full_output_mapping);
+        body.set_output_outlets(&outputs)?;
+        let mut scan = Scan::new(
+            body,
+            input_mapping,
+            output_mapping,
+            self.seq_length_input_slot,
+            self.backward,
+        )?;
+        scan.skip = self.skip / n;
+        Ok(scan)
+    }
+
     fn declutter_body(
         &self,
         model: &TypedModel,
@@ -596,6 +721,23 @@ impl TypedOp for Scan {
     as_op!();
 
     fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        for (ix, mapping) in self.input_mapping.iter().enumerate() {
+            if let Some(StateInitializer::FromInput(slot)) = mapping.as_state() {
+                let init = inputs.get(*slot).ok_or_else(|| {
+                    format!("Scan state #{} initializer slot {} is missing", ix, slot)
+                })?;
+                let state = self.body.input_fact(ix)?;
+                if init.datum_type != state.datum_type || init.shape != state.shape {
+                    bail!(
+                        "Scan state #{} is initialized from input slot {} as {:?}, but the body expects {:?}",
+                        ix,
+                        slot,
+                        init,
+                        state
+                    );
+                }
+            }
+        }
         let mut outputs = tvec!();
         let iters = {
             let (outside_slot, axis, chunk) =
@@ -782,7 +924,7 @@ impl PulsedOp for Scan {
                 if axis == output_mapping.axis {
                     inputs[0].pulse()
                 } else {
-                    d.to_integer().unwrap() as usize
+                    d.to_usize().unwrap()
                 }
             })
             .collect();
@@ -799,3 +941,94 @@ impl PulsedOp for Scan {
     as_op!();
     pulsed_op_to_typed_op!();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // running sum of x rows, starting from the state initializer
+    fn running_sum(init: &[usize]) -> TractResult<TypedModel> {
+        let mut body = TypedModel::default();
+        let state =
+            body.add_source("state", TypedFact::dt_shape(f32::datum_type(), [1, 2].as_ref())?)?;
+        let x = body.add_source("x", TypedFact::dt_shape(f32::datum_type(), [1, 2].as_ref())?)?;
+        let sum = body.wire_node("sum", crate::ops::math::add::bin_typed(), &[state, x])?;
+        body.set_output_outlets(&sum)?;
+        let scan = Scan::new(
+            body,
+            vec![
+                InputMapping::State { initializer: StateInitializer::FromInput(0) },
+                InputMapping::Scan { slot: 1, axis: 0, chunk: 1.to_dim() },
+            ],
+            vec![OutputMapping::new(None, 0, 1.to_dim(), None, Some(0), true)],
+            None,
+            false,
+        )?;
+        let mut model = TypedModel::default();
+        let init = model.add_source("init", TypedFact::dt_shape(f32::datum_type(), init)?)?;
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [3, 2].as_ref())?)?;
+        let scan = model.wire_node("scan", scan, &[init, x])?;
+        model.set_output_outlets(&scan)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn state_from_input() -> TractResult<()> {
+        let model = running_sum(&[1, 2])?;
+        assert_eq!(model.output_fact(0)?.shape.as_finite(), Some(&[1usize, 2][..]));
+        let output = model
+            .into_runnable()?
+            .run(tvec!(tensor2(&[[1f32, 2.]]), tensor2(&[[1f32, 1.], [2., 2.], [3., 3.]])))?;
+        assert_eq!(*output[0], tensor2(&[[7f32, 8.]]));
+        Ok(())
+    }
+
+    // running sum of x rows, emitting every step and the last one
+    fn running_sum_scan() -> TractResult<Scan> {
+        let mut body = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [1, 2].as_ref())?;
+        let state = body.add_source("state", fact.clone())?;
+        let x = body.add_source("x", fact)?;
+        let sum = body.wire_node("sum", crate::ops::math::add::bin_typed(), &[state, x])?;
+        body.set_output_outlets(&sum)?;
+        Scan::new(
+            body,
+            vec![
+                InputMapping::State {
+                    initializer: StateInitializer::Value(rctensor2(&[[0f32, 0.]])),
+                },
+                InputMapping::Scan { slot: 0, axis: 0, chunk: 1.to_dim() },
+            ],
+            vec![OutputMapping::new(Some(0), 0, 1.to_dim(), None, Some(1), true)],
+            None,
+            false,
+        )
+    }
+
+    #[test]
+    fn unrolled_scan_matches() -> TractResult<()> {
+        let scan = running_sum_scan()?;
+        let unrolled = scan.with_chunk(2)?;
+        assert_eq!(unrolled.input_mapping[1].as_scan().unwrap().2, 2.to_dim());
+        let x = tensor2(&[[1f32, 2.], [3., 4.], [5., 6.], [7., 8.], [9., 10.], [11., 12.]]);
+        let mut results = vec![];
+        for op in vec![scan, unrolled] {
+            let mut model = TypedModel::default();
+            let source =
+                model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [6, 2].as_ref())?)?;
+            let outputs = model.wire_node("scan", op, &[source])?;
+            model.set_output_outlets(&outputs)?;
+            results.push(model.into_runnable()?.run(tvec!(x.clone()))?);
+        }
+        assert_eq!(*results[1][0], *results[0][0]);
+        assert_eq!(*results[1][1], *results[0][1]);
+        assert_eq!(*results[1][1], tensor2(&[[36f32, 42.]]));
+        Ok(())
+    }
+
+    #[test]
+    fn state_from_wrong_shaped_input() {
+        let err = running_sum(&[2]).unwrap_err().to_string();
+        assert!(err.contains("input slot 0"), "{}", err);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::internal::*;
+
+/// Loop evaluating `body` as long as `cond` holds.
+///
+/// Every input is a loop-carried value: the current values feed `cond`, which
+/// must output a boolean scalar, and `body`, whose outputs are the values for
+/// the next iteration. The outputs are the values after the last iteration.
+/// As the trip count is only known at runtime, the loop is run as a whole
+/// instead of being lowered to a Scan.
+#[derive(Debug, Clone, Hash)]
+pub struct While {
+    cond: Arc<TypedSimplePlan<TypedModel>>,
+    body: Arc<TypedSimplePlan<TypedModel>>,
+}
+
+tract_linalg::impl_dyn_hash!(While);
+
+impl While {
+    pub fn new(cond: TypedModel, body: TypedModel) -> TractResult<While> {
+        Ok(While { cond: Arc::new(SimplePlan::new(cond)?), body: Arc::new(SimplePlan::new(body)?) })
+    }
+
+    pub fn cond(&self) -> &TypedModel {
+        self.cond.model()
+    }
+
+    pub fn body(&self) -> &TypedModel {
+        self.body.model()
+    }
+}
+
+impl Op for While {
+    fn name(&self) -> Cow<str> {
+        "While".into()
+    }
+
+    fn nested_models(&self) -> Vec<(Cow<str>, &dyn Model, Vec<String>, Vec<String>)> {
+        let names = |n: usize, prefix: &str| (0..n).map(|i| format!("{} #{}", prefix, i)).collect();
+        let inputs = self.body().input_outlets().unwrap().len();
+        vec![
+            ("cond".into(), self.cond(), names(inputs, "state"), names(1, "continue")),
+            ("body".into(), self.body(), names(inputs, "state"), names(inputs, "state")),
+        ]
+    }
+
+    op_core_mir!();
+    op_as_typed_op!();
+    not_a_pulsed_op!();
+}
+
+impl StatelessOp for While {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let mut state = inputs;
+        loop {
+            let go_on = self.cond.run(state.iter().map(|t| t.clone().into_tensor()).collect())?;
+            if !*go_on[0].to_scalar::<bool>()? {
+                return Ok(state);
+            }
+            state = self.body.run(state.into_iter().map(|t| t.into_tensor()).collect())?;
+        }
+    }
+}
+
+impl TypedOp for While {
+    as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        if self.cond().input_outlets()?.len() != inputs.len()
+            || self.body().input_outlets()?.len() != inputs.len()
+            || self.body().output_outlets()?.len() != inputs.len()
+        {
+            bail!("While cond and body must take and body produce {} values", inputs.len());
+        }
+        let cond = self.cond().output_fact(0)?;
+        if cond.datum_type != bool::datum_type() || cond.rank() != 0 {
+            bail!("While condition must be a boolean scalar, got {:?}", cond);
+        }
+        inputs
+            .iter()
+            .enumerate()
+            .map(|(ix, input)| {
+                let next = self.body().output_fact(ix)?;
+                if next.datum_type != input.datum_type || next.shape != input.shape {
+                    bail!("Loop-carried value #{} changes from {:?} to {:?}", ix, input, next);
+                }
+                let mut fact = (*input).clone();
+                fact.konst = None;
+                Ok(fact)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn scalar_model(op: impl Into<Box<dyn TypedOp>>) -> TypedModel {
+        let mut model = TypedModel::default();
+        let i = model
+            .add_source("i", TypedFact::dt_shape(i32::datum_type(), [0usize; 0].as_ref()).unwrap())
+            .unwrap();
+        let wire = model.wire_node("op", op, &[i]).unwrap();
+        model.set_output_outlets(&wire).unwrap();
+        model
+    }
+
+    #[test]
+    fn counter() -> TractResult<()> {
+        let cond = scalar_model(crate::ops::logic::greater::unary(rctensor0(10i32)));
+        let body = scalar_model(crate::ops::math::add::unary(rctensor0(1i32)));
+        let mut model = TypedModel::default();
+        let i = model.add_const("i", tensor0(0i32))?;
+        let wire = model.wire_node("loop", While::new(cond, body)?, &[i])?;
+        model.set_output_outlets(&wire)?;
+        let output = SimplePlan::new(&model)?.run(tvec!())?;
+        assert_eq!(*output[0], tensor0(10i32));
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use crate::internal::*;
+use crate::ops::cast::cast;
+use crate::ops::math;
+use crate::ops::matmul::{MatMul, MatMulUnary};
+use crate::ops::quant::{DequantizeLinearF32, DynamicQuantizeLinearU8, QParams};
+use ndarray::*;
+
+/// Lowers float matmuls with quantized constant weights to integer matmuls.
+///
+/// The activations are quantized at runtime by a DynamicQuantizeLinearU8,
+/// the weights are fed as u8 to an integer MatMul, and the i32 result is
+/// requantized to f32. As the activation zero point is only known at
+/// runtime, it is applied after the product, using the precomputed sums of
+/// the weight rows.
+///
+/// The activations lose precision, so this pass is not part of declutter:
+/// it is run by `TypedModel::dynamic_quantize`.
+#[derive(Debug)]
+pub struct DynamicQuantizeMatMul;
+
+impl super::TypedPass for DynamicQuantizeMatMul {
+    fn pass(&self, model: &mut TypedModel) -> TractResult<bool> {
+        let mut done_something = false;
+        for id in model.eval_order()? {
+            if let Some(patch) = rewrite(model, model.node(id))? {
+                patch.apply(model)?;
+                done_something = true;
+            }
+        }
+        Ok(done_something)
+    }
+}
+
+/// Constant weights going through a DequantizeLinearF32, as u8 weights, zero
+/// point and scale.
+fn quantized_weights(
+    model: &TypedModel,
+    outlet: OutletId,
+) -> TractResult<Option<(Tensor, u8, f32)>> {
+    let dequant = model.node(outlet.node);
+    let op = if let Some(op) = dequant.op_as::<DequantizeLinearF32>() {
+        op
+    } else {
+        return Ok(None);
+    };
+    let weights = if let Some(k) = &model.outlet_fact(dequant.inputs[0])?.konst {
+        k
+    } else {
+        return Ok(None);
+    };
+    let (weights, zero_point) = match weights.datum_type() {
+        DatumType::U8 => (weights.clone().into_tensor(), op.zero_point),
+        DatumType::I8 => (
+            weights.to_array_view::<i8>()?.mapv(|x| (x as i32 + 128) as u8).into_tensor(),
+            op.zero_point + 128,
+        ),
+        _ => return Ok(None),
+    };
+    if weights.rank() != 2 || zero_point < 0 || zero_point > 255 {
+        return Ok(None);
+    }
+    Ok(Some((weights, zero_point as u8, op.scale)))
+}
+
+fn rewrite(model: &TypedModel, node: &TypedNode) -> TractResult<Option<TypedModelPatch>> {
+    let op = if let Some(op) = node.op_as::<MatMul>() {
+        op
+    } else {
+        return Ok(None);
+    };
+    if op.q_params.is_some() {
+        return Ok(None);
+    }
+    let mut found = None;
+    for konst_ix in 0..2 {
+        if let Some(weights) = quantized_weights(model, node.inputs[konst_ix])? {
+            found = Some((konst_ix, weights));
+            break;
+        }
+    }
+    let (konst_ix, (weights, zero_point, scale)) =
+        if let Some(found) = found { found } else { return Ok(None) };
+    let var_ix = 1 - konst_ix;
+    let input_fact = model.outlet_fact(node.inputs[var_ix])?;
+    if input_fact.datum_type != f32::datum_type()
+        || input_fact.konst.is_some()
+        || input_fact.rank() < 2
+    {
+        return Ok(None);
+    }
+    let flip = konst_ix == 1;
+    let t_konst = [op.a_trans, op.b_trans][konst_ix] ^ flip;
+    let t_var = [op.b_trans, op.a_trans][konst_ix] ^ flip;
+    let c_trans = op.c_trans ^ flip;
+
+    // sum of (w - zero_point) over k, shaped to broadcast over the output
+    let row_sums = weights
+        .to_array_view::<u8>()?
+        .mapv(|w| w as f32 - zero_point as f32)
+        .sum_axis(Axis(!t_konst as usize));
+    let m = row_sums.len();
+    let rank = model.outlet_fact(OutletId::new(node.id, 0))?.rank();
+    let mut shape = vec![1; rank];
+    shape[rank - 2 + c_trans as usize] = m;
+    let row_sums = row_sums.into_shape(shape)?.into_tensor();
+
+    let name = &*node.name;
+    let mut patch = TypedModelPatch::default();
+    let input = patch.tap_model(model, node.inputs[var_ix])?;
+    let quant =
+        patch.wire_node(format!("{}.quantize_input", name), DynamicQuantizeLinearU8, &[input])?;
+    let q_params = QParams::new(i32::datum_type()).with_zero_point_a(&rctensor0(zero_point));
+    let mm = MatMulUnary::new(weights.into_arc_tensor(), t_konst, t_var, c_trans, Some(q_params));
+    let wire = patch.wire_node(format!("{}.matmul_integer", name), mm, &[quant[0]])?;
+    let wire = patch.wire_node(format!("{}.cast", name), cast(f32::datum_type()), &wire)?;
+    let mut input_zero_point = patch.wire_node(
+        format!("{}.input_zero_point", name),
+        cast(f32::datum_type()),
+        &[quant[2]],
+    )?;
+    for axis in 0..rank {
+        input_zero_point = patch.wire_node(
+            format!("{}.input_zero_point.add_axis_{}", name, axis),
+            AxisOp::Add(0),
+            &input_zero_point,
+        )?;
+    }
+    let correction = patch.wire_node(
+        format!("{}.correction", name),
+        math::mul::unary(row_sums.into_arc_tensor()),
+        &input_zero_point,
+    )?;
+    let wire = patch.wire_node(
+        format!("{}.zero_point_correction", name),
+        math::sub::bin_typed(),
+        &[wire[0], correction[0]],
+    )?;
+    let scale = patch.wire_node(
+        format!("{}.scale", name),
+        math::mul::unary(rctensor0(scale)),
+        &[quant[1]],
+    )?;
+    let wire = patch.wire_node(name, math::mul::bin_typed(), &[wire[0], scale[0]])?;
+    patch.shunt_outside(model, OutletId::new(node.id, 0), wire[0])?;
+    Ok(Some(patch))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(konst_ix: usize, input: Tensor) -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), input.shape())?)?;
+        let w = tensor2(&[[12u8, 200, 128], [0, 255, 130], [90, 140, 60]]);
+        let w = model.add_const("w", w)?;
+        let w = model.wire_node("dequant", DequantizeLinearF32::new(0.02, 128), &[w])?[0];
+        let inputs = if konst_ix == 0 { [w, x] } else { [x, w] };
+        let y = model.wire_node("mm", MatMul::default(), &inputs)?;
+        model.set_output_outlets(&y)?;
+
+        let expected = SimplePlan::new(&model)?.run(tvec!(input.clone()))?.remove(0);
+        let model = model.dynamic_quantize()?;
+        assert!(model.nodes().iter().any(|n| n.op_is::<DynamicQuantizeLinearU8>()));
+        let found = SimplePlan::new(&model)?.run(tvec!(input))?.remove(0);
+
+        let expected = expected.to_array_view::<f32>()?;
+        let found = found.to_array_view::<f32>()?;
+        let tolerance = expected.iter().fold(0f32, |m, x| m.max(x.abs())) * 0.02;
+        for (e, f) in expected.iter().zip(found.iter()) {
+            assert!((e - f).abs() < tolerance, "expected {} found {}", expected, found);
+        }
+        Ok(())
+    }
+
+    fn input() -> Tensor {
+        tensor2(&[[0.5f32, -1.2, 2.], [0., 0.3, -0.7], [1.1, 1.2, -0.1]])
+    }
+
+    fn batched_input() -> Tensor {
+        tensor3(&[
+            [[0.5f32, -1.2, 2.], [0., 0.3, -0.7], [1.1, 1.2, -0.1]],
+            [[-0.4f32, 0.8, 1.5], [2.1, -0.3, 0.2], [0.6, -1.7, 0.9]],
+        ])
+    }
+
+    #[test]
+    fn dynamic_quant_weights_as_a() -> TractResult<()> {
+        check(0, input())
+    }
+
+    #[test]
+    fn dynamic_quant_weights_as_b() -> TractResult<()> {
+        check(1, input())
+    }
+
+    #[test]
+    fn dynamic_quant_batched_weights_as_a() -> TractResult<()> {
+        check(0, batched_input())
+    }
+
+    #[test]
+    fn dynamic_quant_batched_weights_as_b() -> TractResult<()> {
+        check(1, batched_input())
+    }
+
+    #[test]
+    fn declutter_keeps_float_matmul() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [2, 3].as_ref())?)?;
+        let w = model.add_const("w", tensor2(&[[12u8, 200], [0, 255], [90, 140]]))?;
+        let w = model.wire_node("dequant", DequantizeLinearF32::new(0.02, 128), &[w])?[0];
+        let y = model.wire_node("mm", MatMul::default(), &[x, w])?;
+        model.set_output_outlets(&y)?;
+        let model = model.declutter()?;
+        assert!(!model.nodes().iter().any(|n| n.op_is::<DynamicQuantizeLinearU8>()));
+        Ok(())
+    }
+}
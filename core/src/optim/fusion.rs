@@ -0,0 +1,29 @@
+use crate::internal::*;
+use crate::optim::TypedPass;
+
+/// Applies the fusions registered on the model.
+#[derive(Debug)]
+pub struct Fusions;
+
+impl TypedPass for Fusions {
+    fn pass(&self, model: &mut TypedModel) -> TractResult<bool> {
+        if model.fusions.is_empty() {
+            return Ok(false);
+        }
+        let fusions = model.fusions.clone();
+        let mut done_something = false;
+        'restart: loop {
+            for id in model.eval_order()? {
+                for fusion in &fusions {
+                    if let Some(patch) = fusion.patch(model, model.node(id))? {
+                        debug!("Apply {:?} on {}", fusion, model.node(id));
+                        patch.apply(model)?;
+                        done_something = true;
+                        continue 'restart;
+                    }
+                }
+            }
+            return Ok(done_something);
+        }
+    }
+}
@@ -1,12 +1,17 @@
 use crate::model::*;
 use crate::TractResult;
 use std::fmt::Debug;
+use tract_linalg::{KernelSelector, Ops};
 
 pub mod change_axes;
+mod dynamic_quant;
+mod fusion;
 mod prop_const;
 mod push_split_down;
 
 use self::change_axes::ChangeAxes;
+pub use self::dynamic_quant::DynamicQuantizeMatMul;
+use self::fusion::Fusions;
 use self::prop_const::PropConst;
 use self::push_split_down::PushSplitDown;
 
@@ -18,6 +23,7 @@ pub trait TypedPass: Debug + Send + Sync {
 
 pub fn declutter() -> Vec<Box<dyn TypedPass>> {
     vec![
+        Box::new(Fusions),
         Box::new(PropConst),
         Box::new(OpOptim("declutter", TypedOp::declutter)),
         Box::new(PushSplitDown),
@@ -25,9 +31,40 @@ pub fn declutter() -> Vec<Box<dyn TypedPass>> {
     ]
 }
 
+/// Options of the codegen passes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CodegenConfig {
+    /// Upper bound on the size of the im2col buffer of convolutions, in
+    /// bytes. Convolutions that would need a bigger buffer are computed
+    /// directly on their input.
+    pub max_im2col_bytes: Option<usize>,
+    /// Kernels for the matrix multiplications. Defaults to the ones of
+    /// `tract_linalg::ops()`.
+    pub kernel_selector: Option<KernelSelector>,
+}
+
+impl CodegenConfig {
+    /// The kernels picked by `kernel_selector`, or `tract_linalg::ops()`.
+    pub fn linalg_ops(&self) -> &'static Ops {
+        self.kernel_selector.as_ref().map(tract_linalg::ops_for).unwrap_or_else(tract_linalg::ops)
+    }
+}
+
 pub fn codegen() -> Vec<Box<dyn TypedPass>> {
+    codegen_with_config(&CodegenConfig::default())
+}
+
+pub fn codegen_with_config(config: &CodegenConfig) -> Vec<Box<dyn TypedPass>> {
+    vec![
+        Box::new(Codegen(config.clone())),
+        Box::new(PushSplitDown),
+        Box::new(OpOptim("fuse", TypedOp::fuse)),
+    ]
+}
+
+pub fn codegen_for_size() -> Vec<Box<dyn TypedPass>> {
     vec![
-        Box::new(OpOptim("codegen", TypedOp::codegen)),
+        Box::new(OpOptim("codegen_for_size", TypedOp::codegen_for_size)),
         Box::new(PushSplitDown),
         Box::new(OpOptim("fuse", TypedOp::fuse)),
     ]
@@ -50,36 +87,54 @@ impl std::fmt::Debug for OpOptim {
 
 impl TypedPass for OpOptim {
     fn pass(&self, model: &mut TypedModel) -> TractResult<bool> {
-        let mut hashset = std::collections::HashSet::new();
-        let initial = model.signature();
-        hashset.insert(initial);
-
-        let mut new = model.clone();
-        loop {
-            for id in new.eval_order()? {
-                let reduced = {
-                    let node = &new.nodes()[id];
-                    (self.1)(node.op.as_ref(), &new, node)
-                        .chain_err(|| format!("{:?} node {}", self, node))?
-                };
-                if let Some(red) = reduced {
-                    debug!("Apply a model patch for {:?} {}", self, new.nodes()[id]);
-                    red.apply(&mut new)?;
-                    if cfg!(debug_assertions) {
-                        new.check_edges()?;
-                    }
+        optimize_ops(self, model, &self.1)
+    }
+}
+
+#[derive(Debug)]
+struct Codegen(CodegenConfig);
+
+impl TypedPass for Codegen {
+    fn pass(&self, model: &mut TypedModel) -> TractResult<bool> {
+        optimize_ops(self, model, &|op, model, node| op.codegen_with_config(model, node, &self.0))
+    }
+}
+
+/// Apply `optim` to every node until the model stops changing.
+fn optimize_ops(
+    pass: &dyn Debug,
+    model: &mut TypedModel,
+    optim: &dyn Fn(&dyn TypedOp, &TypedModel, &TypedNode) -> TractResult<Option<TypedModelPatch>>,
+) -> TractResult<bool> {
+    let mut hashset = std::collections::HashSet::new();
+    let initial = model.signature();
+    hashset.insert(initial);
+
+    let mut new = model.clone();
+    loop {
+        for id in new.eval_order()? {
+            let reduced = {
+                let node = &new.nodes()[id];
+                optim(node.op.as_ref(), &new, node)
+                    .chain_err(|| format!("{:?} node {}", pass, node))?
+            };
+            if let Some(red) = reduced {
+                debug!("Apply a model patch for {:?} {}", pass, new.nodes()[id]);
+                red.apply(&mut new)?;
+                if cfg!(debug_assertions) {
+                    new.check_edges()?;
                 }
             }
+        }
 
-            new = crate::model::compact::compact(&new)?;
-            let sig = new.signature();
-            if hashset.contains(&sig) {
-                break;
-            } else {
-                hashset.insert(sig);
-            }
+        new = crate::model::compact::compact(&new)?;
+        let sig = new.signature();
+        if hashset.contains(&sig) {
+            break;
+        } else {
+            hashset.insert(sig);
         }
-        std::mem::swap(model, &mut new);
-        Ok(model.signature() != initial)
     }
+    std::mem::swap(model, &mut new);
+    Ok(model.signature() != initial)
 }
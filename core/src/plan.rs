@@ -10,6 +10,8 @@ use crate::model::{Fact, ModelImpl, OutletId};
 pub struct SessionState {
     pub inputs: HashMap<usize, Arc<Tensor>>,
     pub known_stream_len: Option<usize>,
+    /// Values of the symbols other than the streaming one.
+    pub resolved_symbols: HashMap<char, i64>,
     pub tensors: HashMap<String, Tensor>,
 }
 
@@ -411,3 +413,25 @@ where
     // println!("{} {:?}", node, r);
     r
 }
+
+// checks the portable path, with generic kernels only
+#[cfg(all(test, feature = "wasm"))]
+mod test {
+    use super::*;
+    use crate::ops::matmul::MatMulUnary;
+
+    #[test]
+    fn run_tiny_model() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [2, 2].as_ref())?;
+        let x = model.add_source("x", fact)?;
+        let a = rctensor2(&[[1f32, 2.], [3., 4.]]);
+        let y = model.wire_node("mm", MatMulUnary::new(a, false, false, false, None), &[x])?;
+        let y = model.wire_node("add", crate::ops::math::add::unary(rctensor2(&[[1f32]])), &y)?;
+        model.set_output_outlets(&y)?;
+        let model = model.into_optimized()?;
+        let output = SimplePlan::new(&model)?.run(tvec!(tensor2(&[[1f32, 0.], [0., 1.]])))?;
+        assert_eq!(*output[0], tensor2(&[[2f32, 3.], [4., 5.]]));
+        Ok(())
+    }
+}
@@ -120,6 +120,32 @@ impl TypedOp for Delay {
         Ok(tvec!(fact))
     }
 
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        // a delay without overlap feeding this one can be absorbed: overlapping
+        // pulses do not form a contiguous stream, so the other way round can not.
+        let prec = model.node(node.inputs[0].node);
+        if let Some(other) = prec.op_as::<Delay>() {
+            if other.axis == self.axis
+                && other.overlap == 0
+                && prec.outputs[0].successors.len() == 1
+            {
+                let mut op = self.clone();
+                op.delay += other.delay;
+                op.buffer_shape[op.axis] = op.delay + op.overlap;
+                let mut patch = TypedModelPatch::default();
+                let tap = patch.tap_model(model, prec.inputs[0])?;
+                let wire = patch.wire_node(&*node.name, op, &[tap])?[0];
+                patch.shunt_outside(model, OutletId::new(node.id, 0), wire)?;
+                return Ok(Some(patch));
+            }
+        }
+        Ok(None)
+    }
+
     fn cost(&self, _inputs: &[&TypedFact]) -> TractResult<TVec<(Cost, TDim)>> {
         Ok(tvec!((
             Cost::Buffer(self.datum_type),
@@ -221,4 +247,41 @@ mod test {
             assert_eq!(&output[0].as_slice::<u8>().unwrap()[skip..], &expect[skip..]);
         }
     }
+
+    #[test]
+    fn two_delays_merge() -> TractResult<()> {
+        let pulse = 4;
+        let mut model = PulsedModel::default();
+        let fact_0 = PulsedFact {
+            datum_type: u8::datum_type(),
+            shape: tvec![pulse],
+            axis: 0,
+            dim: TDim::s(),
+            delay: 0,
+        };
+        let source = model.add_source("source", fact_0.clone())?;
+        let delay_1 = model.wire_node("delay-1", Delay::new(&fact_0, 2, 0), &[source])?[0];
+        let fact_1 = model.outlet_fact(delay_1)?.clone();
+        let delay_2 = model.wire_node("delay-2", Delay::new(&fact_1, 2, 1), &[delay_1])?;
+        model.set_output_outlets(&delay_2)?;
+
+        let typed = model.into_typed()?;
+        let decluttered = typed.clone().declutter()?;
+        let delays: Vec<&Delay> =
+            decluttered.nodes().iter().filter_map(|n| n.op_as::<Delay>()).collect();
+        assert_eq!(delays.len(), 1);
+        assert_eq!((delays[0].delay, delays[0].overlap), (4, 1));
+        assert_eq!(delays[0].buffer_shape, tvec!(5));
+
+        let mut reference = crate::plan::SimpleState::new(SimplePlan::new(typed)?)?;
+        let mut merged = crate::plan::SimpleState::new(SimplePlan::new(decluttered)?)?;
+        for i in 0..5 {
+            let input: Vec<u8> = (pulse * i..(pulse * (i + 1))).map(|a| a as u8).collect();
+            let expected = reference.run(tvec!(Tensor::from(arr1(&input))))?;
+            let found = merged.run(tvec!(Tensor::from(arr1(&input))))?;
+            let skip = 5usize.saturating_sub(i * pulse).min(pulse + 1);
+            assert_eq!(&found[0].as_slice::<u8>()?[skip..], &expected[0].as_slice::<u8>()?[skip..]);
+        }
+        Ok(())
+    }
 }
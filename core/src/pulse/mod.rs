@@ -1,8 +1,12 @@
 use crate::internal::*;
 use crate::model::translator::Translate;
+use std::collections::BTreeSet;
 use std::fmt;
 
 pub mod delay;
+mod session;
+
+pub use session::StreamingSession;
 
 #[derive(Clone, PartialEq, Hash)]
 pub struct PulsedFact {
@@ -60,9 +64,21 @@ impl PulsedFact {
     pub fn from_tensor_fact_pulse(tf: &NormalizedFact, pulse: usize) -> TractResult<PulsedFact> {
         let datum_type = tf.datum_type;
         let stream =
-            tf.shape.stream_info.as_ref().ok_or("Can not pulse a tensor with no streaming dim")?;
-        let shape =
-            tf.shape.iter().map(|d| d.to_integer().map(|d| d as usize).unwrap_or(pulse)).collect();
+            tf.shape.stream_info().ok_or("Can not pulse a tensor with no streaming dim")?;
+        let shape = tf
+            .shape
+            .iter()
+            .enumerate()
+            .map(|(ix, d)| {
+                if ix == stream.axis {
+                    Ok(pulse)
+                } else {
+                    Ok(d.to_usize().map_err(|_| {
+                        format!("Can not pulse a tensor with symbolic dim {:?} on axis {}", d, ix)
+                    })?)
+                }
+            })
+            .collect::<TractResult<_>>()?;
         Ok(PulsedFact { datum_type, shape, axis: stream.axis, dim: stream.len.clone(), delay: 0 })
     }
 
@@ -83,9 +99,7 @@ impl PulsedFact {
     }
 
     pub fn to_streaming_fact(&self) -> NormalizedFact {
-        let mut info = self.to_pulse_fact();
-        info.shape.stream_info = Some(StreamFact { axis: self.axis, len: self.dim.clone() });
-        info
+        NormalizedFact::dt_shape(self.datum_type, &*self.streaming_shape()).unwrap()
     }
 }
 
@@ -104,11 +118,64 @@ impl PulsedModel {
         Pulsifier(pulse).translate_model_with_mappings(source)
     }
 
+    /// Delay of each output, in frames along its streaming axis.
+    pub fn output_delays(&self) -> TractResult<Vec<usize>> {
+        self.output_outlets()?.iter().map(|&o| Ok(self.outlet_fact(o)?.delay)).collect()
+    }
+
+    /// Symbols naming the streaming axes of the inputs.
+    pub fn stream_symbols(&self) -> TractResult<BTreeSet<char>> {
+        let mut symbols = BTreeSet::new();
+        for &input in self.input_outlets()? {
+            symbols.extend(self.outlet_fact(input)?.dim.symbols());
+        }
+        Ok(symbols)
+    }
+
+    /// Binds the symbols to their value in `symbols`, and the streaming
+    /// symbols missing from it to `stream_len`, for evaluating output lengths
+    /// with `TDim::eval_with`.
+    ///
+    /// All inputs are pulsed in lockstep, so whether they stream along `S`,
+    /// `N` or both, the streaming symbols resolve to the same length.
+    pub fn stream_resolver(
+        &self,
+        stream_len: usize,
+        symbols: &HashMap<char, i64>,
+    ) -> TractResult<HashMap<char, i64>> {
+        let mut resolver = symbols.clone();
+        for s in self.stream_symbols()? {
+            resolver.entry(s).or_insert(stream_len as i64);
+        }
+        Ok(resolver)
+    }
+
     pub fn into_typed(self) -> TractResult<TypedModel> {
         crate::model::translator::IntoTranslator.translate_model(&self)
     }
 }
 
+/// Evaluates a length along the streaming axis, once the stream length is
+/// known. Symbols bound in `symbols` take their value, and the one left, if
+/// any, names the streaming axis.
+pub fn eval_stream_dim(
+    dim: &TDim,
+    stream_len: usize,
+    symbols: &HashMap<char, i64>,
+) -> TractResult<usize> {
+    let mut resolver = symbols.clone();
+    let unbound =
+        dim.symbols().into_iter().filter(|s| !symbols.contains_key(s)).collect::<Vec<_>>();
+    match &*unbound {
+        [] => (),
+        [stream] => {
+            resolver.insert(*stream, stream_len as i64);
+        }
+        _ => bail!("Can not evaluate {:?}, symbols {:?} are unbound", dim, unbound),
+    }
+    Ok(dim.eval_with(&resolver)?.max(0) as usize)
+}
+
 #[derive(Debug)]
 struct Pulsifier(usize);
 impl
@@ -188,4 +255,42 @@ mod tests {
             TypedFact::dt_shape(DatumType::F32, &*vec!(4, 2, 3)).unwrap()
         );
     }
+
+    #[test]
+    fn test_output_delays() {
+        let mut model = PulsedModel::default();
+        let fact = PulsedFact {
+            datum_type: f32::datum_type(),
+            shape: tvec![4, 2],
+            axis: 0,
+            dim: TDim::s(),
+            delay: 0,
+        };
+        let source = model.add_source("source", fact.clone()).unwrap();
+        model.wire_node("delay", delay::Delay::new(&fact, 4, 0), &[source]).unwrap();
+        model.auto_outputs().unwrap();
+        assert_eq!(model.output_delays().unwrap(), vec![4]);
+    }
+
+    #[test]
+    fn stream_dim_with_other_symbols() -> TractResult<()> {
+        let mut model = PulsedModel::default();
+        let fact = PulsedFact {
+            datum_type: f32::datum_type(),
+            shape: tvec![4],
+            axis: 0,
+            dim: TDim::s(),
+            delay: 0,
+        };
+        model.add_source("source", fact)?;
+        let symbols: HashMap<char, i64> = std::iter::once(('N', 3)).collect();
+        let resolver = model.stream_resolver(10, &symbols)?;
+        assert_eq!(resolver[&'S'], 10);
+        assert_eq!(resolver[&'N'], 3);
+        let dim = TDim::s() * 2 + TDim::sym('N');
+        assert_eq!(dim.eval_with(&resolver)?, 23);
+        assert_eq!(eval_stream_dim(&dim, 10, &symbols)?, 23);
+        assert!(eval_stream_dim(&dim, 10, &HashMap::new()).is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,200 @@
+use crate::internal::*;
+use crate::plan::{SimplePlan, SimpleState};
+
+type PulsedPlan = SimplePlan<PulsedFact, Box<dyn PulsedOp>, PulsedModel>;
+
+/// Runs a pulsed model one pulse at a time.
+///
+/// Every input is expected to be exactly one pulse long. The outputs are
+/// stripped of the frames introduced by the model delay, so concatenating
+/// what `push` and `finish` return gives the output of the equivalent
+/// typed model run on the whole stream.
+#[derive(Debug)]
+pub struct StreamingSession {
+    state: SimpleState<PulsedFact, Box<dyn PulsedOp>, PulsedModel, Arc<PulsedPlan>>,
+    position: usize,
+    produced: TVec<usize>,
+}
+
+impl StreamingSession {
+    pub fn new(model: PulsedModel) -> TractResult<StreamingSession> {
+        let outputs = model.output_outlets()?.len();
+        let state = SimpleState::new(Arc::new(SimplePlan::new(model)?))?;
+        Ok(StreamingSession { state, position: 0, produced: tvec!(0; outputs) })
+    }
+
+    pub fn model(&self) -> &PulsedModel {
+        self.state.model()
+    }
+
+    /// Binds a symbol other than the streaming one, for the lengths depending
+    /// on it.
+    pub fn set_symbol(&mut self, symbol: char, value: i64) {
+        self.state.session_state.resolved_symbols.insert(symbol, value);
+    }
+
+    /// Number of stream frames fed so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Feeds one pulse for each input, returns the output frames that are
+    /// now available.
+    pub fn push(&mut self, inputs: TVec<Tensor>) -> TractResult<TVec<Tensor>> {
+        let pulse = self.model().input_fact(0)?.pulse();
+        for (ix, input) in inputs.iter().enumerate() {
+            let fact = self.model().input_fact(ix)?;
+            if input.shape() != &*fact.shape {
+                bail!("Input #{} expected to be {:?}, got {:?}", ix, fact, input.shape());
+            }
+        }
+        let outputs = self.state.run(inputs)?;
+        self.position += pulse;
+        self.trim(outputs, None)
+    }
+
+    /// Ends the stream, flushing the frames still buffered in the model.
+    pub fn finish(mut self) -> TractResult<TVec<Tensor>> {
+        let stream_len = self.position;
+        let model = self.model();
+        let resolver =
+            model.stream_resolver(stream_len, &self.state.session_state.resolved_symbols)?;
+        let ends = model
+            .output_outlets()?
+            .iter()
+            .map(|&o| {
+                let fact = model.outlet_fact(o)?;
+                let len = fact.dim.eval_with(&resolver)?;
+                Ok(fact.delay + len.max(0) as usize)
+            })
+            .collect::<TractResult<TVec<usize>>>()?;
+        let zeros = model
+            .input_outlets()?
+            .iter()
+            .map(|&i| {
+                let fact = model.outlet_fact(i)?;
+                Tensor::zero_dt(fact.datum_type, &*fact.shape)
+            })
+            .collect::<TractResult<TVec<Tensor>>>()?;
+        let mut chunks: Vec<TVec<Tensor>> = vec![];
+        while self.produced.iter().zip(ends.iter()).any(|(p, e)| p < e) {
+            let outputs = self.state.run(zeros.clone())?;
+            chunks.push(self.trim(outputs, Some(&ends))?);
+        }
+        (0..ends.len())
+            .map(|ix| {
+                let axis = self.model().output_fact(ix)?.axis;
+                if chunks.is_empty() {
+                    let fact = self.model().output_fact(ix)?;
+                    let mut shape = fact.shape.clone();
+                    shape[axis] = 0;
+                    unsafe { Tensor::uninitialized_dt(fact.datum_type, &shape) }
+                } else {
+                    Tensor::stack_tensors(axis, &chunks.iter().map(|c| &c[ix]).collect::<Vec<_>>())
+                }
+            })
+            .collect()
+    }
+
+    fn trim(
+        &mut self,
+        outputs: TVec<Arc<Tensor>>,
+        ends: Option<&[usize]>,
+    ) -> TractResult<TVec<Tensor>> {
+        outputs
+            .into_iter()
+            .enumerate()
+            .map(|(ix, output)| {
+                let fact = self.state.model().output_fact(ix)?;
+                let len = output.shape()[fact.axis];
+                let start = fact.delay.saturating_sub(self.produced[ix]).min(len);
+                let end = ends
+                    .map(|e| e[ix].saturating_sub(self.produced[ix]).min(len))
+                    .unwrap_or(len)
+                    .max(start);
+                self.produced[ix] += len;
+                output.slice(fact.axis, start, end)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::cnn::{ConvUnary, KernelFormat, PaddingSpec, PoolSpec};
+    use crate::ops::nn::DataFormat::HWC;
+    use ndarray::*;
+
+    fn conv_model(len: TDim) -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let fact = TypedFact::dt_shape(f32::datum_type(), [len, 1.to_dim()].as_ref())?;
+        let source = model.add_source("source", fact)?;
+        let pool_spec =
+            PoolSpec::new(HWC, tvec!(3), PaddingSpec::Valid, Some(tvec!(2)), None, Some(1));
+        let kernel = rctensor3(&[[[1f32]], [[2f32]], [[3f32]]]);
+        let conv = ConvUnary::new(pool_spec, KernelFormat::HWIO, kernel, 1, None, None);
+        let wire = model.wire_node("conv", conv, &[source])?;
+        model.set_output_outlets(&wire)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn pulse_by_pulse() -> TractResult<()> {
+        let signal: Vec<f32> = (0..24).map(|x| (x as f32).sin()).collect();
+        let expected = SimplePlan::new(conv_model(signal.len().to_dim())?)?
+            .run(tvec!(Array::from_shape_vec((signal.len(), 1), signal.clone())?.into_tensor()))?;
+
+        let pulsed = PulsedModel::new(&conv_model(TDim::s())?.into_normalized()?, 4)?;
+        let mut session = StreamingSession::new(pulsed)?;
+        let mut chunks = vec![];
+        for chunk in signal.chunks(4) {
+            let chunk = Array::from_shape_vec((4, 1), chunk.to_vec())?.into_tensor();
+            chunks.push(session.push(tvec!(chunk))?);
+        }
+        assert_eq!(session.position(), 24);
+        chunks.push(session.finish()?);
+        let found = Tensor::stack_tensors(0, &chunks.iter().map(|c| &c[0]).collect::<Vec<_>>())?;
+        found.close_enough(&expected[0], true)?;
+        Ok(())
+    }
+
+    #[test]
+    fn pulse_along_other_symbol() -> TractResult<()> {
+        let pulsed = PulsedModel::new(&conv_model(TDim::sym('N'))?.into_normalized()?, 4)?;
+        assert_eq!(pulsed.stream_symbols()?.into_iter().collect::<Vec<_>>(), vec!['N']);
+        let mut session = StreamingSession::new(pulsed)?;
+        let mut len = session.push(tvec!(Tensor::zero::<f32>(&[4, 1])?))?[0].len();
+        len += session.push(tvec!(Tensor::zero::<f32>(&[4, 1])?))?[0].len();
+        len += session.finish()?[0].len();
+        assert_eq!(len, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn finish_flushes_delay() -> TractResult<()> {
+        let mut model = PulsedModel::default();
+        let fact = PulsedFact {
+            datum_type: i32::datum_type(),
+            shape: tvec![4],
+            axis: 0,
+            dim: TDim::s(),
+            delay: 0,
+        };
+        let source = model.add_source("source", fact.clone())?;
+        let delay = crate::pulse::delay::Delay::new(&fact, 5, 0);
+        model.wire_node("delay", delay, &[source])?;
+        model.auto_outputs()?;
+
+        let mut session = StreamingSession::new(model)?;
+        let mut chunks = vec![];
+        for i in 0..3 {
+            chunks.push(session.push(tvec!(tensor1(&[4 * i, 4 * i + 1, 4 * i + 2, 4 * i + 3])))?);
+        }
+        assert_eq!(chunks.iter().map(|c| c[0].len()).sum::<usize>(), 7);
+        chunks.push(session.finish()?);
+        let found = Tensor::stack_tensors(0, &chunks.iter().map(|c| &c[0]).collect::<Vec<_>>())?;
+        assert_eq!(found, tensor1(&(0..12).collect::<Vec<i32>>()));
+        Ok(())
+    }
+}
@@ -115,6 +115,9 @@ impl Tensor {
     }
 
     /// Create an uninitialized tensor with a given alignment (in bytes).
+    ///
+    /// With the `safe-alloc` feature, the memory is zeroed instead, so that
+    /// runs under valgrind or sanitizers do not trip on uninitialized reads.
     pub unsafe fn uninitialized_aligned_dt(
         dt: DatumType,
         shape: &[usize],
@@ -130,7 +133,11 @@ impl Tensor {
         let data = if bytes == 0 {
             std::ptr::null()
         } else {
-            let ptr = alloc::alloc(layout);
+            let ptr = if cfg!(feature = "safe-alloc") {
+                alloc::alloc_zeroed(layout)
+            } else {
+                alloc::alloc(layout)
+            };
             assert!(!ptr.is_null());
             ptr
         } as *mut u8;
@@ -518,6 +525,16 @@ impl Tensor {
         casted.to_scalar::<D>().map(|&x| x)
     }
 
+    /// Copy the data out as a vector of f32, after a cast.
+    pub fn to_f32_vec(&self) -> TractResult<Vec<f32>> {
+        Ok(self.cast_to::<f32>()?.as_slice::<f32>()?.to_vec())
+    }
+
+    /// Create a f32 tensor of the given shape from a slice.
+    pub fn from_f32_vec(shape: &[usize], data: &[f32]) -> TractResult<Tensor> {
+        Ok(ArrayD::from_shape_vec(shape, data.to_vec())?.into_tensor())
+    }
+
     /// Strict equality test on tensors.
     fn eq_t<D: Datum>(&self, other: &Tensor) -> TractResult<bool> {
         Ok(self.to_array_view::<D>()? == other.to_array_view::<D>()?)
@@ -578,6 +595,142 @@ impl Tensor {
         }
         dispatch_datum!(slice_t(self.datum_type())(&self, axis, start, end))
     }
+
+    /// Split the tensor along `axis` in consecutive chunks of `sizes`, which
+    /// must add up to the axis length.
+    pub fn split(&self, axis: usize, sizes: &[usize]) -> TractResult<Vec<Tensor>> {
+        if axis >= self.rank() || sizes.iter().sum::<usize>() != self.shape()[axis] {
+            bail!("Can not split tensor {:?} at axis {} in {:?}", self, axis, sizes);
+        }
+        let mut current = 0;
+        sizes
+            .iter()
+            .map(|&size| {
+                current += size;
+                self.slice(axis, current - size, current)
+            })
+            .collect()
+    }
+
+    /// Permute the axes of the tensor: axis `i` of the result is axis
+    /// `axes[i]` of `self`. The result is in standard layout.
+    pub fn permute_axes(&self, axes: &[usize]) -> TractResult<Tensor> {
+        let mut sorted = axes.to_vec();
+        sorted.sort();
+        if sorted != (0..self.rank()).collect::<Vec<_>>() {
+            bail!("Can not permute axes of tensor {:?} with {:?}", self, axes);
+        }
+        fn permute_axes_t<T: Datum>(t: &Tensor, axes: &[usize]) -> TractResult<Tensor> {
+            let view = t.to_array_view::<T>()?.permuted_axes(axes);
+            let shape = view.shape().to_vec();
+            Ok(ndarray::ArrayD::from_shape_vec(shape, view.iter().cloned().collect())?
+                .into_tensor())
+        }
+        dispatch_datum!(permute_axes_t(self.datum_type())(&self, axes))
+    }
+
+    /// Broadcast the tensor to `shape`, following numpy rules.
+    pub fn broadcast_to(&self, shape: &[usize]) -> TractResult<Tensor> {
+        fn broadcast_to_t<T: Datum>(t: &Tensor, shape: &[usize]) -> TractResult<Tensor> {
+            let view = t.to_array_view::<T>()?;
+            let broadcast = view.broadcast(shape).ok_or_else(|| {
+                format!("Can not broadcast tensor of shape {:?} to {:?}", t.shape(), shape)
+            })?;
+            Ok(broadcast.to_owned().into_tensor())
+        }
+        dispatch_datum!(broadcast_to_t(self.datum_type())(&self, shape))
+    }
+
+    /// Indices, as i64, sorting the tensor along `axis` in ascending or
+    /// descending order. The sort is stable: ties keep their order. NaNs come
+    /// last in both orders.
+    pub fn argsort_along(&self, axis: usize, descending: bool) -> TractResult<Tensor> {
+        if axis >= self.rank() {
+            bail!("Can not sort tensor {:?} along axis {}", self, axis);
+        }
+        fn argsort_t<T: Datum + PartialOrd>(
+            t: &Tensor,
+            axis: usize,
+            descending: bool,
+        ) -> TractResult<Tensor> {
+            let view = t.to_array_view::<T>()?;
+            let mut indices = ArrayD::<i64>::zeros(view.shape());
+            for (lane, mut sorted) in
+                view.lanes(Axis(axis)).into_iter().zip(indices.lanes_mut(Axis(axis)))
+            {
+                let mut order: Vec<usize> = (0..lane.len()).collect();
+                // only NaN is not comparable to itself
+                let is_nan = |x: &T| x.partial_cmp(x).is_none();
+                order.sort_by(|&a, &b| match (is_nan(&lane[a]), is_nan(&lane[b])) {
+                    (true, true) => std::cmp::Ordering::Equal,
+                    (true, false) => std::cmp::Ordering::Greater,
+                    (false, true) => std::cmp::Ordering::Less,
+                    (false, false) => {
+                        let ord = lane[a].partial_cmp(&lane[b]).unwrap();
+                        if descending {
+                            ord.reverse()
+                        } else {
+                            ord
+                        }
+                    }
+                });
+                sorted.iter_mut().zip(order).for_each(|(s, o)| *s = o as i64);
+            }
+            Ok(indices.into_tensor())
+        }
+        dispatch_numbers!(argsort_t(self.datum_type())(&self, axis, descending))
+    }
+
+    /// Sort the tensor along `axis` in ascending or descending order.
+    pub fn sort_along(&self, axis: usize, descending: bool) -> TractResult<Tensor> {
+        let indices = self.argsort_along(axis, descending)?;
+        fn gather_t<T: Datum>(t: &Tensor, indices: &Tensor, axis: usize) -> TractResult<Tensor> {
+            let view = t.to_array_view::<T>()?;
+            let indices = indices.to_array_view::<i64>()?;
+            let mut sorted = view.to_owned();
+            for ((lane, order), mut sorted) in view
+                .lanes(Axis(axis))
+                .into_iter()
+                .zip(indices.lanes(Axis(axis)))
+                .zip(sorted.lanes_mut(Axis(axis)))
+            {
+                sorted.iter_mut().zip(order).for_each(|(s, &o)| *s = lane[o as usize].clone());
+            }
+            Ok(sorted.into_tensor())
+        }
+        dispatch_numbers!(gather_t(self.datum_type())(&self, &indices, axis))
+    }
+
+    /// Quantize a float tensor to u8: `round(x / scale) + zero_point`,
+    /// saturated.
+    pub fn quantize_u8(&self, scale: f32, zero_point: u8) -> TractResult<Tensor> {
+        crate::ops::quant::quantize_linear_f32(
+            self,
+            DatumType::U8,
+            scale.recip(),
+            zero_point as i32,
+        )
+    }
+
+    /// Quantize a float tensor to i8: `round(x / scale) + zero_point`,
+    /// saturated.
+    pub fn quantize_i8(&self, scale: f32, zero_point: i8) -> TractResult<Tensor> {
+        crate::ops::quant::quantize_linear_f32(
+            self,
+            DatumType::I8,
+            scale.recip(),
+            zero_point as i32,
+        )
+    }
+
+    /// Dequantize a u8 or i8 tensor to f32: `(q - zero_point) * scale`.
+    pub fn dequantize(&self, scale: f32, zero_point: i32) -> TractResult<Tensor> {
+        if self.dt != DatumType::U8 && self.dt != DatumType::I8 {
+            bail!("Can only dequantize u8 or i8 tensors, got {:?}", self.dt);
+        }
+        let q = self.cast_to::<i32>()?;
+        Ok(q.to_array_view::<i32>()?.mapv(|q| (q - zero_point) as f32 * scale).into_tensor())
+    }
 }
 
 impl PartialEq for Tensor {
@@ -679,3 +832,135 @@ impl IntoArcTensor for Arc<Tensor> {
         self
     }
 }
+
+/// An uninitialized ndarray array, for outputs about to be overwritten.
+///
+/// With the `safe-alloc` feature, it is zeroed instead, like
+/// `Tensor::uninitialized`.
+pub unsafe fn uninitialized_array<T, Sh>(shape: Sh) -> ndarray::Array<T, Sh::Dim>
+where
+    T: Copy,
+    Sh: ndarray::ShapeBuilder,
+{
+    if cfg!(feature = "safe-alloc") {
+        ndarray::Array::from_elem(shape, std::mem::zeroed())
+    } else {
+        ndarray::Array::uninitialized(shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[cfg(feature = "safe-alloc")]
+    #[test]
+    fn safe_alloc_is_zeroed() -> TractResult<()> {
+        for _ in 0..16 {
+            // recycle some dirty memory first
+            let mut dirty = unsafe { Tensor::uninitialized::<f32>(&[1024])? };
+            dirty.as_slice_mut::<f32>()?.iter_mut().for_each(|x| *x = 1.0);
+            drop(dirty);
+            let tensor = unsafe { Tensor::uninitialized::<f32>(&[1024])? };
+            assert!(tensor.as_slice::<f32>()?.iter().all(|x| *x == 0.0));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn f32_vec_round_trip() -> TractResult<()> {
+        let t = tensor2(&[[1i32, 2, 3], [4, 5, 6]]);
+        let data = t.to_f32_vec()?;
+        assert_eq!(data, vec![1f32, 2., 3., 4., 5., 6.]);
+        let back = Tensor::from_f32_vec(t.shape(), &data)?;
+        assert_eq!(back, tensor2(&[[1f32, 2., 3.], [4., 5., 6.]]));
+        assert_eq!(*back.cast_to::<i32>()?, t);
+        assert!(Tensor::from_f32_vec(&[4], &data).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn broadcast_scalar() {
+        let t = tensor0(3f32).broadcast_to(&[2, 3]).unwrap();
+        assert_eq!(t, tensor2(&[[3f32, 3., 3.], [3., 3., 3.]]));
+    }
+
+    #[test]
+    fn broadcast_row() {
+        let t = tensor2(&[[1i32, 2, 3]]).broadcast_to(&[2, 3]).unwrap();
+        assert_eq!(t, tensor2(&[[1i32, 2, 3], [1, 2, 3]]));
+    }
+
+    #[test]
+    fn split() {
+        let t = tensor2(&[[0i32, 1, 2, 3, 4, 5]]);
+        let parts = t.split(1, &[2, 2, 2]).unwrap();
+        assert_eq!(parts, vec![tensor2(&[[0i32, 1]]), tensor2(&[[2, 3]]), tensor2(&[[4, 5]])]);
+        let parts = t.split(1, &[1, 5]).unwrap();
+        assert_eq!(parts, vec![tensor2(&[[0i32]]), tensor2(&[[1, 2, 3, 4, 5]])]);
+        assert!(t.split(1, &[2, 2]).is_err());
+    }
+
+    #[test]
+    fn permute_axes() {
+        let data: Vec<i32> = (0..24).collect();
+        let t = unsafe { tensor1(&data).into_shape(&[2, 3, 4]).unwrap() };
+        let p = t.permute_axes(&[2, 0, 1]).unwrap();
+        assert_eq!(p.shape(), &[4, 2, 3]);
+        // p[k, i, j] = t[i, j, k] = 12 * i + 4 * j + k
+        assert_eq!(p.to_array_view::<i32>().unwrap()[[3, 1, 2]], 12 + 8 + 3);
+        assert_eq!(p.as_slice::<i32>().unwrap()[..3], [0, 4, 8]);
+        assert!(t.permute_axes(&[0, 0, 1]).is_err());
+    }
+
+    #[test]
+    fn sort_along_rows() {
+        let t = tensor2(&[[3i32, 1, 2, 1], [0, 5, 5, 4]]);
+        assert_eq!(t.argsort_along(1, false).unwrap(), tensor2(&[[1i64, 3, 2, 0], [0, 3, 1, 2]]));
+        assert_eq!(t.sort_along(1, false).unwrap(), tensor2(&[[1i32, 1, 2, 3], [0, 4, 5, 5]]));
+        assert_eq!(t.argsort_along(1, true).unwrap(), tensor2(&[[0i64, 2, 1, 3], [1, 2, 3, 0]]));
+        assert_eq!(t.sort_along(1, true).unwrap(), tensor2(&[[3i32, 2, 1, 1], [5, 5, 4, 0]]));
+        assert!(t.sort_along(2, false).is_err());
+    }
+
+    #[test]
+    fn sort_along_with_nans() {
+        let nan = std::f32::NAN;
+        let t = tensor1(&[2f32, nan, 0., 1., nan, 3.]);
+        assert_eq!(t.argsort_along(0, false).unwrap(), tensor1(&[2i64, 3, 0, 5, 1, 4]));
+        assert_eq!(t.argsort_along(0, true).unwrap(), tensor1(&[5i64, 0, 3, 2, 1, 4]));
+    }
+
+    #[test]
+    fn sort_along_columns() {
+        let t = tensor2(&[[2f32, 0.], [1., 3.]]);
+        assert_eq!(t.sort_along(0, false).unwrap(), tensor2(&[[1f32, 0.], [2., 3.]]));
+    }
+
+    #[test]
+    fn broadcast_incompatible() {
+        assert!(tensor1(&[1f32, 2.]).broadcast_to(&[2, 3]).is_err());
+    }
+
+    #[test]
+    fn quantize_round_trip() {
+        let values: Vec<f32> = (0..100).map(|i| i as f32 * 0.05 - 2.5).collect();
+        let x = tensor1(&values);
+        let scale = 0.02;
+        let u8_trip = x.quantize_u8(scale, 128).unwrap().dequantize(scale, 128).unwrap();
+        let i8_trip = x.quantize_i8(scale, 0).unwrap().dequantize(scale, 0).unwrap();
+        for trip in &[u8_trip, i8_trip] {
+            for (x, y) in values.iter().zip(trip.as_slice::<f32>().unwrap()) {
+                assert!((x - y).abs() <= scale, "{} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn quantize_saturates() {
+        let q = tensor1(&[-10f32, 10.]).quantize_u8(0.01, 128).unwrap();
+        assert_eq!(q, tensor1(&[0u8, 255]));
+        assert!(tensor1(&[1f32]).dequantize(0.1, 0).is_err());
+    }
+}
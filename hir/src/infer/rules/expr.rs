@@ -1,3 +1,4 @@
+use std::convert::TryFrom;
 use std::fmt;
 use std::marker::PhantomData;
 use std::ops::{Add, Div, Mul, Neg, Sub};
@@ -373,6 +374,7 @@ impl TExp<DimFact> for IntoDimExp {
     fn set(&self, context: &mut Context, value: DimFact) -> TractResult<bool> {
         if let Some(concrete) = value.concretize() {
             if let Ok(int) = concrete.to_integer() {
+                let int = i32::try_from(int).map_err(|_| format!("{} overflows an i32", int))?;
                 return self.0.set(context, GenericFactoid::Only(int));
             }
         }
@@ -23,9 +23,8 @@ impl StatelessOp for MultiBroadcastTo {
     fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
         let (input, dims) = args_2!(inputs);
         let dims: Vec<usize> = dims.to_array_view::<i64>()?.iter().map(|i| *i as usize).collect();
-        let dims = tract_core::broadcast::multi_broadcast(&[&*dims, &*input.shape()])
-            .ok_or("incompatible shapes")?;
-        dispatch_datum!(Typed::eval_t(input.datum_type())(&*input, &*dims))
+        let dims = Typed::broadcast_shape(input.shape(), &*dims)?;
+        Ok(tvec!(input.broadcast_to(&*dims)?.into_arc_tensor()))
     }
 }
 
@@ -43,10 +42,7 @@ impl InferenceRulesOp for MultiBroadcastTo {
         s.given(&inputs[0].shape, move |s, shape| {
             s.given(&inputs[1].value, move |s, dims| {
                 let dims = dims.cast_to::<TDim>()?;
-                let dims =
-                    tract_core::broadcast::multi_broadcast(&[&*dims.as_slice::<TDim>()?, &*shape])
-                        .ok_or("incompatible shapes")
-                        .unwrap();
+                let dims = Typed::broadcast_shape(&*shape, dims.as_slice::<TDim>()?)?;
                 s.equals(&outputs[0].shape, ShapeFactoid::from(dims))
             })
         })
@@ -65,8 +61,7 @@ impl InferenceRulesOp for MultiBroadcastTo {
         ) {
             let shape = shape.cast_to::<TDim>()?;
             let shape = shape.as_slice::<TDim>()?;
-            let dims = tract_core::broadcast::multi_broadcast(&[&*input_shape, shape])
-                .ok_or("incompatible shapes")?;
+            let dims = Typed::broadcast_shape(&*input_shape, shape)?;
             let op = Typed::new(dims.into());
             return target.wire_node(&*node.name, op, [mapping[&node.inputs[0]]].as_ref());
         }
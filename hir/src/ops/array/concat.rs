@@ -13,13 +13,7 @@ tract_linalg::impl_dyn_hash!(Concat);
 
 impl Concat {
     fn resolve_axis(&self, rank: i64) -> TractResult<usize> {
-        if 0 <= self.axis && self.axis <= rank - 1 {
-            Ok(self.axis as usize)
-        } else if -rank <= self.axis && self.axis < 0 {
-            Ok((self.axis + rank) as usize)
-        } else {
-            bail!("Illegal combination of values for rank and axis: {} and {}", rank, self.axis)
-        }
+        tract_core::ops::normalize_axis(self.axis as isize, rank as usize)
     }
 }
 
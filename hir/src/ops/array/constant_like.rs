@@ -53,8 +53,7 @@ impl InferenceRulesOp for ConstantLike {
         s.equals(&inputs[0].shape, &outputs[0].shape)?;
         s.given_2(&inputs[0].shape, &inputs[0].datum_type, move |s, shape, dt| {
             if shape.iter().all(|d| d.to_integer().is_ok()) {
-                let shape: Vec<usize> =
-                    shape.iter().map(|d| d.to_integer().unwrap() as usize).collect();
+                let shape: Vec<usize> = shape.iter().map(|d| d.to_usize().unwrap()).collect();
                 let value = dispatch_numbers!(Self::make(dt)(self, &shape))?;
                 s.equals(&outputs[0].value, value)?;
             }
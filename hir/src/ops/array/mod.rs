@@ -37,3 +37,45 @@ pub use split::Split;
 pub use squeeze::Squeeze;
 pub use strided_slice::StridedSlice;
 pub use tile::Tile;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::infer::*;
+    use crate::internal::*;
+
+    #[test]
+    fn negative_axis_on_rank_3() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let fact = InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(2, 3, 4));
+        let source = model.add_source("source", fact)?;
+        let indices = model.add_const("indices", tensor1(&[0i64, 3]))?;
+        let mut outputs = tvec!();
+        outputs.extend(model.wire_node("concat", Concat::new(-1), &[source, source])?);
+        outputs.extend(model.wire_node("gather", Gather::new(-1), &[source, indices])?);
+        outputs.extend(model.wire_node("split", Split::new(-1, 2, None), &[source])?);
+        outputs.extend(model.wire_node("flatten", Flatten::new(-1), &[source])?);
+        outputs.extend(model.wire_node(
+            "softmax",
+            crate::ops::nn::LayerSoftmax::new(-1, true),
+            &[source],
+        )?);
+        model.set_output_outlets(&outputs)?;
+        let model = model.into_typed()?;
+        let shapes = (0..outputs.len())
+            .map(|ix| Ok(model.output_fact(ix)?.shape.as_finite().unwrap().to_vec()))
+            .collect::<TractResult<Vec<_>>>()?;
+        assert_eq!(
+            shapes,
+            vec![
+                vec![2, 3, 8],
+                vec![2, 3, 2],
+                vec![2, 3, 2],
+                vec![2, 3, 2],
+                vec![6, 4],
+                vec![2, 3, 4]
+            ]
+        );
+        Ok(())
+    }
+}
@@ -25,19 +25,6 @@ impl PermuteAxes {
             Ok(new_shape)
         }
     }
-
-    /// Evaluates the operation given the input tensors.
-    fn eval_t<T: Datum>(&self, input: Arc<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
-        if let Some(ref axes) = self.axes {
-            Ok(tvec![input
-                .into_tensor()
-                .into_array::<T>()?
-                .permuted_axes(&**axes)
-                .into_arc_tensor()])
-        } else {
-            Ok(tvec![input.into_tensor().into_array::<T>()?.reversed_axes().into_arc_tensor()])
-        }
-    }
 }
 
 impl Op for PermuteAxes {
@@ -57,7 +44,12 @@ impl Op for PermuteAxes {
 impl StatelessOp for PermuteAxes {
     fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
         let input = args_1!(inputs);
-        dispatch_datum!(Self::eval_t(input.datum_type())(self, input))
+        let axes = if let Some(ref axes) = self.axes {
+            axes.clone()
+        } else {
+            (0..input.rank()).rev().collect()
+        };
+        Ok(tvec![input.permute_axes(&axes)?.into_arc_tensor()])
     }
 }
 
@@ -20,12 +20,12 @@ impl Reshape {
             .collect();
         if let Some(minus_one) = shape.iter().position(|d| *d == -1) {
             let prod_input: usize =
-                input.iter().try_fold(1, |acc, dim| dim.to_integer().map(|a| a as usize * acc))?;
+                input.iter().try_fold(1, |acc, dim| dim.to_usize().map(|a| a * acc))?;
             let prod_shape: usize = result
                 .iter()
                 .enumerate()
                 .filter(|(ix, _)| *ix != minus_one)
-                .try_fold(1, |acc, (_, dim)| dim.to_integer().map(|a| a as usize * acc))?;
+                .try_fold(1, |acc, (_, dim)| dim.to_usize().map(|a| a * acc))?;
             result[minus_one] = D::from(prod_input / prod_shape);
         }
         Ok(result)
@@ -1,5 +1,6 @@
 use crate::infer::*;
 use crate::internal::*;
+use std::convert::TryFrom;
 
 pub use tract_core::ops::array::Shape;
 
@@ -16,7 +17,7 @@ impl InferenceRulesOp for Shape {
         s.given(&inputs[0].rank, move |s, r| s.equals(&outputs[0].shape[0], r.to_dim()))?;
         s.given(&outputs[0].shape[0], move |s, r| {
             if let Ok(d) = r.to_integer() {
-                s.equals(&inputs[0].rank, d)?;
+                s.equals(&inputs[0].rank, d as i32)?;
             }
             Ok(())
         })?;
@@ -27,15 +28,20 @@ impl InferenceRulesOp for Shape {
                 s.equals(&outputs[0].value, tensor)
             } else if self.dt == DatumType::I64 {
                 s.equals(&outputs[0].datum_type, DatumType::I64)?;
-                let tensor = rctensor1(
-                    &shape.iter().map(|i| i.to_integer().unwrap() as i64).collect::<Vec<_>>(),
-                );
+                let tensor =
+                    rctensor1(&shape.iter().map(|i| i.to_integer().unwrap()).collect::<Vec<_>>());
                 s.equals(&outputs[0].value, tensor)
             } else {
                 s.equals(&outputs[0].datum_type, DatumType::I32)?;
-                let tensor = rctensor1(
-                    &shape.iter().map(|i| i.to_integer().unwrap() as i32).collect::<Vec<_>>(),
-                );
+                let shape = shape
+                    .iter()
+                    .map(|d| {
+                        let i = d.to_integer()?;
+                        i32::try_from(i)
+                            .map_err(|_| format!("Dimension {} overflows i32", i).into())
+                    })
+                    .collect::<TractResult<Vec<i32>>>()?;
+                let tensor = rctensor1(&shape);
                 s.equals(&outputs[0].value, tensor)
             }
         })
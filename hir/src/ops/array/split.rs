@@ -1,10 +1,10 @@
 use crate::infer::*;
 use crate::internal::*;
-use tract_ndarray::*;
+use tract_core::ops::normalize_axis;
 
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct Split {
-    axis: usize,
+    axis: isize,
     outputs: usize,
     split: Option<Vec<usize>>,
 }
@@ -19,26 +19,6 @@ impl Split {
             Ok(tvec!(input/self.outputs;self. outputs))
         }
     }
-
-    fn eval_t<T: Datum>(&self, input: Arc<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
-        let mut current = 0;
-        let input = input.to_array_view::<T>()?;
-        Ok(self
-            .split_dims(input.shape()[self.axis])?
-            .iter()
-            .map(|&d| {
-                let slice = if d > 0 {
-                    input.slice_axis(Axis(self.axis), (current..current + d).into()).to_owned()
-                } else {
-                    let mut shape: TVec<usize> = input.shape().into();
-                    shape[self.axis] = 0;
-                    ArrayD::<T>::default(&*shape)
-                };
-                current += d;
-                slice.into_arc_tensor()
-            })
-            .collect())
-    }
 }
 
 impl Op for Split {
@@ -55,7 +35,9 @@ impl StatelessOp for Split {
     /// Evaluates the operation given the input tensors.
     fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
         let input = args_1!(inputs);
-        dispatch_datum!(Self::eval_t(input.datum_type())(self, input))
+        let axis = normalize_axis(self.axis, input.rank())?;
+        let sizes = self.split_dims(input.shape()[axis])?;
+        Ok(input.split(axis, &sizes)?.into_iter().map(|t| t.into_arc_tensor()).collect())
     }
 }
 
@@ -73,10 +55,11 @@ impl InferenceRulesOp for Split {
             s.equals(&inputs[0].rank, &outputs[i].rank)
         })?;
         s.given(&inputs[0].shape, move |s, shape| {
-            let dims = self.split_dims(shape[self.axis].clone())?;
+            let axis = normalize_axis(self.axis, shape.len())?;
+            let dims = self.split_dims(shape[axis].clone())?;
             for i in 0..self.outputs {
                 let mut shape = shape.clone();
-                shape[self.axis] = dims[i].clone();
+                shape[axis] = dims[i].clone();
                 s.equals(&outputs[i].shape, shape)?;
             }
             Ok(())
@@ -100,13 +83,14 @@ impl InferenceRulesOp for Split {
         let input = target.outlet_fact(mapping[&node.inputs[0]])?.clone();
         let wire = mapping[&node.inputs[0]];
         let mut outputs = tvec!();
+        let axis = normalize_axis(self.axis, input.rank())?;
         let mut current = 0.to_dim();
-        for len in self.split_dims(input.shape.dim(self.axis))? {
+        for len in self.split_dims(input.shape.dim(axis))? {
             let end = current.clone() + len;
             outputs.push(
                 target.wire_node(
                     format!("{}-{}..{}", node.name, current, end),
-                    crate::ops::array::Slice::new(self.axis, current, end.clone()),
+                    crate::ops::array::Slice::new(axis, current, end.clone()),
                     &[wire],
                 )?[0],
             );
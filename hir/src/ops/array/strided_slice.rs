@@ -8,6 +8,8 @@ pub struct StridedSlice {
     pub begin_mask: i64,
     pub end_mask: i64,
     pub shrink_axis_mask: i64,
+    pub new_axis_mask: i64,
+    pub ellipsis_mask: i64,
 }
 
 tract_linalg::impl_dyn_hash!(StridedSlice);
@@ -38,12 +40,22 @@ impl Dim {
     }
 }
 
+fn resolve_axes(axes: &Tensor, rank: usize) -> TractResult<TVec<usize>> {
+    let axes = axes.cast_to::<i64>()?;
+    axes.as_slice::<i64>()?
+        .iter()
+        .map(|&a| tract_core::ops::normalize_axis(a as isize, rank))
+        .collect()
+}
+
 impl StridedSlice {
     pub fn tensorflow(begin_mask: i64, end_mask: i64, shrink_axis_mask: i64) -> StridedSlice {
         StridedSlice {
             begin_mask,
             end_mask,
             shrink_axis_mask,
+            new_axis_mask: 0,
+            ellipsis_mask: 0,
             optional_axes_input: None,
             optional_steps_input: Some(3),
         }
@@ -57,6 +69,8 @@ impl StridedSlice {
             begin_mask: 0,
             end_mask: 0,
             shrink_axis_mask: 0,
+            new_axis_mask: 0,
+            ellipsis_mask: 0,
             optional_axes_input,
             optional_steps_input,
         }
@@ -71,6 +85,72 @@ impl StridedSlice {
     fn ignore_end(&self, ix: usize) -> bool {
         self.end_mask & (1 << ix) != 0
     }
+    fn is_new_axis(&self, ix: usize) -> bool {
+        self.new_axis_mask & (1 << ix) != 0
+    }
+    fn is_ellipsis(&self, ix: usize) -> bool {
+        self.ellipsis_mask & (1 << ix) != 0
+    }
+
+    /// Bounds for each input axis, and the positions in the output of the
+    /// axes inserted by new_axis_mask.
+    ///
+    /// Without an explicit axes input, the slicing spec entries are matched
+    /// to input axes in order, the ellipsis expanding to as many full axes
+    /// as needed, and new axis entries consuming no input axis.
+    fn prepare(
+        &self,
+        input_shape: &[TDim],
+        begin: &ArrayView1<TDim>,
+        end: &ArrayView1<TDim>,
+        strides: &[i32],
+        axes: Option<&[usize]>,
+    ) -> TractResult<(TVec<Dim>, TVec<usize>)> {
+        let full =
+            |dim: &TDim| Dim { begin: 0.to_dim(), end: dim.clone(), stride: 1, shrink: false };
+        if let Some(axes) = axes {
+            let bounds = input_shape
+                .iter()
+                .enumerate()
+                .map(|(axis, dim)| {
+                    if let Some(ix) = axes.iter().position(|&x| x == axis) {
+                        self.prepare_one_dim(ix, dim, begin, end, strides)
+                    } else {
+                        full(dim)
+                    }
+                })
+                .collect();
+            return Ok((bounds, tvec!()));
+        }
+        let mut bounds = tvec!();
+        let mut new_axes = tvec!();
+        let mut output_axis = 0;
+        for ix in 0..begin.len() {
+            if self.is_ellipsis(ix) {
+                let following = (ix + 1..begin.len()).filter(|&i| !self.is_new_axis(i)).count();
+                while bounds.len() + following < input_shape.len() {
+                    bounds.push(full(&input_shape[bounds.len()]));
+                    output_axis += 1;
+                }
+            } else if self.is_new_axis(ix) {
+                new_axes.push(output_axis);
+                output_axis += 1;
+            } else {
+                if bounds.len() >= input_shape.len() {
+                    bail!("StridedSlice spec has more axes than the input ({:?})", input_shape);
+                }
+                let dim = self.prepare_one_dim(ix, &input_shape[bounds.len()], begin, end, strides);
+                if !dim.shrink {
+                    output_axis += 1;
+                }
+                bounds.push(dim);
+            }
+        }
+        while bounds.len() < input_shape.len() {
+            bounds.push(full(&input_shape[bounds.len()]));
+        }
+        Ok((bounds, new_axes))
+    }
     fn prepare_one_dim(
         &self,
         ix: usize,
@@ -175,14 +255,10 @@ impl StatelessOp for StridedSlice {
         } else {
             tvec![1; input_rank]
         };
-        let axes: TVec<usize> = if let Some(i) = self.optional_axes_input {
-            let axes = inputs[i].cast_to::<i32>()?;
-            axes.as_slice::<i32>()?
-                .iter()
-                .map(|&i| if i < 0 { input_rank as i32 + i } else { i } as usize)
-                .collect()
+        let axes = if let Some(i) = self.optional_axes_input {
+            Some(resolve_axes(&inputs[i], input_rank)?)
         } else {
-            (0..input_rank).collect()
+            None
         };
         trace!(
             "StridedSlice {:?} computing shapes: input_shape:{:?} begin:{:?} end:{:?} strides:{:?} axes:{:?}",
@@ -193,24 +269,20 @@ impl StatelessOp for StridedSlice {
             strides,
             axes,
         );
-        let bounds: TVec<Dim> = (0..inputs[0].rank())
-            .map(|axis| {
-                let dim = inputs[0].shape()[axis].to_dim();
-                if let Some(ix) = axes.iter().position(|&x| x == axis) {
-                    self.prepare_one_dim(ix, &dim, &begin, &end, &strides)
-                } else {
-                    Dim { begin: 0.to_dim(), end: dim, stride: 1, shrink: false }
-                }
-            })
-            .collect();
+        let input_shape = inputs[0].shape().iter().map(|d| d.to_dim()).collect::<TVec<_>>();
+        let (bounds, new_axes) =
+            self.prepare(&input_shape, &begin, &end, &strides, axes.as_ref().map(|v| &**v))?;
         trace!("StridedSlice bounds {:?}", bounds);
         let mid_shape: Vec<usize> =
             bounds.iter().map(|d| d.len()).collect::<TractResult<Vec<usize>>>()?;
-        let end_shape: Vec<usize> = bounds
+        let mut end_shape: Vec<usize> = bounds
             .iter()
             .filter(|d| !d.shrink)
             .map(|d| d.len())
             .collect::<TractResult<Vec<usize>>>()?;
+        for &axis in &new_axes {
+            end_shape.insert(axis, 1);
+        }
         let dt = inputs[0].datum_type();
         let output =
             dispatch_datum!(Self::slice_t(dt)(self, inputs[0].as_ref(), &mid_shape, &bounds))?;
@@ -261,17 +333,33 @@ impl InferenceRulesOp for StridedSlice {
                     let t = params[i - 1].cast_to::<i32>()?;
                     t.as_slice::<i32>()?.iter().cloned().collect()
                 } else {
-                    vec![1; input_shape.len()]
+                    vec![1; begin.len()]
                 };
-                let mut current_out_dim = 0;
-                for (ix, d) in input_shape.iter().enumerate() {
-                    if !self.must_shrink(ix) {
-                        let preped = self.prepare_one_dim(ix, d, &begin, &end, &strides);
-                        s.equals(&outputs[0].shape[current_out_dim], preped.soft_len()?)?;
-                        current_out_dim += 1;
-                    }
+                let axes = if let Some(i) = self.optional_axes_input {
+                    Some(resolve_axes(&params[i - 1], input_shape.len())?)
+                } else {
+                    None
+                };
+                let (bounds, new_axes) = self.prepare(
+                    &input_shape,
+                    &begin,
+                    &end,
+                    &strides,
+                    axes.as_ref().map(|v| &**v),
+                )?;
+                let mut output_shape = bounds
+                    .iter()
+                    .filter(|d| !d.shrink)
+                    .map(|d| d.soft_len())
+                    .collect::<TractResult<TVec<TDim>>>()?;
+                for &axis in &new_axes {
+                    output_shape.insert(axis, 1.to_dim());
                 }
-                s.equals(&outputs[0].rank, current_out_dim as i32)
+                s.equals(&outputs[0].rank, output_shape.len() as i32)?;
+                for (ix, d) in output_shape.into_iter().enumerate() {
+                    s.equals(&outputs[0].shape[ix], d)?;
+                }
+                Ok(())
             })
         })
     }
@@ -295,60 +383,71 @@ impl InferenceRulesOp for StridedSlice {
             let begin = casted_begin.to_array_view::<TDim>()?.into_dimensionality()?;
             let casted_end = params[1].cast_to::<TDim>()?;
             let end = casted_end.to_array_view::<TDim>()?.into_dimensionality()?;
-            let input_shape = target.outlet_fact(mapping[&node.inputs[0]])?.shape.clone();
+            let input_shape = target.outlet_fact(mapping[&node.inputs[0]])?.shape.to_tvec();
             let strides: TVec<i32> = if let Some(i) = self.optional_steps_input {
                 let strides = params[i - 1].cast_to::<i32>()?;
                 strides.as_slice::<i32>()?.into()
             } else {
-                tvec![1; input_shape.rank()]
+                tvec![1; begin.len()]
             };
-            if strides.iter().any(|&s| s < 0) {
-                bail!("FIXME: negative strides are not yet supported by tract-core");
-            }
-            let axes: TVec<usize> = if let Some(i) = self.optional_axes_input {
-                let axes = params[i - 1].cast_to::<i32>()?;
-                axes.as_slice::<i32>()?
-                    .iter()
-                    .map(|&i| if i < 0 { input_shape.rank() as i32 + i } else { i } as usize)
-                    .collect()
+            let axes = if let Some(i) = self.optional_axes_input {
+                Some(resolve_axes(params[i - 1], input_shape.len())?)
             } else {
-                (0..input_shape.rank()).collect()
+                None
             };
-            let mut wire = mapping[&node.inputs[0]];
-            let input = target.outlet_fact(wire)?.clone();
-            for (ix, &axis) in axes.iter().enumerate() {
-                let d = input_shape.dim(axis);
-                let preped = self.prepare_one_dim(ix, &d, &begin, &end, &strides);
-                if preped.begin != 0.to_dim() || preped.end != input.shape.dim(ix) {
+            let (bounds, new_axes) =
+                self.prepare(&input_shape, &begin, &end, &strides, axes.as_ref().map(|v| &**v))?;
+            let input = mapping[&node.inputs[0]];
+            let mut wire = input;
+            for (axis, (dim, bound)) in input_shape.iter().zip(bounds.iter()).enumerate() {
+                if bound.stride < 0 {
+                    // gather the reversed positions
+                    let begin = bound.begin.to_integer()?;
+                    let indices = (0..bound.len()? as i64)
+                        .map(|k| begin + k * bound.stride as i64)
+                        .collect::<Vec<_>>();
+                    let indices = target
+                        .add_const(format!("{}-Indices-{}", node.name, axis), tensor1(&indices))?;
                     wire = target.wire_node(
-                        format!("{}-Slice", node.name),
-                        crate::ops::array::Slice::new(axis, preped.begin, preped.end),
+                        format!("{}-Gather-{}", node.name, axis),
+                        tract_core::ops::array::Gather::new(axis as i64),
+                        [wire, indices].as_ref(),
+                    )?[0];
+                    continue;
+                }
+                if bound.begin != 0.to_dim() || &bound.end != dim {
+                    wire = target.wire_node(
+                        format!("{}-Slice-{}", node.name, axis),
+                        crate::ops::array::Slice::new(axis, bound.begin.clone(), bound.end.clone()),
                         [wire].as_ref(),
                     )?[0];
                 }
-                if preped.stride != 1 {
+                if bound.stride != 1 {
                     wire = target.wire_node(
-                        format!("{}-Stride-{}", node.name, ix),
-                        crate::ops::downsample::Downsample::new(ix, preped.stride as usize, 0),
+                        format!("{}-Stride-{}", node.name, axis),
+                        crate::ops::downsample::Downsample::new(axis, bound.stride as usize, 0),
                         [wire].as_ref(),
                     )?[0];
                 }
             }
-            let mut shrink = input
-                .shape
-                .iter()
-                .enumerate()
-                .filter(|(ix, d)| {
-                    let preped = self.prepare_one_dim(*ix, &d, &begin, &end, &strides);
-                    preped.shrink
-                })
-                .map(|pair| pair.0)
-                .collect::<Vec<_>>();
-            shrink.sort();
-            for axis in shrink.iter().rev() {
+            for (axis, _) in bounds.iter().enumerate().rev().filter(|(_, b)| b.shrink) {
                 wire = target.wire_node(
                     format!("{}-RmDim-{}", node.name, axis),
-                    AxisOp::Rm(*axis),
+                    AxisOp::Rm(axis),
+                    [wire].as_ref(),
+                )?[0];
+            }
+            for &axis in &new_axes {
+                wire = target.wire_node(
+                    format!("{}-AddDim-{}", node.name, axis),
+                    AxisOp::Add(axis),
+                    [wire].as_ref(),
+                )?[0];
+            }
+            if wire == input {
+                wire = target.wire_node(
+                    &*node.name,
+                    tract_core::ops::identity::Identity,
                     [wire].as_ref(),
                 )?[0];
             }
@@ -617,4 +716,69 @@ mod tests {
             )]
         );
     }
+
+    fn typed(
+        op: StridedSlice,
+        input: Tensor,
+        begin: Tensor,
+        end: Tensor,
+        strides: Tensor,
+    ) -> Tensor {
+        let mut model = InferenceModel::default();
+        let fact = InferenceFact::dt_shape(input.datum_type(), input.shape());
+        let source = model.add_source("input", fact).unwrap();
+        let begin = model.add_const("begin", begin).unwrap();
+        let end = model.add_const("end", end).unwrap();
+        let strides = model.add_const("strides", strides).unwrap();
+        let output = model.wire_node("slice", op, &[source, begin, end, strides]).unwrap();
+        model.set_output_outlets(&output).unwrap();
+        let model = model.into_typed().unwrap();
+        let plan = SimplePlan::new(&model).unwrap();
+        let mut outputs = plan.run(tvec!(input)).unwrap();
+        assert_eq!(model.output_fact(0).unwrap().shape.rank(), outputs[0].rank());
+        outputs.pop().unwrap().into_tensor()
+    }
+
+    #[test]
+    fn typed_shrink_axis() {
+        let mut op = StridedSlice::tensorflow(0, 0, 0);
+        op.shrink_axis_mask = 0b101;
+        let input = tensor3(&[[[1, 2, 3], [4, 5, 6]], [[7, 8, 9], [10, 11, 12]]]);
+        let args = (tensor1(&[1, 0, 2]), tensor1(&[2, 2, 3]), tensor1(&[1, 1, 1]));
+        let expected = tensor1(&[9, 12]);
+        assert_eq!(
+            eval(op.clone(), input.clone(), args.0.clone(), args.1.clone(), args.2.clone()),
+            expected
+        );
+        assert_eq!(typed(op, input, args.0, args.1, args.2), expected);
+    }
+
+    #[test]
+    fn typed_negative_strides() {
+        let op = StridedSlice::tensorflow(0, 0, 0);
+        let input =
+            tensor3(&[[[1, 1, 1], [2, 2, 2]], [[3, 3, 3], [4, 4, 4]], [[5, 5, 5], [6, 6, 6]]]);
+        let args = (tensor1(&[2, -1, 0]), tensor1(&[-4, -3, 3]), tensor1(&[-2, -1, 1]));
+        let expected = tensor3(&[[[6, 6, 6], [5, 5, 5]], [[2, 2, 2], [1, 1, 1]]]);
+        assert_eq!(
+            eval(op.clone(), input.clone(), args.0.clone(), args.1.clone(), args.2.clone()),
+            expected
+        );
+        assert_eq!(typed(op, input, args.0, args.1, args.2), expected);
+    }
+
+    #[test]
+    fn typed_new_axis_and_ellipsis() {
+        let mut op = StridedSlice::tensorflow(0, 0, 0b100);
+        op.ellipsis_mask = 0b001;
+        op.new_axis_mask = 0b010;
+        let input = tensor3(&[[[1, 2], [3, 4]], [[5, 6], [7, 8]]]);
+        let args = (tensor1(&[0, 0, 1]), tensor1(&[0, 0, 2]), tensor1(&[1, 1, 1]));
+        let expected = tensor3(&[[[2], [4]], [[6], [8]]]);
+        assert_eq!(
+            eval(op.clone(), input.clone(), args.0.clone(), args.1.clone(), args.2.clone()),
+            expected
+        );
+        assert_eq!(typed(op, input, args.0, args.1, args.2), expected);
+    }
 }
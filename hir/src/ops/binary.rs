@@ -44,33 +44,64 @@ impl InferenceRulesOp for mir::binary::InferenceBinOp {
         let operating_datum_type =
             self.0.operating_datum_type(facts[0].datum_type, facts[1].datum_type)?;
         let max_rank = facts[0].rank().max(facts[1].rank());
-        let mut inputs = tvec!();
-        for i in 0..2 {
-            let mut wire = mapping[&node.inputs[i]];
-            if facts[i].datum_type != operating_datum_type {
-                wire = target.wire_node(
-                    format!("{}Cast{}", &*node.name, i),
-                    mir::element_wise::ElementWiseOp(Box::new(mir::cast::Cast::new(
-                        operating_datum_type,
-                    ))),
-                    &[wire],
-                )?[0];
-            }
-            for i in facts[i].rank()..max_rank {
-                wire = target.wire_node(
-                    format!("{}-BroadcastToRank-{}", &*node.name, i),
-                    AxisOp::Add(0),
-                    &[wire],
-                )?[0];
-            }
-            inputs.push(wire);
-        }
+        let inputs = (0..2)
+            .map(|i| {
+                wire_cast_and_rank(
+                    target,
+                    node,
+                    i,
+                    mapping[&node.inputs[i]],
+                    operating_datum_type,
+                    max_rank,
+                )
+            })
+            .collect::<TractResult<TVec<_>>>()?;
         target.wire_node(&*node.name, mir::binary::TypedBinOp(self.0.clone()), &*inputs)
     }
 
     as_op!();
 }
 
+/// Cast an input to the operating type and add leading axes up to `rank`.
+fn wire_cast_and_rank(
+    target: &mut TypedModel,
+    node: &InferenceNode,
+    ix: usize,
+    mut wire: OutletId,
+    datum_type: DatumType,
+    rank: usize,
+) -> TractResult<OutletId> {
+    if target.outlet_fact(wire)?.datum_type != datum_type {
+        wire = target.wire_node(
+            format!("{}Cast{}", &*node.name, ix),
+            mir::element_wise::ElementWiseOp(Box::new(mir::cast::Cast::new(datum_type))),
+            &[wire],
+        )?[0];
+    }
+    for axis in target.outlet_fact(wire)?.rank()..rank {
+        wire = target.wire_node(
+            format!("{}-BroadcastToRank-{}-{}", &*node.name, ix, axis),
+            AxisOp::Add(0),
+            &[wire],
+        )?[0];
+    }
+    Ok(wire)
+}
+
+/// Common type of all the inputs, to promote them to before operating.
+fn inputs_super_type(
+    node: &InferenceNode,
+    target: &TypedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+) -> TractResult<DatumType> {
+    let types = node
+        .inputs
+        .iter()
+        .map(|i| Ok(target.outlet_fact(mapping[i])?.datum_type))
+        .collect::<TractResult<TVec<DatumType>>>()?;
+    Ok(DatumType::super_type_for(&types).ok_or_else(|| format!("No super type for {:?}", types))?)
+}
+
 #[derive(Debug, Clone, Hash)]
 pub struct Nary(pub Box<dyn mir::binary::BinMiniOp>, pub bool);
 tract_linalg::impl_dyn_hash!(Nary);
@@ -126,15 +157,7 @@ impl InferenceRulesOp for Nary {
         inputs: &'p [TensorProxy],
         outputs: &'p [TensorProxy],
     ) -> InferenceResult {
-        check_output_arity(&outputs, 1)?;
-        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
-        let n = inputs.len();
-        s.equals_all((0..n).map(|i| (&inputs[i].datum_type).bex()).collect())?;
-        s.given_all(inputs.iter().map(|i| &i.shape), move |s, shapes: Vec<TVec<TDim>>| {
-            let out = tract_core::broadcast::multi_broadcast(&*shapes)
-                .ok_or_else(|| format!("Failed to broadcast {:?}", &shapes))?;
-            s.equals(&outputs[0].shape, ShapeFactoid::from(out))
-        })
+        nary_rules(s, inputs, outputs)
     }
 
     fn to_typed(
@@ -144,7 +167,13 @@ impl InferenceRulesOp for Nary {
         target: &mut TypedModel,
         mapping: &HashMap<OutletId, OutletId>,
     ) -> TractResult<TVec<OutletId>> {
-        let inputs = node.inputs.iter().map(|i| mapping[i]).collect::<Vec<_>>();
+        let datum_type = inputs_super_type(node, target, mapping)?;
+        let inputs = node
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(ix, i)| wire_cast_and_rank(target, node, ix, mapping[i], datum_type, 0))
+            .collect::<TractResult<Vec<_>>>()?;
         let mut wire = inputs[0];
         for (ix, i) in inputs[1..].iter().enumerate() {
             wire = target.wire_node(
@@ -171,3 +200,149 @@ impl InferenceRulesOp for Nary {
 
     as_op!();
 }
+
+fn nary_rules<'r, 'p: 'r, 's: 'r>(
+    s: &mut Solver<'r>,
+    inputs: &'p [TensorProxy],
+    outputs: &'p [TensorProxy],
+) -> InferenceResult {
+    check_output_arity(&outputs, 1)?;
+    s.given_all(inputs.iter().map(|i| &i.datum_type), move |s, types: Vec<DatumType>| {
+        let dt = DatumType::super_type_for(&types)
+            .ok_or_else(|| format!("No super type for {:?}", types))?;
+        s.equals(&outputs[0].datum_type, dt)
+    })?;
+    s.given_all(inputs.iter().map(|i| &i.shape), move |s, shapes: Vec<TVec<TDim>>| {
+        let out = tract_core::broadcast::multi_broadcast(&*shapes)
+            .ok_or_else(|| format!("Failed to broadcast {:?}", &shapes))?;
+        s.equals(&outputs[0].shape, ShapeFactoid::from(out))
+    })
+}
+
+fn nary_to_typed(
+    op: Box<dyn TypedOp>,
+    node: &InferenceNode,
+    target: &mut TypedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+) -> TractResult<TVec<OutletId>> {
+    let datum_type = inputs_super_type(node, target, mapping)?;
+    let max_rank = node
+        .inputs
+        .iter()
+        .map(|i| Ok(target.outlet_fact(mapping[i])?.rank()))
+        .collect::<TractResult<Vec<usize>>>()?
+        .into_iter()
+        .max()
+        .unwrap_or(0);
+    let inputs = node
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(ix, i)| wire_cast_and_rank(target, node, ix, mapping[i], datum_type, max_rank))
+        .collect::<TractResult<TVec<_>>>()?;
+    target.wire_node(&*node.name, op, &*inputs)
+}
+
+impl InferenceRulesOp for mir::math::Sum {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        nary_rules(s, inputs, outputs)
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        nary_to_typed(Box::new(self.clone()), node, target, mapping)
+    }
+
+    as_op!();
+}
+
+impl InferenceRulesOp for mir::math::Mean {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        nary_rules(s, inputs, outputs)
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        nary_to_typed(Box::new(self.clone()), node, target, mapping)
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn add_promotes_i32_to_f32() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let a =
+            model.add_source("a", InferenceFact::dt_shape(i32::datum_type(), shapefactoid!(2)))?;
+        let b =
+            model.add_source("b", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(2)))?;
+        let add = model.wire_node("add", mir::math::add::bin(), &[a, b])?;
+        model.set_output_outlets(&add)?;
+        let model = model.into_typed()?;
+        assert_eq!(model.outlet_fact(model.output_outlets()?[0])?.datum_type, f32::datum_type());
+        let cast = model.node_by_name("addCast0")?;
+        assert_eq!(cast.inputs[0].node, model.node_by_name("a")?.id);
+        let result =
+            model.into_runnable()?.run(tvec!(tensor1(&[1i32, 2]), tensor1(&[0.5f32, 0.25])))?;
+        assert_eq!(*result[0], tensor1(&[1.5f32, 2.25]));
+        Ok(())
+    }
+
+    #[test]
+    fn sum_promotes_mixed_inputs() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let a =
+            model.add_source("a", InferenceFact::dt_shape(i32::datum_type(), shapefactoid!(2)))?;
+        let b =
+            model.add_source("b", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(2)))?;
+        let sum = model.wire_node("sum", Nary(Box::new(mir::math::Add), false), &[a, b, a])?;
+        model.set_output_outlets(&sum)?;
+        let model = model.into_typed()?;
+        assert_eq!(model.outlet_fact(model.output_outlets()?[0])?.datum_type, f32::datum_type());
+        let result =
+            model.into_runnable()?.run(tvec!(tensor1(&[1i32, 2]), tensor1(&[0.5f32, 0.25])))?;
+        assert_eq!(*result[0], tensor1(&[2.5f32, 4.25]));
+        Ok(())
+    }
+
+    #[test]
+    fn broadcast_to_rank_names_are_unique() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let a = model
+            .add_source("a", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(2, 1, 2)))?;
+        let b =
+            model.add_source("b", InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(2)))?;
+        let add = model.wire_node("add", mir::math::add::bin(), &[a, b])?;
+        model.set_output_outlets(&add)?;
+        let model = model.into_typed()?;
+        let first = model.node_by_name("add-BroadcastToRank-1-1")?;
+        let second = model.node_by_name("add-BroadcastToRank-1-2")?;
+        assert_eq!(first.inputs[0].node, model.node_by_name("b")?.id);
+        assert_eq!(second.inputs[0].node, first.id);
+        Ok(())
+    }
+}
@@ -278,8 +278,7 @@ impl InferenceRulesOp for Conv {
         })?;
         s.given_2(&inputs[0].shape, &k_input.shape, move |s, ishape, kshape| {
             if kshape.iter().all(|d| d.to_integer().is_ok()) {
-                let kshape: TVec<usize> =
-                    kshape.iter().map(|d| d.to_integer().unwrap() as _).collect();
+                let kshape: TVec<usize> = kshape.iter().map(|d| d.to_usize().unwrap()).collect();
                 let oshape = self.output_shape(&*ishape, &*kshape)?;
                 s.equals(&outputs[0].shape, oshape)?;
             }
@@ -353,6 +352,46 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_infer_dilations_per_axis() {
+        for (padding, output) in &[(PaddingSpec::Valid, (5, 3)), (PaddingSpec::SameUpper, (7, 7))] {
+            let mut op = Conv::default().dilations(tvec![1, 2]).padding(padding.clone());
+            let ifact = InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 1, 7, 7));
+            let kfact = InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 1, 3, 3));
+            let ofact = InferenceFact::default();
+            let facts = op.infer_facts(tvec!(&ifact, &kfact), tvec!(&ofact), tvec!()).unwrap();
+            assert_eq!(
+                facts.1,
+                tvec!(InferenceFact::dt_shape(
+                    DatumType::F32,
+                    shapefactoid!(1, 1, (output.0), (output.1))
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_eval_dilations_per_axis_same_upper() {
+        let op = Conv::default().dilations(tvec![1, 2]).padding(PaddingSpec::SameUpper);
+        let i = ArrayD::<f32>::ones(vec![1, 1, 3, 5]).into_arc_tensor();
+        let k = ArrayD::<f32>::ones(vec![1, 1, 3, 3]).into_arc_tensor();
+        let res = op.clone().eval(tvec!(i, k)).unwrap();
+        // the kernel covers 3 rows and columns j-2, j, j+2
+        let e = tensor4(&[[[[4f32, 4., 6., 4., 4.], [6., 6., 9., 6., 6.], [4., 4., 6., 4., 4.]]]]);
+        assert_eq!(*res[0], e);
+
+        let mut model = InferenceModel::default();
+        let fact = InferenceFact::dt_shape(DatumType::F32, shapefactoid!(1, 1, 3, 5));
+        let source = model.add_source("source", fact).unwrap();
+        let kernel = model.add_const("kernel", tensor4(&[[[[1f32; 3]; 3]]])).unwrap();
+        let conv = model.wire_node("conv", op, &[source, kernel]).unwrap();
+        model.set_output_outlets(&conv).unwrap();
+        let model = model.into_optimized().unwrap();
+        assert_eq!(model.output_fact(0).unwrap().shape.as_finite().unwrap(), &[1, 1, 3, 5]);
+        let res = model.into_runnable().unwrap().run(tvec!(tensor4(&[[[[1f32; 5]; 3]]]))).unwrap();
+        assert_eq!(*res[0], e);
+    }
+
     #[test]
     fn test_infer_nhwc_1() {
         let mut op = Conv::default().nhwc().hwio().padding(PaddingSpec::SameUpper);
@@ -2,5 +2,5 @@ mod conv;
 mod pools;
 
 pub use conv::Conv;
-pub use pools::{AvgPool, MaxPool};
+pub use pools::{AvgPool, MaxPool, MaxUnpool};
 pub use tract_core::ops::cnn::{ConvUnary, PaddingSpec, PoolSpec};
@@ -1,7 +1,7 @@
 use crate::infer::*;
 use crate::internal::*;
 
-pub use tract_core::ops::cnn::{AvgPool, MaxPool, PoolSpec};
+pub use tract_core::ops::cnn::{AvgPool, MaxPool, MaxUnpool, PoolSpec};
 
 impl InferenceRulesOp for AvgPool {
     fn rules<'r, 'p: 'r, 's: 'r>(
@@ -45,6 +45,51 @@ impl InferenceRulesOp for MaxPool {
     to_typed!();
 }
 
+impl InferenceRulesOp for MaxUnpool {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&inputs[1].datum_type, i64::datum_type())?;
+        s.equals(&inputs[0].shape, &inputs[1].shape)?;
+        s.equals(&inputs[2].rank, 1)?;
+        s.given(&inputs[2].shape[0], move |s, rank| {
+            s.equals(&outputs[0].rank, rank.to_integer()? as i32)
+        })?;
+        s.given(&inputs[2].value, move |s, shape| {
+            let shape = shape.cast_to::<TDim>()?;
+            s.equals(
+                &outputs[0].shape,
+                shape.as_slice::<TDim>()?.iter().cloned().collect::<TVec<_>>(),
+            )
+        })
+    }
+
+    as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        let op = if target.outlet_fact(inputs[2])?.konst.is_some() {
+            self.clone()
+        } else {
+            let rank = target.outlet_fact(inputs[0])?.rank();
+            MaxUnpool::fresh(target, rank.saturating_sub(2))?
+        };
+        target.wire_node(&*node.name, op, &*inputs)
+    }
+}
+
 pub fn rules_for_shape<'r, 'p: 'r, 's: 'r>(
     pool_spec: &'s PoolSpec,
     s: &mut Solver<'r>,
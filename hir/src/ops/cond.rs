@@ -0,0 +1,152 @@
+use crate::infer::*;
+use crate::internal::*;
+
+pub use tract_core::ops::cond::If;
+
+/// Inference form of If: branches are analysed against the outer facts, and
+/// typed once when the node is translated to If.
+#[derive(Debug, Clone, new, Hash)]
+pub struct InferenceIf {
+    pub then_body: InferenceModel,
+    pub then_input_mapping: Vec<usize>,
+    pub else_body: InferenceModel,
+    pub else_input_mapping: Vec<usize>,
+}
+
+tract_linalg::impl_dyn_hash!(InferenceIf);
+
+impl InferenceIf {
+    fn to_mir_if(&self) -> TractResult<If> {
+        If::new(
+            self.then_body.clone().into_typed()?,
+            self.then_input_mapping.clone(),
+            self.else_body.clone().into_typed()?,
+            self.else_input_mapping.clone(),
+        )
+    }
+}
+
+impl Op for InferenceIf {
+    fn name(&self) -> Cow<str> {
+        "If".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("then inputs: {:?}", self.then_input_mapping),
+            format!("else inputs: {:?}", self.else_input_mapping),
+        ])
+    }
+
+    fn nested_models(&self) -> Vec<(Cow<str>, &dyn Model, Vec<String>, Vec<String>)> {
+        let inputs = |mapping: &[usize]| mapping.iter().map(|i| format!("input #{}", i)).collect();
+        let outputs = |body: &InferenceModel| {
+            (0..body.output_outlets().unwrap().len()).map(|o| format!("output #{}", o)).collect()
+        };
+        vec![
+            (
+                "then".into(),
+                &self.then_body,
+                inputs(&self.then_input_mapping),
+                outputs(&self.then_body),
+            ),
+            (
+                "else".into(),
+                &self.else_body,
+                inputs(&self.else_input_mapping),
+                outputs(&self.else_body),
+            ),
+        ]
+    }
+
+    op_hir!();
+    not_a_typed_op!();
+    not_a_pulsed_op!();
+}
+
+impl StatefullOp for InferenceIf {
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(InferenceIfState(self.to_mir_if()?))))
+    }
+}
+
+#[derive(Debug)]
+struct InferenceIfState(If);
+
+impl OpState for InferenceIfState {
+    fn eval(
+        &mut self,
+        _session: &mut SessionState,
+        _op: &dyn Op,
+        inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        self.0.eval(inputs)
+    }
+}
+
+impl InferenceOp for InferenceIf {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&InferenceFact>,
+        outputs: TVec<&InferenceFact>,
+        _observed: TVec<&InferenceFact>,
+    ) -> TractResult<(TVec<InferenceFact>, TVec<InferenceFact>, TVec<InferenceFact>)> {
+        if outputs.len() != self.nboutputs()? {
+            bail!("If has {} outputs, branches have {}", outputs.len(), self.nboutputs()?);
+        }
+        let mut inputs: TVec<InferenceFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<InferenceFact> = outputs.into_iter().cloned().collect();
+        inputs[0].unify_with(&InferenceFact::dt_shape(bool::datum_type(), shapefactoid!()))?;
+        loop {
+            let mut changed = false;
+            for (body, mapping) in &mut [
+                (&mut self.then_body, &self.then_input_mapping),
+                (&mut self.else_body, &self.else_input_mapping),
+            ] {
+                for (ix, &slot) in mapping.iter().enumerate() {
+                    let outer = inputs.get_mut(slot).ok_or("If input mapping out of range")?;
+                    if outer.unify_with_mut(body.input_fact_mut(ix)?)? {
+                        changed = true;
+                    }
+                }
+                for (ix, outer) in outputs.iter_mut().enumerate() {
+                    let inner = body.output_fact_mut(ix)?;
+                    if outer.datum_type.unify_with_mut(&mut inner.datum_type)? {
+                        changed = true;
+                    }
+                    if outer.shape.unify_with_mut(&mut inner.shape)? {
+                        changed = true;
+                    }
+                }
+                if body.analyse(false)? {
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        Ok((inputs, outputs, tvec!()))
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        target.wire_node(&*node.name, self.to_mir_if()?, &*inputs)
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.then_body.output_outlets()?.len())
+    }
+
+    as_op!();
+}
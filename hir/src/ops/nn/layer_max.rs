@@ -1,9 +1,11 @@
 use crate::infer::*;
 use crate::internal::*;
+use tract_ndarray::prelude::*;
 
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct LayerHardmax {
     axis: isize,
+    coerce_to_2d: bool,
 }
 
 tract_linalg::impl_dyn_hash!(LayerHardmax);
@@ -15,12 +17,8 @@ impl LayerHardmax {
     ) -> TractResult<TVec<Arc<Tensor>>> {
         let array = input.into_tensor().into_array::<D>()?;
         let shape = array.shape().to_vec();
-        let axis =
-            if self.axis < 0 { shape.len() as isize + self.axis } else { self.axis } as usize;
-        let first_dim: usize = array.shape()[0..axis].iter().product();
-        let second_dim: usize = array.len() / first_dim;
-        let mut array = array.into_shape((first_dim, second_dim))?;
-        array.outer_iter_mut().for_each(|mut layer| {
+        let (mut array, axis) = layers(array, self.axis, self.coerce_to_2d)?;
+        array.lanes_mut(Axis(axis)).into_iter().for_each(|mut layer| {
             let max = layer
                 .iter()
                 .enumerate()
@@ -43,7 +41,7 @@ impl Op for LayerHardmax {
     }
 
     fn info(&self) -> TractResult<Vec<String>> {
-        Ok(vec![format!("axis: {}", self.axis)])
+        Ok(vec![format!("axis: {} coerce_to_2d: {}", self.axis, self.coerce_to_2d)])
     }
 
     op_hir!();
@@ -82,6 +80,7 @@ impl TypedOp for LayerHardmax {
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct LayerLogSoftmax {
     axis: isize,
+    coerce_to_2d: bool,
 }
 
 tract_linalg::impl_dyn_hash!(LayerLogSoftmax);
@@ -93,7 +92,7 @@ impl LayerLogSoftmax {
         &self,
         input: Arc<Tensor>,
     ) -> TractResult<Tensor> {
-        let mut softmax = LayerSoftmax::new(self.axis).eval_t::<T>(input)?;
+        let mut softmax = LayerSoftmax::new(self.axis, self.coerce_to_2d).eval_t::<T>(input)?;
         softmax.as_slice_mut::<T>()?.iter_mut().for_each(|x| *x = x.ln());
         Ok(softmax)
     }
@@ -104,7 +103,7 @@ impl Op for LayerLogSoftmax {
         "LayerLogSoftmax".into()
     }
     fn info(&self) -> TractResult<Vec<String>> {
-        Ok(vec![format!("axis: {}", self.axis)])
+        Ok(vec![format!("axis: {} coerce_to_2d: {}", self.axis, self.coerce_to_2d)])
     }
     fn validation(&self) -> Validation {
         Validation::Rounding
@@ -140,8 +139,8 @@ impl InferenceRulesOp for LayerLogSoftmax {
         target: &mut TypedModel,
         mapping: &HashMap<OutletId, OutletId>,
     ) -> TractResult<TVec<OutletId>> {
-        let softmax =
-            InferenceOp::to_typed(&LayerSoftmax::new(self.axis), source, node, target, mapping)?[0];
+        let softmax = LayerSoftmax::new(self.axis, self.coerce_to_2d);
+        let softmax = InferenceOp::to_typed(&softmax, source, node, target, mapping)?[0];
         target.wire_node(
             format!("{}-logsoftmax", node.name),
             tract_core::ops::math::ln(),
@@ -153,6 +152,7 @@ impl InferenceRulesOp for LayerLogSoftmax {
 #[derive(Debug, Clone, new, Default, Hash)]
 pub struct LayerSoftmax {
     axis: isize,
+    coerce_to_2d: bool,
 }
 
 tract_linalg::impl_dyn_hash!(LayerSoftmax);
@@ -166,12 +166,8 @@ impl LayerSoftmax {
     ) -> TractResult<Tensor> {
         let array = input.into_tensor().into_array::<T>()?;
         let shape = array.shape().to_vec();
-        let axis =
-            if self.axis < 0 { shape.len() as isize + self.axis } else { self.axis } as usize;
-        let first_dim: usize = array.shape()[0..axis].iter().product();
-        let second_dim: usize = array.len() / first_dim;
-        let mut array = array.into_shape((first_dim, second_dim))?;
-        array.outer_iter_mut().for_each(|mut layer| {
+        let (mut array, axis) = layers(array, self.axis, self.coerce_to_2d)?;
+        array.lanes_mut(Axis(axis)).into_iter().for_each(|mut layer| {
             // https://jamesmccaffrey.wordpress.com/2016/03/04/the-max-trick-when-computing-softmax/
             let max: Option<T> = layer
                 .iter()
@@ -190,7 +186,7 @@ impl Op for LayerSoftmax {
         "LayerSoftmax".into()
     }
     fn info(&self) -> TractResult<Vec<String>> {
-        Ok(vec![format!("axis: {}", self.axis)])
+        Ok(vec![format!("axis: {} coerce_to_2d: {}", self.axis, self.coerce_to_2d)])
     }
     fn validation(&self) -> Validation {
         Validation::Rounding
@@ -229,8 +225,9 @@ impl InferenceRulesOp for LayerSoftmax {
         use tract_core::ops::nn;
         let input = mapping[&node.inputs[0]];
         let rank = target.outlet_fact(input)?.rank();
-        let axis = if self.axis < 0 { rank as isize + self.axis } else { self.axis } as usize;
-        let reducing_axes = (axis..rank).collect::<TVec<usize>>();
+        let axis = tract_core::ops::normalize_axis(self.axis, rank)?;
+        let reducing_axes =
+            if self.coerce_to_2d { (axis..rank).collect::<TVec<usize>>() } else { tvec!(axis) };
         let maxes = target.wire_node(
             format!("{}-max", node.name),
             nn::Reduce::new(reducing_axes.clone(), nn::Reducer::Max),
@@ -259,6 +256,23 @@ impl InferenceRulesOp for LayerSoftmax {
     }
 }
 
+/// Views the array as lanes along the returned axis: the input axis itself,
+/// or the second axis of a 2D view flattening the axes before and after it.
+fn layers<D: Datum>(
+    array: ArrayD<D>,
+    axis: isize,
+    coerce_to_2d: bool,
+) -> TractResult<(ArrayD<D>, usize)> {
+    let axis = tract_core::ops::normalize_axis(axis, array.ndim())?;
+    if coerce_to_2d {
+        let first_dim: usize = array.shape()[0..axis].iter().product();
+        let second_dim: usize = array.len() / first_dim;
+        Ok((array.into_shape(&[first_dim, second_dim][..])?, 1))
+    } else {
+        Ok((array, axis))
+    }
+}
+
 fn rules<'r, 'p: 'r, 's: 'r>(
     s: &mut Solver<'r>,
     inputs: &'p [TensorProxy],
@@ -9,6 +9,6 @@ pub use layer_max::*;
 pub use reduce::{Reduce, Reducer};
 
 pub use tract_core::ops::nn::{
-    elu, hard_sigmoid, leaky_relu, parametric_softplus, scaled_tanh, selu, sigmoid, softplus,
-    softsign, threshold_relu, DataFormat,
+    elu, hard_sigmoid, hard_swish, leaky_relu, parametric_softplus, scaled_tanh, selu, sigmoid,
+    softplus, softsign, swish, threshold_relu, DataFormat,
 };
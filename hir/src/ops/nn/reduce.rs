@@ -80,8 +80,7 @@ impl Reducer {
                 wire =
                     target.wire_node(name, TReduce::new(axes.clone(), TReducer::Sum), &[wire])?[0];
                 let size =
-                    axes.iter().map(|ax| fact.shape.dim(*ax)).maybe_product()?.to_integer()?
-                        as f64;
+                    axes.iter().map(|ax| fact.shape.dim(*ax)).maybe_product()?.to_integer()? as f64;
                 let size = unsafe {
                     tensor0(size)
                         .cast_to_dt(fact.datum_type)?
@@ -116,7 +115,7 @@ impl Reduce {
             Some(original_axes) => {
                 let mut ans: Vec<usize> = vec![];
                 for or_ax in original_axes.iter() {
-                    ans.push(Self::resolve_axis(*or_ax, rank).unwrap());
+                    ans.push(tract_core::ops::normalize_axis(*or_ax as isize, rank).unwrap());
                 }
                 Some(ans)
             }
@@ -143,20 +142,13 @@ impl Reduce {
             .collect()
     }
 
-    fn resolve_axis(axis: i64, rank: usize) -> TractResult<usize> {
-        if 0 <= axis && axis as usize <= rank - 1 {
-            Ok(axis as usize)
-        } else if -(rank as i64) <= axis && axis < 0 {
-            Ok((axis + rank as i64) as usize)
-        } else {
-            bail!("Illegal combination of values for rank and axis: {} and {}", rank, axis)
-        }
-    }
-
     fn resolve_axes(&self, input_rank: usize) -> TractResult<TVec<usize>> {
         let mut axes: TVec<usize> = match self.axes.as_ref() {
             None => Ok((0..input_rank).collect()),
-            Some(axis) => axis.iter().map(|&a| Self::resolve_axis(a, input_rank)).collect(),
+            Some(axis) => axis
+                .iter()
+                .map(|&a| tract_core::ops::normalize_axis(a as isize, input_rank))
+                .collect(),
         }?;
         axes.sort();
         Ok(axes)
@@ -194,7 +186,12 @@ impl Expansion for Reduce {
         })
     }
 
-    fn wire(&self, name: &str, target: &mut TypedModel, inputs: &[OutletId]) -> TractResult<TVec<OutletId>> {
+    fn wire(
+        &self,
+        name: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
         let input = inputs[0];
         let fact = target.outlet_fact(input)?;
         let mut axes = self.resolve_axes(fact.rank())?;
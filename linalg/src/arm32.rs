@@ -6,7 +6,7 @@ use crate::frame::QMatMatMulImpl;
 use crate::frame::SigmoidImpl;
 use crate::frame::TanhImpl;
 
-use crate::Ops;
+use crate::{MmmF32, Ops};
 
 fn has_neon_cpuinfo() -> std::io::Result<bool> {
     let cpu_info = fs::read_to_string("/proc/cpuinfo")?;
@@ -22,6 +22,18 @@ fn has_neon() -> bool {
     has_neon_cpuinfo().unwrap_or(false)
 }
 
+pub fn mmm_f32_for_kernel(name: &str) -> Option<MmmF32> {
+    match name {
+        "neon" if has_neon() => Some(Box::new(|m, k, n| {
+            Box::new(MatMatMulImpl::<armv7neon::MatMatMulF32x8x4, f32, f32, f32, f32>::new(m, k, n))
+        })),
+        "vfpv2" => Some(Box::new(|m, k, n| {
+            Box::new(MatMatMulImpl::<armvfpv2::MatMatMulF32x4x4, f32, f32, f32, f32>::new(m, k, n))
+        })),
+        _ => None,
+    }
+}
+
 pub fn plug(ops: &mut Ops) {
     if has_neon() {
         log::info!("armv7neon activated (smmm, ssigmoid), stanh)");
@@ -1,12 +1,21 @@
 mod arm64simd;
 
-use crate::Ops;
+use crate::{MmmF32, Ops};
 
 use crate::frame::MatMatMulImpl;
 use crate::frame::QMatMatMulImpl;
 use crate::frame::SigmoidImpl;
 use crate::frame::TanhImpl;
 
+pub fn mmm_f32_for_kernel(name: &str) -> Option<MmmF32> {
+    match name {
+        "arm64simd" => Some(Box::new(|m, k, n| {
+            Box::new(MatMatMulImpl::<arm64simd::MatMatMulF32x8x8, f32, f32, f32, f32>::new(m, k, n))
+        })),
+        _ => None,
+    }
+}
+
 pub fn plug(ops: &mut Ops) {
     log::info!("arm64simd activated for smmm");
     ops.mmm_f32 = Box::new(|m, k, n| {
@@ -107,6 +107,18 @@ macro_rules! test_mmm_kernel_u8 {
     };
 }
 
+#[macro_export]
+macro_rules! test_mmm_kernel_i16 {
+    ($k: ty, $id: ident, $cond: expr) => {
+        #[cfg(test)]
+        #[allow(non_snake_case)]
+        mod $id {
+            qmmm_frame_tests!($cond, $k, i16, i16, i32, i64);
+            qmmm_s_frame_tests!($cond, $k, i16, i16, i32, i64);
+        }
+    };
+}
+
 #[cfg(test)]
 #[macro_use]
 pub mod test {
@@ -21,6 +21,9 @@ where
     fn a_pack(&self) -> PackA<TA>;
     fn b_pack(&self) -> PackB<TB>;
 
+    /// Name of the kernel doing the actual work.
+    fn kernel_name(&self) -> &'static str;
+
     fn a_storage(&self) -> &MatrixStoreSpec;
     fn b_storage(&self) -> &MatrixStoreSpec;
     fn c_storage(&self) -> &MatrixStoreSpec;
@@ -144,6 +147,10 @@ where
         PackB::new(self.k, self.n, K::nr(), K::alignment_bytes_packed_b())
     }
 
+    fn kernel_name(&self) -> &'static str {
+        K::name()
+    }
+
     fn m(&self) -> usize {
         self.m
     }
@@ -9,6 +9,19 @@ use num_traits::{AsPrimitive, Bounded, Zero};
 use super::MatMatMul;
 use super::*;
 
+/// Decompose a requantization scale factor into the fixed-point multiplier
+/// and shift used by the `QTowardsEven` and `QTowardsPlusInf` fused specs, so
+/// that `factor ~= multiplier * 2^-(31 + shift)`. The factor must be below 1.
+pub fn scale_factor_to_fixed_point(factor: f32) -> (i32, usize) {
+    // https://github.com/microsoft/onnxruntime/blob/master/onnxruntime/core/util/gemmlowp_common.h#L16
+    let factor_bits = factor.to_bits();
+    let current_exponent = factor_bits >> 23;
+    let bumped_multi = f32::from_bits(factor_bits & 0x007fffff | 0x3f000000);
+    let int_multi = (bumped_multi * (1i64 << 31) as f32).round() as i32;
+    let shift = 126 - current_exponent;
+    (int_multi, shift as usize)
+}
+
 pub trait QMatMatMul<TA, TB, TC, TI>:
     Debug + fmt::Display + dyn_clone::DynClone + Send + Sync + DynHash
 where
@@ -265,13 +278,8 @@ where
     }
 
     unsafe fn set_scale_factor(&mut self, factor: f32) {
-        // https://github.com/microsoft/onnxruntime/blob/master/onnxruntime/core/util/gemmlowp_common.h#L16
-        let factor_bits = factor.to_bits();
-        let current_exponent = factor_bits >> 23;
-        let bumped_multi = f32::from_bits(factor_bits & 0x007fffff | 0x3f000000);
-        let int_multi = (bumped_multi * (1i64 << 31) as f32).round() as i32;
-        let shift = 126 - current_exponent;
-        self.scale_factor = Some((int_multi.as_(), shift as usize));
+        let (int_multi, shift) = scale_factor_to_fixed_point(factor);
+        self.scale_factor = Some((int_multi.as_(), shift));
     }
 
     unsafe fn run(&self, a: *const TA, b: *const TB, c: *mut TC, non_linear: &[FusedSpec<TI>]) {
@@ -31,6 +31,24 @@ impl PseudoRightShift for i32 {
     }
 }
 
+impl PseudoRightShift for i64 {
+    fn q_even(self, mult: Self, shift: usize) -> Self {
+        let v = ((self as i128 * mult as i128) >> (30 + shift)) as i64;
+        let truncated = v.abs();
+        let nudge = ((truncated & 0x3) == 0x3) as usize as i64;
+        let pos = (truncated + nudge) >> 1;
+        if v.is_negative() {
+            -pos
+        } else {
+            pos
+        }
+    }
+    fn q_to_plus_inf(self, mult: Self, shift: usize) -> Self {
+        let v = ((self as i128 * mult as i128) >> (30 + shift)) as i64;
+        (v + 1) >> 1
+    }
+}
+
 impl PseudoRightShift for f32 {
     fn q_even(self, mult: Self, shift: usize) -> Self {
         self * mult * 2f32.powi(-(shift as i32))
@@ -629,8 +647,10 @@ test_mmm_kernel_f32!(crate::generic::mmm::GenericMmm4x4<f32, f32, f32, f32>, tes
 test_mmm_kernel_i8!(crate::generic::mmm::GenericMmm4x4<i8, i8, i8, i32>, test_GenericMmm4x4_i8, true);
 test_mmm_kernel_u8!(crate::generic::mmm::GenericMmm4x4<u8, u8, u8, i32>, test_GenericMmm4x4_u8, true);
 test_mmm_kernel_i8_i32!(crate::generic::mmm::GenericMmm4x4<i8, i8, i32, i32>, test_GenericMmm4x4_i8_i32, true);
+test_mmm_kernel_i16!(crate::generic::mmm::GenericMmm4x4<i16, i16, i32, i64>, test_GenericMmm4x4_i16, true);
 
 test_mmm_kernel_f32!(crate::generic::mmm::GenericMmmTest3x2<f32, f32, f32, f32>, test_GenericMmmTest3x2_f32, true);
 test_mmm_kernel_i8!(crate::generic::mmm::GenericMmmTest3x2<i8, i8, i8, i32>, test_GenericMmmTest3x2_i8, true);
 test_mmm_kernel_u8!(crate::generic::mmm::GenericMmmTest3x2<u8, u8, u8, i32>, test_GenericMmmTest3x2_u8, true);
 test_mmm_kernel_i8_i32!(crate::generic::mmm::GenericMmmTest3x2<i8, i8, i32, i32>, test_GenericMmmTest3x2_i8_i32, true);
+test_mmm_kernel_i16!(crate::generic::mmm::GenericMmmTest3x2<i16, i16, i32, i64>, test_GenericMmmTest3x2_i16, true);
@@ -14,7 +14,7 @@ pub mod hash;
 pub mod f16;
 #[macro_use]
 pub mod frame;
-mod generic;
+pub mod generic;
 
 #[cfg(target_arch = "x86_64")]
 pub mod x86_64_fma;
@@ -30,10 +30,11 @@ pub use self::frame::mmm;
 pub use self::frame::sigmoid;
 pub use self::frame::tanh;
 
+pub type MmmF32 =
+    Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul<f32, f32, f32, f32>> + Send + Sync>;
+
 pub struct Ops {
-    pub mmm_f32: Box<
-        dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul<f32, f32, f32, f32>> + Send + Sync,
-    >,
+    pub mmm_f32: MmmF32,
     pub qmmm_i8_i32: Box<
         dyn Fn(usize, usize, usize) -> Box<dyn mmm::QMatMatMul<i8, i8, i32, i32>> + Send + Sync,
     >,
@@ -44,6 +45,9 @@ pub struct Ops {
         Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::QMatMatMul<u8, u8, u8, i32>> + Send + Sync>,
     pub qmmm_i8_i8:
         Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::QMatMatMul<i8, i8, i8, i32>> + Send + Sync>,
+    pub qmmm_i16_i32: Box<
+        dyn Fn(usize, usize, usize) -> Box<dyn mmm::QMatMatMul<i16, i16, i32, i64>> + Send + Sync,
+    >,
     pub sigmoid_f32: Box<dyn Fn() -> Box<dyn sigmoid::Sigmoid<f32>> + Send + Sync>,
     pub tanh_f32: Box<dyn Fn() -> Box<dyn tanh::Tanh<f32>> + Send + Sync>,
     pub lut_u8: Box<dyn Fn(&[u8]) -> Box<dyn lut::Lut> + Send + Sync>,
@@ -96,25 +100,95 @@ pub fn generic() -> Ops {
                 i32,
             >::new(m, k, n)))
         }),
+        // products of 16-bit values overflow an i32 accumulator after a couple of terms
+        qmmm_i16_i32: Box::new(|m, k, n| {
+            Box::new(mmm::QMatMatMulImpl::from(mmm::MatMatMulImpl::<
+                generic::GenericMmm4x4<i16, i16, i32, i64>,
+                i16,
+                i16,
+                i32,
+                i64,
+            >::new(m, k, n)))
+        }),
         sigmoid_f32: Box::new(|| Box::new(sigmoid::SigmoidImpl::<generic::SSigmoid4, f32>::new())),
         tanh_f32: Box::new(|| Box::new(tanh::TanhImpl::<generic::STanh4, f32>::new())),
         lut_u8: Box::new(|table: &[u8]| Box::new(lut::LutImpl::<generic::GenericLut8>::new(table))),
     }
 }
 
-#[allow(unreachable_code, unused_mut)]
+/// Kernel choices overriding the pick of `best_for()`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct KernelSelector {
+    /// Stick to the generic kernels: they accumulate in a fixed order, so
+    /// results are bit-identical from one machine to another, at the cost of
+    /// speed.
+    pub deterministic: bool,
+    /// Name of the f32 matrix multiplication kernel to use, as understood by
+    /// `mmm_f32_for_kernel`. Ignored if the kernel is not available.
+    pub mmm_f32_kernel: Option<String>,
+}
+
+impl KernelSelector {
+    /// The selection made by the `TRACT_DETERMINISTIC` and
+    /// `TRACT_MMM_F32_KERNEL` environment variables.
+    pub fn from_env() -> KernelSelector {
+        KernelSelector {
+            deterministic: std::env::var("TRACT_DETERMINISTIC").is_ok(),
+            mmm_f32_kernel: std::env::var("TRACT_MMM_F32_KERNEL").ok(),
+        }
+    }
+}
+
+/// List the matrix multiplication kernels of `fast` that `deterministic`
+/// replaces, as "<operation>: <fast kernel> kernel replaced by <kernel>".
+pub fn deterministic_report(fast: &Ops, deterministic: &Ops) -> Vec<String> {
+    let kernels = |ops: &Ops| {
+        vec![
+            ("mmm_f32", (ops.mmm_f32)(1, 1, 1).kernel_name()),
+            ("qmmm_i8_i32", (ops.qmmm_i8_i32)(1, 1, 1).as_mmm().kernel_name()),
+            ("qmmm_u8_i32", (ops.qmmm_u8_i32)(1, 1, 1).as_mmm().kernel_name()),
+            ("qmmm_u8_u8", (ops.qmmm_u8_u8)(1, 1, 1).as_mmm().kernel_name()),
+            ("qmmm_i8_i8", (ops.qmmm_i8_i8)(1, 1, 1).as_mmm().kernel_name()),
+            ("qmmm_i16_i32", (ops.qmmm_i16_i32)(1, 1, 1).as_mmm().kernel_name()),
+        ]
+    };
+    kernels(fast)
+        .into_iter()
+        .zip(kernels(deterministic).into_iter())
+        .filter(|(fast, det)| fast.1 != det.1)
+        .map(|(fast, det)| format!("{}: {} kernel replaced by {}", fast.0, fast.1, det.1))
+        .collect()
+}
+
+/// The fastest kernels for the running CPU, unless overridden by the
+/// environment (see `KernelSelector::from_env()`).
 pub fn best() -> Ops {
+    best_for(&KernelSelector::from_env())
+}
+
+/// The fastest kernels for the running CPU, unless overridden by `selector`.
+///
+/// With the `wasm` feature, this is always `generic()`: the x86_64 fma and
+/// avx2 matrix multipliers, the arm32 vfpv2 and neon kernels, the arm64 simd
+/// kernels and the `selector` override are all left out.
+#[allow(unreachable_code, unused_mut)]
+pub fn best_for(selector: &KernelSelector) -> Ops {
+    if cfg!(feature = "wasm") {
+        return generic();
+    }
+    if selector.deterministic {
+        let fast = best_for(&KernelSelector { deterministic: false, ..selector.clone() });
+        let ops = generic();
+        for replaced in deterministic_report(&fast, &ops) {
+            log::warn!("deterministic mode, {}", replaced);
+        }
+        return ops;
+    }
     let mut ops = generic();
     #[cfg(target_arch = "x86_64")]
     {
-        if is_x86_feature_detected!("fma") {
-            ops.mmm_f32 = Box::new(|m, k, n| {
-                Box::new(
-                    mmm::MatMatMulImpl::<x86_64_fma::mmm::MatMatMulF32x16x6, f32, f32, f32, f32>::new(
-                        m, k, n,
-                    ),
-                )
-            });
+        if let Some(mmm) = mmm_f32_for_kernel("fma") {
+            ops.mmm_f32 = mmm;
             log::info!("mmm_f32 x86_64/fma activated");
         }
         if is_x86_feature_detected!("avx2") {
@@ -143,9 +217,47 @@ pub fn best() -> Ops {
     arm32::plug(&mut ops);
     #[cfg(target_arch = "aarch64")]
     arm64::plug(&mut ops);
+    if let Some(kernel) = &selector.mmm_f32_kernel {
+        if let Some(mmm) = mmm_f32_for_kernel(kernel) {
+            ops.mmm_f32 = mmm;
+            log::info!("mmm_f32 forced to {} kernel", kernel);
+        } else {
+            log::warn!("mmm_f32 kernel {} is not available, ignoring it", kernel);
+        }
+    }
     return ops;
 }
 
+/// Build a f32 matrix multiplier for a given kernel name, if the kernel
+/// exists and is supported by the running CPU.
+///
+/// Known kernels are "generic", "fma" (x86_64), "vfpv2" and "neon" (arm32),
+/// "arm64simd" (aarch64). `best_for()` will use the one named in its
+/// `KernelSelector` instead of its own pick.
+#[allow(unreachable_code)]
+pub fn mmm_f32_for_kernel(name: &str) -> Option<MmmF32> {
+    if name == "generic" {
+        return Some(generic().mmm_f32);
+    }
+    #[cfg(target_arch = "x86_64")]
+    {
+        if name == "fma" && is_x86_feature_detected!("fma") {
+            return Some(Box::new(|m, k, n| {
+                Box::new(
+                    mmm::MatMatMulImpl::<x86_64_fma::mmm::MatMatMulF32x16x6, f32, f32, f32, f32>::new(
+                        m, k, n,
+                    ),
+                )
+            }));
+        }
+    }
+    #[cfg(any(target_arch = "arm", target_arch = "armv7"))]
+    return arm32::mmm_f32_for_kernel(name);
+    #[cfg(target_arch = "aarch64")]
+    return arm64::mmm_f32_for_kernel(name);
+    None
+}
+
 lazy_static::lazy_static! {
     static ref OPS: Ops = {
         best()
@@ -156,6 +268,20 @@ pub fn ops() -> &'static Ops {
     &*OPS
 }
 
+/// Same as `best_for(selector)`, built once per selector.
+pub fn ops_for(selector: &KernelSelector) -> &'static Ops {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    lazy_static::lazy_static! {
+        static ref SELECTED: Mutex<HashMap<KernelSelector, &'static Ops>> = Default::default();
+    }
+    let mut selected = SELECTED.lock().unwrap();
+    *selected.entry(selector.clone()).or_insert_with(|| {
+        let ops: &'static Ops = Box::leak(Box::new(best_for(selector)));
+        ops
+    })
+}
+
 #[cfg(test)]
 mod test {
     use num_traits::*;
@@ -210,6 +336,115 @@ mod test {
         }
     }
 
+    fn run_mmm_f32(
+        op: &dyn crate::mmm::MatMatMul<f32, f32, f32, f32>,
+        (m, k, n): (usize, usize, usize),
+        a: &[f32],
+        b: &[f32],
+    ) -> Vec<f32> {
+        let mut found = vec![0f32; m * n];
+        unsafe {
+            let mut packed_a =
+                crate::align::Buffer::uninitialized(op.a_pack().len(), op.a_pack().alignment());
+            op.a_pack().pack(packed_a.as_mut_ptr(), a.as_ptr(), k as isize, 1);
+            let mut packed_b =
+                crate::align::Buffer::uninitialized(op.b_pack().len(), op.b_pack().alignment());
+            op.b_pack().pack(packed_b.as_mut_ptr(), b.as_ptr(), n as isize, 1);
+            op.run(packed_a.as_ptr(), packed_b.as_ptr(), found.as_mut_ptr(), &[]);
+        }
+        found
+    }
+
+    #[test]
+    fn mmm_f32_kernel_override() {
+        for &name in &["generic", "fma", "vfpv2", "neon", "arm64simd"] {
+            if let Some(mmm) = super::mmm_f32_for_kernel(name) {
+                let (m, k, n) = (5, 3, 7);
+                let op = mmm(m, k, n);
+                assert_eq!(op.kernel_name(), name);
+                let a = (0..m * k).map(|i| i as f32).collect::<Vec<_>>();
+                let b = (0..k * n).map(|i| i as f32 / 2.0).collect::<Vec<_>>();
+                let found = run_mmm_f32(&*op, (m, k, n), &a, &b);
+                for y in 0..m {
+                    for x in 0..n {
+                        let expected = (0..k).map(|i| a[i + k * y] * b[x + i * n]).sum::<f32>();
+                        assert!(found[x + y * n].close(&expected));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn best_honours_selected_kernel() {
+        let kernel_name = |selector| (super::best_for(&selector).mmm_f32)(5, 3, 7).kernel_name();
+        let default = kernel_name(super::KernelSelector::default());
+        let generic = super::KernelSelector {
+            mmm_f32_kernel: Some("generic".into()),
+            ..super::KernelSelector::default()
+        };
+        assert_eq!(kernel_name(generic), "generic");
+        let unknown = super::KernelSelector {
+            mmm_f32_kernel: Some("no-such-kernel".into()),
+            ..super::KernelSelector::default()
+        };
+        assert_eq!(kernel_name(unknown), default);
+    }
+
+    #[test]
+    fn ops_for_is_built_once_per_selector() {
+        let generic = super::KernelSelector { deterministic: true, ..Default::default() };
+        let ops = super::ops_for(&generic);
+        assert!(std::ptr::eq(ops, super::ops_for(&generic)));
+        assert!(!std::ptr::eq(ops, super::ops_for(&super::KernelSelector::default())));
+        assert_eq!((ops.mmm_f32)(5, 3, 7).kernel_name(), "generic");
+    }
+
+    #[test]
+    fn deterministic_selects_generic_kernels() {
+        let selector =
+            super::KernelSelector { deterministic: true, mmm_f32_kernel: Some("fma".into()) };
+        let ops = super::best_for(&selector);
+        assert!(super::deterministic_report(&super::generic(), &ops).is_empty());
+    }
+
+    #[test]
+    fn deterministic_reports_replaced_fast_kernels() {
+        use crate::generic::mmm::GenericMmmTest3x2;
+        let mut fast = super::generic();
+        fast.mmm_f32 = Box::new(|m, k, n| {
+            Box::new(crate::mmm::MatMatMulImpl::<
+                GenericMmmTest3x2<f32, f32, f32, f32>,
+                f32,
+                f32,
+                f32,
+                f32,
+            >::new(m, k, n))
+        });
+        assert_eq!(
+            super::deterministic_report(&fast, &super::generic()),
+            vec!["mmm_f32: generic-test-3x2 kernel replaced by generic".to_string()]
+        );
+        assert!(super::deterministic_report(&super::generic(), &super::generic()).is_empty());
+    }
+
+    #[test]
+    fn mmm_f32_generic_is_ordered() {
+        // values of mixed magnitudes, so that the sums depend on the order
+        let (m, k, n) = (5, 37, 7);
+        let a = (0..m * k).map(|i| ((i * 7919) % 1000) as f32 / 7.0 - 70.0).collect::<Vec<_>>();
+        let b = (0..k * n).map(|i| ((i * 104729) % 1000) as f32 / 3.0e3).collect::<Vec<_>>();
+        let op = (super::generic().mmm_f32)(m, k, n);
+        let found = run_mmm_f32(&*op, (m, k, n), &a, &b);
+        assert_eq!(found, run_mmm_f32(&*op, (m, k, n), &a, &b));
+        for y in 0..m {
+            for x in 0..n {
+                let expected = (0..k).fold(0f32, |acc, i| acc + a[i + k * y] * b[x + i * n]);
+                assert_eq!(found[x + y * n].to_bits(), expected.to_bits());
+            }
+        }
+    }
+
     pub(crate) fn check_close<T: Datum>(
         found: &[T],
         expected: &[T],
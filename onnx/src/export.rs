@@ -0,0 +1,556 @@
+//! Translation of a `TypedModel` back to an ONNX `ModelProto`.
+//!
+//! Only a subset of tract operators can be exported: convolutions, matrix
+//! products, pooling, padding, reshaping and axes manipulations, reductions,
+//! and the usual element-wise and binary arithmetic operators. Softmax has no
+//! typed counterpart in tract: typing a model lowers it to ReduceMax, Sub,
+//! Exp, ReduceSum and Div, so it is exported in this decomposed form.
+use crate::pb::attribute_proto::AttributeType;
+use crate::pb::tensor_proto::DataType;
+use crate::pb::*;
+use std::convert::TryFrom;
+use tract_hir::internal::*;
+use tract_hir::tract_core::ops;
+use tract_hir::tract_core::ops::cnn::{KernelFormat, PaddingSpec, PoolSpec};
+use tract_hir::tract_core::ops::nn::{DataFormat, Reducer};
+
+/// Version of the default ONNX operator set the exported models use.
+const OPSET: i64 = 9;
+
+/// Build an ONNX model from a typed model.
+pub fn model_to_proto(model: &TypedModel) -> TractResult<ModelProto> {
+    let mut exporter = GraphExporter { model, graph: GraphProto::default() };
+    exporter.export()?;
+    Ok(ModelProto {
+        ir_version: Version::IrVersion as i64,
+        opset_import: vec![OperatorSetIdProto { domain: String::new(), version: OPSET }],
+        producer_name: "tract".to_string(),
+        producer_version: env!("CARGO_PKG_VERSION").to_string(),
+        graph: Some(exporter.graph),
+        ..ModelProto::default()
+    })
+}
+
+struct GraphExporter<'a> {
+    model: &'a TypedModel,
+    graph: GraphProto,
+}
+
+impl<'a> GraphExporter<'a> {
+    fn export(&mut self) -> TractResult<()> {
+        for &input in self.model.input_outlets()? {
+            let info = self.value_info(input)?;
+            self.graph.input.push(info);
+        }
+        for id in self.model.eval_order()? {
+            let node = &self.model.nodes()[id];
+            self.export_node(node).chain_err(|| format!("Exporting {} to ONNX", node))?;
+        }
+        for &output in self.model.output_outlets()? {
+            let info = self.value_info(output)?;
+            self.graph.output.push(info);
+        }
+        Ok(())
+    }
+
+    fn outlet_name(&self, outlet: OutletId) -> String {
+        let node = &self.model.nodes()[outlet.node];
+        if outlet.slot == 0 {
+            node.name.clone()
+        } else {
+            format!("{}:{}", node.name, outlet.slot)
+        }
+    }
+
+    fn value_info(&self, outlet: OutletId) -> TractResult<ValueInfoProto> {
+        let fact = self.model.outlet_fact(outlet)?;
+        let dim = fact
+            .shape
+            .iter()
+            .map(|d| {
+                let value = if let Ok(d) = d.to_integer() {
+                    tensor_shape_proto::dimension::Value::DimValue(d as i64)
+                } else {
+                    tensor_shape_proto::dimension::Value::DimParam(d.to_string())
+                };
+                tensor_shape_proto::Dimension { value: Some(value), ..Default::default() }
+            })
+            .collect();
+        let tensor_type = type_proto::Tensor {
+            elem_type: DataType::try_from(fact.datum_type)? as i32,
+            shape: Some(TensorShapeProto { dim }),
+        };
+        Ok(ValueInfoProto {
+            name: self.outlet_name(outlet),
+            r#type: Some(TypeProto {
+                value: Some(type_proto::Value::TensorType(tensor_type)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    fn add_initializer(&mut self, name: String, tensor: &Tensor) -> TractResult<String> {
+        let mut proto = TensorProto::try_from(tensor)?;
+        proto.name = name.clone();
+        self.graph.initializer.push(proto);
+        Ok(name)
+    }
+
+    fn add_node(
+        &mut self,
+        name: String,
+        op_type: &str,
+        input: Vec<String>,
+        output: String,
+        attribute: Vec<AttributeProto>,
+    ) -> String {
+        self.graph.node.push(NodeProto {
+            name,
+            op_type: op_type.to_string(),
+            input,
+            output: vec![output.clone()],
+            attribute,
+            ..Default::default()
+        });
+        output
+    }
+
+    fn transpose_last_axes(&mut self, name: String, input: String, rank: usize) -> String {
+        let mut perm: Vec<usize> = (0..rank).collect();
+        perm.swap(rank - 2, rank - 1);
+        self.add_node(name.clone(), "Transpose", vec![input], name, vec![attr_ints("perm", perm)])
+    }
+
+    fn export_node(&mut self, node: &TypedNode) -> TractResult<()> {
+        let inputs: Vec<String> = node.inputs.iter().map(|&i| self.outlet_name(i)).collect();
+        let output = self.outlet_name(OutletId::new(node.id, 0));
+        let name = node.name.clone();
+        if node.op_is::<ops::source::TypedSource>() {
+            return Ok(());
+        } else if let Some(konst) = node.op_as::<ops::konst::Const>() {
+            self.add_initializer(output, &konst.0)?;
+        } else if node.op_is::<ops::identity::Identity>() {
+            self.add_node(name, "Identity", inputs, output, vec![]);
+        } else if let Some(conv) = node.op_as::<ops::cnn::ConvUnary>() {
+            if conv.q_params.is_some() {
+                bail!("Quantized convolutions can not be exported");
+            }
+            if conv.kernel_fmt != KernelFormat::OIHW {
+                bail!("Only OIHW kernels can be exported, got {:?}", conv.kernel_fmt);
+            }
+            let mut attributes = pool_spec_attributes(&conv.pool_spec, true)?;
+            attributes.push(attr_int("group", conv.group as i64));
+            let mut inputs = inputs;
+            inputs.push(self.add_initializer(format!("{}.kernel", name), &conv.kernel)?);
+            if let Some(bias) = &conv.bias {
+                let len = bias.len();
+                let bias = unsafe { bias.clone().into_tensor().into_shape(&[len])? };
+                inputs.push(self.add_initializer(format!("{}.bias", name), &bias)?);
+            }
+            self.add_node(name, "Conv", inputs, output, attributes);
+        } else if let Some(mm) = node.op_as::<ops::matmul::MatMul>() {
+            if mm.q_params.is_some() {
+                bail!("Quantized matrix products can not be exported");
+            }
+            let ranks = (
+                self.model.outlet_fact(node.inputs[0])?.rank(),
+                self.model.outlet_fact(node.inputs[1])?.rank(),
+            );
+            let (a, b) = (inputs[0].clone(), inputs[1].clone());
+            self.export_matmul(
+                name,
+                (a, ranks.0),
+                (b, ranks.1),
+                output,
+                mm.a_trans,
+                mm.b_trans,
+                mm.c_trans,
+            );
+        } else if let Some(mm) = node.op_as::<ops::matmul::MatMulUnary>() {
+            if mm.q_params.is_some() {
+                bail!("Quantized matrix products can not be exported");
+            }
+            let a = self.add_initializer(format!("{}.a", name), &mm.a)?;
+            let b_rank = self.model.outlet_fact(node.inputs[0])?.rank();
+            let b = inputs[0].clone();
+            self.export_matmul(
+                name,
+                (a, mm.a.rank()),
+                (b, b_rank),
+                output,
+                mm.a_trans,
+                mm.b_trans,
+                mm.c_trans,
+            );
+        } else if let Some(ew) = node.op_as::<ops::element_wise::ElementWiseOp>() {
+            if let Some(max) = ew.0.downcast_ref::<ops::math::ScalarMax>() {
+                if max.max.cast_to_scalar::<f64>()? == 0.0 {
+                    self.add_node(name, "Relu", inputs, output, vec![]);
+                } else {
+                    let mut inputs = inputs;
+                    inputs.push(self.add_initializer(format!("{}.max", name), &max.max)?);
+                    self.add_node(name, "Max", inputs, output, vec![]);
+                }
+            } else if let Some(min) = ew.0.downcast_ref::<ops::math::ScalarMin>() {
+                let mut inputs = inputs;
+                inputs.push(self.add_initializer(format!("{}.min", name), &min.min)?);
+                self.add_node(name, "Min", inputs, output, vec![]);
+            } else {
+                let op_type = match &*ew.0.name() {
+                    "Ln" => "Log".to_string(),
+                    "Recip" => "Reciprocal".to_string(),
+                    it @ "Abs"
+                    | it @ "Exp"
+                    | it @ "Sqrt"
+                    | it @ "Rsqrt"
+                    | it @ "Neg"
+                    | it @ "Sign"
+                    | it @ "Ceil"
+                    | it @ "Floor"
+                    | it @ "Cos"
+                    | it @ "Sin"
+                    | it @ "Tan"
+                    | it @ "Acos"
+                    | it @ "Asin"
+                    | it @ "Atan"
+                    | it @ "Cosh"
+                    | it @ "Sinh"
+                    | it @ "Tanh"
+                    | it @ "Acosh"
+                    | it @ "Asinh"
+                    | it @ "Atanh"
+                    | it @ "Sigmoid"
+                    | it @ "Softplus"
+                    | it @ "Softsign" => it.to_string(),
+                    it => bail!("No ONNX operator for element-wise {}", it),
+                };
+                self.add_node(name, &op_type, inputs, output, vec![]);
+            }
+        } else if let Some(bin) = node.op_as::<ops::binary::TypedBinOp>() {
+            let op_type = bin_op_type(&*bin.0)?;
+            self.add_node(name, op_type, inputs, output, vec![]);
+        } else if let Some(unary) = node.op_as::<ops::binary::UnaryOp>() {
+            let op_type = bin_op_type(&*unary.mini_op)?;
+            let a = self.add_initializer(format!("{}.a", name), &unary.a)?;
+            self.add_node(name, op_type, vec![a, inputs[0].clone()], output, vec![]);
+        } else if let Some(ops::change_axes::AxisOp::Permute(perm)) =
+            node.op_as::<ops::change_axes::AxisOp>()
+        {
+            let perm = attr_ints("perm", perm.iter().cloned());
+            self.add_node(name, "Transpose", inputs, output, vec![perm]);
+        } else if node.op_is::<ops::array::TypedReshape>()
+            || node.op_is::<ops::array::FiniteReshape>()
+            || node.op_is::<ops::change_axes::AxisOp>()
+        {
+            let shape = self
+                .model
+                .outlet_fact(OutletId::new(node.id, 0))?
+                .shape
+                .iter()
+                .map(|d| d.to_integer())
+                .collect::<TractResult<Vec<i64>>>()?;
+            let shape = tensor1(&*shape);
+            let mut inputs = inputs;
+            inputs.push(self.add_initializer(format!("{}.shape", name), &shape)?);
+            self.add_node(name, "Reshape", inputs, output, vec![]);
+        } else if let Some(pad) = node.op_as::<ops::array::Pad>() {
+            let pads = pad.pads.iter().map(|p| p.0).chain(pad.pads.iter().map(|p| p.1));
+            let mut attributes = vec![attr_ints("pads", pads)];
+            match &pad.mode {
+                ops::array::PadMode::Constant(c) => {
+                    attributes.push(attr_string("mode", "constant"));
+                    attributes.push(attr_float("value", c.cast_to_scalar::<f32>()?));
+                }
+                ops::array::PadMode::Reflect => attributes.push(attr_string("mode", "reflect")),
+                ops::array::PadMode::Edge => attributes.push(attr_string("mode", "edge")),
+            }
+            self.add_node(name, "Pad", inputs, output, attributes);
+        } else if let Some(pool) = node.op_as::<ops::cnn::MaxPool>() {
+            if pool.with_index_outputs.is_some() {
+                bail!("MaxPool with index output can not be exported");
+            }
+            // MaxPool has no dilations before opset 10
+            let attributes = pool_spec_attributes(&pool.pool_spec, OPSET >= 10)?;
+            self.add_node(name, "MaxPool", inputs, output, attributes);
+        } else if let Some(pool) = node.op_as::<ops::cnn::AvgPool>() {
+            let mut attributes = pool_spec_attributes(&pool.pool_spec, false)?;
+            attributes.push(attr_int("count_include_pad", pool.count_include_pad as i64));
+            self.add_node(name, "AveragePool", inputs, output, attributes);
+        } else if let Some(reduce) = node.op_as::<ops::nn::Reduce>() {
+            let op_type = match reduce.reducer {
+                Reducer::Max => "ReduceMax",
+                Reducer::Min => "ReduceMin",
+                Reducer::Prod => "ReduceProd",
+                Reducer::Sum => "ReduceSum",
+            };
+            let attributes =
+                vec![attr_ints("axes", reduce.axes.iter().cloned()), attr_int("keepdims", 1)];
+            self.add_node(name, op_type, inputs, output, attributes);
+        } else {
+            bail!("Operator {} can not be exported to ONNX", node.op.name());
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn export_matmul(
+        &mut self,
+        name: String,
+        a: (String, usize),
+        b: (String, usize),
+        output: String,
+        a_trans: bool,
+        b_trans: bool,
+        c_trans: bool,
+    ) {
+        // C^T = B^T A^T: a transposed output is obtained by swapping the operands
+        let (left, left_trans, right, right_trans) =
+            if c_trans { (b, !b_trans, a, !a_trans) } else { (a, a_trans, b, b_trans) };
+        let left = if left_trans {
+            self.transpose_last_axes(format!("{}.left_transpose", name), left.0, left.1)
+        } else {
+            left.0
+        };
+        let right = if right_trans {
+            self.transpose_last_axes(format!("{}.right_transpose", name), right.0, right.1)
+        } else {
+            right.0
+        };
+        self.add_node(name, "MatMul", vec![left, right], output, vec![]);
+    }
+}
+
+fn bin_op_type(mini_op: &dyn ops::binary::BinMiniOp) -> TractResult<&'static str> {
+    match mini_op.name() {
+        it @ "Add"
+        | it @ "Sub"
+        | it @ "Mul"
+        | it @ "Div"
+        | it @ "Pow"
+        | it @ "Min"
+        | it @ "Max" => Ok(it),
+        it => bail!("No ONNX operator for binary {}", it),
+    }
+}
+
+/// Attributes shared by Conv and the pooling operators. Non-unit dilations
+/// are refused when the operator has no `dilations` attribute.
+fn pool_spec_attributes(
+    pool_spec: &PoolSpec,
+    has_dilations: bool,
+) -> TractResult<Vec<AttributeProto>> {
+    if pool_spec.data_format != DataFormat::NCHW {
+        bail!("Only NCHW data format can be exported, got {:?}", pool_spec.data_format);
+    }
+    let mut attributes = vec![attr_ints("kernel_shape", pool_spec.kernel_shape.iter().cloned())];
+    if let Some(strides) = &pool_spec.strides {
+        attributes.push(attr_ints("strides", strides.iter().cloned()));
+    }
+    if let Some(dilations) = &pool_spec.dilations {
+        if has_dilations {
+            attributes.push(attr_ints("dilations", dilations.iter().cloned()));
+        } else if dilations.iter().any(|&d| d != 1) {
+            bail!("Dilations {:?} can not be exported to ONNX opset {}", dilations, OPSET);
+        }
+    }
+    match &pool_spec.padding {
+        PaddingSpec::Explicit(before, after) => {
+            attributes.push(attr_ints("pads", before.iter().chain(after.iter()).cloned()))
+        }
+        PaddingSpec::Valid => attributes.push(attr_string("auto_pad", "VALID")),
+        PaddingSpec::SameUpper => attributes.push(attr_string("auto_pad", "SAME_UPPER")),
+        PaddingSpec::SameLower => attributes.push(attr_string("auto_pad", "SAME_LOWER")),
+    }
+    Ok(attributes)
+}
+
+fn attr_int(name: &str, i: i64) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: AttributeType::Int as i32,
+        i,
+        ..Default::default()
+    }
+}
+
+fn attr_ints(name: &str, ints: impl IntoIterator<Item = usize>) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: AttributeType::Ints as i32,
+        ints: ints.into_iter().map(|i| i as i64).collect(),
+        ..Default::default()
+    }
+}
+
+fn attr_float(name: &str, f: f32) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: AttributeType::Float as i32,
+        f,
+        ..Default::default()
+    }
+}
+
+fn attr_string(name: &str, s: &str) -> AttributeProto {
+    AttributeProto {
+        name: name.to_string(),
+        r#type: AttributeType::String as i32,
+        s: s.as_bytes().to_vec(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+    use tract_hir::tract_core::ops::array::{FiniteReshape, Pad, PadMode};
+    use tract_hir::tract_core::ops::cnn::{ConvUnary, MaxPool};
+    use tract_hir::tract_core::ops::matmul::MatMulUnary;
+
+    fn value_info(name: &str, shape: &[i64]) -> ValueInfoProto {
+        let dim = shape
+            .iter()
+            .map(|&d| tensor_shape_proto::Dimension {
+                value: Some(tensor_shape_proto::dimension::Value::DimValue(d)),
+                ..Default::default()
+            })
+            .collect();
+        let tensor_type = type_proto::Tensor {
+            elem_type: DataType::Float as i32,
+            shape: Some(TensorShapeProto { dim }),
+        };
+        ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(TypeProto {
+                value: Some(type_proto::Value::TensorType(tensor_type)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn node(op_type: &str, input: &[&str], attribute: Vec<AttributeProto>) -> NodeProto {
+        NodeProto {
+            name: op_type.to_lowercase(),
+            op_type: op_type.to_string(),
+            input: input.iter().map(|s| s.to_string()).collect(),
+            output: vec![op_type.to_lowercase()],
+            attribute,
+            ..Default::default()
+        }
+    }
+
+    fn reload(model: &TypedModel) -> TractResult<TypedModel> {
+        let proto = model_to_proto(model)?;
+        let mut buffer = vec![];
+        proto.encode(&mut buffer).map_err(|e| format!("{:?}", e))?;
+        let proto = ModelProto::decode(&*buffer).map_err(|e| format!("{:?}", e))?;
+        crate::onnx().model_for_proto_model(&proto)?.into_typed()
+    }
+
+    #[test]
+    fn onnx_round_trip() -> TractResult<()> {
+        let weights: Vec<f32> = (0..12).map(|i| (i % 5) as f32 - 2.).collect();
+        let weights = tract_ndarray::Array::from_shape_vec((3, 4), weights)?.into_tensor();
+        let mut weights = TensorProto::try_from(&weights)?;
+        weights.name = "w".to_string();
+        let mut bias = TensorProto::try_from(&tensor1(&[0.5f32, -0.5, 1., 0.]))?;
+        bias.name = "b".to_string();
+        let graph = GraphProto {
+            node: vec![
+                node("MatMul", &["x", "w"], vec![]),
+                node("Add", &["matmul", "b"], vec![]),
+                node("Relu", &["add"], vec![]),
+                node("Softmax", &["relu"], vec![attr_int("axis", 1)]),
+            ],
+            initializer: vec![weights, bias],
+            input: vec![value_info("x", &[2, 3])],
+            output: vec![value_info("softmax", &[2, 4])],
+            ..Default::default()
+        };
+        let proto = ModelProto {
+            ir_version: Version::IrVersion as i64,
+            opset_import: vec![OperatorSetIdProto { domain: String::new(), version: 9 }],
+            graph: Some(graph),
+            ..ModelProto::default()
+        };
+        let model = crate::onnx().model_for_proto_model(&proto)?.into_typed()?;
+        let reloaded = reload(&model)?;
+        assert!(reloaded.nodes().iter().any(|n| n.op_is::<ops::nn::Reduce>()));
+
+        let data = tensor2(&[[1f32, -2., 0.5], [3., 0.25, -1.]]);
+        let expected = SimplePlan::new(&model)?.run(tvec!(data.clone()))?;
+        let found = SimplePlan::new(&reloaded)?.run(tvec!(data))?;
+        found[0].close_enough(&expected[0], true)
+    }
+
+    #[test]
+    fn typed_round_trip() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let input = model
+            .add_source("input", TypedFact::dt_shape(f32::datum_type(), [1, 2, 5, 5].as_ref())?)?;
+        let padded = model.wire_node(
+            "pad",
+            Pad::new(vec![(0, 0), (0, 0), (1, 1), (1, 1)], PadMode::Constant(rctensor0(0f32))),
+            &[input],
+        )?;
+        let kernel: Vec<f32> = (0..54).map(|i| (i as f32 - 27.) / 10.).collect();
+        let kernel = tract_ndarray::Array::from_shape_vec((3, 2, 3, 3), kernel)?;
+        let conv = ConvUnary::new(
+            PoolSpec::new(DataFormat::NCHW, tvec!(3, 3), PaddingSpec::Valid, None, None, Some(3)),
+            KernelFormat::OIHW,
+            kernel.into_arc_tensor(),
+            1,
+            Some(rctensor1(&[0.1f32, -0.2, 0.3])),
+            None,
+        );
+        let conv = model.wire_node("conv", conv, &padded)?;
+        let relu = model.wire_node("relu", ops::math::scalar_max(0f32.into()), &conv)?;
+        let pool_spec = PoolSpec::new(
+            DataFormat::NCHW,
+            tvec!(2, 2),
+            PaddingSpec::Valid,
+            None,
+            Some(tvec!(2, 2)),
+            None,
+        );
+        let pool = model.wire_node("pool", MaxPool::new(pool_spec, None), &relu)?;
+        let flat = model.wire_node("flat", FiniteReshape::new(tvec!(1, 12)), &pool)?;
+        let weights: Vec<f32> = (0..48).map(|i| (i % 7) as f32 - 3.).collect();
+        let weights = tract_ndarray::Array::from_shape_vec((4, 12), weights)?;
+        let mm = MatMulUnary::new(weights.into_arc_tensor(), false, true, true, None);
+        let mm = model.wire_node("mm", mm, &flat)?;
+        let bias = ops::math::add::unary(rctensor2(&[[1f32, 2., 3., 4.]]));
+        let output = model.wire_node("bias", bias, &mm)?;
+        model.set_output_outlets(&output)?;
+
+        let reloaded = reload(&model)?;
+
+        let data: Vec<f32> = (0..50).map(|i| (i as f32 * 0.37).sin()).collect();
+        let data = tract_ndarray::Array::from_shape_vec((1, 2, 5, 5), data)?.into_tensor();
+        let expected = SimplePlan::new(&model)?.run(tvec!(data.clone()))?;
+        let found = SimplePlan::new(&reloaded)?.run(tvec!(data))?;
+        found[0].close_enough(&expected[0], true)
+    }
+
+    #[test]
+    fn dilated_max_pool_needs_opset_10() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let input = model
+            .add_source("input", TypedFact::dt_shape(f32::datum_type(), [1, 1, 5, 5].as_ref())?)?;
+        let pool_spec = PoolSpec::new(
+            DataFormat::NCHW,
+            tvec!(2, 2),
+            PaddingSpec::Valid,
+            Some(tvec!(2, 2)),
+            None,
+            None,
+        );
+        let pool = model.wire_node("pool", MaxPool::new(pool_spec, None), &[input])?;
+        model.set_output_outlets(&pool)?;
+        assert!(model_to_proto(&model).is_err());
+        Ok(())
+    }
+}
@@ -11,6 +11,7 @@ extern crate num_integer;
 #[macro_use]
 pub extern crate tract_hir;
 
+pub mod export;
 pub mod model;
 pub mod ops;
 
@@ -21,7 +22,7 @@ pub mod pb {
 pub mod pb_helpers;
 pub mod tensor;
 
-pub use model::Onnx;
+pub use model::{Onnx, UnknownOps};
 
 pub use tract_hir::tract_core;
 pub mod prelude {
@@ -44,5 +45,5 @@ pub fn for_reader<R: std::io::Read>(mut r: R) -> TractResult<InferenceModel> {
 pub fn onnx() -> Onnx {
     let mut ops = crate::model::OnnxOpRegister::default();
     ops::register_all_ops(&mut ops);
-    Onnx { op_register: ops }
+    Onnx { op_register: ops, ..Onnx::default() }
 }
@@ -35,6 +35,8 @@ pub fn optional_outputs(pb: &pb::NodeProto) -> impl Iterator<Item = Option<usize
 #[derive(Clone)]
 pub struct ParsingContext<'a> {
     pub onnx_operator_set_version: i64,
+    /// Operator set versions imported by the model, by domain.
+    pub opset_versions: HashMap<String, i64>,
     pub framework: &'a Onnx,
     pub model: &'a pb::ModelProto,
     pub parent_graphs: Vec<&'a pb::GraphProto>,
@@ -48,6 +50,31 @@ pub struct ParseResult {
 }
 
 impl<'a> ParsingContext<'a> {
+    /// Operator set version imported for `domain`, "ai.onnx" being an alias
+    /// for the default domain.
+    pub fn opset_version(&self, domain: &str) -> TractResult<i64> {
+        let domain = if domain == "ai.onnx" { "" } else { domain };
+        Ok(*self
+            .opset_versions
+            .get(domain)
+            .ok_or_else(|| format!("Operator set {:?} is not imported by the model", domain))?)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn for_opset(
+        framework: &'a Onnx,
+        model: &'a pb::ModelProto,
+        version: i64,
+    ) -> ParsingContext<'a> {
+        ParsingContext {
+            onnx_operator_set_version: version,
+            opset_versions: std::iter::once((String::new(), version)).collect(),
+            framework,
+            model,
+            parent_graphs: vec![],
+        }
+    }
+
     pub fn parse_graph(&self, graph: &pb::GraphProto) -> TractResult<ParseResult> {
         let mut ctx = self.clone();
         ctx.parent_graphs.push(graph);
@@ -88,7 +115,7 @@ impl<'a> ParsingContext<'a> {
             let id = model.add_const(name, t)?;
             outlets_by_name.insert(name.to_string(), id);
         }
-        let consts = model.nodes().len();
+        let mut node_ids = vec![];
         for pbnode in graph.node.iter() {
             let name = if pbnode.name != "" {
                 pbnode.name.to_string()
@@ -107,17 +134,35 @@ impl<'a> ParsingContext<'a> {
             trace!("  outputs {:?}", pbnode.output);
             let (op, closures) = match self.framework.op_register.0.get(&pbnode.op_type) {
                 Some(builder) => (builder)(&ctx, pbnode)?,
-                None => (
-                    tract_hir::ops::unimpl::UnimplementedOp::new(
-                        pbnode.output.len(),
-                        &*pbnode.op_type,
-                        format!("{:?}", pbnode),
-                    )
-                    .into(),
-                    vec![],
-                ),
+                None => match self.framework.unknown_ops {
+                    UnknownOps::Stub => (
+                        tract_hir::ops::unimpl::UnimplementedOp::new(
+                            pbnode.output.len(),
+                            &*pbnode.op_type,
+                            format!("{:?}", pbnode),
+                        )
+                        .into(),
+                        vec![],
+                    ),
+                    UnknownOps::Error => {
+                        bail!("Unimplemented operator {} for node {}", pbnode.op_type, name)
+                    }
+                    UnknownOps::Skip => {
+                        warn!(
+                            "Skipping unimplemented operator {} for node {}",
+                            pbnode.op_type, name
+                        );
+                        for output in pbnode.output.iter().filter(|s| !s.is_empty()) {
+                            let id = model.add_source(&**output, InferenceFact::default())?;
+                            outlets_by_name.insert(output.to_owned(), id);
+                        }
+                        node_ids.push(None);
+                        continue;
+                    }
+                },
             };
             let id = model.add_node(name, op, facts)?;
+            node_ids.push(Some(id));
             for (ix, output) in pbnode.output.iter().filter(|s| !s.is_empty()).enumerate() {
                 outlets_by_name.insert(output.to_owned(), OutletId::new(id, ix));
                 model.set_outlet_label(OutletId::new(id, ix), output.to_owned())?;
@@ -127,7 +172,8 @@ impl<'a> ParsingContext<'a> {
                 closures_to_wire.push((id, closure))
             }
         }
-        for (id, pbnode) in graph.node.iter().enumerate() {
+        for (id, pbnode) in node_ids.into_iter().zip(graph.node.iter()) {
+            let id = if let Some(id) = id { id } else { continue };
             for (ix, input) in pbnode.input.iter().filter(|s| !s.is_empty()).enumerate() {
                 if !outlets_by_name.contains_key(&*input) {
                     let id = model.add_source(input.clone(), InferenceFact::default())?;
@@ -135,7 +181,7 @@ impl<'a> ParsingContext<'a> {
                     outlets_by_name.insert(input.to_string(), id);
                 }
                 let outlet = outlets_by_name[&*input];
-                model.add_edge(outlet, InletId::new(id + consts, ix))?;
+                model.add_edge(outlet, InletId::new(id, ix))?;
             }
         }
         for (id, closure) in closures_to_wire {
@@ -185,15 +231,46 @@ impl OnnxOpRegister {
     }
 }
 
+/// What to do with operators missing from the register.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnknownOps {
+    /// Load them as UnimplementedOp stubs.
+    Stub,
+    /// Fail the parsing.
+    Error,
+    /// Drop them. Their outputs become new model sources, appended to
+    /// `input_outlets()` after the declared inputs: callers must feed them too.
+    Skip,
+}
+
+impl Default for UnknownOps {
+    fn default() -> UnknownOps {
+        UnknownOps::Stub
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct Onnx {
     pub op_register: OnnxOpRegister,
+    pub unknown_ops: UnknownOps,
 }
 
 impl Onnx {
+    pub fn with_unknown_ops(self, unknown_ops: UnknownOps) -> Onnx {
+        Onnx { unknown_ops, ..self }
+    }
+
     pub fn parse(&self, proto: &pb::ModelProto) -> TractResult<ParseResult> {
+        let opset_versions: HashMap<String, i64> = proto
+            .opset_import
+            .iter()
+            .map(|import| {
+                let domain = if import.domain == "ai.onnx" { "" } else { &*import.domain };
+                (domain.to_string(), import.version)
+            })
+            .collect();
         let onnx_operator_set_version =
-            proto.opset_import.iter().find(|import| import.domain == "").unwrap().version;
+            *opset_versions.get("").ok_or("Model does not import the default ONNX operator set")?;
         let graph = &proto.graph;
         debug!("ONNX operator set version: {:?}", onnx_operator_set_version);
         if onnx_operator_set_version < 9 || onnx_operator_set_version > 10 {
@@ -206,18 +283,49 @@ impl Onnx {
             model: proto,
             parent_graphs: vec![],
             onnx_operator_set_version,
+            opset_versions,
         };
         ctx.parse_graph(graph.as_ref().unwrap())
     }
 }
 
+/// Load the tensors of a graph (and its subgraphs) stored in external files.
+fn load_external_data(graph: &mut pb::GraphProto, model_dir: &path::Path) -> TractResult<()> {
+    for tensor in &mut graph.initializer {
+        crate::tensor::load_external_data(tensor, model_dir)?;
+    }
+    for node in &mut graph.node {
+        for attr in &mut node.attribute {
+            for tensor in attr.t.iter_mut().chain(attr.tensors.iter_mut()) {
+                crate::tensor::load_external_data(tensor, model_dir)?;
+            }
+            for graph in attr.g.iter_mut().chain(attr.graphs.iter_mut()) {
+                load_external_data(graph, model_dir)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 impl Framework<pb::ModelProto> for Onnx {
     fn proto_model_for_path(&self, p: impl AsRef<path::Path>) -> TractResult<pb::ModelProto> {
+        let p = p.as_ref();
         #[cfg(not(target_arch = "wasm32"))]
-        let map = unsafe { memmap::Mmap::map(&fs::File::open(p)?)? };
+        let map = unsafe {
+            memmap::Mmap::map(&fs::File::open(p).chain_err(|| format!("Could not open {:?}", p))?)?
+        };
         #[cfg(target_arch = "wasm32")]
-        let map = fs::read(p)?;
-        Ok(crate::pb::ModelProto::decode(&*map).map_err(|e| format!("{:?}", e))?)
+        let map = fs::read(p).chain_err(|| format!("Could not open {:?}", p))?;
+        let mut proto = crate::pb::ModelProto::decode(&*map).map_err(|e| format!("{:?}", e))?;
+        if let Some(graph) = &mut proto.graph {
+            load_external_data(graph, p.parent().unwrap_or(path::Path::new(".")))?;
+        }
+        Ok(proto)
+    }
+
+    fn model_for_path(&self, p: impl AsRef<path::Path>) -> TractResult<InferenceModel> {
+        let proto = self.proto_model_for_path(p)?;
+        self.model_for_proto_model(&proto)
     }
 
     fn proto_model_for_read(&self, r: &mut dyn std::io::Read) -> TractResult<pb::ModelProto> {
@@ -235,3 +343,114 @@ impl Framework<pb::ModelProto> for Onnx {
         Ok(model)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tract_hir::ops::unimpl::UnimplementedOp;
+
+    fn model_with_unknown_op() -> TractResult<pb::ModelProto> {
+        let mut model = TypedModel::default();
+        let input =
+            model.add_source("input", TypedFact::dt_shape(f32::datum_type(), [2].as_ref())?)?;
+        let neg = model.wire_node("neg", tract_hir::tract_core::ops::math::neg(), &[input])?;
+        let abs = model.wire_node("abs", tract_hir::tract_core::ops::math::abs(), &neg)?;
+        model.set_output_outlets(&abs)?;
+        let mut proto = crate::export::model_to_proto(&model)?;
+        let graph = proto.graph.as_mut().unwrap();
+        graph.node.iter_mut().find(|n| n.op_type == "Neg").unwrap().op_type = "Frobnicate".into();
+        Ok(proto)
+    }
+
+    #[test]
+    fn unknown_op_as_stub_by_default() -> TractResult<()> {
+        let proto = model_with_unknown_op()?;
+        let model = crate::onnx().model_for_proto_model(&proto)?;
+        let stubs: Vec<&InferenceNode> =
+            model.nodes().iter().filter(|n| n.op_is::<UnimplementedOp>()).collect();
+        assert_eq!(stubs.len(), 1);
+        assert_eq!(stubs[0].op().name(), "Unimplemented(Frobnicate)");
+        assert_eq!(model.nodes().len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_op_as_error() -> TractResult<()> {
+        let proto = model_with_unknown_op()?;
+        let onnx = crate::onnx().with_unknown_ops(UnknownOps::Error);
+        assert!(onnx.model_for_proto_model(&proto).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_op_skipped() -> TractResult<()> {
+        let proto = model_with_unknown_op()?;
+        let onnx = crate::onnx().with_unknown_ops(UnknownOps::Skip);
+        let model = onnx.model_for_proto_model(&proto)?;
+        assert!(model.nodes().iter().all(|n| !n.op_is::<UnimplementedOp>()));
+        // the skipped output is an extra input, after the declared one
+        let inputs =
+            model.input_outlets()?.iter().map(|o| &*model.node(o.node).name).collect::<Vec<_>>();
+        assert_eq!(inputs, vec!["input", "neg"]);
+        let abs = model.node_by_name("abs")?;
+        assert_eq!(model.node(abs.inputs[0].node).op().name(), "Source");
+        Ok(())
+    }
+
+    #[test]
+    fn external_initializer() -> TractResult<()> {
+        let mut model = TypedModel::default();
+        let input =
+            model.add_source("input", TypedFact::dt_shape(f32::datum_type(), [4].as_ref())?)?;
+        let add = tract_hir::tract_core::ops::math::add::unary(rctensor1(&[1f32, 2., 3., 4.]));
+        let add = model.wire_node("add", add, &[input])?;
+        model.set_output_outlets(&add)?;
+        let mut proto = crate::export::model_to_proto(&model)?;
+
+        let dir = std::env::temp_dir().join(format!("tract-onnx-external-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let weight = &mut proto.graph.as_mut().unwrap().initializer[0];
+        let mut data = vec![0u8; 8];
+        for f in weight.float_data.drain(..) {
+            data.extend_from_slice(&f.to_bits().to_le_bytes());
+        }
+        fs::write(dir.join("weights.data"), &data)?;
+        let entry = |key: &str, value: &str| pb::StringStringEntryProto {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        weight.external_data =
+            vec![entry("location", "weights.data"), entry("offset", "8"), entry("length", "16")];
+        weight.data_location = pb::tensor_proto::DataLocation::External as i32;
+        let mut buffer = vec![];
+        proto.encode(&mut buffer).map_err(|e| format!("{:?}", e))?;
+        fs::write(dir.join("model.onnx"), &buffer)?;
+
+        let loaded = crate::onnx().model_for_path(dir.join("model.onnx"));
+        fs::remove_dir_all(&dir)?;
+        let result = loaded?.into_runnable()?.run(tvec!(tensor1(&[1f32, 1., 1., 1.])))?;
+        assert_eq!(*result[0], tensor1(&[2f32, 3., 4., 5.]));
+        Ok(())
+    }
+
+    #[test]
+    fn external_data_stays_below_model_dir() {
+        let model_dir = std::env::temp_dir();
+        for location in &["../weights.data", "data/../../weights.data", "/etc/passwd"] {
+            let mut tensor = pb::TensorProto::default();
+            tensor.data_location = pb::tensor_proto::DataLocation::External as i32;
+            tensor.external_data = vec![pb::StringStringEntryProto {
+                key: "location".to_string(),
+                value: location.to_string(),
+            }];
+            let err = crate::tensor::load_external_data(&mut tensor, &model_dir).unwrap_err();
+            assert!(err.to_string().contains("must be relative"), "{}: {}", location, err);
+        }
+    }
+
+    #[test]
+    fn missing_model_file_is_named() {
+        let err = crate::onnx().model_for_path("no/such/model.onnx").unwrap_err();
+        assert!(err.to_string().contains("Could not open \"no/such/model.onnx\""));
+    }
+}
@@ -1,5 +1,6 @@
 mod compress;
 mod slice;
+mod squeeze;
 
 use tract_hir::internal::*;
 use tract_hir::ops::array;
@@ -25,7 +26,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Tile", |_, _| Ok((Box::new(array::Tile::default()), vec![])));
     reg.insert("Slice", slice::slice);
     reg.insert("Split", split);
-    reg.insert("Squeeze", squeeze);
+    reg.insert("Squeeze", squeeze::squeeze);
     reg.insert("Unsqueeze", unsqueeze);
 }
 
@@ -128,14 +129,6 @@ pub fn split(
     Ok((Box::new(array::Split::new(axis, node.output.len(), split)), vec![]))
 }
 
-pub fn squeeze(
-    _ctx: &ParsingContext,
-    node: &NodeProto,
-) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let axes = node.get_attr_opt_vec("axes")?;
-    Ok((Box::new(array::Squeeze::new(axes)), vec![]))
-}
-
 pub fn transpose(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -6,13 +6,13 @@ pub fn slice(
     ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let v = ctx.onnx_operator_set_version;
+    let v = ctx.opset_version(&node.domain)?;
     if v >= 1 && v < 10 {
         slice1(ctx, node)
-    } else if v == 10 {
+    } else if v >= 10 {
         slice10(ctx, node)
     } else {
-        bail!("Only Slice-1 and Slice-10 are supported")
+        bail!("Unsupported operator set version {} for Slice", v)
     }
 }
 
@@ -97,10 +97,10 @@ impl InferenceRulesOp for Slice1 {
                 };
                 if let Some((mut b, mut e)) = spec {
                     if let Ok(d) = d.to_integer() {
-                        if b as i32 > d {
+                        if b as i64 > d {
                             b = (d as isize).into();
                         }
-                        if e as i32 > d {
+                        if e as i64 > d {
                             e = (d as isize).into();
                         }
                     }
@@ -161,3 +161,49 @@ fn slice10(
         vec![],
     ))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_hir::ops::array::StridedSlice;
+
+    fn slice_at_opset(version: i64, node: &NodeProto) -> TractResult<Box<dyn InferenceOp>> {
+        let (onnx, proto) = (crate::onnx(), ModelProto::default());
+        Ok(slice(&ParsingContext::for_opset(&onnx, &proto, version), node)?.0)
+    }
+
+    #[test]
+    fn slice_bounds_from_attributes_before_10() -> TractResult<()> {
+        let mut node = NodeProto { op_type: "Slice".into(), ..NodeProto::default() };
+        for (name, value) in &[("starts", 1), ("ends", 3)] {
+            node.attribute.push(AttributeProto {
+                name: name.to_string(),
+                r#type: attribute_proto::AttributeType::Ints as i32,
+                ints: vec![*value],
+                ..AttributeProto::default()
+            });
+        }
+        let op = slice_at_opset(9, &node)?;
+        let op = op.as_op().downcast_ref::<Slice1>().unwrap();
+        let output = op.eval(tvec!(rctensor1(&[0f32, 1., 2., 3., 4.])))?;
+        assert_eq!(*output[0], tensor1(&[1f32, 2.]));
+        Ok(())
+    }
+
+    #[test]
+    fn slice_bounds_from_inputs_since_10() -> TractResult<()> {
+        let mut node = NodeProto { op_type: "Slice".into(), ..NodeProto::default() };
+        node.input = vec!["x".into(), "starts".into(), "ends".into(), "axes".into()];
+        let input = rctensor2(&[[0f32, 1., 2., 3.], [4., 5., 6., 7.]]);
+        for version in &[10, 11, 13] {
+            let op = slice_at_opset(*version, &node)?;
+            let op = op.as_op().downcast_ref::<StridedSlice>().unwrap();
+            let bounds = tvec!(rctensor1(&[1i64]), rctensor1(&[-1i64]), rctensor1(&[1i64]));
+            let mut inputs = tvec!(input.clone());
+            inputs.extend(bounds);
+            let output = op.eval(inputs)?;
+            assert_eq!(*output[0], tensor2(&[[1f32, 2.], [5., 6.]]));
+        }
+        Ok(())
+    }
+}
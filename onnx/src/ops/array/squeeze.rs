@@ -0,0 +1,142 @@
+use crate::model::ParsingContext;
+use crate::pb::*;
+use tract_hir::internal::*;
+use tract_hir::ops::array;
+
+pub fn squeeze(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    if ctx.opset_version(&node.domain)? < 13 {
+        let axes = node.get_attr_opt_vec("axes")?;
+        Ok((Box::new(array::Squeeze::new(axes)), vec![]))
+    } else if node.input.get(1).filter(|s| !s.is_empty()).is_some() {
+        Ok((Box::new(Squeeze13), vec![]))
+    } else {
+        Ok((Box::new(array::Squeeze::new(None)), vec![]))
+    }
+}
+
+/// Squeeze-13, taking the axes to remove as a second input.
+#[derive(Debug, Clone, Default, Hash)]
+pub struct Squeeze13;
+
+tract_linalg::impl_dyn_hash!(Squeeze13);
+
+fn resolve_axes(axes: &Tensor, rank: usize) -> TractResult<Vec<usize>> {
+    let axes = axes.cast_to::<i64>()?;
+    let mut axes = axes
+        .as_slice::<i64>()?
+        .iter()
+        .map(|&a| tract_hir::tract_core::ops::normalize_axis(a as isize, rank))
+        .collect::<TractResult<Vec<usize>>>()?;
+    axes.sort();
+    Ok(axes)
+}
+
+impl Op for Squeeze13 {
+    fn name(&self) -> Cow<str> {
+        "Squeeze13".into()
+    }
+
+    op_onnx!();
+    not_a_typed_op!();
+}
+
+impl StatelessOp for Squeeze13 {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (input, axes) = args_2!(inputs);
+        let axes = resolve_axes(&axes, input.rank())?;
+        array::Squeeze::new(Some(axes)).eval(tvec!(input))
+    }
+}
+
+impl InferenceRulesOp for Squeeze13 {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> TractResult<()> {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.given_2(&inputs[0].shape, &inputs[1].value, move |s, shape, axes| {
+            let axes = resolve_axes(&axes, shape.len())?;
+            if let Some(&axis) = axes.iter().find(|&&axis| shape[axis] != 1.to_dim()) {
+                bail!("Attempt to squeeze axis {} of {:?}, which is not one", axis, shape);
+            }
+            let output: TVec<TDim> = shape
+                .iter()
+                .enumerate()
+                .filter(|(ix, _)| !axes.contains(ix))
+                .map(|(_, d)| d.clone())
+                .collect();
+            s.equals(&outputs[0].shape, output)
+        })
+    }
+
+    fn to_typed(
+        &self,
+        source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let axes = target
+            .outlet_fact(mapping[&node.inputs[1]])?
+            .konst
+            .clone()
+            .ok_or("Squeeze13 expects constant axes")?;
+        let rank = target.outlet_fact(mapping[&node.inputs[0]])?.rank();
+        let axes = resolve_axes(&axes, rank)?;
+        InferenceOp::to_typed(&array::Squeeze::new(Some(axes)), source, node, target, mapping)
+    }
+
+    as_op!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use attribute_proto::AttributeType;
+
+    fn squeeze_node(axes_as_input: bool) -> NodeProto {
+        let mut node = NodeProto { op_type: "Squeeze".into(), ..NodeProto::default() };
+        node.input.push("x".into());
+        node.output.push("y".into());
+        if axes_as_input {
+            node.input.push("axes".into());
+        } else {
+            node.attribute.push(AttributeProto {
+                name: "axes".into(),
+                r#type: AttributeType::Ints as i32,
+                ints: vec![0],
+                ..AttributeProto::default()
+            });
+        }
+        node
+    }
+
+    #[test]
+    fn squeeze_axes_from_attribute_before_13() -> TractResult<()> {
+        let (onnx, proto) = (crate::onnx(), ModelProto::default());
+        let ctx = ParsingContext::for_opset(&onnx, &proto, 11);
+        let (op, _) = squeeze(&ctx, &squeeze_node(false))?;
+        assert!(op.as_op().downcast_ref::<array::Squeeze>().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn squeeze_axes_from_input_since_13() -> TractResult<()> {
+        let (onnx, proto) = (crate::onnx(), ModelProto::default());
+        let ctx = ParsingContext::for_opset(&onnx, &proto, 13);
+        let (op, _) = squeeze(&ctx, &squeeze_node(true))?;
+        let op = op.as_op().downcast_ref::<Squeeze13>().unwrap();
+        let input = rctensor3(&[[[1f32], [2.]]]);
+        let output = op.eval(tvec!(input, rctensor1(&[-1i64, 0])))?;
+        assert_eq!(*output[0], tensor1(&[1f32, 2.]));
+        Ok(())
+    }
+}
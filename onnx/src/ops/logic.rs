@@ -1,4 +1,6 @@
-use crate::model::OnnxOpRegister;
+use crate::model::{OnnxOpRegister, ParseResult, ParsingContext};
+use crate::pb::*;
+use tract_hir::internal::*;
 use tract_hir::ops;
 
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
@@ -12,4 +14,92 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Less", |_, _| Ok((Box::new(ops::logic::lesser::bin()), vec![])));
 
     reg.insert("Where", |_, _| Ok((Box::new(ops::logic::Iff::default()), vec![])));
+
+    reg.insert("If", if_then_else);
+}
+
+/// ONNX branches have no inputs: the outer values they use are closures,
+/// wired after the condition.
+pub fn if_then_else(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let then_graph: &GraphProto = node.get_attr("then_branch")?;
+    let else_graph: &GraphProto = node.get_attr("else_branch")?;
+    let ParseResult { model: then_body, unresolved_inputs: then_closures, .. } =
+        ctx.parse_graph(then_graph)?;
+    let ParseResult { model: else_body, unresolved_inputs: else_closures, .. } =
+        ctx.parse_graph(else_graph)?;
+    let mut closures = then_closures.clone();
+    for closure in &else_closures {
+        if !closures.contains(closure) {
+            closures.push(closure.clone());
+        }
+    }
+    let slot = |name: &String| 1 + closures.iter().position(|c| c == name).unwrap();
+    let then_input_mapping = then_closures.iter().map(slot).collect();
+    let else_input_mapping = else_closures.iter().map(slot).collect();
+    Ok((
+        Box::new(ops::cond::InferenceIf::new(
+            then_body,
+            then_input_mapping,
+            else_body,
+            else_input_mapping,
+        )),
+        closures,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tract_hir::tract_core::ops::math;
+
+    fn branch(op: Box<dyn TypedOp>) -> TractResult<GraphProto> {
+        let mut model = TypedModel::default();
+        let x = model.add_source("x", TypedFact::dt_shape(f32::datum_type(), [3].as_ref())?)?;
+        let y = model.wire_node("y", op, &[x])?;
+        model.set_output_outlets(&y)?;
+        let mut graph = crate::export::model_to_proto(&model)?.graph.unwrap();
+        graph.input.clear();
+        Ok(graph)
+    }
+
+    #[test]
+    fn if_closes_on_outer_values() -> TractResult<()> {
+        let mut outer = TypedModel::default();
+        let scalar = [0usize; 0];
+        outer.add_source("cond", TypedFact::dt_shape(bool::datum_type(), scalar.as_ref())?)?;
+        let x = outer.add_source("x", TypedFact::dt_shape(f32::datum_type(), [3].as_ref())?)?;
+        let z = outer.wire_node("z", math::neg(), &[x])?;
+        outer.set_output_outlets(&z)?;
+        let mut proto = crate::export::model_to_proto(&outer)?;
+        let graph_attr = |name: &str, g: GraphProto| AttributeProto {
+            name: name.to_string(),
+            r#type: attribute_proto::AttributeType::Graph as i32,
+            g: Some(g),
+            ..Default::default()
+        };
+        let then_branch = branch(Box::new(math::add::unary(rctensor1(&[1f32]))))?;
+        let else_branch = branch(Box::new(math::mul::unary(rctensor1(&[2f32]))))?;
+        proto.graph.as_mut().unwrap().node = vec![NodeProto {
+            name: "if".to_string(),
+            op_type: "If".to_string(),
+            input: vec!["cond".to_string()],
+            output: vec!["z".to_string()],
+            attribute: vec![
+                graph_attr("then_branch", then_branch),
+                graph_attr("else_branch", else_branch),
+            ],
+            ..Default::default()
+        }];
+        let model = crate::onnx().model_for_proto_model(&proto)?.into_typed()?;
+        assert!(model.nodes().iter().any(|n| n.op_is::<ops::cond::If>()));
+        let plan = SimplePlan::new(&model)?;
+        let output = plan.run(tvec!(tensor0(true), tensor1(&[1f32, 2., 3.])))?;
+        assert_eq!(*output[0], tensor1(&[2f32, 3., 4.]));
+        let output = plan.run(tvec!(tensor0(false), tensor1(&[1f32, 2., 3.])))?;
+        assert_eq!(*output[0], tensor1(&[2f32, 4., 6.]));
+        Ok(())
+    }
 }
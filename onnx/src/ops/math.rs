@@ -12,10 +12,10 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Mul", |_, _| Ok((Box::new(ops::math::mul::bin()), vec![])));
     reg.insert("Div", |_, _| Ok((Box::new(ops::math::div::bin()), vec![])));
 
-    reg.insert("Sum", |_, _| Ok((Box::new(Nary(Box::new(ops::math::Add), false)), vec![])));
+    reg.insert("Sum", |_, _| Ok((Box::new(ops::math::Sum), vec![])));
     reg.insert("Max", |_, _| Ok((Box::new(Nary(Box::new(ops::math::Max), false)), vec![])));
     reg.insert("Min", |_, _| Ok((Box::new(Nary(Box::new(ops::math::Min), false)), vec![])));
-    reg.insert("Mean", |_, _| Ok((Box::new(Nary(Box::new(ops::math::Add), true)), vec![])));
+    reg.insert("Mean", |_, _| Ok((Box::new(ops::math::Mean), vec![])));
 
     reg.insert("Abs", |_, _| Ok((Box::new(ops::math::abs()), vec![])));
     reg.insert("Ceil", |_, _| Ok((Box::new(ops::math::ceil()), vec![])));
@@ -144,8 +144,11 @@ impl Expansion for Gemm {
         inputs: &'p [TensorProxy],
         outputs: &'p [TensorProxy],
     ) -> InferenceResult {
-        check_input_arity(&inputs, 3)?;
-        s.equals(&inputs[2].datum_type, &outputs[0].datum_type)?;
+        if inputs.len() == 3 {
+            s.equals(&inputs[2].datum_type, &outputs[0].datum_type)?;
+        } else {
+            check_input_arity(&inputs, 2)?;
+        }
         s.equals(&inputs[0].rank, 2)?;
         s.equals(&inputs[1].rank, 2)?;
         check_output_arity(&outputs, 1)?;
@@ -166,7 +169,7 @@ impl Expansion for Gemm {
         model: &mut TypedModel,
         inputs: &[OutletId],
     ) -> TractResult<TVec<OutletId>> {
-        let (a, b, mut c) = (inputs[0], inputs[1], inputs[2]);
+        let (a, b) = (inputs[0], inputs[1]);
         let mut wire = model.wire_node(
             format!("{}.ab", name),
             ops::matmul::MatMul::default().with_a_trans(self.trans_a).with_b_trans(self.trans_b),
@@ -175,22 +178,23 @@ impl Expansion for Gemm {
         if self.alpha != 1.0 {
             let alpha = tensor0(self.alpha).broadcast_into_rank(model.outlet_fact(wire)?.rank())?;
             wire = model.wire_node(
-                format!("{}.alpha_ab", self.alpha),
+                format!("{}.alpha_ab", name),
                 ops::math::mul::unary(alpha.into_arc_tensor()),
                 &[wire],
             )?[0];
         }
-        if self.beta != 0.0f32 {
+        if let Some(&c) = inputs.get(2).filter(|_| self.beta != 0.0f32) {
+            let mut c = c;
             while model.outlet_fact(wire)?.rank() > model.outlet_fact(c)?.rank() {
                 c = model.wire_node(
-                    format!("{}.c_broadcast_to_{}", self.beta, model.outlet_fact(c)?.rank()),
+                    format!("{}.c_broadcast_to_{}", name, model.outlet_fact(c)?.rank()),
                     tract_hir::tract_core::ops::change_axes::AxisOp::Add(0),
                     &[c],
                 )?[0];
             }
             let beta = tensor0(self.beta).broadcast_into_rank(model.outlet_fact(wire)?.rank())?;
             let beta_c = model.wire_node(
-                format!("{}.beta_c", self.beta),
+                format!("{}.beta_c", name),
                 ops::math::mul::unary(beta.into_arc_tensor()),
                 &[c],
             )?[0];
@@ -199,3 +203,25 @@ impl Expansion for Gemm {
         Ok(tvec!(wire))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gemm_without_c() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let fact = InferenceFact::dt_shape(f32::datum_type(), shapefactoid!(2, 2));
+        let a = model.add_source("a", fact.clone())?;
+        let b = model.add_source("b", fact)?;
+        let gemm = model.wire_node("gemm", expand(Gemm::new(2.0, 1.0, false, false)), &[a, b])?;
+        model.set_output_outlets(&gemm)?;
+        let model = model.into_typed()?;
+        assert!(model.nodes().iter().all(|n| n.op.name() != "Add"));
+        let a = tensor2(&[[1f32, 2.], [3., 4.]]);
+        let b = tensor2(&[[1f32, 0.], [1., 1.]]);
+        let output = SimplePlan::new(&model)?.run(tvec!(a, b))?;
+        assert_eq!(*output[0], tensor2(&[[6f32, 4.], [14., 8.]]));
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use tract_hir::internal::*;
+use tract_hir::ops::cnn;
+
+/// MaxUnpool without the optional `output_shape` input: the output shape is
+/// the one the pooling attributes map back to.
+#[derive(Debug, Clone, new, Hash)]
+pub struct MaxUnpool {
+    kernel_shape: TVec<usize>,
+    strides: Option<TVec<usize>>,
+    pads: Option<TVec<usize>>,
+}
+
+tract_linalg::impl_dyn_hash!(MaxUnpool);
+
+impl MaxUnpool {
+    fn output_shape(&self, input: &[TDim]) -> TractResult<TVec<TDim>> {
+        let rank = self.kernel_shape.len();
+        if input.len() != rank + 2 {
+            bail!(
+                "MaxUnpool kernel {:?} does not match input shape {:?}",
+                self.kernel_shape,
+                input
+            );
+        }
+        let mut shape: TVec<TDim> = input[..2].into();
+        for (ix, (d, &k)) in input[2..].iter().zip(self.kernel_shape.iter()).enumerate() {
+            let stride = self.strides.as_ref().map(|s| s[ix]).unwrap_or(1);
+            let pads = self.pads.as_ref().map(|p| p[ix] + p[ix + rank]).unwrap_or(0);
+            shape.push((d.clone() - 1) * stride as i64 + (k as i64 - pads as i64));
+        }
+        Ok(shape)
+    }
+}
+
+impl tract_hir::ops::expandable::Expansion for MaxUnpool {
+    fn name(&self) -> Cow<str> {
+        "MaxUnpool".into()
+    }
+
+    op_onnx!();
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&inputs[1].datum_type, i64::datum_type())?;
+        s.equals(&inputs[0].shape, &inputs[1].shape)?;
+        s.given(&inputs[0].shape, move |s, shape| {
+            s.equals(&outputs[0].shape, self.output_shape(&shape)?)
+        })
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        target: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let shape = self.output_shape(&target.outlet_fact(inputs[0])?.shape.to_tvec())?;
+        let shape = target.add_const(format!("{}.shape", prefix), tensor1(&shape))?;
+        target.wire_node(prefix, cnn::MaxUnpool::default(), &[inputs[0], inputs[1], shape])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn output_shape_from_attributes() -> TractResult<()> {
+        let mut model = InferenceModel::default();
+        let fact = |dt| InferenceFact::dt_shape(dt, shapefactoid!(1, 1, 2, 2));
+        let values = model.add_source("values", fact(f32::datum_type()))?;
+        let indices = model.add_source("indices", fact(i64::datum_type()))?;
+        let op = MaxUnpool::new(tvec!(2, 2), Some(tvec!(2, 2)), None);
+        let unpool = model.wire_node("unpool", expand(op), &[values, indices])?;
+        model.set_output_outlets(&unpool)?;
+        let model = model.into_typed()?;
+        let values = tensor4(&[[[[1f32, 2.], [3., 4.]]]]);
+        let indices = tensor4(&[[[[0i64, 3], [9, 14]]]]);
+        let output = SimplePlan::new(&model)?.run(tvec!(values, indices))?;
+        let expected = tensor4(&[[[
+            [1f32, 0., 0., 2.],
+            [0., 0., 0., 0.],
+            [0., 3., 0., 0.],
+            [0., 0., 4., 0.],
+        ]]]);
+        assert_eq!(*output[0], expected);
+        Ok(())
+    }
+
+    #[test]
+    fn output_shape_removes_pads() -> TractResult<()> {
+        let op = MaxUnpool::new(tvec!(3, 3), Some(tvec!(2, 1)), Some(tvec!(1, 0, 1, 2)));
+        let shape = op.output_shape(&[1.into(), 3.into(), 4.into(), TDim::s()])?;
+        assert_eq!(shape, tvec!(1.into(), 3.into(), 7.into(), TDim::s()));
+        Ok(())
+    }
+}
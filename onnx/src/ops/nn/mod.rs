@@ -11,6 +11,7 @@ use tract_num_traits::AsPrimitive;
 mod batch_norm;
 mod dropout;
 mod lrn;
+mod max_unpool;
 
 fn reduce(
     node: &NodeProto,
@@ -37,10 +38,12 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("GlobalMaxPool", |_, _| Ok((Box::new(ops::nn::GlobalMaxPool::default()), vec![])));
     reg.insert("Hardmax", layer_hard_max);
     reg.insert("HardSigmoid", hard_sigmoid);
+    reg.insert("HardSwish", |_, _| Ok((Box::new(nn::hard_swish(1. / 6., 0.5)), vec![])));
     reg.insert("LeakyRelu", leaky_relu);
     reg.insert("LogSoftmax", layer_log_soft_max);
     reg.insert("LRN", lrn);
     reg.insert("MaxPool", max_pool);
+    reg.insert("MaxUnpool", max_unpool);
     reg.insert("ParametricSoftplus", parametric_softplus);
     reg.insert("QLinearConv", conv_qlinear);
     reg.insert("PRelu", |_, _| Ok((Box::new(prelu::bin()), vec![])));
@@ -224,28 +227,38 @@ pub fn hard_sigmoid(
     Ok((Box::new(nn::hard_sigmoid(alpha, beta)), vec![]))
 }
 
+/// Softmax and its siblings flatten the input to 2D around `axis` (1 by
+/// default) until opset 13, and work along `axis` only (-1 by default) since.
+fn layer_axis(ctx: &ParsingContext, node: &NodeProto) -> TractResult<(isize, bool)> {
+    if ctx.opset_version(&node.domain)? < 13 {
+        Ok((node.get_attr_opt("axis")?.unwrap_or(1), true))
+    } else {
+        Ok((node.get_attr_opt("axis")?.unwrap_or(-1), false))
+    }
+}
+
 pub fn layer_hard_max(
-    _ctx: &ParsingContext,
+    ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let axis = node.get_attr_opt("axis")?.unwrap_or(1);
-    Ok((Box::new(ops::nn::LayerHardmax::new(axis)), vec![]))
+    let (axis, coerce_to_2d) = layer_axis(ctx, node)?;
+    Ok((Box::new(ops::nn::LayerHardmax::new(axis, coerce_to_2d)), vec![]))
 }
 
 pub fn layer_log_soft_max(
-    _ctx: &ParsingContext,
+    ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let axis = node.get_attr_opt("axis")?.unwrap_or(1);
-    Ok((Box::new(ops::nn::LayerLogSoftmax::new(axis)), vec![]))
+    let (axis, coerce_to_2d) = layer_axis(ctx, node)?;
+    Ok((Box::new(ops::nn::LayerLogSoftmax::new(axis, coerce_to_2d)), vec![]))
 }
 
 pub fn layer_soft_max(
-    _ctx: &ParsingContext,
+    ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let axis = node.get_attr_opt("axis")?.unwrap_or(1);
-    Ok((Box::new(ops::nn::LayerSoftmax::new(axis)), vec![]))
+    let (axis, coerce_to_2d) = layer_axis(ctx, node)?;
+    Ok((Box::new(ops::nn::LayerSoftmax::new(axis, coerce_to_2d)), vec![]))
 }
 
 pub fn leaky_relu(
@@ -283,6 +296,18 @@ pub fn max_pool(
     ))
 }
 
+pub fn max_unpool(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    if node.input.len() == 3 {
+        return Ok((Box::new(cnn::MaxUnpool::default()), vec![]));
+    }
+    let kernel_shape = node.get_attr_tvec("kernel_shape")?;
+    let pads = node.get_attr_opt_tvec("pads")?;
+    Ok((expand(max_unpool::MaxUnpool::new(kernel_shape, strides(node)?, pads)), vec![]))
+}
+
 pub fn parametric_softplus(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -384,3 +409,36 @@ pub fn thresholded_relu(
     let alpha = node.get_attr_opt("alpha")?.unwrap_or(1.);
     Ok((Box::new(ops::nn::threshold_relu(alpha)), vec![]))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pb::*;
+
+    fn softmax_at_opset(version: i64) -> TractResult<Arc<Tensor>> {
+        let (onnx, proto) = (crate::onnx(), ModelProto::default());
+        let ctx = ParsingContext::for_opset(&onnx, &proto, version);
+        let mut node = NodeProto { op_type: "Softmax".into(), ..NodeProto::default() };
+        node.attribute.push(AttributeProto {
+            name: "axis".into(),
+            r#type: attribute_proto::AttributeType::Int as i32,
+            i: 0,
+            ..AttributeProto::default()
+        });
+        let (op, _) = layer_soft_max(&ctx, &node)?;
+        let op = op.as_op().downcast_ref::<ops::nn::LayerSoftmax>().unwrap();
+        Ok(op.eval(tvec!(rctensor2(&[[1f32, 1.], [1., 1.]])))?.remove(0))
+    }
+
+    #[test]
+    fn softmax_flattens_from_axis_before_13() -> TractResult<()> {
+        assert_eq!(*softmax_at_opset(11)?, tensor2(&[[0.25f32, 0.25], [0.25, 0.25]]));
+        Ok(())
+    }
+
+    #[test]
+    fn softmax_along_axis_since_13() -> TractResult<()> {
+        assert_eq!(*softmax_at_opset(13)?, tensor2(&[[0.5f32, 0.5], [0.5, 0.5]]));
+        Ok(())
+    }
+}
@@ -53,14 +53,12 @@ impl StatelessOp for QuantizeLinear {
         let tensor = if y_zero_point.datum_type() == u8::datum_type() {
             let y_zero_point = y_zero_point.as_slice::<u8>()?[0];
             x.to_array_view::<f32>()?
-                .map(|x| ((x * y_scale).round() as i32 + y_zero_point as i32).max(0).min(255) as u8)
+                .map(|&x| quantize_linear_f32_u8(x, y_scale, y_zero_point as i32))
                 .into_arc_tensor()
         } else {
             let y_zero_point = y_zero_point.as_slice::<i8>()?[0];
             x.to_array_view::<f32>()?
-                .map(|x| {
-                    ((x * y_scale).round() as i32 + y_zero_point as i32).max(-128).min(127) as i8
-                })
+                .map(|&x| quantize_linear_f32_i8(x, y_scale, y_zero_point as i32))
                 .into_arc_tensor()
         };
         Ok(tvec!(tensor))
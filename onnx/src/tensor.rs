@@ -24,6 +24,26 @@ impl TryFrom<DataType> for DatumType {
     }
 }
 
+impl TryFrom<DatumType> for DataType {
+    type Error = TractError;
+    fn try_from(t: DatumType) -> TractResult<DataType> {
+        match t {
+            DatumType::Bool => Ok(DataType::Bool),
+            DatumType::U8 => Ok(DataType::Uint8),
+            DatumType::U16 => Ok(DataType::Uint16),
+            DatumType::I8 => Ok(DataType::Int8),
+            DatumType::I16 => Ok(DataType::Int16),
+            DatumType::I32 => Ok(DataType::Int32),
+            DatumType::I64 => Ok(DataType::Int64),
+            DatumType::F16 => Ok(DataType::Float16),
+            DatumType::F32 => Ok(DataType::Float),
+            DatumType::F64 => Ok(DataType::Double),
+            DatumType::String => Ok(DataType::String),
+            _ => Err(format!("No ONNX DataType for {:?}", t))?,
+        }
+    }
+}
+
 impl<'a> TryFrom<&'a type_proto::Tensor> for InferenceFact {
     type Error = TractError;
     fn try_from(t: &'a type_proto::Tensor) -> TractResult<InferenceFact> {
@@ -59,6 +79,9 @@ impl TryFrom<type_proto::Tensor> for InferenceFact {
 impl<'a> TryFrom<&'a TensorProto> for Tensor {
     type Error = TractError;
     fn try_from(t: &TensorProto) -> TractResult<Tensor> {
+        if t.data_location == tensor_proto::DataLocation::External as i32 {
+            bail!("External data for tensor {:?} has not been loaded", t.name);
+        }
         let dt = DataType::from_i32(t.data_type).unwrap().try_into()?;
         let shape: Vec<usize> = t.dims.iter().map(|&i| i as usize).collect();
         if t.raw_data.len() > 0 {
@@ -107,6 +130,14 @@ impl<'a> TryFrom<&'a TensorProto> for Tensor {
                 .into(),
                 DatumType::I32 => Array::from_shape_vec(&*shape, t.int32_data.to_vec())?.into(),
                 DatumType::I64 => Array::from_shape_vec(&*shape, t.int64_data.to_vec())?.into(),
+                DatumType::F16 => {
+                    let bits: Vec<u8> = t
+                        .int32_data
+                        .iter()
+                        .flat_map(|&x| (x as u16).to_ne_bytes().to_vec())
+                        .collect();
+                    unsafe { Tensor::from_raw::<f16>(&*shape, &bits)? }
+                }
                 DatumType::F32 => Array::from_shape_vec(&*shape, t.float_data.to_vec())?.into(),
                 DatumType::F64 => Array::from_shape_vec(&*shape, t.double_data.to_vec())?.into(),
                 DatumType::String => {
@@ -133,6 +164,46 @@ impl TryFrom<TensorProto> for Tensor {
     }
 }
 
+impl<'a> TryFrom<&'a Tensor> for TensorProto {
+    type Error = TractError;
+    fn try_from(t: &Tensor) -> TractResult<TensorProto> {
+        let mut proto = TensorProto::default();
+        proto.dims = t.shape().iter().map(|&d| d as i64).collect();
+        proto.data_type = DataType::try_from(t.datum_type())? as i32;
+        match t.datum_type() {
+            DatumType::Bool => {
+                proto.int32_data = t.as_slice::<bool>()?.iter().map(|&x| x as i32).collect()
+            }
+            DatumType::U8 => {
+                proto.int32_data = t.as_slice::<u8>()?.iter().map(|&x| x as i32).collect()
+            }
+            DatumType::U16 => {
+                proto.int32_data = t.as_slice::<u16>()?.iter().map(|&x| x as i32).collect()
+            }
+            DatumType::I8 => {
+                proto.int32_data = t.as_slice::<i8>()?.iter().map(|&x| x as i32).collect()
+            }
+            DatumType::I16 => {
+                proto.int32_data = t.as_slice::<i16>()?.iter().map(|&x| x as i32).collect()
+            }
+            DatumType::I32 => proto.int32_data = t.as_slice::<i32>()?.to_vec(),
+            DatumType::I64 => proto.int64_data = t.as_slice::<i64>()?.to_vec(),
+            DatumType::F16 => {
+                proto.int32_data =
+                    t.as_slice::<f16>()?.iter().map(|x| x.0.to_bits() as i32).collect()
+            }
+            DatumType::F32 => proto.float_data = t.as_slice::<f32>()?.to_vec(),
+            DatumType::F64 => proto.double_data = t.as_slice::<f64>()?.to_vec(),
+            DatumType::String => {
+                proto.string_data =
+                    t.as_slice::<String>()?.iter().map(|s| s.as_bytes().to_vec()).collect()
+            }
+            dt => bail!("Can not export {:?} tensor to ONNX", dt),
+        }
+        Ok(proto)
+    }
+}
+
 pub fn proto_from_reader<R: ::std::io::Read>(mut r: R) -> TractResult<TensorProto> {
     let mut v = vec![];
     r.read_to_end(&mut v)?;
@@ -143,3 +214,72 @@ pub fn proto_from_reader<R: ::std::io::Read>(mut r: R) -> TractResult<TensorProt
 pub fn from_reader<R: ::std::io::Read>(r: R) -> TractResult<Tensor> {
     proto_from_reader(r)?.try_into()
 }
+
+/// Check an external data location is a path below the model directory: no
+/// root, drive prefix or parent directory component.
+fn external_location(location: &str) -> TractResult<&std::path::Path> {
+    use std::path::Component;
+    let path = std::path::Path::new(location);
+    let below = path.components().all(|c| match c {
+        Component::Normal(_) | Component::CurDir => true,
+        _ => false,
+    });
+    if !below {
+        bail!("External data location {:?} must be relative to the model directory", location)
+    }
+    Ok(path)
+}
+
+/// Load the content of a tensor stored in an external file into its raw_data.
+///
+/// External data locations are relative to `model_dir`, the directory of the
+/// model file.
+pub fn load_external_data(t: &mut TensorProto, model_dir: &std::path::Path) -> TractResult<()> {
+    use std::io::{Read, Seek, SeekFrom};
+    if t.data_location != tensor_proto::DataLocation::External as i32 {
+        return Ok(());
+    }
+    let mut location = None;
+    let mut offset = 0;
+    let mut length = None;
+    for entry in &t.external_data {
+        let invalid = || format!("Invalid {} for external tensor {:?}", entry.key, t.name);
+        match &*entry.key {
+            "location" => location = Some(model_dir.join(external_location(&entry.value)?)),
+            "offset" => offset = entry.value.parse::<u64>().map_err(|_| invalid())?,
+            "length" => length = Some(entry.value.parse::<usize>().map_err(|_| invalid())?),
+            _ => (),
+        }
+    }
+    let location =
+        location.ok_or_else(|| format!("No location for external tensor {:?}", t.name))?;
+    let mut file = std::fs::File::open(&location)
+        .map_err(|e| format!("Could not open {:?}: {}", location, e))?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut data = vec![];
+    if let Some(length) = length {
+        data.resize(length, 0);
+        file.read_exact(&mut data)?;
+    } else {
+        file.read_to_end(&mut data)?;
+    }
+    t.raw_data = data;
+    t.external_data.clear();
+    t.data_location = tensor_proto::DataLocation::Default as i32;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn f16_round_trip() -> TractResult<()> {
+        let t = tensor1(&[f16::from(1f32), f16::from(-2.5), f16::from(0.1)]);
+        let proto = TensorProto::try_from(&t)?;
+        assert_eq!(proto.data_type, DataType::Float16 as i32);
+        assert_eq!(&proto.int32_data[..2], &[0x3C00, 0xC100]);
+        assert_eq!(Tensor::try_from(proto)?, t);
+        Ok(())
+    }
+}
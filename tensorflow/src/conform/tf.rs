@@ -185,7 +185,7 @@ impl Tensorflow {
                 }
 
                 // this one pretends to have 5 outputs, but has only one
-                if operation.op_type()? == "FusedBatchNorm" {
+                if operation.op_type()?.starts_with("FusedBatchNorm") {
                     continue;
                 }
 
@@ -1,5 +1,7 @@
-use crate::tfpb::tensorflow::{GraphDef, NodeDef, SavedModel};
+use crate::ops::control_flow as cf;
+use crate::tfpb::tensorflow::{DataType, GraphDef, NodeDef, SavedModel};
 use prost::Message;
+use std::convert::TryInto;
 use std::{fs, path};
 use tract_hir::internal::*;
 
@@ -114,8 +116,6 @@ impl Tensorflow {
     }
 
     pub fn parse_graph(&self, graph: &GraphDef) -> TractResult<TfModelAndExtensions> {
-        use crate::ops::control_flow as cf;
-
         let mut model = InferenceModel::default();
         let mut inputs = tvec!();
         let mut context = ParsingContext::default();
@@ -142,14 +142,19 @@ impl Tensorflow {
                 continue;
             }
 
-            let op = match self.op_register.0.get(&pbnode.op) {
-                Some(builder) => (builder)(&context, pbnode)?,
-                None => tract_hir::ops::unimpl::UnimplementedOp::new(
-                    context.node_output_arities.get(name).cloned().unwrap_or(1),
-                    &pbnode.op,
-                    format!("{:?}", pbnode),
-                )
-                .into(),
+            let op: Box<dyn InferenceOp> = if pbnode.op == "While" || pbnode.op == "StatelessWhile"
+            {
+                Box::new(self.parse_while(graph, pbnode)?)
+            } else {
+                match self.op_register.0.get(&pbnode.op) {
+                    Some(builder) => (builder)(&context, pbnode)?,
+                    None => tract_hir::ops::unimpl::UnimplementedOp::new(
+                        context.node_output_arities.get(name).cloned().unwrap_or(1),
+                        &pbnode.op,
+                        format!("{:?}", pbnode),
+                    )
+                    .into(),
+                }
             };
 
             let noutputs =
@@ -229,6 +234,78 @@ impl Tensorflow {
         let extensions = TfModelExtensions { control_inputs, initializing_nodes: vec![] };
         Ok(TfModelAndExtensions(model, extensions))
     }
+
+    fn parse_while(&self, graph: &GraphDef, pbnode: &NodeDef) -> TractResult<cf::While> {
+        let types = pbnode.get_attr_opt_list_datum_type("T")?.unwrap_or_default();
+        let cond = self.parse_function(graph, pbnode.get_attr_func_name("cond")?, &types)?;
+        let body = self.parse_function(graph, pbnode.get_attr_func_name("body")?, &types)?;
+        Ok(cf::While::new(cond, body))
+    }
+
+    /// Parse a function of the graph library as a model, with one input per
+    /// argument and one output per result.
+    ///
+    /// Inside a function, node outputs are referred to as "node:arg:index".
+    /// They are mapped to "node:index", assuming the producing op has a single
+    /// output argument, which holds for the ops tract supports.
+    fn parse_function(
+        &self,
+        graph: &GraphDef,
+        name: &str,
+        types: &[DatumType],
+    ) -> TractResult<InferenceModel> {
+        let func = graph
+            .library
+            .as_ref()
+            .and_then(|lib| {
+                lib.function.iter().find(|f| f.signature.as_ref().map(|s| &*s.name) == Some(name))
+            })
+            .ok_or_else(|| format!("Function {} not found in graph library", name))?;
+        let signature = func.signature.as_ref().unwrap();
+        let rename = |input: &str| -> String {
+            let splits: Vec<_> = input.split(':').collect();
+            if splits.len() == 3 {
+                format!("{}:{}", splits[0], splits[2])
+            } else {
+                input.to_string()
+            }
+        };
+        let mut nodes = vec![];
+        for (ix, arg) in signature.input_arg.iter().enumerate() {
+            let dt = if let Some(dt) =
+                DataType::from_i32(arg.r#type).filter(|dt| *dt != DataType::DtInvalid)
+            {
+                dt
+            } else if let Some(dt) = types.get(ix) {
+                (*dt).try_into()?
+            } else {
+                bail!("Can not determine type of argument {} of function {}", arg.name, name)
+            };
+            nodes.push(crate::tfpb::node().name(&arg.name).op("Placeholder").attr("dtype", dt));
+        }
+        for node in &func.node_def {
+            let mut node = node.clone();
+            node.input = node.input.iter().map(|i| rename(i)).collect();
+            nodes.push(node);
+        }
+        let body = GraphDef { node: nodes, library: graph.library.clone(), ..GraphDef::default() };
+        let mut model = self.parse_graph(&body)?.0;
+        let outputs = signature
+            .output_arg
+            .iter()
+            .map(|arg| {
+                let ret = func
+                    .ret
+                    .get(&arg.name)
+                    .ok_or_else(|| format!("No result for {} in function {}", arg.name, name))?;
+                let ret = rename(ret);
+                let (node, slot) = Self::parse_input(&ret)?;
+                Ok(OutletId::new(model.node_id_by_name(node)?, slot))
+            })
+            .collect::<TractResult<TVec<_>>>()?;
+        model.set_output_outlets(&outputs)?;
+        Ok(model)
+    }
 }
 
 impl Framework<GraphDef> for Tensorflow {
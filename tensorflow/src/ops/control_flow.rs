@@ -109,3 +109,119 @@ impl InferenceRulesOp for NextIteration {
 
     as_op!();
 }
+
+/// Functional `While` (and `StatelessWhile`), holding its `cond` and `body`
+/// functions as models. All inputs are loop-carried values, mapped to the
+/// core While loop.
+#[derive(Debug, Clone, new, Hash)]
+pub struct While {
+    pub cond: InferenceModel,
+    pub body: InferenceModel,
+}
+
+tract_linalg::impl_dyn_hash!(While);
+
+impl While {
+    fn to_core(
+        &self,
+        facts: &[InferenceFact],
+    ) -> TractResult<tract_hir::tract_core::ops::while_loop::While> {
+        let typed = |model: &InferenceModel| -> TractResult<TypedModel> {
+            let mut model = model.clone();
+            for (ix, fact) in facts.iter().enumerate() {
+                model.set_input_fact(ix, fact.clone().without_value())?;
+            }
+            model.into_typed()
+        };
+        tract_hir::tract_core::ops::while_loop::While::new(typed(&self.cond)?, typed(&self.body)?)
+    }
+}
+
+impl Op for While {
+    fn name(&self) -> Cow<str> {
+        "While".into()
+    }
+
+    fn nested_models(&self) -> Vec<(Cow<str>, &dyn Model, Vec<String>, Vec<String>)> {
+        let names = |n: usize, prefix: &str| (0..n).map(|i| format!("{} #{}", prefix, i)).collect();
+        let inputs = self.body.input_outlets().unwrap().len();
+        vec![
+            ("cond".into(), &self.cond, names(inputs, "state"), names(1, "continue")),
+            ("body".into(), &self.body, names(inputs, "state"), names(inputs, "state")),
+        ]
+    }
+
+    op_tf!();
+    not_a_typed_op!();
+}
+
+impl StatefullOp for While {
+    fn state(
+        &self,
+        _state: &mut SessionState,
+        _id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(WhileState::default())))
+    }
+}
+
+/// Core loop typed for the last input facts seen, so that the cond and body
+/// are only typed again when the inputs change.
+#[derive(Debug, Default)]
+struct WhileState(Option<(Vec<InferenceFact>, tract_hir::tract_core::ops::while_loop::While)>);
+
+impl OpState for WhileState {
+    fn eval(
+        &mut self,
+        _session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let op = op.downcast_ref::<While>().ok_or("Wrong Op type")?;
+        let facts =
+            inputs.iter().map(|t| InferenceFact::dt_shape_from_tensor(t)).collect::<Vec<_>>();
+        if self.0.as_ref().map(|(typed_for, _)| typed_for != &facts).unwrap_or(true) {
+            let core = op.to_core(&facts)?;
+            self.0 = Some((facts, core));
+        }
+        self.0.as_ref().unwrap().1.eval(inputs)
+    }
+}
+
+impl InferenceRulesOp for While {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, self.body.input_outlets()?.len())?;
+        check_output_arity(&outputs, inputs.len())?;
+        for (input, output) in inputs.iter().zip(outputs.iter()) {
+            s.equals(&input.datum_type, &output.datum_type)?;
+            s.equals(&input.shape, &output.shape)?;
+        }
+        Ok(())
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.body.input_outlets()?.len())
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        let facts = inputs
+            .iter()
+            .map(|i| Ok(InferenceFact::from(target.outlet_fact(*i)?)))
+            .collect::<TractResult<Vec<_>>>()?;
+        target.wire_node(&*node.name, self.to_core(&facts)?, &*inputs)
+    }
+
+    as_op!();
+}
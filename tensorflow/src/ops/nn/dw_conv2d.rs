@@ -77,8 +77,7 @@ impl InferenceRulesOp for DepthwiseConv2d {
             s.equals(&inputs[1].shape[2], &inputs[0].shape[img.c_axis()])?;
             s.equals(&outputs[0].shape[img.n_axis().unwrap()], img.n_dim().unwrap())?;
             if ker.iter().all(|d| d.to_integer().is_ok()) {
-                let ker: TVec<usize> =
-                    ker.iter().map(|d| d.to_integer().unwrap() as usize).collect();
+                let ker: TVec<usize> = ker.iter().map(|d| d.to_usize().unwrap()).collect();
                 let output_shape = self.padding.compute(
                     img.hw_dims(),
                     &ker[0..2],
@@ -1,17 +1,23 @@
 use tract_hir::internal::*;
+use tract_hir::ops::nn::DataFormat;
 
 use crate::model::ParsingContext;
 use crate::tfpb::tensorflow::NodeDef;
 
 pub fn fused_batch_norm(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    if pb.get_attr_opt_bool("is_training")? == Some(true) {
+        bail!("{} ({}): only inference mode (is_training=false) is supported", pb.name, pb.op);
+    }
     let epsilon = pb.get_attr_float::<f32>("epsilon")?;
-    Ok(Box::new(FusedBatchNorm::new(epsilon)))
+    let data_format = super::data_format(pb)?;
+    Ok(Box::new(FusedBatchNorm::new(data_format, epsilon)))
 }
 
 #[derive(Debug, Clone, new, Educe)]
 #[educe(Hash)]
 struct FusedBatchNorm {
-    #[educe(Hash(method="hash_f32"))]
+    data_format: DataFormat,
+    #[educe(Hash(method = "hash_f32"))]
     epsilon: f32,
 }
 
@@ -33,6 +39,38 @@ impl FusedBatchNorm {
         let beta = izip!(offset, mean, &alpha).map(|(o, m, s)| o - m * s).collect();
         Ok((alpha, beta))
     }
+
+    fn c_axis(&self, rank: usize) -> usize {
+        if self.data_format == DataFormat::NHWC {
+            rank - 1
+        } else {
+            1
+        }
+    }
+
+    // slope and intercept, shaped to broadcast over the channel axis
+    fn slope_and_inter(
+        &self,
+        rank: usize,
+        scale: &Tensor,
+        offset: &Tensor,
+        mean: &Tensor,
+        variance: &Tensor,
+    ) -> TractResult<(Tensor, Tensor)> {
+        let (alpha, beta) = self.coeffs(
+            scale.as_slice::<f32>()?,
+            offset.as_slice::<f32>()?,
+            mean.as_slice::<f32>()?,
+            variance.as_slice::<f32>()?,
+        )?;
+        let mut slope = tensor1(&*alpha);
+        let mut inter = tensor1(&*beta);
+        for _ in self.c_axis(rank) + 1..rank {
+            slope.insert_axis(1)?;
+            inter.insert_axis(1)?;
+        }
+        Ok((slope, inter))
+    }
 }
 
 impl Op for FusedBatchNorm {
@@ -53,15 +91,10 @@ impl StatelessOp for FusedBatchNorm {
     fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
         let (data, scale, offset, mean, variance) = args_5!(inputs);
         let mut data = data.into_tensor().into_array::<f32>()?;
-        let scale = scale.as_slice::<f32>()?;
-        let offset = offset.as_slice::<f32>()?;
-        let mean = mean.as_slice::<f32>()?;
-        let variance = variance.as_slice::<f32>()?;
-        let (alpha, beta) = self.coeffs(scale, offset, mean, variance)?;
-        let alpha = tract_ndarray::arr1(&*alpha);
-        let beta = tract_ndarray::arr1(&*beta);
-        data *= &alpha;
-        data += &beta;
+        let (slope, inter) =
+            self.slope_and_inter(data.ndim(), &scale, &offset, &mean, &variance)?;
+        data *= &slope.to_array_view::<f32>()?;
+        data += &inter.to_array_view::<f32>()?;
         Ok(tvec!(data.into_arc_tensor()))
     }
 }
@@ -81,17 +114,18 @@ impl InferenceRulesOp for FusedBatchNorm {
         s.equals(&inputs[3].datum_type, f32::datum_type())?;
         s.equals(&inputs[4].datum_type, f32::datum_type())?;
         s.equals(&outputs[0].datum_type, f32::datum_type())?;
-        s.equals(&inputs[0].rank, 4)?;
         s.equals(&inputs[1].rank, 1)?;
         s.equals(&inputs[2].rank, 1)?;
         s.equals(&inputs[3].rank, 1)?;
         s.equals(&inputs[4].rank, 1)?;
         s.equals(&inputs[0].shape, &outputs[0].shape)?;
-        s.equals(&inputs[1].shape[0], &inputs[0].shape[3])?;
-        s.equals(&inputs[2].shape[0], &inputs[0].shape[3])?;
-        s.equals(&inputs[3].shape[0], &inputs[0].shape[3])?;
-        s.equals(&inputs[4].shape[0], &inputs[0].shape[3])?;
-        Ok(())
+        s.given(&inputs[0].rank, move |s, rank| {
+            let c_axis = self.c_axis(rank as usize);
+            for input in &inputs[1..5] {
+                s.equals(&input.shape[0], &inputs[0].shape[c_axis])?;
+            }
+            Ok(())
+        })
     }
 
     as_op!();
@@ -103,6 +137,7 @@ impl InferenceRulesOp for FusedBatchNorm {
         target: &mut TypedModel,
         mapping: &HashMap<OutletId, OutletId>,
     ) -> TractResult<TVec<OutletId>> {
+        let rank = target.outlet_fact(mapping[&node.inputs[0]])?.rank();
         let scale = target.outlet_fact(mapping[&node.inputs[1]])?;
         let offset = target.outlet_fact(mapping[&node.inputs[2]])?;
         let mean = target.outlet_fact(mapping[&node.inputs[3]])?;
@@ -110,20 +145,16 @@ impl InferenceRulesOp for FusedBatchNorm {
         if let (Some(scale), Some(offset), Some(mean), Some(variance)) =
             (&scale.konst, &offset.konst, &mean.konst, &variance.konst)
         {
-            let scale = scale.as_slice::<f32>()?;
-            let offset = offset.as_slice::<f32>()?;
-            let mean = mean.as_slice::<f32>()?;
-            let variance = variance.as_slice::<f32>()?;
-            let (alpha, beta) = self.coeffs(scale, offset, mean, variance)?;
-            let slope = target
-                .add_const(format!("{}-slope", node.name), tensor1(&*alpha).into_arc_tensor())?;
+            let (slope, inter) = self.slope_and_inter(rank, scale, offset, mean, variance)?;
+            let slope =
+                target.add_const(format!("{}-slope", node.name), slope.into_arc_tensor())?;
             let wire = target.wire_node(
                 format!("{}-mul", node.name),
                 tract_hir::ops::math::mul::bin_typed(),
                 [slope, mapping[&node.inputs[0]]].as_ref(),
             )?[0];
-            let offset = target
-                .add_const(format!("{}-offset", node.name), tensor1(&*beta).into_arc_tensor())?;
+            let offset =
+                target.add_const(format!("{}-offset", node.name), inter.into_arc_tensor())?;
             return target.wire_node(
                 format!("{}-add", node.name),
                 tract_hir::ops::math::add::bin_typed(),
@@ -133,3 +164,73 @@ impl InferenceRulesOp for FusedBatchNorm {
         bail!("Batch norm parameters expected to be known")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tfpb::tensorflow::{DataType, TensorProto};
+    use std::convert::TryInto;
+
+    fn run(data_format: &str, shape: &[usize]) -> TractResult<()> {
+        let c_axis = if data_format == "NHWC" { shape.len() - 1 } else { 1 };
+        let c = shape[c_axis];
+        let param = |name: &str, offset: f32| {
+            let t = tensor1(&*(0..c).map(|i| offset + i as f32 / 4.).collect::<Vec<_>>());
+            let value: TensorProto = (&t).try_into().unwrap();
+            crate::tfpb::node()
+                .name(name)
+                .op("Const")
+                .attr("dtype", DataType::DtFloat)
+                .attr("value", value)
+        };
+        let graph = crate::tfpb::graph()
+            .node(crate::tfpb::node().name("x").op("Placeholder").attr("dtype", DataType::DtFloat))
+            .node(param("scale", 0.5))
+            .node(param("offset", -1.))
+            .node(param("mean", 0.25))
+            .node(param("variance", 2.))
+            .node(
+                crate::tfpb::node()
+                    .name("bn")
+                    .op("FusedBatchNormV3")
+                    .input("x")
+                    .input("scale")
+                    .input("offset")
+                    .input("mean")
+                    .input("variance")
+                    .attr("data_format", data_format)
+                    .attr("epsilon", 0.001f32)
+                    .attr("is_training", false),
+            );
+        let mut model = crate::tensorflow().model_for_proto_model(&graph)?;
+        model.set_input_fact(0, InferenceFact::dt_shape(f32::datum_type(), shape))?;
+        let model = model.into_optimized()?;
+        let len = shape.iter().product::<usize>();
+        let x = tract_ndarray::Array::from_shape_vec(
+            shape,
+            (0..len).map(|i| i as f32 / 10. - 3.).collect(),
+        )?;
+        let output = SimplePlan::new(&model)?.run(tvec!(x.clone().into_tensor()))?;
+        let expected = tract_ndarray::Array::from_shape_fn(shape, |ix| {
+            let c = ix[c_axis] as f32 / 4.;
+            let (scale, offset, mean, variance) = (0.5 + c, -1. + c, 0.25 + c, 2. + c);
+            (x[&ix] - mean) / (variance + 0.001).sqrt() * scale + offset
+        });
+        output[0].close_enough(&expected.into_tensor(), true)
+    }
+
+    #[test]
+    fn fold_nhwc() -> TractResult<()> {
+        run("NHWC", &[1, 2, 3, 4])
+    }
+
+    #[test]
+    fn fold_nchw() -> TractResult<()> {
+        run("NCHW", &[1, 4, 2, 3])
+    }
+
+    #[test]
+    fn fold_channels_last_3d() -> TractResult<()> {
+        run("NHWC", &[2, 3, 4])
+    }
+}
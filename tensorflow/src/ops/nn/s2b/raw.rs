@@ -1,4 +1,5 @@
 use tract_hir::internal::*;
+use tract_hir::tract_core::ops::cnn;
 use tract_ndarray::prelude::*;
 
 #[derive(Debug, Clone, new, Hash)]
@@ -24,7 +25,7 @@ impl StatelessOp for SpaceToBatch {
         let block_shape = block_shape.to_array_view::<i32>()?.into_dimensionality()?;
         let paddings = paddings.cast_to::<i32>()?;
         let paddings = paddings.to_array_view::<i32>()?.into_dimensionality()?;
-        let r = dispatch_numbers!(super::space_to_batch(input.datum_type())(
+        let r = dispatch_numbers!(cnn::space_to_batch(input.datum_type())(
             input,
             &block_shape.view(),
             &paddings.view()
@@ -64,16 +65,14 @@ impl InferenceRulesOp for SpaceToBatch {
             let mut paddings = tvec![];
             for p in paddings_view.outer_iter() {
                 let pad = match (p[0].to_integer(), p[1].to_integer()) {
-                    (Ok(bef), Ok(aft)) => {
-                        super::unary::PaddingStrat::FixedFixed(bef as usize, aft as usize)
-                    }
-                    (_, Ok(aft)) => super::unary::PaddingStrat::FlexFixed(aft as usize),
-                    (Ok(bef), _) => super::unary::PaddingStrat::FixedFlex(bef as usize),
+                    (Ok(bef), Ok(aft)) => cnn::PaddingStrat::FixedFixed(bef as usize, aft as usize),
+                    (_, Ok(aft)) => cnn::PaddingStrat::FlexFixed(aft as usize),
+                    (Ok(bef), _) => cnn::PaddingStrat::FixedFlex(bef as usize),
                     _ => bail!("Failed to unarize SpaceToBatch because of padding"),
                 };
                 paddings.push(pad);
             }
-            let op = super::unary::SpaceToBatchUnary::new(
+            let op = cnn::SpaceToBatchND::new(
                 self.datum_type,
                 target.outlet_fact(mapping[&node.inputs[0]])?.shape.to_tvec(),
                 node.outputs[0]
@@ -118,7 +117,7 @@ impl StatelessOp for BatchToSpace {
         let block_shape = block_shape.to_array_view::<i32>()?.into_dimensionality()?;
         let crops = crops.cast_to::<i32>()?;
         let crops = crops.to_array_view::<i32>()?.into_dimensionality()?;
-        let r = dispatch_numbers!(super::batch_to_space(input.datum_type())(
+        let r = dispatch_numbers!(cnn::batch_to_space(input.datum_type())(
             input,
             &block_shape.view(),
             &crops.view()
@@ -158,15 +157,15 @@ impl InferenceRulesOp for BatchToSpace {
                 .map(|p| {
                     Ok(match (p[0].to_integer(), p[1].to_integer()) {
                         (Ok(bef), Ok(aft)) => {
-                            super::unary::PaddingStrat::FixedFixed(bef as usize, aft as usize)
+                            cnn::PaddingStrat::FixedFixed(bef as usize, aft as usize)
                         }
-                        (_, Ok(aft)) => super::unary::PaddingStrat::FlexFixed(aft as usize),
-                        (Ok(bef), _) => super::unary::PaddingStrat::FixedFlex(bef as usize),
+                        (_, Ok(aft)) => cnn::PaddingStrat::FlexFixed(aft as usize),
+                        (Ok(bef), _) => cnn::PaddingStrat::FixedFlex(bef as usize),
                         _ => bail!("Failed to unarize SpaceToBatch because of padding"),
                     })
                 })
                 .collect::<TractResult<_>>()?;
-            let op = super::unary::BatchToSpaceUnary::new(
+            let op = cnn::BatchToSpaceND::new(
                 self.datum_type,
                 target.outlet_fact(mapping[&node.inputs[0]])?.shape.to_tvec(),
                 node.outputs[0]
@@ -220,6 +220,33 @@ impl NodeDef {
         };
         Ok(None)
     }
+
+    pub fn get_attr_opt_list_datum_type(&self, name: &str) -> TractResult<Option<Vec<DatumType>>> {
+        if let Some(a) = self.attr.get(name) {
+            if let Value::List(list) = a.value.as_ref().unwrap() {
+                return Ok(Some(
+                    list.r#type
+                        .iter()
+                        .map(|&t| -> TractResult<DatumType> {
+                            DataType::from_i32(t)
+                                .ok_or_else(|| format!("Unknown TensorFlow data type {}", t))?
+                                .try_into()
+                        })
+                        .collect::<TractResult<_>>()?,
+                ));
+            }
+        };
+        Ok(None)
+    }
+
+    pub fn get_attr_func_name(&self, name: &str) -> TractResult<&str> {
+        if let Some(a) = self.attr.get(name) {
+            if let Value::Func(func) = a.value.as_ref().unwrap() {
+                return Ok(&func.name);
+            }
+        };
+        bail!("Node {} ({}) expected func attribute '{}'", self.name, self.op, name)
+    }
 }
 
 impl From<DataType> for AttrValue {
@@ -246,6 +273,12 @@ impl From<i64> for AttrValue {
     }
 }
 
+impl From<bool> for AttrValue {
+    fn from(t: bool) -> AttrValue {
+        AttrValue { value: Some(Value::B(t)) }
+    }
+}
+
 impl From<f32> for AttrValue {
     fn from(t: f32) -> AttrValue {
         AttrValue { value: Some(Value::F(t)) }
@@ -0,0 +1,114 @@
+#![allow(non_snake_case)]
+#[cfg(feature = "conform")]
+extern crate env_logger;
+#[cfg(feature = "conform")]
+#[macro_use]
+extern crate log;
+#[cfg(feature = "conform")]
+#[macro_use]
+extern crate proptest;
+extern crate tract_tensorflow;
+
+#[cfg(feature = "conform")]
+mod utils;
+
+use std::convert::TryInto;
+use tract_tensorflow::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::tensorflow::attr_value::Value;
+use tract_tensorflow::tfpb::tensorflow::op_def::ArgDef;
+use tract_tensorflow::tfpb::tensorflow::DataType::{self, DtBool, DtInt32};
+use tract_tensorflow::tfpb::tensorflow::*;
+
+fn func_attr(name: &str) -> AttrValue {
+    AttrValue { value: Some(Value::Func(NameAttrList { name: name.into(), ..Default::default() })) }
+}
+
+fn scalar_const(name: &str, value: i32) -> NodeDef {
+    let value: TensorProto = (&tensor0(value)).try_into().unwrap();
+    tfpb::node().name(name).op("Const").attr("dtype", DtInt32).attr("value", value)
+}
+
+// single argument function computing `op(i, k)`
+fn function(name: &str, op: &str, k: i32, output_type: DataType) -> FunctionDef {
+    let arg = |name: &str, dt: DataType| ArgDef {
+        name: name.into(),
+        r#type: dt.into(),
+        ..Default::default()
+    };
+    FunctionDef {
+        signature: Some(OpDef {
+            name: name.into(),
+            input_arg: vec![arg("i", DtInt32)],
+            output_arg: vec![arg("output", output_type)],
+            ..Default::default()
+        }),
+        node_def: vec![
+            scalar_const("k", k),
+            tfpb::node().name("op").op(op).attr("T", DtInt32).input("i").input("k:output:0"),
+        ],
+        ret: vec![("output".to_string(), "op:z:0".to_string())].into_iter().collect(),
+        ..Default::default()
+    }
+}
+
+// while i < 10: i = i + 1
+fn counter(i: NodeDef) -> GraphDef {
+    let mut graph = tfpb::graph().node(i).node(
+        tfpb::node()
+            .name("loop")
+            .op("StatelessWhile")
+            .input("i")
+            .attr(
+                "T",
+                AttrValue {
+                    value: Some(Value::List(attr_value::ListValue {
+                        r#type: vec![DtInt32.into()],
+                        ..Default::default()
+                    })),
+                },
+            )
+            .attr("cond", func_attr("cond"))
+            .attr("body", func_attr("body")),
+    );
+    graph.library = Some(FunctionDefLibrary {
+        function: vec![function("cond", "Less", 10, DtBool), function("body", "AddV2", 1, DtInt32)],
+        ..Default::default()
+    });
+    graph
+}
+
+#[test]
+fn while_counter() -> TractResult<()> {
+    let model =
+        tract_tensorflow::tensorflow().model_for_proto_model(&counter(scalar_const("i", 0)))?;
+    let plan = SimplePlan::new(&model)?;
+    let mut state = SimpleState::new(&plan)?;
+    for _ in 0..2 {
+        assert_eq!(*state.run(tvec!())?[0], tensor0(10i32));
+    }
+    let model = model.into_optimized()?;
+    let output = SimplePlan::new(&model)?.run(tvec!())?;
+    assert_eq!(*output[0], tensor0(10i32));
+    Ok(())
+}
+
+#[cfg(feature = "conform")]
+mod conform {
+    use super::*;
+    use crate::utils::*;
+    use proptest::prelude::*;
+    use tract_tensorflow::conform::*;
+
+    fn counter_from(start: i32) -> proptest::test_runner::TestCaseResult {
+        let graph = counter(placeholder_i32("i")).write_to_bytes().unwrap();
+        compare(&graph, vec![("i", tensor0(start))], "loop")
+    }
+
+    proptest! {
+        #[test]
+        fn proptest_while_counter(start in -5i32..15) {
+            counter_from(start)?
+        }
+    }
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "conform")]
+#![allow(non_snake_case)]
+extern crate env_logger;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate proptest;
+extern crate tract_tensorflow;
+
+mod utils;
+
+use crate::utils::*;
+use proptest::prelude::*;
+use tract_tensorflow::conform::*;
+use tract_tensorflow::prelude::*;
+use tract_tensorflow::tfpb;
+
+fn fused_batch_norm(
+    op: &str,
+    data_format: &str,
+    input: Tensor,
+    params: [Vec<f32>; 4],
+) -> proptest::test_runner::TestCaseResult {
+    let [scale, offset, mean, variance] = params;
+    let graph = tfpb::graph()
+        .node(placeholder_f32("x"))
+        .node(const_f32("scale", &tensor1(&scale)))
+        .node(const_f32("offset", &tensor1(&offset)))
+        .node(const_f32("mean", &tensor1(&mean)))
+        .node(const_f32("variance", &tensor1(&variance)))
+        .node(
+            tfpb::node()
+                .name("bn")
+                .op(op)
+                .input("x")
+                .input("scale")
+                .input("offset")
+                .input("mean")
+                .input("variance")
+                .attr("T", tfpb::tensorflow::DataType::DtFloat)
+                .attr("data_format", data_format)
+                .attr("epsilon", 0.001f32)
+                .attr("is_training", false),
+        );
+    let graph = graph.write_to_bytes().unwrap();
+    compare(&graph, vec![("x", input)], "bn")
+}
+
+fn strat() -> BoxedStrategy<(bool, Tensor, [Vec<f32>; 4])> {
+    (any::<bool>(), 1usize..3, 1usize..4, 1usize..4, 1usize..5)
+        .prop_flat_map(|(nhwc, n, h, w, c)| {
+            let shape = if nhwc { vec![n, h, w, c] } else { vec![n, c, h, w] };
+            let len = n * h * w * c;
+            let param = |min: f32| proptest::collection::vec(min..3f32, c..=c);
+            (
+                Just(nhwc),
+                Just(shape),
+                proptest::collection::vec(-3f32..3f32, len..=len),
+                (param(-3.), param(-3.), param(-3.), param(0.)),
+            )
+        })
+        .prop_map(|(nhwc, shape, data, (scale, offset, mean, variance))| {
+            let input = tract_ndarray::Array::from_shape_vec(shape, data).unwrap().into_tensor();
+            (nhwc, input, [scale, offset, mean, variance])
+        })
+        .boxed()
+}
+
+proptest! {
+    #[test]
+    fn proptest_fused_batch_norm((nhwc, input, params) in strat()) {
+        let data_format = if nhwc { "NHWC" } else { "NCHW" };
+        fused_batch_norm("FusedBatchNormV3", data_format, input, params)?
+    }
+}
+
+#[test]
+fn fused_batch_norm_v1_nhwc() {
+    let input = tract_ndarray::Array::from_shape_fn((1, 2, 2, 3), |(_, h, w, c)| {
+        (h * 6 + w * 3 + c) as f32 / 4.
+    });
+    let params = [vec![1., 2., 0.5], vec![0., -1., 1.], vec![0.5, 1., 1.5], vec![1., 0.25, 4.]];
+    fused_batch_norm("FusedBatchNorm", "NHWC", input.into_tensor(), params).unwrap();
+}